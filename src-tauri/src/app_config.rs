@@ -57,6 +57,59 @@ impl McpApps {
     }
 }
 
+/// 单个应用的 MCP 工具过滤规则
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct McpToolFilter {
+    /// 允许的工具（为空表示不限制）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// 禁用的工具
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+impl McpToolFilter {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// MCP 工具过滤规则（按应用分治）
+///
+/// 同步时写入各客户端的原生格式：Claude 为 settings.json 的 `permissions.deny`（仅支持禁用列表），
+/// Codex 为 `enabled_tools` / `disabled_tools`，Gemini 为 `includeTools` / `excludeTools`。
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct McpToolFilters {
+    #[serde(default, skip_serializing_if = "McpToolFilter::is_empty")]
+    pub claude: McpToolFilter,
+    #[serde(default, skip_serializing_if = "McpToolFilter::is_empty")]
+    pub codex: McpToolFilter,
+    #[serde(default, skip_serializing_if = "McpToolFilter::is_empty")]
+    pub gemini: McpToolFilter,
+}
+
+impl McpToolFilters {
+    pub fn for_app(&self, app: &AppType) -> &McpToolFilter {
+        match app {
+            AppType::Claude => &self.claude,
+            AppType::Codex => &self.codex,
+            AppType::Gemini => &self.gemini,
+        }
+    }
+
+    pub fn for_app_mut(&mut self, app: &AppType) -> &mut McpToolFilter {
+        match app {
+            AppType::Claude => &mut self.claude,
+            AppType::Codex => &mut self.codex,
+            AppType::Gemini => &mut self.gemini,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.claude.is_empty() && self.codex.is_empty() && self.gemini.is_empty()
+    }
+}
+
 /// Skill 应用启用状态（标记 Skill 应用到哪些客户端）
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct SkillApps {
@@ -134,6 +187,15 @@ pub struct InstalledSkill {
     pub apps: SkillApps,
     /// 安装时间（Unix 时间戳）
     pub installed_at: i64,
+    /// 非仓库安装来源（`file://`、`git+https://...`、ZIP URL），仓库安装时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// 安装来源的提交 SHA（来自仓库归档）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_commit: Option<String>,
+    /// 安装时技能目录的内容哈希（sha256），用于检测上游更新与本地修改
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
 }
 
 /// 未管理的 Skill（在应用目录中发现但未被 CC Switch 管理）
@@ -166,6 +228,12 @@ pub struct McpServer {
     pub docs: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+    /// Codex 原始 TOML 片段（`[mcp_servers.<id>]` 表内容），保留 JSON 无法表达的字段与注释
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex_toml: Option<String>,
+    /// 按应用的工具允许/拒绝列表
+    #[serde(default, skip_serializing_if = "McpToolFilters::is_empty")]
+    pub tools: McpToolFilters,
 }
 
 /// MCP 配置：单客户端维度（v3.6.x 及以前，保留用于向后兼容）
@@ -611,6 +679,7 @@ impl MultiAppConfig {
             enabled: true, // 自动启用
             created_at: Some(timestamp),
             updated_at: Some(timestamp),
+            stack_order: None,
         };
 
         // 插入到对应的应用配置中
@@ -722,6 +791,8 @@ impl MultiAppConfig {
                             homepage,
                             docs,
                             tags,
+                            codex_toml: None,
+                            tools: Default::default(),
                         },
                     );
                 }
@@ -0,0 +1,206 @@
+//! 加密的配置导出与备份
+//!
+//! SQL 导出包含全部 API Key、用量脚本令牌等敏感数据。加密文件使用口令派生密钥
+//! （PBKDF2-HMAC-SHA256）和认证加密（AES-256-GCM），格式如下：
+//!
+//! ```text
+//! magic "CCSWENC" | version u8 | kdf u8 | iterations u32(BE) | salt [16] | cipher u8 | nonce [12] | ciphertext+tag
+//! ```
+//!
+//! 整个文件头作为附加认证数据（AAD），任何篡改都会导致解密失败。导入时按魔数自动识别。
+
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::error::AppError;
+
+/// 未显式提供口令时读取的环境变量；设置后自动备份也会加密
+pub const PASSPHRASE_ENV: &str = "CC_SWITCH_BACKUP_PASSPHRASE";
+
+const MAGIC: &[u8; 7] = b"CCSWENC";
+const FORMAT_VERSION: u8 = 1;
+const KDF_PBKDF2_SHA256: u8 = 1;
+const CIPHER_AES_256_GCM: u8 = 1;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// 解密时接受的迭代次数上限，防止构造的文件拖慢导入
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 1 + 4 + SALT_LEN + 1 + NONCE_LEN;
+
+/// 从环境变量读取口令（空值视为未设置）
+pub fn passphrase_from_env() -> Option<String> {
+    std::env::var(PASSPHRASE_ENV)
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// 数据是否为加密格式（只检查魔数）
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// 文件是否为加密格式
+pub fn is_encrypted_file(path: &Path) -> Result<bool, AppError> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).map_err(|e| AppError::io(path, e))?;
+    let mut magic = [0u8; MAGIC.len()];
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(is_encrypted(&magic)),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(AppError::io(path, e)),
+    }
+}
+
+/// 使用口令加密
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, AppError> {
+    if passphrase.is_empty() {
+        return Err(AppError::InvalidInput("加密口令不能为空".to_string()));
+    }
+
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| AppError::Message("生成随机数失败".to_string()))?;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + plaintext.len() + AES_256_GCM.tag_len());
+    out.extend_from_slice(MAGIC);
+    out.push(FORMAT_VERSION);
+    out.push(KDF_PBKDF2_SHA256);
+    out.extend_from_slice(&PBKDF2_ITERATIONS.to_be_bytes());
+    out.extend_from_slice(&salt);
+    out.push(CIPHER_AES_256_GCM);
+    out.extend_from_slice(&nonce);
+
+    let key = derive_key(passphrase, &salt, PBKDF2_ITERATIONS)?;
+    let mut in_out = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(&out[..HEADER_LEN]),
+        &mut in_out,
+    )
+    .map_err(|_| AppError::Message("加密失败".to_string()))?;
+    out.extend_from_slice(&in_out);
+    Ok(out)
+}
+
+/// 使用口令解密；口令错误与数据被篡改无法区分，统一报告
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, AppError> {
+    if !is_encrypted(data) {
+        return Err(AppError::InvalidInput(
+            "不是 CC Switch 加密文件".to_string(),
+        ));
+    }
+    if data.len() < HEADER_LEN + AES_256_GCM.tag_len() {
+        return Err(corrupted());
+    }
+
+    let mut pos = MAGIC.len();
+    let version = data[pos];
+    if version != FORMAT_VERSION {
+        return Err(AppError::localized(
+            "backup.encrypted.unsupported_version",
+            format!("不支持的加密备份版本 {version}，请升级 CC Switch"),
+            format!("Unsupported encrypted backup version {version}; please upgrade CC Switch"),
+        ));
+    }
+    pos += 1;
+    if data[pos] != KDF_PBKDF2_SHA256 {
+        return Err(corrupted());
+    }
+    pos += 1;
+    let iterations = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
+    pos += 4;
+    if iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(corrupted());
+    }
+    let salt = &data[pos..pos + SALT_LEN];
+    pos += SALT_LEN;
+    if data[pos] != CIPHER_AES_256_GCM {
+        return Err(corrupted());
+    }
+    pos += 1;
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&data[pos..pos + NONCE_LEN]);
+
+    let key = derive_key(passphrase, salt, iterations)?;
+    let mut in_out = data[HEADER_LEN..].to_vec();
+    let plaintext = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&data[..HEADER_LEN]),
+            &mut in_out,
+        )
+        .map_err(|_| {
+            AppError::localized(
+                "backup.encrypted.decrypt_failed",
+                "解密失败：口令错误或文件已损坏",
+                "Decryption failed: wrong passphrase or corrupted file",
+            )
+        })?;
+    Ok(plaintext.to_vec())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, AppError> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(corrupted)?;
+    let mut key_bytes = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key_bytes,
+    );
+    let key = UnboundKey::new(&AES_256_GCM, &key_bytes)
+        .map_err(|_| AppError::Message("无效的加密密钥".to_string()))?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn corrupted() -> AppError {
+    AppError::localized(
+        "backup.encrypted.corrupted",
+        "加密备份文件头无效或已损坏",
+        "Encrypted backup header is invalid or corrupted",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_and_tamper_detection() {
+        let data = encrypt(b"INSERT INTO providers VALUES ('sk-secret');", "hunter2").unwrap();
+        assert!(is_encrypted(&data));
+        assert!(!data.windows(9).any(|w| w == b"sk-secret"));
+        assert_eq!(
+            decrypt(&data, "hunter2").unwrap(),
+            b"INSERT INTO providers VALUES ('sk-secret');"
+        );
+
+        assert!(decrypt(&data, "wrong").is_err());
+
+        // 篡改文件头（迭代次数）或密文都会被认证检测到
+        let mut tampered = data.clone();
+        tampered[MAGIC.len() + 5] ^= 1;
+        assert!(decrypt(&tampered, "hunter2").is_err());
+        let mut tampered = data.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(decrypt(&tampered, "hunter2").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let mut data = encrypt(b"x", "pw").unwrap();
+        data[MAGIC.len()] = 2;
+        let err = decrypt(&data, "pw").unwrap_err().to_string();
+        assert!(err.contains('2'), "{err}");
+    }
+}
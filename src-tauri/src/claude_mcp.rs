@@ -4,8 +4,12 @@ use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::config::{atomic_write, get_claude_mcp_path, get_default_claude_mcp_path};
+use crate::app_config::McpToolFilter;
+use crate::config::{
+    atomic_write, get_claude_mcp_path, get_claude_settings_path, get_default_claude_mcp_path,
+};
 use crate::error::AppError;
+use crate::mcp::McpProjectScope;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -263,7 +267,23 @@ pub fn set_mcp_servers_map(
         serde_json::json!({})
     };
 
-    // 构建 mcpServers 对象：移除 UI 辅助字段（enabled/source），仅保留实际 MCP 规范
+    let out = build_servers_object(servers)?;
+
+    {
+        let obj = root
+            .as_object_mut()
+            .ok_or_else(|| AppError::Config("~/.claude.json 根必须是对象".into()))?;
+        obj.insert("mcpServers".into(), Value::Object(out));
+    }
+
+    write_json_value(&path, &root)?;
+    Ok(())
+}
+
+/// 构建 mcpServers 对象：移除 UI 辅助字段（enabled/source），仅保留实际 MCP 规范
+fn build_servers_object(
+    servers: &std::collections::HashMap<String, Value>,
+) -> Result<Map<String, Value>, AppError> {
     let mut out: Map<String, Value> = Map::new();
     for (id, spec) in servers.iter() {
         let mut obj = if let Some(map) = spec.as_object() {
@@ -292,14 +312,261 @@ pub fn set_mcp_servers_map(
 
         out.insert(id.clone(), Value::Object(obj));
     }
+    Ok(out)
+}
 
-    {
-        let obj = root
-            .as_object_mut()
-            .ok_or_else(|| AppError::Config("~/.claude.json 根必须是对象".into()))?;
-        obj.insert("mcpServers".into(), Value::Object(out));
+// ============================================================================
+// 项目级 MCP：<project>/.mcp.json 与 ~/.claude.json 的 projects.<path>.mcpServers
+// ============================================================================
+
+/// 项目级共享 MCP 配置文件路径（<project>/.mcp.json）
+pub fn get_project_mcp_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(".mcp.json")
+}
+
+/// ~/.claude.json 中 projects 段使用的键（项目绝对路径）
+pub fn project_key(project_dir: &Path) -> String {
+    let abs = fs::canonicalize(project_dir).unwrap_or_else(|_| project_dir.to_path_buf());
+    let key = abs.to_string_lossy().to_string();
+
+    // Claude Code 在 Windows 上使用正斜杠且不带 \\?\ 前缀
+    #[cfg(windows)]
+    let key = key.strip_prefix(r"\\?\").unwrap_or(&key).replace('\\', "/");
+
+    key
+}
+
+/// 读取项目级 Claude MCP 服务器映射
+pub fn read_project_mcp_servers_map(
+    project_dir: &Path,
+    scope: McpProjectScope,
+) -> Result<std::collections::HashMap<String, Value>, AppError> {
+    let root = match scope {
+        McpProjectScope::Shared => read_json_value(&get_project_mcp_path(project_dir))?,
+        McpProjectScope::Local => read_json_value(&user_config_path())?,
+    };
+
+    let servers = match scope {
+        McpProjectScope::Shared => root.get("mcpServers"),
+        McpProjectScope::Local => root
+            .get("projects")
+            .and_then(|v| v.get(project_key(project_dir)))
+            .and_then(|v| v.get("mcpServers")),
+    };
+
+    Ok(servers
+        .and_then(|v| v.as_object())
+        .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default())
+}
+
+/// 将 MCP 服务器映射写入项目级 Claude 配置
+///
+/// - Shared：仅覆盖 `<project>/.mcp.json` 的 mcpServers
+/// - Local：仅覆盖 `~/.claude.json` 中 `projects.<path>.mcpServers`，其他字段保持不变
+pub fn set_project_mcp_servers_map(
+    project_dir: &Path,
+    scope: McpProjectScope,
+    servers: &std::collections::HashMap<String, Value>,
+) -> Result<(), AppError> {
+    let out = build_servers_object(servers)?;
+
+    match scope {
+        McpProjectScope::Shared => {
+            let path = get_project_mcp_path(project_dir);
+            if out.is_empty() && !path.exists() {
+                return Ok(());
+            }
+            let mut root = read_json_value(&path)?;
+            let obj = root
+                .as_object_mut()
+                .ok_or_else(|| AppError::Config(".mcp.json 根必须是对象".into()))?;
+            obj.insert("mcpServers".into(), Value::Object(out));
+            write_json_value(&path, &root)
+        }
+        McpProjectScope::Local => {
+            let path = user_config_path();
+            let mut root = read_json_value(&path)?;
+            let key = project_key(project_dir);
+
+            let root_obj = root
+                .as_object_mut()
+                .ok_or_else(|| AppError::Config("~/.claude.json 根必须是对象".into()))?;
+            let projects = root_obj
+                .entry("projects")
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+                .ok_or_else(|| AppError::Config("~/.claude.json projects 必须是对象".into()))?;
+            if out.is_empty() && !projects.contains_key(&key) {
+                return Ok(());
+            }
+            let project = projects
+                .entry(key)
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+                .ok_or_else(|| AppError::Config("~/.claude.json 项目条目必须是对象".into()))?;
+            project.insert("mcpServers".into(), Value::Object(out));
+
+            write_json_value(&path, &root)
+        }
     }
+}
 
-    write_json_value(&path, &root)?;
+// ============================================================================
+// 工具过滤：~/.claude/settings.json 的 permissions.deny
+// ============================================================================
+//
+// Claude 的 `permissions.allow` 只会免确认地放行工具，并不能把服务器限制在这些工具上，
+// 且服务器的完整工具列表无从得知，因此 Claude 只支持禁用列表（写入 `permissions.deny`）。
+
+/// Claude 权限规则中的 MCP 工具标识（`mcp__<server>__<tool>`）
+pub fn tool_permission_rule(server_id: &str, tool: &str) -> String {
+    format!("mcp__{server_id}__{tool}")
+}
+
+fn deny_rules(server_id: &str, filter: Option<&McpToolFilter>) -> Vec<String> {
+    filter
+        .into_iter()
+        .flat_map(|f| &f.deny)
+        .map(|tool| tool_permission_rule(server_id, tool))
+        .collect()
+}
+
+/// 同步单个服务器的工具权限规则
+///
+/// 先移除 `previous` 生成的规则（含旧版本写入 `allow` 的规则），再追加 `current` 的禁用规则；
+/// 只处理 cc-switch 生成的规则，用户手写的其它规则保持不变。
+pub fn update_tool_permissions(
+    server_id: &str,
+    previous: Option<&McpToolFilter>,
+    current: Option<&McpToolFilter>,
+) -> Result<(), AppError> {
+    let remove: Vec<String> = previous
+        .into_iter()
+        .flat_map(|f| f.allow.iter().chain(&f.deny))
+        .map(|tool| tool_permission_rule(server_id, tool))
+        .collect();
+    rewrite_tool_permissions(
+        &get_claude_settings_path(),
+        &["allow", "deny"],
+        |rule| remove.iter().any(|r| r == rule),
+        deny_rules(server_id, current),
+    )?;
     Ok(())
 }
+
+/// 项目级工具权限规则所在的文件：共享范围为 `<project>/.claude/settings.json`，
+/// 本机范围为 `<project>/.claude/settings.local.json`
+pub fn get_project_settings_path(project_dir: &Path, scope: McpProjectScope) -> PathBuf {
+    let file = match scope {
+        McpProjectScope::Shared => "settings.json",
+        McpProjectScope::Local => "settings.local.json",
+    };
+    project_dir.join(".claude").join(file)
+}
+
+/// 同步项目级服务器的工具权限规则（`current` 为 None 时只移除）
+///
+/// 项目文件中写入的规则记录在 settings 中，之后只移除这些规则；
+/// 用户手写的规则（包括同一服务器的 `allow` 规则）保持不变。
+pub fn update_project_tool_permissions(
+    project_dir: &Path,
+    scope: McpProjectScope,
+    server_id: &str,
+    current: Option<&McpToolFilter>,
+) -> Result<(), AppError> {
+    let path = get_project_settings_path(project_dir, scope);
+    let key = get_project_settings_path(Path::new(&project_key(project_dir)), scope)
+        .to_string_lossy()
+        .to_string();
+    let mut settings = crate::settings::get_settings();
+    let previous = settings
+        .project_tool_rules
+        .get(&key)
+        .and_then(|servers| servers.get(server_id))
+        .cloned()
+        .unwrap_or_default();
+
+    let written = rewrite_tool_permissions(
+        &path,
+        &["deny"],
+        |rule| previous.iter().any(|r| r == rule),
+        deny_rules(server_id, current),
+    )?;
+
+    let servers = settings.project_tool_rules.entry(key.clone()).or_default();
+    if written.is_empty() {
+        servers.remove(server_id);
+    } else {
+        servers.insert(server_id.to_string(), written);
+    }
+    if servers.is_empty() {
+        settings.project_tool_rules.remove(&key);
+    }
+    if !previous.is_empty() || settings.project_tool_rules.contains_key(&key) {
+        crate::settings::update_settings(settings)?;
+    }
+    Ok(())
+}
+
+/// 从 `keys` 列出的权限列表中移除 `is_managed` 匹配的规则，再向 `deny` 追加规则
+///
+/// 返回由 cc-switch 写入的 deny 规则（不含文件中原本就存在的同名规则）。
+fn rewrite_tool_permissions(
+    path: &Path,
+    keys: &[&str],
+    is_managed: impl Fn(&str) -> bool,
+    deny: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    if !path.exists() && deny.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut root = read_json_value(path)?;
+    let original = root.clone();
+    let obj = root
+        .as_object_mut()
+        .ok_or_else(|| AppError::Config("Claude settings.json 根必须是对象".into()))?;
+
+    let permissions = obj
+        .entry("permissions")
+        .or_insert_with(|| Value::Object(Map::new()));
+    let Some(permissions) = permissions.as_object_mut() else {
+        return Err(AppError::Config(
+            "Claude settings.json 中 permissions 必须是对象".into(),
+        ));
+    };
+
+    let mut written = Vec::new();
+    for &key in keys {
+        let mut list: Vec<Value> = permissions
+            .get(key)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        list.retain(|v| v.as_str().is_none_or(|s| !is_managed(s)));
+        if key == "deny" {
+            for rule in &deny {
+                if !list.iter().any(|v| v.as_str() == Some(rule.as_str())) {
+                    list.push(Value::String(rule.clone()));
+                    written.push(rule.clone());
+                }
+            }
+        }
+
+        if list.is_empty() {
+            permissions.remove(key);
+        } else {
+            permissions.insert(key.to_string(), Value::Array(list));
+        }
+    }
+
+    if permissions.is_empty() {
+        obj.remove("permissions");
+    }
+
+    if root != original {
+        write_json_value(path, &root)?;
+    }
+    Ok(written)
+}
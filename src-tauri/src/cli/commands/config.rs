@@ -3,10 +3,16 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::app_config::AppType;
+use crate::backup_crypto;
 use crate::cli::i18n::texts;
-use crate::cli::ui::{error, highlight, info, success, to_json};
+use crate::cli::ui::{create_table, error, highlight, info, success, to_json, warning};
 use crate::error::AppError;
-use crate::services::ConfigService;
+use crate::services::bundle::BundleFormat;
+use crate::services::{
+    BundleAction, BundleImportReport, BundleSelection, BundleService, ConfigService,
+    ConflictStrategy, RemoteBackupService, RemoteSnapshot, SkillLockOutcome, SkillService,
+};
+use crate::settings::{RemoteBackupKind, RemoteBackupTarget};
 use crate::store::AppState;
 
 #[derive(Subcommand)]
@@ -15,21 +21,48 @@ pub enum ConfigCommand {
     Show,
     /// Show configuration file path
     Path,
-    /// Export configuration to file
+    /// Export configuration to file (.sql for the full database, .json/.yaml for a bundle)
+    ///
+    /// Both formats store API keys, provider env/auth values and MCP env/headers as
+    /// plain text; bundles are not redacted. Use --encrypt before sharing the file.
     Export {
         /// Output file path
         file: PathBuf,
+        /// Encrypt with a passphrase (read from CC_SWITCH_BACKUP_PASSPHRASE or prompted)
+        #[arg(long)]
+        encrypt: bool,
+        /// Bundle only: include providers (all, or the comma-separated ids)
+        #[arg(long, value_delimiter = ',', num_args = 0..=1)]
+        providers: Option<Vec<String>>,
+        /// Bundle only: include MCP servers
+        #[arg(long)]
+        mcp: bool,
+        /// Bundle only: include prompt presets
+        #[arg(long)]
+        prompts: bool,
+        /// Bundle only: include installed skills (as lock entries)
+        #[arg(long)]
+        skills: bool,
     },
-    /// Import configuration from file
+    /// Import configuration from file (.sql replaces the database, .json/.yaml bundles are merged)
     Import {
         /// Input file path
         file: PathBuf,
+        /// Bundle only: how to handle IDs that already exist
+        #[arg(long, value_enum, default_value_t = ConflictStrategy::Skip)]
+        on_conflict: ConflictStrategy,
+        /// Bundle only: show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Create a backup of current configuration
     Backup {
         /// Optional custom name for the backup
         #[arg(long)]
         name: Option<String>,
+        /// Encrypt with a passphrase (automatic when CC_SWITCH_BACKUP_PASSPHRASE is set)
+        #[arg(long)]
+        encrypt: bool,
     },
     /// Restore from a backup
     Restore {
@@ -38,8 +71,12 @@ pub enum ConfigCommand {
         backup: Option<String>,
 
         /// External file path to restore from
-        #[arg(long, conflicts_with = "backup")]
+        #[arg(long, conflicts_with_all = ["backup", "remote"])]
         file: Option<PathBuf>,
+
+        /// Restore a snapshot from remote backup targets (all, or the named one)
+        #[arg(long, num_args = 0..=1, default_missing_value = "")]
+        remote: Option<String>,
     },
     /// Validate configuration file
     Validate,
@@ -49,6 +86,71 @@ pub enum ConfigCommand {
     /// Manage common configuration snippet (per app)
     #[command(subcommand)]
     Common(CommonConfigCommand),
+
+    /// Manage remote backup targets (WebDAV, S3-compatible storage)
+    #[command(subcommand)]
+    Remote(RemoteCommand),
+}
+
+#[derive(Subcommand)]
+pub enum RemoteCommand {
+    /// List configured remote backup targets
+    List,
+    /// Add (or replace) a WebDAV target
+    AddWebdav {
+        /// Target name
+        name: String,
+        /// Collection URL that stores the backups
+        url: String,
+        #[arg(long)]
+        username: Option<String>,
+        /// Password, or a `${secret:NAME}` reference to the secret store
+        #[arg(long, requires = "username")]
+        password: Option<String>,
+        #[command(flatten)]
+        retention: RetentionArgs,
+    },
+    /// Add (or replace) an S3-compatible target (AWS S3, MinIO, R2, ...)
+    AddS3 {
+        /// Target name
+        name: String,
+        /// Endpoint URL (e.g. https://s3.us-east-1.amazonaws.com or http://localhost:9000)
+        #[arg(long)]
+        endpoint: String,
+        #[arg(long)]
+        bucket: String,
+        #[arg(long, default_value = "us-east-1")]
+        region: String,
+        /// Key prefix inside the bucket
+        #[arg(long, default_value = "")]
+        prefix: String,
+        /// Access key ID, or a `${secret:NAME}` reference
+        #[arg(long)]
+        access_key_id: String,
+        /// Secret access key, or a `${secret:NAME}` reference
+        #[arg(long)]
+        secret_access_key: String,
+        /// Address the bucket as a subdomain instead of a path segment
+        #[arg(long)]
+        virtual_hosted: bool,
+        #[command(flatten)]
+        retention: RetentionArgs,
+    },
+    /// Remove a target (remote snapshots are kept)
+    Remove {
+        /// Target name
+        name: String,
+    },
+}
+
+#[derive(clap::Args)]
+pub struct RetentionArgs {
+    /// Keep at most this many snapshots on the target
+    #[arg(long)]
+    keep_last: Option<usize>,
+    /// Delete snapshots older than this many days
+    #[arg(long)]
+    max_age_days: Option<u32>,
 }
 
 #[derive(Subcommand)]
@@ -81,14 +183,40 @@ pub fn execute(cmd: ConfigCommand, app: Option<AppType>) -> Result<(), AppError>
     match cmd {
         ConfigCommand::Show => show_config(),
         ConfigCommand::Path => show_path(),
-        ConfigCommand::Export { file } => export_config(&file),
-        ConfigCommand::Import { file } => import_config(&file),
-        ConfigCommand::Backup { name } => backup_config(name.as_deref()),
-        ConfigCommand::Restore { backup, file } => {
+        ConfigCommand::Export {
+            file,
+            encrypt,
+            providers,
+            mcp,
+            prompts,
+            skills,
+        } => {
+            let selection = BundleSelection {
+                app,
+                providers,
+                mcp,
+                prompts,
+                skills,
+            };
+            export_config(&file, encrypt, selection)
+        }
+        ConfigCommand::Import {
+            file,
+            on_conflict,
+            dry_run,
+        } => import_config(&file, on_conflict, dry_run),
+        ConfigCommand::Backup { name, encrypt } => backup_config(name.as_deref(), encrypt),
+        ConfigCommand::Restore {
+            backup,
+            remote: Some(target),
+            ..
+        } => restore_remote(&target, backup.as_deref()),
+        ConfigCommand::Restore { backup, file, .. } => {
             restore_config(backup.as_deref(), file.as_deref())
         }
         ConfigCommand::Validate => validate_config(),
         ConfigCommand::Reset => reset_config(),
+        ConfigCommand::Remote(cmd) => execute_remote(cmd),
         ConfigCommand::Common(cmd) => execute_common(cmd, app.unwrap_or(AppType::Claude)),
     }
 }
@@ -274,7 +402,52 @@ fn show_path() -> Result<(), AppError> {
     Ok(())
 }
 
-fn export_config(file: &PathBuf) -> Result<(), AppError> {
+/// 加密用口令：优先读取环境变量，否则交互输入（需确认）
+fn new_passphrase() -> Result<String, AppError> {
+    if let Some(passphrase) = backup_crypto::passphrase_from_env() {
+        return Ok(passphrase);
+    }
+    let passphrase = inquire::Password::new("Encryption passphrase:")
+        .with_custom_confirmation_message("Confirm passphrase:")
+        .with_display_mode(inquire::PasswordDisplayMode::Masked)
+        .prompt()
+        .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?;
+    if passphrase.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Passphrase must not be empty".to_string(),
+        ));
+    }
+    Ok(passphrase)
+}
+
+/// 导入加密文件所需的口令：未加密或已设置环境变量时返回 None
+fn passphrase_for(file: &Path) -> Result<Option<String>, AppError> {
+    if !backup_crypto::is_encrypted_file(file)? || backup_crypto::passphrase_from_env().is_some() {
+        return Ok(None);
+    }
+    println!("{}", info("This backup is encrypted."));
+    let passphrase = inquire::Password::new("Passphrase:")
+        .without_confirmation()
+        .with_display_mode(inquire::PasswordDisplayMode::Masked)
+        .prompt()
+        .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?;
+    Ok(Some(passphrase))
+}
+
+fn export_config(
+    file: &PathBuf,
+    encrypt: bool,
+    selection: BundleSelection,
+) -> Result<(), AppError> {
+    let is_bundle = BundleFormat::from_path(file).is_some();
+    let filtered =
+        selection.providers.is_some() || selection.mcp || selection.prompts || selection.skills;
+    if filtered && !is_bundle {
+        return Err(AppError::InvalidInput(
+            "Filters only apply to bundles; use a .json or .yaml output file".to_string(),
+        ));
+    }
+
     println!(
         "{}",
         info(&format!("Exporting configuration to {}...", file.display()))
@@ -302,17 +475,73 @@ fn export_config(file: &PathBuf) -> Result<(), AppError> {
     }
 
     // Export configuration
-    ConfigService::export_config_to_path(file)?;
+    let passphrase = if encrypt {
+        Some(new_passphrase()?)
+    } else {
+        None
+    };
+    if is_bundle {
+        let state = get_state()?;
+        let bundle = BundleService::build(&state, &selection)?;
+        BundleService::write(&bundle, file, passphrase.as_deref())?;
 
-    println!(
-        "{}",
-        success(&format!("✓ Configuration exported to {}", file.display()))
-    );
+        println!(
+            "{}",
+            success(&format!("✓ Bundle exported to {}", file.display()))
+        );
+        println!(
+            "  {} provider(s), {} MCP server(s), {} prompt(s), {} skill(s)",
+            bundle.providers.values().map(Vec::len).sum::<usize>(),
+            bundle.mcp_servers.len(),
+            bundle.prompts.values().map(Vec::len).sum::<usize>(),
+            bundle.skills.len()
+        );
+        if passphrase.is_none() && !(bundle.providers.is_empty() && bundle.mcp_servers.is_empty()) {
+            println!(
+                "{}",
+                warning("⚠ The bundle contains API keys and MCP env/headers in plain text; use --encrypt before sharing it.")
+            );
+        }
+    } else {
+        match &passphrase {
+            Some(passphrase) => ConfigService::export_config_to_path_encrypted(file, passphrase)?,
+            None => ConfigService::export_config_to_path(file)?,
+        }
+
+        println!(
+            "{}",
+            success(&format!("✓ Configuration exported to {}", file.display()))
+        );
+    }
+    if encrypt {
+        println!(
+            "{}",
+            info("  Encrypted: the passphrase is required to import it.")
+        );
+    } else {
+        println!(
+            "{}",
+            info("  Note: the export contains API keys in plain text; use --encrypt before sharing it.")
+        );
+    }
 
     Ok(())
 }
 
-fn import_config(file: &PathBuf) -> Result<(), AppError> {
+fn import_config(
+    file: &PathBuf,
+    on_conflict: ConflictStrategy,
+    dry_run: bool,
+) -> Result<(), AppError> {
+    if BundleFormat::from_path(file).is_some() {
+        return import_bundle(file, on_conflict, dry_run);
+    }
+    if dry_run || on_conflict != ConflictStrategy::Skip {
+        return Err(AppError::InvalidInput(
+            "--on-conflict and --dry-run only apply to .json/.yaml bundles".to_string(),
+        ));
+    }
+
     println!(
         "{}",
         info(&format!(
@@ -347,8 +576,13 @@ fn import_config(file: &PathBuf) -> Result<(), AppError> {
     }
 
     // Perform import
+    let passphrase = passphrase_for(file)?;
     let state = get_state()?;
-    let backup_id = ConfigService::import_config_from_path(file, &state)?;
+    let backup_id = ConfigService::import_config_from_path_with_passphrase(
+        file,
+        &state,
+        passphrase.as_deref(),
+    )?;
 
     println!(
         "{}",
@@ -366,7 +600,99 @@ fn import_config(file: &PathBuf) -> Result<(), AppError> {
     Ok(())
 }
 
-fn backup_config(custom_name: Option<&str>) -> Result<(), AppError> {
+fn import_bundle(
+    file: &Path,
+    on_conflict: ConflictStrategy,
+    dry_run: bool,
+) -> Result<(), AppError> {
+    if !file.exists() {
+        return Err(AppError::Message(format!(
+            "File '{}' not found",
+            file.display()
+        )));
+    }
+
+    let passphrase = passphrase_for(file)?;
+    let bundle = BundleService::read(file, passphrase.as_deref())?;
+    let state = get_state()?;
+
+    if dry_run {
+        let report = BundleService::plan(&state, &bundle, on_conflict)?;
+        print_bundle_report(&report);
+        println!();
+        println!("{}", info("Dry run: nothing was changed."));
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        info(&format!("Merging bundle from {}...", file.display()))
+    );
+    let report = BundleService::import(&state, &bundle, on_conflict)?;
+    print_bundle_report(&report);
+
+    if !report.pending_skills.is_empty() {
+        let service = SkillService::new()?;
+        let results = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| AppError::Message(format!("Failed to create runtime: {e}")))?
+            .block_on(service.install_lock_entries(&report.pending_skills))?;
+        for result in results {
+            if let SkillLockOutcome::Failed(e) = result.outcome {
+                println!("{}", error(&format!("✗ Skill '{}': {e}", result.directory)));
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{}",
+        success(&format!("✓ Bundle merged from {}", file.display()))
+    );
+    if let Some(backup_id) = &report.backup_id {
+        println!("{}", info(&format!("  Backup created: {}", backup_id)));
+    }
+    Ok(())
+}
+
+fn print_bundle_report(report: &BundleImportReport) {
+    if report.changes.is_empty() {
+        println!("{}", info("The bundle is empty."));
+        return;
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["Type", "App", "ID", "Action"]);
+    for change in &report.changes {
+        let action = match &change.action {
+            BundleAction::Add => "add".to_string(),
+            BundleAction::Overwrite => "overwrite".to_string(),
+            BundleAction::Rename(new_id) => format!("rename → {new_id}"),
+            BundleAction::Skip => "skip (exists)".to_string(),
+        };
+        table.add_row(vec![
+            change.kind.as_str().to_string(),
+            change
+                .app
+                .as_ref()
+                .map(|app| app.as_str().to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            change.id.clone(),
+            action,
+        ]);
+    }
+    println!("{}", table);
+    println!(
+        "{} to add, {} to overwrite, {} to rename, {} skipped",
+        report.count(|a| *a == BundleAction::Add),
+        report.count(|a| *a == BundleAction::Overwrite),
+        report.count(|a| matches!(a, BundleAction::Rename(_))),
+        report.count(|a| *a == BundleAction::Skip)
+    );
+}
+
+fn backup_config(custom_name: Option<&str>, encrypt: bool) -> Result<(), AppError> {
     let config_path = crate::config::get_app_config_path();
 
     if let Some(name) = custom_name {
@@ -378,7 +704,17 @@ fn backup_config(custom_name: Option<&str>) -> Result<(), AppError> {
         println!("{}", info("Creating backup of current configuration..."));
     }
 
-    let backup_id = ConfigService::create_backup(&config_path, custom_name.map(|s| s.to_string()))?;
+    let passphrase = if encrypt {
+        Some(new_passphrase()?)
+    } else {
+        backup_crypto::passphrase_from_env()
+    };
+    let (backup_id, uploads) = ConfigService::create_backup_with_uploads(
+        &config_path,
+        custom_name.map(|s| s.to_string()),
+        passphrase.as_deref(),
+        None,
+    )?;
 
     if backup_id.is_empty() {
         println!("{}", error("Failed to create backup."));
@@ -388,11 +724,260 @@ fn backup_config(custom_name: Option<&str>) -> Result<(), AppError> {
 
         println!("{}", success(&format!("✓ Backup created: {}", backup_id)));
         println!("Location: {}", backup_file.display());
+        if passphrase.is_some() {
+            println!("{}", info("  Encrypted with passphrase."));
+        }
+        for outcome in uploads {
+            match outcome.result {
+                Ok(0) => println!(
+                    "{}",
+                    success(&format!("✓ Uploaded to '{}'", outcome.target))
+                ),
+                Ok(pruned) => println!(
+                    "{}",
+                    success(&format!(
+                        "✓ Uploaded to '{}' ({} old snapshot(s) pruned)",
+                        outcome.target, pruned
+                    ))
+                ),
+                Err(e) => println!(
+                    "{}",
+                    warning(&format!("⚠ Upload to '{}' failed: {}", outcome.target, e))
+                ),
+            }
+        }
     }
 
     Ok(())
 }
 
+fn execute_remote(cmd: RemoteCommand) -> Result<(), AppError> {
+    let (name, kind, retention) = match cmd {
+        RemoteCommand::List => return list_remote_targets(),
+        RemoteCommand::Remove { name } => {
+            RemoteBackupService::remove_target(&name)?;
+            println!(
+                "{}",
+                success(&format!("✓ Removed remote backup target '{}'", name))
+            );
+            return Ok(());
+        }
+        RemoteCommand::AddWebdav {
+            name,
+            url,
+            username,
+            password,
+            retention,
+        } => (
+            name,
+            RemoteBackupKind::Webdav {
+                url,
+                username,
+                password,
+            },
+            retention,
+        ),
+        RemoteCommand::AddS3 {
+            name,
+            endpoint,
+            bucket,
+            region,
+            prefix,
+            access_key_id,
+            secret_access_key,
+            virtual_hosted,
+            retention,
+        } => (
+            name,
+            RemoteBackupKind::S3 {
+                endpoint,
+                bucket,
+                region,
+                prefix,
+                access_key_id,
+                secret_access_key,
+                path_style: !virtual_hosted,
+            },
+            retention,
+        ),
+    };
+
+    RemoteBackupService::save_target(RemoteBackupTarget {
+        name: name.clone(),
+        kind,
+        keep_last: retention.keep_last,
+        max_age_days: retention.max_age_days,
+    })?;
+    println!(
+        "{}",
+        success(&format!("✓ Saved remote backup target '{}'", name))
+    );
+    println!(
+        "{}",
+        info("Every new backup is now uploaded to it; try `cc-switch config backup`.")
+    );
+    Ok(())
+}
+
+fn list_remote_targets() -> Result<(), AppError> {
+    let targets = RemoteBackupService::targets();
+    if targets.is_empty() {
+        println!("{}", info("No remote backup targets configured."));
+        println!(
+            "{}",
+            info("Add one with `cc-switch config remote add-webdav` or `add-s3`.")
+        );
+        return Ok(());
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["Name", "Type", "Location", "Retention"]);
+    for target in targets {
+        let (kind, location) = match &target.kind {
+            RemoteBackupKind::Webdav { url, .. } => ("webdav", url.clone()),
+            RemoteBackupKind::S3 {
+                endpoint,
+                bucket,
+                prefix,
+                ..
+            } => (
+                "s3",
+                format!("{}/{}/{}", endpoint.trim_end_matches('/'), bucket, prefix)
+                    .trim_end_matches('/')
+                    .to_string(),
+            ),
+        };
+        let mut retention = Vec::new();
+        if let Some(keep) = target.keep_last {
+            retention.push(format!("last {keep}"));
+        }
+        if let Some(days) = target.max_age_days {
+            retention.push(format!("{days} days"));
+        }
+        let retention = if retention.is_empty() {
+            "keep all".to_string()
+        } else {
+            retention.join(", ")
+        };
+        table.add_row(vec![
+            target.name.clone(),
+            kind.to_string(),
+            location,
+            retention,
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
+
+/// 从远程目标恢复：列出快照、下载到本地备份目录后按本地备份恢复
+fn restore_remote(target: &str, backup_id: Option<&str>) -> Result<(), AppError> {
+    let targets = if target.is_empty() {
+        RemoteBackupService::targets()
+    } else {
+        vec![RemoteBackupService::find_target(target)?]
+    };
+    if targets.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No remote backup targets configured; see `cc-switch config remote --help`."
+                .to_string(),
+        ));
+    }
+
+    let mut snapshots: Vec<RemoteSnapshot> = Vec::new();
+    for target in &targets {
+        match RemoteBackupService::list_snapshots(target) {
+            Ok(list) => snapshots.extend(list),
+            Err(e) => println!(
+                "{}",
+                warning(&format!("⚠ Could not list '{}': {}", target.name, e))
+            ),
+        }
+    }
+
+    let selected = match backup_id {
+        Some(id) => snapshots
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AppError::Message(format!("Remote snapshot '{}' not found", id)))?,
+        None => {
+            if snapshots.is_empty() {
+                println!("{}", info("No remote snapshots found."));
+                return Ok(());
+            }
+            println!("{}", highlight("Remote snapshots"));
+            println!("{}", "=".repeat(50));
+            println!();
+            let choices: Vec<String> = snapshots
+                .iter()
+                .map(|s| format!("[{}] {} - {}", s.target, s.display_name, s.id))
+                .collect();
+            let selection = inquire::Select::new(texts::select_backup_to_restore(), choices)
+                .raw_prompt()
+                .map_err(|_| AppError::Message(texts::selection_cancelled().to_string()))?;
+            &snapshots[selection.index]
+        }
+    };
+
+    println!();
+    println!("{}", highlight(texts::warning_title()));
+    println!("{}", texts::config_restore_warning_replace());
+    println!("{}", texts::config_restore_warning_pre_backup());
+    println!();
+
+    let confirm = inquire::Confirm::new(texts::config_restore_confirm_prompt())
+        .with_default(false)
+        .prompt()
+        .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?;
+    if !confirm {
+        println!("{}", info(texts::cancelled()));
+        return Ok(());
+    }
+
+    let target = targets
+        .iter()
+        .find(|t| t.name == selected.target)
+        .ok_or_else(|| AppError::Message(texts::invalid_selection().to_string()))?;
+    let backup_dir = crate::config::get_app_config_dir().join("backups");
+    let path = backup_dir.join(format!("{}.sql", selected.id));
+    RemoteBackupService::download(target, &selected.id, &path)?;
+    println!(
+        "{}",
+        info(&format!(
+            "Downloaded '{}' from '{}'",
+            selected.id, target.name
+        ))
+    );
+
+    let passphrase = passphrase_for(&path)?;
+    let state = get_state()?;
+    let pre_restore_backup = ConfigService::restore_from_backup_id_with_passphrase(
+        &selected.id,
+        &state,
+        passphrase.as_deref(),
+    )?;
+
+    println!(
+        "{}",
+        success(&format!(
+            "✓ Configuration restored from remote snapshot: {}",
+            selected.display_name
+        ))
+    );
+    if !pre_restore_backup.is_empty() {
+        println!(
+            "{}",
+            info(&format!("  Pre-restore backup: {}", pre_restore_backup))
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        info("Note: Restart your CLI clients to apply the changes.")
+    );
+    Ok(())
+}
+
 fn restore_config(backup_id: Option<&str>, file_path: Option<&Path>) -> Result<(), AppError> {
     let config_path = crate::config::get_app_config_path();
 
@@ -411,8 +996,13 @@ fn restore_config(backup_id: Option<&str>, file_path: Option<&Path>) -> Result<(
             return Ok(());
         }
 
+        let passphrase = passphrase_for(&ConfigService::backup_path(id)?)?;
         let state = get_state()?;
-        let pre_restore_backup = ConfigService::restore_from_backup_id(id, &state)?;
+        let pre_restore_backup = ConfigService::restore_from_backup_id_with_passphrase(
+            id,
+            &state,
+            passphrase.as_deref(),
+        )?;
 
         println!(
             "{}",
@@ -466,8 +1056,13 @@ fn restore_config(backup_id: Option<&str>, file_path: Option<&Path>) -> Result<(
             return Ok(());
         }
 
+        let passphrase = passphrase_for(file)?;
         let state = get_state()?;
-        let pre_restore_backup = ConfigService::import_config_from_path(file, &state)?;
+        let pre_restore_backup = ConfigService::import_config_from_path_with_passphrase(
+            file,
+            &state,
+            passphrase.as_deref(),
+        )?;
 
         println!(
             "{}",
@@ -507,7 +1102,13 @@ fn restore_config(backup_id: Option<&str>, file_path: Option<&Path>) -> Result<(
 
     let choices: Vec<String> = backups
         .iter()
-        .map(|b| format!("{} - {}", b.display_name, b.id))
+        .map(|b| {
+            if b.encrypted {
+                format!("{} - {} [encrypted]", b.display_name, b.id)
+            } else {
+                format!("{} - {}", b.display_name, b.id)
+            }
+        })
         .collect();
 
     let selection = inquire::Select::new(texts::select_backup_to_restore(), choices)
@@ -535,8 +1136,13 @@ fn restore_config(backup_id: Option<&str>, file_path: Option<&Path>) -> Result<(
         return Ok(());
     }
 
+    let passphrase = passphrase_for(&selected_backup.path)?;
     let state = get_state()?;
-    let pre_restore_backup = ConfigService::restore_from_backup_id(&selected_backup.id, &state)?;
+    let pre_restore_backup = ConfigService::restore_from_backup_id_with_passphrase(
+        &selected_backup.id,
+        &state,
+        passphrase.as_deref(),
+    )?;
 
     println!(
         "{}",
@@ -0,0 +1,91 @@
+use crate::cli::ui::{error, highlight, info, success, warning};
+use crate::error::AppError;
+use crate::services::{DoctorReport, DoctorService, DoctorSeverity};
+use crate::store::AppState;
+
+pub fn execute(fix: bool) -> Result<(), AppError> {
+    let state = AppState::try_new();
+    let report = DoctorService::diagnose(state.as_ref());
+    print_report(&report);
+
+    let fixable = report.fixes().count();
+    let report = match (&state, fix) {
+        (Ok(state), true) if fixable > 0 => {
+            println!("{}", highlight("Applying fixes"));
+            for outcome in DoctorService::fix(state, &report) {
+                match outcome.result {
+                    Ok(()) => println!("  {}", success(&format!("✓ {}", outcome.fix.describe()))),
+                    Err(e) => println!(
+                        "  {}",
+                        error(&format!("✗ {}: {}", outcome.fix.describe(), e))
+                    ),
+                }
+            }
+            println!();
+            let after = DoctorService::diagnose(Ok(state));
+            println!("{}", highlight("After fixes"));
+            print_summary(&after);
+            after
+        }
+        _ => {
+            print_summary(&report);
+            if fixable > 0 && !fix {
+                println!(
+                    "{}",
+                    info(&format!(
+                        "{} problem(s) can be repaired automatically: run `cc-switch doctor --fix`.",
+                        fixable
+                    ))
+                );
+            }
+            report
+        }
+    };
+
+    if report.error_count() > 0 {
+        return Err(AppError::Message(format!(
+            "doctor found {} error(s)",
+            report.error_count()
+        )));
+    }
+    Ok(())
+}
+
+fn print_report(report: &DoctorReport) {
+    let mut area = None;
+    for finding in &report.findings {
+        if area != Some(finding.area.as_str()) {
+            if area.is_some() {
+                println!();
+            }
+            area = Some(finding.area.as_str());
+            println!("{}", highlight(&finding.area));
+        }
+        let line = match finding.severity {
+            DoctorSeverity::Ok => success(&format!("✓ {}", finding.message)),
+            DoctorSeverity::Warning => warning(&format!("⚠ {}", finding.message)),
+            DoctorSeverity::Error => error(&format!("✗ {}", finding.message)),
+        };
+        println!("  {}", line);
+        if let Some(fix) = &finding.fix {
+            println!("    → --fix will {}", fix.describe());
+        } else if let Some(hint) = &finding.hint {
+            println!("    → {}", hint);
+        }
+    }
+    println!();
+}
+
+fn print_summary(report: &DoctorReport) {
+    let (errors, warnings) = (report.error_count(), report.warning_count());
+    if errors == 0 && warnings == 0 {
+        println!("{}", success("✓ No problems found"));
+    } else {
+        let text = format!("{} error(s), {} warning(s)", errors, warnings);
+        if errors > 0 {
+            println!("{}", error(&text));
+        } else {
+            println!("{}", warning(&text));
+        }
+    }
+}
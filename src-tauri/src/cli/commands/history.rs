@@ -0,0 +1,130 @@
+use crate::cli::ui::{create_table, highlight, info, success, warning};
+use crate::database::JournalEntry;
+use crate::error::AppError;
+use crate::services::history::describe_entry;
+use crate::services::{HistoryChangeSet, HistoryService};
+use crate::store::AppState;
+
+pub fn history(limit: usize) -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    let sets = HistoryService::list(&state, limit)?;
+    if sets.is_empty() {
+        println!("{}", info("No changes recorded yet."));
+        return Ok(());
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["#", "When", "User", "Command", "Changes"]);
+    for set in &sets {
+        let mut changes: Vec<String> = set.entries.iter().map(change_line).collect();
+        if let Some(reverted) = set.reverts {
+            changes.insert(0, format!("undo of #{reverted}"));
+        }
+        let id = if set.undone {
+            format!("{} (undone)", set.change_set)
+        } else {
+            set.change_set.to_string()
+        };
+        table.add_row(vec![
+            id,
+            format_time(set.created_at),
+            set.actor.clone(),
+            set.source.clone(),
+            changes.join("\n"),
+        ]);
+    }
+    println!("{}", table);
+    println!(
+        "{}",
+        info("Run `cc-switch undo [<n>]` to revert the most recent change sets.")
+    );
+    Ok(())
+}
+
+pub fn undo(count: usize, force: bool, yes: bool) -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    let sets = HistoryService::undo_candidates(&state, count)?;
+    if sets.is_empty() {
+        println!("{}", info("Nothing to undo."));
+        return Ok(());
+    }
+
+    println!("{}", highlight("Changes to revert"));
+    print_sets(&sets);
+    if sets.len() < count {
+        println!(
+            "{}",
+            warning(&format!(
+                "⚠ Only {} change set(s) can be undone.",
+                sets.len()
+            ))
+        );
+    }
+
+    if !yes {
+        let confirm = inquire::Confirm::new("Revert these changes?")
+            .with_default(false)
+            .prompt()
+            .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?;
+        if !confirm {
+            println!("{}", info("Cancelled."));
+            return Ok(());
+        }
+    }
+
+    let undone = HistoryService::undo(&state, count, force)?;
+    println!(
+        "{}",
+        success(&format!("✓ Reverted {} change set(s)", undone.len()))
+    );
+    println!(
+        "{}",
+        info("The revert is recorded too; see `cc-switch history`.")
+    );
+    Ok(())
+}
+
+fn print_sets(sets: &[HistoryChangeSet]) {
+    for set in sets {
+        println!(
+            "#{}  {}  {}",
+            set.change_set,
+            format_time(set.created_at),
+            set.source
+        );
+        for entry in &set.entries {
+            println!("    {}", change_line(entry));
+        }
+    }
+    println!();
+}
+
+fn change_line(entry: &JournalEntry) -> String {
+    let marker = match entry.action.as_str() {
+        "create" => "+",
+        "delete" => "-",
+        _ => "~",
+    };
+    let mut line = format!("{marker} {}", describe_entry(entry));
+    if entry.entity_type == "current_provider" {
+        let id = |value: &Option<serde_json::Value>| {
+            value
+                .as_ref()
+                .and_then(|v| v.as_str())
+                .unwrap_or("none")
+                .to_string()
+        };
+        line.push_str(&format!(": {} → {}", id(&entry.before), id(&entry.after)));
+    }
+    line
+}
+
+fn format_time(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| {
+            dt.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
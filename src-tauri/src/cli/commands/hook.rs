@@ -0,0 +1,270 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+
+use crate::app_config::AppType;
+use crate::cli::ui::{create_table, highlight, info, success, warning};
+use crate::error::AppError;
+use crate::services::hook::{ENV_KEY_VAR, ENV_VARS_VAR, PROJECT_FILE};
+use crate::services::{HookService, HookShell, ResolvedSelection, SelectionSource};
+use crate::settings::{get_settings, DirectoryAppSelection, DirectorySelection};
+use crate::store::AppState;
+
+#[derive(Subcommand)]
+pub enum HookCommand {
+    /// Print the shell integration script, e.g. `eval "$(cc-switch hook init bash)"`
+    Init {
+        #[arg(value_enum)]
+        shell: HookShell,
+        /// Export the provider's environment variables instead of rewriting live config files
+        #[arg(long)]
+        env: bool,
+    },
+    /// Apply or restore the selection for the current directory (called by the shell hook)
+    Apply {
+        #[arg(value_enum)]
+        shell: HookShell,
+        /// Print the environment variables to export instead of switching
+        #[arg(long)]
+        env: bool,
+    },
+    /// Show which selection applies to the current directory
+    Status,
+    /// Trust the nearest .cc-switch.toml (needed before it is applied; re-run after editing it)
+    Allow {
+        /// Directory to search from (defaults to the current directory)
+        dir: Option<PathBuf>,
+    },
+    /// Revoke trust for the nearest .cc-switch.toml
+    Deny {
+        /// Directory to search from (defaults to the current directory)
+        dir: Option<PathBuf>,
+    },
+    /// Register a directory selection in settings (same as a .cc-switch.toml in that directory)
+    Map {
+        /// Directory (defaults to the current directory)
+        dir: Option<PathBuf>,
+        /// Profile to apply first
+        #[arg(long)]
+        profile: Option<String>,
+        /// Provider ID for the app selected with --app
+        #[arg(long)]
+        provider: Option<String>,
+        /// MCP servers to enable for the app selected with --app (comma-separated, empty for none)
+        #[arg(long, value_delimiter = ',')]
+        mcp: Option<Vec<String>>,
+    },
+    /// Remove a directory selection from settings
+    Unmap {
+        /// Directory (defaults to the current directory)
+        dir: Option<PathBuf>,
+    },
+}
+
+pub fn execute(cmd: HookCommand, app: Option<AppType>) -> Result<(), AppError> {
+    match cmd {
+        HookCommand::Init { shell, env } => init(shell, env),
+        HookCommand::Apply { shell, env } => {
+            if env {
+                apply_env(shell)
+            } else {
+                apply()
+            }
+        }
+        HookCommand::Status => status(),
+        HookCommand::Allow { dir } => set_trusted(dir, true),
+        HookCommand::Deny { dir } => set_trusted(dir, false),
+        HookCommand::Map {
+            dir,
+            profile,
+            provider,
+            mcp,
+        } => map(dir, profile, provider, mcp, app.unwrap_or(AppType::Claude)),
+        HookCommand::Unmap { dir } => unmap(dir),
+    }
+}
+
+fn current_dir() -> Result<PathBuf, AppError> {
+    let cwd = std::env::current_dir().map_err(|e| AppError::Message(e.to_string()))?;
+    Ok(cwd.canonicalize().unwrap_or(cwd))
+}
+
+fn init(shell: HookShell, env: bool) -> Result<(), AppError> {
+    let exe = std::env::current_exe().map_err(|e| AppError::Message(e.to_string()))?;
+    print!("{}", HookService::init_script(shell, &exe, env));
+    Ok(())
+}
+
+/// stdout 由 shell 求值，提示信息一律写到 stderr
+fn apply() -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    let outcome = HookService::apply(&state, &current_dir()?)?;
+    if let Some(file) = &outcome.blocked {
+        eprint_blocked(file);
+    }
+    if let Some(dir) = &outcome.restored {
+        eprintln!("cc-switch: restored the selection from before {dir}");
+    }
+    if let Some(applied) = &outcome.applied {
+        eprintln!(
+            "cc-switch: applied {} ({})",
+            applied.dir.display(),
+            applied.describe()
+        );
+    }
+    Ok(())
+}
+
+fn apply_env(shell: HookShell) -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    let env = HookService::env(&state, &current_dir()?)?;
+    if let Some(file) = &env.blocked {
+        eprint_blocked(file);
+    }
+    let previous_key = std::env::var(ENV_KEY_VAR).ok().filter(|k| !k.is_empty());
+    if env.key == previous_key {
+        return Ok(());
+    }
+
+    let previous: Vec<String> = std::env::var(ENV_VARS_VAR)
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    for warning in &env.warnings {
+        eprintln!("cc-switch: {warning}");
+    }
+    if let Some(key) = &env.key {
+        let names: Vec<&str> = env.set.iter().map(|(name, _)| name.as_str()).collect();
+        eprintln!(
+            "cc-switch: exporting {} for {}",
+            names.join(" "),
+            key.split('#').next().unwrap_or(key)
+        );
+    }
+    println!("{}", HookService::env_script(shell, &env, &previous));
+    Ok(())
+}
+
+fn eprint_blocked(file: &std::path::Path) {
+    eprintln!(
+        "cc-switch: {} is not allowed; review it and run `cc-switch hook allow`",
+        file.display()
+    );
+}
+
+fn set_trusted(dir: Option<PathBuf>, allow: bool) -> Result<(), AppError> {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => current_dir()?,
+    };
+    let file = HookService::set_trusted(&dir, allow)?;
+    let message = if allow {
+        format!("✓ Allowed {}", file.display())
+    } else {
+        format!("✓ Revoked trust for {}", file.display())
+    };
+    println!("{}", success(&message));
+    Ok(())
+}
+
+fn status() -> Result<(), AppError> {
+    let cwd = current_dir()?;
+    match HookService::find(&cwd)? {
+        Some(found) => {
+            let source = match &found.source {
+                SelectionSource::File(path) => path.display().to_string(),
+                SelectionSource::Settings => "settings".to_string(),
+            };
+            println!(
+                "{}",
+                highlight(&format!("Directory: {}", found.dir.display()))
+            );
+            println!("Source:    {source}");
+            println!("Selection: {}", found.describe());
+            if !found.trusted {
+                println!(
+                    "{}",
+                    warning("Not allowed: review the file and run `cc-switch hook allow`")
+                );
+            }
+        }
+        None => println!(
+            "{}",
+            info(&format!(
+                "No {PROJECT_FILE} or mapped directory applies to {}",
+                cwd.display()
+            ))
+        ),
+    }
+
+    let mappings = get_settings().directory_selections;
+    if !mappings.is_empty() {
+        println!();
+        let mut table = create_table();
+        table.set_header(vec!["Mapped directory", "Selection"]);
+        for (dir, selection) in mappings {
+            let described = ResolvedSelection {
+                dir: PathBuf::from(&dir),
+                source: SelectionSource::Settings,
+                selection,
+                trusted: true,
+            }
+            .describe();
+            table.add_row(vec![dir, described]);
+        }
+        println!("{}", table);
+    }
+    Ok(())
+}
+
+fn map(
+    dir: Option<PathBuf>,
+    profile: Option<String>,
+    provider: Option<String>,
+    mcp: Option<Vec<String>>,
+    app: AppType,
+) -> Result<(), AppError> {
+    if profile.is_none() && provider.is_none() && mcp.is_none() {
+        return Err(AppError::InvalidInput(
+            "Specify at least one of --profile, --provider or --mcp".to_string(),
+        ));
+    }
+    let dir = match dir {
+        Some(dir) => dir,
+        None => current_dir()?,
+    };
+
+    let mut selection = DirectorySelection {
+        profile,
+        ..Default::default()
+    };
+    if provider.is_some() || mcp.is_some() {
+        let app_selection = Some(DirectoryAppSelection {
+            provider,
+            mcp: mcp.map(|ids| ids.into_iter().filter(|id| !id.is_empty()).collect()),
+        });
+        match app {
+            AppType::Claude => selection.claude = app_selection,
+            AppType::Codex => selection.codex = app_selection,
+            AppType::Gemini => selection.gemini = app_selection,
+        }
+    }
+
+    let dir = HookService::set_mapping(&dir, Some(selection))?;
+    println!("{}", success(&format!("✓ Mapped {}", dir.display())));
+    Ok(())
+}
+
+fn unmap(dir: Option<PathBuf>) -> Result<(), AppError> {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => current_dir()?,
+    };
+    let dir = HookService::set_mapping(&dir, None)?;
+    println!(
+        "{}",
+        success(&format!("✓ Removed mapping for {}", dir.display()))
+    );
+    Ok(())
+}
@@ -1,21 +1,40 @@
 use clap::Subcommand;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
 
 use crate::app_config::{AppType, McpApps, McpServer};
-use crate::cli::ui::{create_table, error, highlight, info, success};
+use crate::cli::ui::{create_table, error, highlight, info, success, warning};
 use crate::error::AppError;
+use crate::mcp::{McpDriftKind, McpProjectScope, McpReconcileChoice, McpServerDrift};
 use crate::services::McpService;
 use crate::store::AppState;
 
 #[derive(Subcommand)]
 pub enum McpCommand {
     /// List all MCP servers
-    List,
+    List {
+        /// List servers configured in a project directory instead
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
+    },
     /// Add a new MCP server (interactive)
     Add,
     /// Edit an MCP server
     Edit {
         /// Server ID to edit
         id: String,
+        /// Overwrite manual edits in live files without asking
+        #[arg(long)]
+        force: bool,
+        /// Only expose these tools to the selected app (repeatable; replaces the allow list; Codex and Gemini only)
+        #[arg(long = "allow-tool", value_name = "TOOL")]
+        allow_tools: Vec<String>,
+        /// Hide these tools from the selected app (repeatable; replaces the deny list)
+        #[arg(long = "deny-tool", value_name = "TOOL")]
+        deny_tools: Vec<String>,
+        /// Remove the selected app's tool filter before applying --allow-tool/--deny-tool
+        #[arg(long)]
+        clear_tools: bool,
     },
     /// Delete an MCP server
     Delete {
@@ -26,11 +45,29 @@ pub enum McpCommand {
     Enable {
         /// Server ID to enable
         id: String,
+        /// Write to a project's MCP config instead of the user-level config
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
+        /// Use the project section of ~/.claude.json instead of <DIR>/.mcp.json (Claude only)
+        #[arg(long, requires = "project")]
+        local: bool,
+        /// Overwrite manual edits in live files without asking
+        #[arg(long, conflicts_with = "project")]
+        force: bool,
     },
     /// Disable an MCP server for specific app(s)
     Disable {
         /// Server ID to disable
         id: String,
+        /// Remove from a project's MCP config instead of the user-level config
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
+        /// Use the project section of ~/.claude.json instead of <DIR>/.mcp.json (Claude only)
+        #[arg(long, requires = "project")]
+        local: bool,
+        /// Overwrite manual edits in live files without asking
+        #[arg(long, conflicts_with = "project")]
+        force: bool,
     },
     /// Validate a command is in PATH
     Validate {
@@ -38,24 +75,108 @@ pub enum McpCommand {
         command: String,
     },
     /// Sync MCP configuration to live files
-    Sync,
+    Sync {
+        /// Overwrite manual edits in live files without asking
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show differences between live config files and cc-switch, per server
+    Diff,
+    /// Resolve drift by keeping the live or the stored version per server
+    Reconcile {
+        /// Server IDs to reconcile (default: all drifted servers)
+        ids: Vec<String>,
+        /// Apply the same choice to every server instead of prompting
+        #[arg(long, value_enum)]
+        keep: Option<McpReconcileChoice>,
+    },
     /// Import MCP servers from live configuration
-    Import,
+    Import {
+        /// Import project-level servers from a project directory instead
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
+    },
+    /// Manage secrets referenced as ${secret:NAME} in server specs
+    #[command(subcommand)]
+    Secrets(McpSecretsCommand),
+}
+
+#[derive(Subcommand)]
+pub enum McpSecretsCommand {
+    /// List secret names and the servers referencing them (values are never shown)
+    List,
+    /// Set a secret (prompts for the value, or reads it from stdin when piped)
+    Set {
+        /// Secret name (letters, digits, '_', '.', '-')
+        name: String,
+        /// Secret value. Unsafe: it stays in shell history and is visible to other users in `ps` output; prefer the prompt or stdin
+        #[arg(long)]
+        value: Option<String>,
+    },
+    /// Remove a secret
+    Remove {
+        /// Secret name
+        name: String,
+    },
 }
 
 pub fn execute(cmd: McpCommand, app: Option<AppType>) -> Result<(), AppError> {
-    let app_type = app.unwrap_or(AppType::Claude);
+    let app_type = app.clone().unwrap_or(AppType::Claude);
 
     match cmd {
-        McpCommand::List => list_servers(app_type),
+        McpCommand::List { project: None } => list_servers(app_type),
+        McpCommand::List { project: Some(dir) } => list_project_servers(&dir),
         McpCommand::Add => add_server(app_type),
-        McpCommand::Edit { id } => edit_server(app_type, &id),
+        McpCommand::Edit {
+            id,
+            force,
+            allow_tools,
+            deny_tools,
+            clear_tools,
+        } if clear_tools || !allow_tools.is_empty() || !deny_tools.is_empty() => {
+            set_tool_filter(app_type, &id, allow_tools, deny_tools, clear_tools, force)
+        }
+        McpCommand::Edit { id, force, .. } => edit_server(app_type, &id, force),
         McpCommand::Delete { id } => delete_server(&id),
-        McpCommand::Enable { id } => enable_server(app_type, &id),
-        McpCommand::Disable { id } => disable_server(app_type, &id),
+        McpCommand::Enable {
+            id,
+            project: None,
+            force,
+            ..
+        } => enable_server(app_type, &id, force),
+        McpCommand::Enable {
+            id,
+            project: Some(dir),
+            local,
+            ..
+        } => enable_project_server(app_type, &id, &dir, project_scope(local)),
+        McpCommand::Disable {
+            id,
+            project: None,
+            force,
+            ..
+        } => disable_server(app_type, &id, force),
+        McpCommand::Disable {
+            id,
+            project: Some(dir),
+            local,
+            ..
+        } => disable_project_server(app_type, &id, &dir, project_scope(local)),
         McpCommand::Validate { command } => validate_command(&command),
-        McpCommand::Sync => sync_servers(),
-        McpCommand::Import => import_servers(app_type),
+        McpCommand::Sync { force } => sync_servers(force),
+        McpCommand::Diff => diff_servers(app),
+        McpCommand::Reconcile { ids, keep } => reconcile_servers(app, &ids, keep),
+        McpCommand::Import { project: None } => import_servers(app_type),
+        McpCommand::Import { project: Some(dir) } => import_project_servers(&dir),
+        McpCommand::Secrets(cmd) => execute_secrets(cmd),
+    }
+}
+
+fn project_scope(local: bool) -> McpProjectScope {
+    if local {
+        McpProjectScope::Local
+    } else {
+        McpProjectScope::Shared
     }
 }
 
@@ -182,7 +303,7 @@ fn delete_server(id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-fn enable_server(app_type: AppType, id: &str) -> Result<(), AppError> {
+fn enable_server(app_type: AppType, id: &str, force: bool) -> Result<(), AppError> {
     let state = get_state()?;
     let app_str = app_type.as_str().to_string();
 
@@ -192,6 +313,14 @@ fn enable_server(app_type: AppType, id: &str) -> Result<(), AppError> {
         return Err(AppError::Message(format!("MCP server '{}' not found", id)));
     }
 
+    if !force && !confirm_overwrite_drift(&state, std::slice::from_ref(&app_type), Some(id))? {
+        println!("{}", info("Cancelled."));
+        return Ok(());
+    }
+
+    // 写入 live 配置前提示未定义的密钥
+    warn_unresolved_secrets(&state, Some(id))?;
+
     // 执行启用
     McpService::toggle_app(&state, id, app_type, true)?;
 
@@ -207,7 +336,7 @@ fn enable_server(app_type: AppType, id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-fn disable_server(app_type: AppType, id: &str) -> Result<(), AppError> {
+fn disable_server(app_type: AppType, id: &str, force: bool) -> Result<(), AppError> {
     let state = get_state()?;
     let app_str = app_type.as_str().to_string();
 
@@ -217,6 +346,11 @@ fn disable_server(app_type: AppType, id: &str) -> Result<(), AppError> {
         return Err(AppError::Message(format!("MCP server '{}' not found", id)));
     }
 
+    if !force && !confirm_overwrite_drift(&state, std::slice::from_ref(&app_type), Some(id))? {
+        println!("{}", info("Cancelled."));
+        return Ok(());
+    }
+
     // 执行禁用
     McpService::toggle_app(&state, id, app_type, false)?;
 
@@ -232,10 +366,16 @@ fn disable_server(app_type: AppType, id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-fn sync_servers() -> Result<(), AppError> {
+fn sync_servers(force: bool) -> Result<(), AppError> {
     let state = get_state()?;
 
+    if !force && !confirm_overwrite_drift(&state, &target_apps(None), None)? {
+        println!("{}", info("Cancelled."));
+        return Ok(());
+    }
+
     println!("{}", info("Syncing all enabled MCP servers..."));
+    warn_unresolved_secrets(&state, None)?;
 
     McpService::sync_all_enabled(&state)?;
 
@@ -248,6 +388,211 @@ fn sync_servers() -> Result<(), AppError> {
     Ok(())
 }
 
+/// 写入 live 配置前检查其中的手动修改，避免静默覆盖
+///
+/// `id` 为 None 时检查全部服务器（live 独有的服务器不会被同步覆盖，不计入）。
+pub(crate) fn confirm_overwrite_drift(
+    state: &AppState,
+    apps: &[AppType],
+    id: Option<&str>,
+) -> Result<bool, AppError> {
+    let mut drifted = Vec::new();
+    for app in apps {
+        for drift in McpService::diff_live(state, app)? {
+            let affected = match id {
+                Some(id) => drift.id == id,
+                None => drift.kind != McpDriftKind::Added,
+            };
+            if affected {
+                drifted.push(format!("{}/{}", app.as_str(), drift.id));
+            }
+        }
+    }
+
+    if drifted.is_empty() {
+        return Ok(true);
+    }
+
+    println!(
+        "{}",
+        warning(&format!(
+            "⚠ Live MCP config was edited outside cc-switch: {}",
+            drifted.join(", ")
+        ))
+    );
+    println!(
+        "{}",
+        info(
+            "Run 'cc-switch mcp diff' to inspect or 'cc-switch mcp reconcile' to keep those edits."
+        )
+    );
+
+    inquire::Confirm::new("Overwrite the live changes with the stored versions?")
+        .with_default(false)
+        .prompt()
+        .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))
+}
+
+fn target_apps(app: Option<AppType>) -> Vec<AppType> {
+    match app {
+        Some(app) => vec![app],
+        None => vec![AppType::Claude, AppType::Codex, AppType::Gemini],
+    }
+}
+
+fn live_config_label(app: &AppType) -> String {
+    let path = match app {
+        AppType::Claude => crate::config::get_claude_mcp_path(),
+        AppType::Codex => crate::codex_config::get_codex_config_path(),
+        AppType::Gemini => crate::gemini_config::get_gemini_settings_path(),
+    };
+    format!("{} ({})", app.as_str(), path.display())
+}
+
+fn format_field_value(value: Option<&serde_json::Value>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "(unset)".to_string())
+}
+
+fn print_drift(drift: &McpServerDrift) {
+    match drift.kind {
+        McpDriftKind::Added => println!("  {} {}  only in live config", success("+"), drift.id),
+        McpDriftKind::Removed => println!(
+            "  {} {}  enabled in cc-switch but missing from live config",
+            error("-"),
+            drift.id
+        ),
+        McpDriftKind::Changed => println!("  {} {}", warning("~"), drift.id),
+    }
+    for change in &drift.fields {
+        println!(
+            "      {}: {} → {}",
+            change.path_label(),
+            format_field_value(change.stored.as_ref()),
+            format_field_value(change.live.as_ref())
+        );
+    }
+}
+
+fn diff_servers(app: Option<AppType>) -> Result<(), AppError> {
+    let state = get_state()?;
+    let mut total = 0;
+
+    for app in target_apps(app) {
+        let drifts = McpService::diff_live(&state, &app)?;
+        println!("{}", highlight(&live_config_label(&app)));
+        if drifts.is_empty() {
+            println!("  {}", success("✓ In sync"));
+        } else {
+            for drift in &drifts {
+                print_drift(drift);
+            }
+        }
+        println!();
+        total += drifts.len();
+    }
+
+    if total > 0 {
+        println!(
+            "{}",
+            info("Run 'cc-switch mcp reconcile' to keep the live or stored version per server.")
+        );
+    }
+    Ok(())
+}
+
+fn reconcile_servers(
+    app: Option<AppType>,
+    ids: &[String],
+    keep: Option<McpReconcileChoice>,
+) -> Result<(), AppError> {
+    let state = get_state()?;
+    let mut found = 0;
+    let mut reconciled = 0;
+
+    for app in target_apps(app) {
+        let drifts: Vec<_> = McpService::diff_live(&state, &app)?
+            .into_iter()
+            .filter(|d| ids.is_empty() || ids.contains(&d.id))
+            .collect();
+        if drifts.is_empty() {
+            continue;
+        }
+
+        println!("{}", highlight(&live_config_label(&app)));
+        for drift in drifts {
+            found += 1;
+            print_drift(&drift);
+
+            let choice = match keep {
+                Some(choice) => choice,
+                None => match prompt_reconcile_choice(&drift)? {
+                    Some(choice) => choice,
+                    None => {
+                        println!("    {}", info("Skipped"));
+                        continue;
+                    }
+                },
+            };
+
+            McpService::reconcile(&state, &app, &drift.id, choice)?;
+            reconciled += 1;
+            let kept = match choice {
+                McpReconcileChoice::Live => "✓ Kept live version",
+                McpReconcileChoice::Stored => "✓ Kept stored version",
+            };
+            println!("    {}", success(kept));
+        }
+        println!();
+    }
+
+    if found == 0 {
+        println!("{}", success("✓ Live MCP config matches cc-switch"));
+    } else {
+        println!(
+            "{}",
+            success(&format!(
+                "✓ Reconciled {} of {} server(s)",
+                reconciled, found
+            ))
+        );
+    }
+    Ok(())
+}
+
+fn prompt_reconcile_choice(drift: &McpServerDrift) -> Result<Option<McpReconcileChoice>, AppError> {
+    let (live_label, stored_label) = match drift.kind {
+        McpDriftKind::Added => (
+            "Keep live (import into cc-switch)",
+            "Keep stored (remove from live config)",
+        ),
+        McpDriftKind::Removed => (
+            "Keep live (disable for this app)",
+            "Keep stored (restore in live config)",
+        ),
+        McpDriftKind::Changed => (
+            "Keep live (update cc-switch)",
+            "Keep stored (rewrite live config)",
+        ),
+    };
+
+    let selected = inquire::Select::new(
+        &format!("Resolve '{}':", drift.id),
+        vec![live_label, stored_label, "Skip"],
+    )
+    .prompt()
+    .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?;
+
+    Ok(if selected == live_label {
+        Some(McpReconcileChoice::Live)
+    } else if selected == stored_label {
+        Some(McpReconcileChoice::Stored)
+    } else {
+        None
+    })
+}
+
 fn import_servers(app_type: AppType) -> Result<(), AppError> {
     let state = get_state()?;
     let app_str = app_type.as_str().to_string();
@@ -288,6 +633,188 @@ fn import_servers(app_type: AppType) -> Result<(), AppError> {
     Ok(())
 }
 
+fn project_config_label(app_type: &AppType, dir: &Path, scope: McpProjectScope) -> String {
+    match (app_type, scope) {
+        (AppType::Claude, McpProjectScope::Local) => format!(
+            "~/.claude.json (project {})",
+            crate::claude_mcp::project_key(dir)
+        ),
+        (AppType::Claude, McpProjectScope::Shared) => crate::claude_mcp::get_project_mcp_path(dir)
+            .display()
+            .to_string(),
+        _ => crate::gemini_mcp::get_project_settings_path(dir)
+            .display()
+            .to_string(),
+    }
+}
+
+fn enable_project_server(
+    app_type: AppType,
+    id: &str,
+    dir: &Path,
+    scope: McpProjectScope,
+) -> Result<(), AppError> {
+    let state = get_state()?;
+
+    warn_unresolved_secrets(&state, Some(id))?;
+    McpService::enable_in_project(&state, id, &app_type, dir, scope)?;
+
+    println!(
+        "{}",
+        success(&format!(
+            "✓ Enabled MCP server '{}' for {} in project",
+            id,
+            app_type.as_str()
+        ))
+    );
+    println!(
+        "{}",
+        info(&format!(
+            "  Written to: {}",
+            project_config_label(&app_type, dir, scope)
+        ))
+    );
+
+    Ok(())
+}
+
+fn disable_project_server(
+    app_type: AppType,
+    id: &str,
+    dir: &Path,
+    scope: McpProjectScope,
+) -> Result<(), AppError> {
+    let removed = McpService::disable_in_project(id, &app_type, dir, scope)?;
+    let label = project_config_label(&app_type, dir, scope);
+
+    if removed {
+        println!(
+            "{}",
+            success(&format!(
+                "✓ Disabled MCP server '{}' for {} in project",
+                id,
+                app_type.as_str()
+            ))
+        );
+        println!("{}", info(&format!("  Removed from: {}", label)));
+    } else {
+        println!(
+            "{}",
+            info(&format!(
+                "MCP server '{}' is not configured in {}",
+                id, label
+            ))
+        );
+    }
+
+    Ok(())
+}
+
+fn list_project_servers(dir: &Path) -> Result<(), AppError> {
+    let state = get_state()?;
+    let managed = McpService::get_all_servers(&state)?;
+
+    let sources = [
+        (
+            "Claude (.mcp.json)",
+            AppType::Claude,
+            McpProjectScope::Shared,
+        ),
+        ("Claude (local)", AppType::Claude, McpProjectScope::Local),
+        ("Gemini", AppType::Gemini, McpProjectScope::Shared),
+    ];
+
+    let mut rows: Vec<(String, Vec<bool>)> = Vec::new();
+    for (index, (_, app, scope)) in sources.iter().enumerate() {
+        for id in McpService::get_project_servers(app, dir, *scope)?.into_keys() {
+            let pos = match rows.iter().position(|(existing, _)| *existing == id) {
+                Some(pos) => pos,
+                None => {
+                    rows.push((id, vec![false; sources.len()]));
+                    rows.len() - 1
+                }
+            };
+            rows[pos].1[index] = true;
+        }
+    }
+
+    if rows.is_empty() {
+        println!(
+            "{}",
+            info(&format!(
+                "No project MCP servers found in {}.",
+                dir.display()
+            ))
+        );
+        println!("Use 'cc-switch mcp enable <id> --project <dir>' to add one.");
+        return Ok(());
+    }
+
+    rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut table = create_table();
+    let mut header = vec!["ID"];
+    header.extend(sources.iter().map(|(label, _, _)| *label));
+    header.push("Managed");
+    table.set_header(header);
+
+    for (id, present) in rows {
+        let mut row = vec![id.clone()];
+        row.extend(
+            present
+                .iter()
+                .map(|p| if *p { "✓" } else { " " }.to_string()),
+        );
+        row.push(
+            if managed.contains_key(&id) {
+                "✓"
+            } else {
+                " "
+            }
+            .to_string(),
+        );
+        table.add_row(row);
+    }
+
+    println!("{}", table);
+    println!("\n{} Project: {}", info("ℹ"), dir.display());
+    println!(
+        "{} Managed = stored in cc-switch (use 'cc-switch mcp import --project' to import)",
+        info("→")
+    );
+
+    Ok(())
+}
+
+fn import_project_servers(dir: &Path) -> Result<(), AppError> {
+    let state = get_state()?;
+
+    println!(
+        "{}",
+        info(&format!(
+            "Importing MCP servers from project {}...",
+            dir.display()
+        ))
+    );
+
+    let count = McpService::import_from_project(&state, dir)?;
+
+    if count > 0 {
+        println!(
+            "{}",
+            success(&format!("✓ Imported {} MCP server(s) from project", count))
+        );
+        println!(
+            "{}",
+            info("Note: Imported servers are not enabled for any app at user level.")
+        );
+    } else {
+        println!("{}", info("No new MCP servers found in project config."));
+    }
+
+    Ok(())
+}
+
 fn add_server(_app_type: AppType) -> Result<(), AppError> {
     let state = get_state()?;
 
@@ -306,6 +833,8 @@ fn add_server(_app_type: AppType) -> Result<(), AppError> {
         homepage: None,
         docs: None,
         tags: vec![],
+        codex_toml: None,
+        tools: Default::default(),
     };
     let initial = serde_json::to_string_pretty(&template)
         .map_err(|e| AppError::Message(format!("failed to serialize template: {e}")))?;
@@ -323,6 +852,11 @@ fn add_server(_app_type: AppType) -> Result<(), AppError> {
         ));
     }
 
+    if !confirm_overwrite_drift(&state, &server.apps.enabled_apps(), Some(&server.id))? {
+        println!("{}", info("Cancelled."));
+        return Ok(());
+    }
+    warn_missing_secrets(&server)?;
     McpService::upsert_server(&state, server)?;
 
     println!("{}", success("✓ MCP server saved"));
@@ -333,7 +867,7 @@ fn add_server(_app_type: AppType) -> Result<(), AppError> {
     Ok(())
 }
 
-fn edit_server(_app_type: AppType, id: &str) -> Result<(), AppError> {
+fn edit_server(_app_type: AppType, id: &str, force: bool) -> Result<(), AppError> {
     let state = get_state()?;
     let servers = McpService::get_all_servers(&state)?;
     let Some(existing) = servers.get(id).cloned() else {
@@ -364,12 +898,224 @@ fn edit_server(_app_type: AppType, id: &str) -> Result<(), AppError> {
         ));
     }
 
+    let mut apps = existing.apps.enabled_apps();
+    apps.extend(
+        server
+            .apps
+            .enabled_apps()
+            .into_iter()
+            .filter(|app| !existing.apps.is_enabled_for(app)),
+    );
+    if !force && !confirm_overwrite_drift(&state, &apps, Some(id))? {
+        println!("{}", info("Cancelled."));
+        return Ok(());
+    }
+    warn_missing_secrets(&server)?;
     McpService::upsert_server(&state, server)?;
 
     println!("{}", success("✓ MCP server updated"));
     Ok(())
 }
 
+fn set_tool_filter(
+    app_type: AppType,
+    id: &str,
+    allow_tools: Vec<String>,
+    deny_tools: Vec<String>,
+    clear_tools: bool,
+    force: bool,
+) -> Result<(), AppError> {
+    let state = get_state()?;
+    let Some(mut server) = McpService::get_all_servers(&state)?.remove(id) else {
+        return Err(AppError::InvalidInput(format!(
+            "MCP server '{id}' not found"
+        )));
+    };
+
+    let filter = server.tools.for_app_mut(&app_type);
+    if clear_tools {
+        *filter = Default::default();
+    }
+    if !allow_tools.is_empty() {
+        filter.allow = allow_tools;
+    }
+    if !deny_tools.is_empty() {
+        filter.deny = deny_tools;
+    }
+    let summary = if filter.is_empty() {
+        "all tools".to_string()
+    } else {
+        format!(
+            "allow [{}], deny [{}]",
+            filter.allow.join(", "),
+            filter.deny.join(", ")
+        )
+    };
+    let enabled = server.apps.is_enabled_for(&app_type);
+
+    if !force && !confirm_overwrite_drift(&state, &server.apps.enabled_apps(), Some(id))? {
+        println!("{}", info("Cancelled."));
+        return Ok(());
+    }
+    McpService::upsert_server(&state, server)?;
+
+    println!(
+        "{}",
+        success(&format!(
+            "✓ Tool filter for '{}' in {}: {}",
+            id,
+            app_type.as_str(),
+            summary
+        ))
+    );
+    if !enabled {
+        println!(
+            "{}",
+            info(&format!(
+                "  Takes effect once enabled: cc-switch mcp enable {} --app {}",
+                id,
+                app_type.as_str()
+            ))
+        );
+    }
+    Ok(())
+}
+
+/// 提示引用了未定义密钥的服务器（占位符会原样写入 live 配置），须在写入前调用
+fn warn_unresolved_secrets(state: &AppState, only_id: Option<&str>) -> Result<(), AppError> {
+    for (server_id, names) in McpService::unresolved_secrets(state, only_id)? {
+        print_unresolved_secrets(&server_id, &names);
+    }
+    Ok(())
+}
+
+/// 同上，用于尚未保存的服务器规范
+fn warn_missing_secrets(server: &McpServer) -> Result<(), AppError> {
+    let names = McpService::missing_secrets(&server.server)?;
+    if !names.is_empty() {
+        print_unresolved_secrets(&server.id, &names);
+    }
+    Ok(())
+}
+
+fn print_unresolved_secrets(server_id: &str, names: &[String]) {
+    println!(
+        "{}",
+        warning(&format!(
+            "⚠ MCP server '{}' references undefined secret(s): {} (written as literal placeholders)",
+            server_id,
+            names.join(", ")
+        ))
+    );
+    println!(
+        "{}",
+        info(&format!(
+            "  Define with: cc-switch mcp secrets set {}",
+            names[0]
+        ))
+    );
+}
+
+fn execute_secrets(cmd: McpSecretsCommand) -> Result<(), AppError> {
+    match cmd {
+        McpSecretsCommand::List => list_secrets(),
+        McpSecretsCommand::Set { name, value } => set_secret(&name, value),
+        McpSecretsCommand::Remove { name } => remove_secret(&name),
+    }
+}
+
+fn list_secrets() -> Result<(), AppError> {
+    let state = get_state()?;
+    let usages = McpService::list_secrets(&state)?;
+
+    if usages.is_empty() {
+        println!("{}", info("No secrets defined."));
+        println!("Reference secrets in server specs as ${{secret:NAME}} and define them with 'cc-switch mcp secrets set NAME'.");
+        return Ok(());
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["Name", "Defined", "Used by"]);
+    for usage in usages {
+        table.add_row(vec![
+            usage.name,
+            if usage.defined { "✓" } else { "✗" }.to_string(),
+            usage.servers.join(", "),
+        ]);
+    }
+
+    println!("{}", table);
+    println!(
+        "\n{} Stored in {} (excluded from exports and backups)",
+        info("ℹ"),
+        crate::secrets::SecretStore::path().display()
+    );
+    Ok(())
+}
+
+fn set_secret(name: &str, value: Option<String>) -> Result<(), AppError> {
+    let state = get_state()?;
+
+    let value = match value {
+        Some(v) => {
+            println!(
+                "{}",
+                warning("⚠ --value leaves the secret in shell history; omit it to be prompted")
+            );
+            v
+        }
+        None if !std::io::stdin().is_terminal() => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| AppError::Message(format!("Failed to read stdin: {}", e)))?;
+            input.trim_end_matches(['\n', '\r']).to_string()
+        }
+        None => inquire::Password::new(&format!("Value for secret '{}':", name))
+            .without_confirmation()
+            .with_display_mode(inquire::PasswordDisplayMode::Masked)
+            .prompt()
+            .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?,
+    };
+    if value.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "Secret '{}' must not be empty",
+            name
+        )));
+    }
+
+    let resynced = McpService::set_secret(&state, name, &value)?;
+
+    println!("{}", success(&format!("✓ Secret '{}' saved", name)));
+    if resynced > 0 {
+        println!(
+            "{}",
+            info(&format!(
+                "  Re-synced {} MCP server(s) referencing it",
+                resynced
+            ))
+        );
+    }
+    Ok(())
+}
+
+fn remove_secret(name: &str) -> Result<(), AppError> {
+    let state = get_state()?;
+
+    // 删除前提示：这些服务器重新同步后会写入原样占位符
+    if crate::secrets::SecretStore::load()?.contains(name) {
+        for server_id in McpService::secret_users(&state, name)? {
+            print_unresolved_secrets(&server_id, &[name.to_string()]);
+        }
+    }
+    if McpService::remove_secret(&state, name)? {
+        println!("{}", success(&format!("✓ Secret '{}' removed", name)));
+    } else {
+        println!("{}", info(&format!("Secret '{}' not found.", name)));
+    }
+    Ok(())
+}
+
 fn validate_command(command: &str) -> Result<(), AppError> {
     println!("{}", info(&format!("Validating command '{}'...", command)));
 
@@ -1,7 +1,13 @@
 pub mod config;
+pub mod doctor;
 pub mod env;
+pub mod history;
+pub mod hook;
 pub mod mcp;
+pub mod profile;
 pub mod prompts;
 pub mod provider;
 pub mod provider_input;
 pub mod skills;
+pub mod sync;
+pub mod watch;
@@ -0,0 +1,196 @@
+use clap::Subcommand;
+
+use crate::app_config::AppType;
+use crate::cli::ui::{create_table, highlight, info, success};
+use crate::error::AppError;
+use crate::profile::{Profile, ProfileApp};
+use crate::services::ProfileService;
+use crate::store::AppState;
+
+#[derive(Subcommand)]
+pub enum ProfileCommand {
+    /// List saved profiles
+    List,
+    /// Show what a profile selects for each app
+    Show {
+        /// Profile name
+        name: String,
+    },
+    /// Save the current provider, MCP servers, prompt stack and skills as a profile
+    /// (all apps unless --app is given)
+    Save {
+        /// Profile name
+        name: String,
+        /// Profile description
+        #[arg(long, short = 'd')]
+        description: Option<String>,
+        /// Overwrite an existing profile
+        #[arg(long)]
+        force: bool,
+    },
+    /// Apply a profile to every app it covers (rolled back if any step fails)
+    Use {
+        /// Profile name
+        name: String,
+    },
+    /// Delete a profile
+    Delete {
+        /// Profile name
+        name: String,
+        /// Skip confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+pub fn execute(cmd: ProfileCommand, app: Option<AppType>) -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    match cmd {
+        ProfileCommand::List => list_profiles(&state),
+        ProfileCommand::Show { name } => show_profile(&state, &name),
+        ProfileCommand::Save {
+            name,
+            description,
+            force,
+        } => save_profile(&state, &name, description, app, force),
+        ProfileCommand::Use { name } => use_profile(&state, &name),
+        ProfileCommand::Delete { name, yes } => delete_profile(&state, &name, yes),
+    }
+}
+
+fn list_profiles(state: &AppState) -> Result<(), AppError> {
+    let profiles = ProfileService::list(state)?;
+    if profiles.is_empty() {
+        println!(
+            "{}",
+            info("No profiles saved yet. Run `cc-switch profile save <name>` to create one.")
+        );
+        return Ok(());
+    }
+
+    let active = ProfileService::active(state)?;
+    let mut table = create_table();
+    table.set_header(vec!["", "Name", "Apps", "Description"]);
+    for profile in &profiles {
+        let marker = if active.as_deref() == Some(profile.name.as_str()) {
+            "✓"
+        } else {
+            ""
+        };
+        let apps: Vec<String> = profile
+            .apps
+            .iter()
+            .map(|(app, selection)| format!("{app}: {}", summary(selection)))
+            .collect();
+        table.add_row(vec![
+            marker.to_string(),
+            profile.name.clone(),
+            apps.join("\n"),
+            profile.description.clone().unwrap_or_default(),
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
+
+fn show_profile(state: &AppState, name: &str) -> Result<(), AppError> {
+    let profile = ProfileService::get(state, name)?;
+    print_profile(&profile);
+    Ok(())
+}
+
+fn save_profile(
+    state: &AppState,
+    name: &str,
+    description: Option<String>,
+    app: Option<AppType>,
+    force: bool,
+) -> Result<(), AppError> {
+    let apps = match app {
+        Some(app) => vec![app],
+        None => vec![AppType::Claude, AppType::Codex, AppType::Gemini],
+    };
+    let profile = ProfileService::save_current(state, name, description, &apps, force)?;
+    println!(
+        "{}",
+        success(&format!("✓ Saved profile '{}'", profile.name))
+    );
+    print_profile(&profile);
+    Ok(())
+}
+
+fn use_profile(state: &AppState, name: &str) -> Result<(), AppError> {
+    let profile = ProfileService::use_profile(state, name)?;
+    println!(
+        "{}",
+        success(&format!("✓ Applied profile '{}'", profile.name))
+    );
+    for (app, selection) in &profile.apps {
+        println!("  {app}: {}", summary(selection));
+    }
+    println!(
+        "{}",
+        info("Restart the affected CLI tools to pick up the new configuration.")
+    );
+    Ok(())
+}
+
+fn delete_profile(state: &AppState, name: &str, yes: bool) -> Result<(), AppError> {
+    let profile = ProfileService::get(state, name)?;
+    if !yes {
+        let confirm = inquire::Confirm::new(&format!(
+            "Are you sure you want to delete profile '{}'?",
+            profile.name
+        ))
+        .with_default(false)
+        .prompt()
+        .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?;
+        if !confirm {
+            println!("{}", info("Cancelled."));
+            return Ok(());
+        }
+    }
+
+    ProfileService::delete(state, &profile.name)?;
+    println!(
+        "{}",
+        success(&format!("✓ Deleted profile '{}'", profile.name))
+    );
+    Ok(())
+}
+
+fn print_profile(profile: &Profile) {
+    println!("{}", highlight(&format!("Profile: {}", profile.name)));
+    if let Some(description) = &profile.description {
+        println!("{}", description);
+    }
+    for (app, selection) in &profile.apps {
+        println!();
+        println!("{}", highlight(app));
+        println!(
+            "  Provider:    {}",
+            selection.provider.as_deref().unwrap_or("(unchanged)")
+        );
+        println!("  MCP servers: {}", list_or_none(&selection.mcp_servers));
+        println!("  Prompts:     {}", list_or_none(&selection.prompts));
+        println!("  Skills:      {}", list_or_none(&selection.skills));
+    }
+}
+
+fn summary(selection: &ProfileApp) -> String {
+    format!(
+        "{}, {} MCP, {} prompt(s), {} skill(s)",
+        selection.provider.as_deref().unwrap_or("-"),
+        selection.mcp_servers.len(),
+        selection.prompts.len(),
+        selection.skills.len()
+    )
+}
+
+fn list_or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "(none)".to_string()
+    } else {
+        items.join(", ")
+    }
+}
@@ -1,9 +1,11 @@
 use clap::Subcommand;
+use std::path::{Path, PathBuf};
 
 use crate::app_config::AppType;
-use crate::cli::ui::{create_table, highlight, info, success};
+use crate::cli::ui::{create_table, highlight, info, success, warning};
 use crate::error::AppError;
 use crate::prompt::Prompt;
+use crate::prompt_files::{project_prompt_file_path, PromptFileLayout};
 use crate::services::PromptService;
 use crate::store::AppState;
 
@@ -11,15 +13,30 @@ use crate::store::AppState;
 pub enum PromptsCommand {
     /// List all prompt presets
     List,
-    /// Show current active prompt
+    /// Show the active prompt layers
     Current,
-    /// Activate a prompt preset
+    /// Activate a prompt preset as the only layer (replaces the layer stack)
     Activate {
         /// Prompt preset ID
         id: String,
+        /// Write to <DIR>/CLAUDE.md, AGENTS.md or GEMINI.md instead of the user-level file
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
     },
-    /// Deactivate the current active prompt
-    Deactivate,
+    /// Deactivate all prompt layers (content outside cc-switch markers is kept)
+    Deactivate {
+        /// Remove the preset from a project's prompt file instead
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
+    },
+    /// Import the live prompt file as a new preset
+    Import {
+        /// Import <DIR>/CLAUDE.md, AGENTS.md or GEMINI.md instead of the user-level file
+        #[arg(long, value_name = "DIR")]
+        project: Option<PathBuf>,
+    },
+    /// List projects using prompt presets (all apps unless --app is given)
+    Projects,
     /// Create a new prompt preset
     Create,
     /// Edit a prompt preset
@@ -37,23 +54,118 @@ pub enum PromptsCommand {
         /// Prompt preset ID
         id: String,
     },
+    /// Preview a prompt rendered for the selected app (variables, includes, app sections)
+    Render {
+        /// Prompt preset ID
+        id: String,
+    },
+    /// Manage the ordered stack of active prompt layers
+    #[command(subcommand)]
+    Layer(PromptLayerCommand),
+}
+
+#[derive(Subcommand)]
+pub enum PromptLayerCommand {
+    /// List active layers in the order they are written to the prompt file
+    List,
+    /// Add a prompt as a layer (appended unless --position is given)
+    Add {
+        /// Prompt preset ID
+        id: String,
+        /// 1-based position in the stack
+        #[arg(long)]
+        position: Option<usize>,
+    },
+    /// Remove a layer from the stack
+    Remove {
+        /// Prompt preset ID
+        id: String,
+    },
+    /// Move a layer to another position
+    Move {
+        /// Prompt preset ID
+        id: String,
+        /// 1-based position in the stack
+        position: usize,
+    },
 }
 
 pub fn execute(cmd: PromptsCommand, app: Option<AppType>) -> Result<(), AppError> {
+    if let PromptsCommand::Projects = cmd {
+        return list_projects(app);
+    }
     let app_type = app.unwrap_or(AppType::Claude);
 
     match cmd {
         PromptsCommand::List => list_prompts(app_type),
         PromptsCommand::Current => show_current(app_type),
-        PromptsCommand::Activate { id } => activate_prompt(app_type, &id),
-        PromptsCommand::Deactivate => deactivate_prompt(app_type),
+        PromptsCommand::Activate { id, project: None } => activate_prompt(app_type, &id),
+        PromptsCommand::Activate {
+            id,
+            project: Some(dir),
+        } => activate_project_prompt(app_type, &id, &dir),
+        PromptsCommand::Deactivate { project: None } => deactivate_prompt(app_type),
+        PromptsCommand::Deactivate { project: Some(dir) } => {
+            deactivate_project_prompt(app_type, &dir)
+        }
+        PromptsCommand::Import { project } => import_prompt(app_type, project.as_deref()),
+        PromptsCommand::Projects => list_projects(Some(app_type)),
         PromptsCommand::Create => create_prompt(app_type),
         PromptsCommand::Edit { id } => edit_prompt(app_type, &id),
         PromptsCommand::Delete { id } => delete_prompt(app_type, &id),
         PromptsCommand::Show { id } => show_prompt(app_type, &id),
+        PromptsCommand::Render { id } => render_prompt(app_type, &id),
+        PromptsCommand::Layer(cmd) => execute_layer(cmd, app_type),
     }
 }
 
+fn execute_layer(cmd: PromptLayerCommand, app_type: AppType) -> Result<(), AppError> {
+    let state = get_state()?;
+    match cmd {
+        PromptLayerCommand::List => {}
+        PromptLayerCommand::Add { id, position } => {
+            PromptService::add_layer(&state, app_type.clone(), &id, position)?;
+            println!("{}", success(&format!("✓ Added layer '{id}'")));
+        }
+        PromptLayerCommand::Remove { id } => {
+            PromptService::disable_prompt(&state, app_type.clone(), &id)?;
+            println!("{}", success(&format!("✓ Removed layer '{id}'")));
+        }
+        PromptLayerCommand::Move { id, position } => {
+            PromptService::move_layer(&state, app_type.clone(), &id, position)?;
+            println!("{}", success(&format!("✓ Moved layer '{id}'")));
+        }
+    }
+    print_stack(&state, &app_type)
+}
+
+fn print_stack(state: &AppState, app_type: &AppType) -> Result<(), AppError> {
+    let stack = PromptService::active_stack(state, app_type.clone())?;
+    if stack.is_empty() {
+        println!("{}", info("No active prompt layers."));
+        println!("Use 'cc-switch prompts layer add <id>' to add a layer.");
+        return Ok(());
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["#", "ID", "Name", "Lines"]);
+    for (index, prompt) in stack.iter().enumerate() {
+        table.add_row(vec![
+            (index + 1).to_string(),
+            prompt.id.clone(),
+            prompt.name.clone(),
+            prompt.content.lines().count().to_string(),
+        ]);
+    }
+    println!("{}", table);
+    println!("\n{} Application: {}", info("ℹ"), app_type.as_str());
+    println!(
+        "{} Layers are written in this order between cc-switch markers; other content in the file is kept.",
+        info("→")
+    );
+    Ok(())
+}
+
 fn get_state() -> Result<AppState, AppError> {
     AppState::try_new()
 }
@@ -77,7 +189,14 @@ fn list_prompts(app_type: AppType) -> Result<(), AppError> {
     prompt_list.sort_by(|(_, a), (_, b)| b.updated_at.unwrap_or(0).cmp(&a.updated_at.unwrap_or(0)));
 
     for (id, prompt) in prompt_list {
-        let enabled_marker = if prompt.enabled { "✓" } else { " " };
+        let enabled_marker = if prompt.enabled {
+            match prompt.stack_order {
+                Some(order) => format!("✓{order}"),
+                None => "✓".to_string(),
+            }
+        } else {
+            String::new()
+        };
         let updated = prompt
             .updated_at
             .map(|ts| {
@@ -102,7 +221,7 @@ fn list_prompts(app_type: AppType) -> Result<(), AppError> {
         };
 
         let row = vec![
-            enabled_marker.to_string(),
+            enabled_marker,
             id.clone(),
             prompt.name.clone(),
             description,
@@ -114,60 +233,64 @@ fn list_prompts(app_type: AppType) -> Result<(), AppError> {
 
     println!("{}", table);
     println!("\n{} Application: {}", info("ℹ"), app_type.as_str());
-    println!("{} ✓ = Currently active", info("→"));
+    println!("{} ✓N = Active layer N", info("→"));
 
     Ok(())
 }
 
 fn show_current(app_type: AppType) -> Result<(), AppError> {
     let state = get_state()?;
-    let prompts = PromptService::get_prompts(&state, app_type.clone())?;
+    let stack = PromptService::active_stack(&state, app_type.clone())?;
 
-    // 找到当前激活的 prompt
-    let active = prompts
-        .iter()
-        .find(|(_, p)| p.enabled)
-        .map(|(id, p)| (id.clone(), p.clone()));
-
-    match active {
-        Some((id, prompt)) => {
-            let updated = prompt
-                .updated_at
-                .and_then(|ts| {
-                    use chrono::{DateTime, Utc};
-                    DateTime::<Utc>::from_timestamp(ts, 0)
-                        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                })
-                .unwrap_or_else(|| "Unknown".to_string());
+    if stack.is_empty() {
+        println!("{}", info("No active prompt preset."));
+        println!("Use 'cc-switch prompts activate <id>' to activate a prompt.");
+        return Ok(());
+    }
 
-            println!("{}", highlight("Current Active Prompt"));
-            println!("{}", "=".repeat(50));
-            println!("ID:          {}", id);
-            println!("Name:        {}", prompt.name);
-            if let Some(desc) = &prompt.description {
-                println!("Description: {}", desc);
-            }
-            println!("Updated:     {}", updated);
-            println!("App:         {}", app_type.as_str());
-            println!();
-            println!("{}", highlight("Content Preview:"));
-            println!("{}", "-".repeat(50));
-
-            // 显示内容预览（前 10 行）
-            let lines: Vec<&str> = prompt.content.lines().collect();
-            let preview_lines = lines.iter().take(10);
-            for line in preview_lines {
-                println!("{}", line);
-            }
+    let total = stack.len();
+    for (index, prompt) in stack.into_iter().enumerate() {
+        let updated = prompt
+            .updated_at
+            .and_then(|ts| {
+                use chrono::{DateTime, Utc};
+                DateTime::<Utc>::from_timestamp(ts, 0)
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            })
+            .unwrap_or_else(|| "Unknown".to_string());
 
-            if lines.len() > 10 {
-                println!("...");
-                println!("{}", info(&format!("({} more lines)", lines.len() - 10)));
-            }
+        if index > 0 {
+            println!();
         }
-        None => {
-            println!("{}", info("No active prompt preset."));
-            println!("Use 'cc-switch prompts activate <id>' to activate a prompt.");
+        if total == 1 {
+            println!("{}", highlight("Current Active Prompt"));
+        } else {
+            println!(
+                "{}",
+                highlight(&format!("Active Prompt Layer {}/{}", index + 1, total))
+            );
+        }
+        println!("{}", "=".repeat(50));
+        println!("ID:          {}", prompt.id);
+        println!("Name:        {}", prompt.name);
+        if let Some(desc) = &prompt.description {
+            println!("Description: {}", desc);
+        }
+        println!("Updated:     {}", updated);
+        println!("App:         {}", app_type.as_str());
+        println!();
+        println!("{}", highlight("Content Preview:"));
+        println!("{}", "-".repeat(50));
+
+        // 显示内容预览（前 10 行）
+        let lines: Vec<&str> = prompt.content.lines().collect();
+        for line in lines.iter().take(10) {
+            println!("{}", line);
+        }
+
+        if lines.len() > 10 {
+            println!("...");
+            println!("{}", info(&format!("({} more lines)", lines.len() - 10)));
         }
     }
 
@@ -187,8 +310,8 @@ fn activate_prompt(app_type: AppType, id: &str) -> Result<(), AppError> {
         )));
     }
 
-    // 执行激活
-    PromptService::enable_prompt(&state, app_type, id)?;
+    // 执行激活：替换整个提示词栈
+    PromptService::set_stack(&state, app_type, &[id.to_string()])?;
 
     println!(
         "{}",
@@ -204,6 +327,123 @@ fn activate_prompt(app_type: AppType, id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+fn activate_project_prompt(app_type: AppType, id: &str, dir: &Path) -> Result<(), AppError> {
+    let state = get_state()?;
+    let path = PromptService::activate_in_project(&state, app_type.clone(), id, dir)?;
+
+    println!(
+        "{}",
+        success(&format!(
+            "✓ Activated prompt preset '{}' for {} in project",
+            id,
+            app_type.as_str()
+        ))
+    );
+    println!("{}", info(&format!("  Written to: {}", path.display())));
+    println!(
+        "{}",
+        info("Note: Content outside the cc-switch markers in this file is kept.")
+    );
+
+    Ok(())
+}
+
+fn deactivate_project_prompt(app_type: AppType, dir: &Path) -> Result<(), AppError> {
+    let state = get_state()?;
+    let was_active = PromptService::deactivate_in_project(&state, app_type.clone(), dir)?;
+    let path = project_prompt_file_path(&app_type, dir);
+
+    if was_active {
+        println!(
+            "{}",
+            success(&format!(
+                "✓ Deactivated project prompt for {}",
+                app_type.as_str()
+            ))
+        );
+        println!("{}", info(&format!("  Removed from: {}", path.display())));
+    } else {
+        println!(
+            "{}",
+            info(&format!(
+                "No prompt preset is active for {} in {}",
+                app_type.as_str(),
+                dir.display()
+            ))
+        );
+    }
+
+    Ok(())
+}
+
+fn import_prompt(app_type: AppType, project: Option<&Path>) -> Result<(), AppError> {
+    let state = get_state()?;
+    let id = match project {
+        Some(dir) => PromptService::import_from_project(&state, app_type.clone(), dir)?,
+        None => PromptService::import_from_file(&state, app_type.clone())?,
+    };
+
+    println!("{}", success(&format!("✓ Imported prompt preset '{id}'")));
+    if let Some(dir) = project {
+        println!(
+            "{}",
+            info(&format!(
+                "Tip: Use 'cc-switch prompts activate {id} --project {}' to manage the project file with it.",
+                dir.display()
+            ))
+        );
+    }
+
+    Ok(())
+}
+
+fn list_projects(app: Option<AppType>) -> Result<(), AppError> {
+    let state = get_state()?;
+    let projects = PromptService::project_usages(&state, app)?;
+
+    if projects.is_empty() {
+        println!("{}", info("No projects use prompt presets."));
+        println!("Use 'cc-switch prompts activate <id> --project <dir>' to add one.");
+        return Ok(());
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["Project", "App", "Preset", "Activated", "Status"]);
+    for project in projects {
+        let activated = chrono::DateTime::<chrono::Utc>::from_timestamp(project.activated_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let status = project_status(&project.app_type, &project.project_path, &project.prompt_id);
+        table.add_row(vec![
+            project.project_path,
+            project.app_type,
+            project.prompt_id,
+            activated,
+            status.to_string(),
+        ]);
+    }
+    println!("{}", table);
+
+    Ok(())
+}
+
+fn project_status(app_type: &str, project_path: &str, prompt_id: &str) -> &'static str {
+    let Ok(app) = app_type.parse::<AppType>() else {
+        return "unknown app";
+    };
+    let dir = Path::new(project_path);
+    if !dir.is_dir() {
+        return "missing directory";
+    }
+    let content = std::fs::read_to_string(project_prompt_file_path(&app, dir)).unwrap_or_default();
+    let layout = PromptFileLayout::parse(&content);
+    if layout.sections.iter().any(|s| s.id == prompt_id) {
+        "ok"
+    } else {
+        "section missing"
+    }
+}
+
 fn delete_prompt(app_type: AppType, id: &str) -> Result<(), AppError> {
     let state = get_state()?;
 
@@ -296,6 +536,28 @@ fn show_prompt(app_type: AppType, id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+fn render_prompt(app_type: AppType, id: &str) -> Result<(), AppError> {
+    let state = get_state()?;
+    let rendered = PromptService::render_prompt(&state, app_type.clone(), id)?;
+
+    print!("{}", rendered.content);
+    if !rendered.content.ends_with('\n') {
+        println!();
+    }
+    if !rendered.unresolved.is_empty() {
+        eprintln!(
+            "{}",
+            warning(&format!(
+                "Undefined variables left as-is for {}: {}",
+                app_type.as_str(),
+                rendered.unresolved.join(", ")
+            ))
+        );
+    }
+
+    Ok(())
+}
+
 fn create_prompt(_app_type: AppType) -> Result<(), AppError> {
     let state = get_state()?;
     let timestamp = std::time::SystemTime::now()
@@ -322,6 +584,7 @@ fn create_prompt(_app_type: AppType) -> Result<(), AppError> {
         enabled: false,
         created_at: Some(timestamp),
         updated_at: Some(timestamp),
+        stack_order: None,
     };
 
     PromptService::upsert_prompt(&state, _app_type.clone(), &id, prompt)?;
@@ -336,35 +599,30 @@ fn create_prompt(_app_type: AppType) -> Result<(), AppError> {
 
 fn deactivate_prompt(app_type: AppType) -> Result<(), AppError> {
     let state = get_state()?;
-    let prompts = PromptService::get_prompts(&state, app_type.clone())?;
+    let stack = PromptService::active_stack(&state, app_type.clone())?;
 
-    // Find currently enabled prompt
-    let active = prompts
-        .iter()
-        .find(|(_, p)| p.enabled)
-        .map(|(id, _)| id.clone());
+    if stack.is_empty() {
+        println!("{}", info("No active prompt to deactivate."));
+        println!("Use 'cc-switch prompts activate <id>' to activate a prompt preset.");
+        return Ok(());
+    }
 
-    match active {
-        Some(id) => {
-            // Deactivate the current prompt
-            PromptService::disable_prompt(&state, app_type.clone(), &id)?;
+    PromptService::set_stack(&state, app_type.clone(), &[])?;
 
-            println!(
-                "{}",
-                success(&format!("✓ Deactivated prompt preset '{}'", id))
-            );
-            println!("{}", info(&format!("  Application: {}", app_type.as_str())));
-            println!();
-            println!(
-                "{}",
-                info("Note: The live configuration file has been cleared.")
-            );
-        }
-        None => {
-            println!("{}", info("No active prompt to deactivate."));
-            println!("Use 'cc-switch prompts activate <id>' to activate a prompt preset.");
-        }
+    for prompt in &stack {
+        println!(
+            "{}",
+            success(&format!("✓ Deactivated prompt preset '{}'", prompt.id))
+        );
     }
+    println!("{}", info(&format!("  Application: {}", app_type.as_str())));
+    println!();
+    println!(
+        "{}",
+        info(
+            "Note: Managed sections were removed from the live prompt file; other content is kept."
+        )
+    );
 
     Ok(())
 }
@@ -13,6 +13,7 @@ use crate::provider::Provider;
 use crate::services::{ProviderService, SpeedtestService};
 use crate::store::AppState;
 use inquire::{Confirm, Select, Text};
+use std::io::IsTerminal;
 
 fn supports_official_provider(app_type: &AppType) -> bool {
     matches!(app_type, AppType::Codex)
@@ -51,6 +52,28 @@ pub enum ProviderCommand {
         /// Provider ID to test
         id: String,
     },
+    /// Compare the live config with the stored current provider and resolve differences
+    Status {
+        /// Copy the live values into the current provider
+        #[arg(long, conflicts_with_all = ["save_as", "reapply"])]
+        adopt: bool,
+        /// Save the live config as a new provider with this ID and make it current
+        #[arg(long, value_name = "ID", conflicts_with = "reapply")]
+        save_as: Option<String>,
+        /// Name for the provider created by --save-as (defaults to the ID)
+        #[arg(long, requires = "save_as")]
+        name: Option<String>,
+        /// Overwrite the live config with the stored current provider
+        #[arg(long)]
+        reapply: bool,
+    },
+}
+
+/// 如何处理 live 与存储的当前供应商之间的差异
+enum StatusAction {
+    Adopt,
+    SaveAs { id: String, name: String },
+    Reapply,
 }
 
 pub fn execute(cmd: ProviderCommand, app: Option<AppType>) -> Result<(), AppError> {
@@ -65,6 +88,23 @@ pub fn execute(cmd: ProviderCommand, app: Option<AppType>) -> Result<(), AppErro
         ProviderCommand::Delete { id } => delete_provider(app_type, &id),
         ProviderCommand::Duplicate { id } => duplicate_provider(app_type, &id),
         ProviderCommand::Speedtest { id } => speedtest_provider(app_type, &id),
+        ProviderCommand::Status {
+            adopt,
+            save_as,
+            name,
+            reapply,
+        } => {
+            let action = match (adopt, save_as, reapply) {
+                (true, _, _) => Some(StatusAction::Adopt),
+                (_, Some(id), _) => Some(StatusAction::SaveAs {
+                    name: name.unwrap_or_else(|| id.clone()),
+                    id,
+                }),
+                (_, _, true) => Some(StatusAction::Reapply),
+                _ => None,
+            };
+            provider_status(app_type, action)
+        }
     }
 }
 
@@ -223,6 +263,160 @@ fn switch_provider(app_type: AppType, id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+fn provider_status(app_type: AppType, action: Option<StatusAction>) -> Result<(), AppError> {
+    let state = get_state()?;
+    let app_str = app_type.as_str().to_string();
+
+    let Some(drift) = ProviderService::live_drift(&state, app_type.clone())? else {
+        println!(
+            "{}",
+            info(&format!(
+                "No live config to compare for {} (app not set up, no current provider, or live files missing).",
+                app_str
+            ))
+        );
+        return Ok(());
+    };
+
+    println!(
+        "{}",
+        highlight(&format!(
+            "Current provider: {} ({})",
+            drift.provider_name, drift.provider_id
+        ))
+    );
+    if drift.is_clean() {
+        println!("{}", success("✓ Live config matches the stored provider"));
+        return Ok(());
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["Key", "Stored", "Live"]);
+    for change in &drift.changes {
+        table.add_row(vec![
+            change.key.clone(),
+            display_value(&change.key, change.stored.as_ref()),
+            display_value(&change.key, change.live.as_ref()),
+        ]);
+    }
+    println!("{}", table);
+    println!(
+        "{}",
+        warning(&format!(
+            "⚠ {} key(s) in the live config differ from the stored provider",
+            drift.changes.len()
+        ))
+    );
+
+    let action = match action {
+        Some(action) => action,
+        None => {
+            if !std::io::stdin().is_terminal() {
+                println!(
+                    "{}",
+                    info("Use --adopt, --save-as <id> or --reapply to resolve the differences.")
+                );
+                return Ok(());
+            }
+            match prompt_status_action(&drift.provider_id)? {
+                Some(action) => action,
+                None => {
+                    println!("{}", info("Left unchanged."));
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    match action {
+        StatusAction::Adopt => {
+            ProviderService::adopt_live(&state, app_type)?;
+            println!(
+                "{}",
+                success(&format!(
+                    "✓ Adopted the live config into provider '{}'",
+                    drift.provider_id
+                ))
+            );
+        }
+        StatusAction::SaveAs { id, name } => {
+            ProviderService::save_live_as(&state, app_type, &id, &name)?;
+            println!(
+                "{}",
+                success(&format!(
+                    "✓ Saved the live config as provider '{}' and made it current ('{}' is unchanged)",
+                    id, drift.provider_id
+                ))
+            );
+        }
+        StatusAction::Reapply => {
+            ProviderService::reapply_current(&state, app_type)?;
+            println!(
+                "{}",
+                success(&format!(
+                    "✓ Re-applied provider '{}' to the live config",
+                    drift.provider_id
+                ))
+            );
+            println!(
+                "\n{}",
+                info("Note: Restart your CLI client to apply the changes.")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn prompt_status_action(provider_id: &str) -> Result<Option<StatusAction>, AppError> {
+    let adopt = format!("Adopt the live values into '{}'", provider_id);
+    let save_as = "Save the live config as a new provider".to_string();
+    let reapply = format!("Re-apply '{}' (discard the live changes)", provider_id);
+    let nothing = "Do nothing".to_string();
+    let choice = Select::new(
+        "How should the differences be resolved?",
+        vec![adopt.clone(), save_as.clone(), reapply.clone(), nothing],
+    )
+    .prompt()
+    .map_err(|e| AppError::Message(format!("Selection failed: {}", e)))?;
+
+    if choice == adopt {
+        Ok(Some(StatusAction::Adopt))
+    } else if choice == save_as {
+        let id = Text::new("New provider ID:")
+            .prompt()
+            .map_err(|e| AppError::Message(format!("Input failed: {}", e)))?;
+        let name = Text::new("New provider name:")
+            .with_default(&id)
+            .prompt()
+            .map_err(|e| AppError::Message(format!("Input failed: {}", e)))?;
+        Ok(Some(StatusAction::SaveAs { id, name }))
+    } else if choice == reapply {
+        Ok(Some(StatusAction::Reapply))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 显示差异值，密钥类字段脱敏
+fn display_value(key: &str, value: Option<&serde_json::Value>) -> String {
+    let Some(value) = value else {
+        return "-".to_string();
+    };
+    let text = match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let last = key.rsplit('.').next().unwrap_or(key).to_ascii_uppercase();
+    if ["KEY", "TOKEN", "SECRET", "PASSWORD"]
+        .iter()
+        .any(|word| last.contains(word))
+    {
+        mask_api_key(&text)
+    } else {
+        text
+    }
+}
+
 fn delete_provider(app_type: AppType, id: &str) -> Result<(), AppError> {
     let state = get_state()?;
 
@@ -1,10 +1,15 @@
 use clap::Subcommand;
 use std::future::Future;
+use std::path::{Path, PathBuf};
 
 use crate::app_config::AppType;
-use crate::cli::ui::{create_table, highlight, info, success};
+use crate::cli::ui::{create_table, error, highlight, info, success, warning};
 use crate::error::AppError;
-use crate::services::skill::{SkillRepo, SyncMethod};
+use crate::services::skill::{
+    NewSkillOptions, SkillFileChangeKind, SkillLockOutcome, SkillRepo, SkillUpdateOutcome,
+    SkillVersionCheck, SyncMethod,
+};
+use crate::services::skill_lint::{LintReport, LintSeverity};
 use crate::services::SkillService;
 
 #[derive(Subcommand)]
@@ -16,11 +21,58 @@ pub enum SkillsCommand {
     Discover {
         /// Optional query filter (matches name/directory)
         query: Option<String>,
+        /// Use cached repo indexes only (no network)
+        #[arg(long)]
+        offline: bool,
     },
     /// Install a skill (SSOT -> app skills dir)
     Install {
-        /// Skill directory name or full key (owner/name:directory)
+        /// Skill directory name, full key (owner/name:directory), or a source:
+        /// file:///path, git+https://host/org/repo[@ref][#subdir], https://host/skill.zip[#subdir]
+        #[arg(required_unless_present = "from_lock", conflicts_with = "from_lock")]
+        spec: Option<String>,
+        /// Reproduce the skill set pinned in a lockfile (verifies content hashes)
+        #[arg(long, value_name = "FILE")]
+        from_lock: Option<PathBuf>,
+        /// Install even if `skills lint` reports errors
+        #[arg(long)]
+        allow_lint_errors: bool,
+    },
+    /// Check a skill for front matter, missing references, oversized files and risky scripts
+    Lint {
+        /// Local directory, source spec (file://, git+https://, ZIP URL), installed skill, or repo skill
+        spec: String,
+    },
+    /// Write installed skills (source, commit, content hash, apps) to a lockfile
+    Lock {
+        /// Output file
+        #[arg(long, short, default_value = "skills.lock")]
+        output: PathBuf,
+    },
+    /// Scaffold a new local skill in the SSOT dir and sync it to apps
+    New {
+        /// Skill name (lowercase letters, digits and hyphens); used as the directory name
+        name: String,
+        /// One-line description of when the skill should be used
+        #[arg(long, short)]
+        description: String,
+        /// Add a scripts/ folder with an example script
+        #[arg(long)]
+        scripts: bool,
+        /// Add a references/ folder with a reference doc
+        #[arg(long)]
+        references: bool,
+        /// Apps to enable (comma-separated; default: the selected app)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        apps: Vec<AppType>,
+    },
+    /// Zip an installed skill for sharing
+    Pack {
+        /// Skill directory or id
         spec: String,
+        /// Output file or directory (default: ./<directory>.zip)
+        #[arg(long, short, default_value = ".")]
+        output: PathBuf,
     },
     /// Uninstall a skill (remove from SSOT and app dirs)
     Uninstall {
@@ -31,6 +83,9 @@ pub enum SkillsCommand {
     Enable {
         /// Skill directory or id
         spec: String,
+        /// Enable even if `skills lint` reports errors
+        #[arg(long)]
+        allow_lint_errors: bool,
     },
     /// Disable a skill for the selected app
     Disable {
@@ -39,6 +94,23 @@ pub enum SkillsCommand {
     },
     /// Sync enabled skills to app skills dirs
     Sync,
+    /// Check installed repo skills for upstream changes
+    Outdated {
+        /// Skill directory or id (default: all repo skills)
+        spec: Option<String>,
+    },
+    /// Update installed repo skills from upstream
+    Update {
+        /// Skill directory or id
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        spec: Option<String>,
+        /// Update every installed repo skill
+        #[arg(long)]
+        all: bool,
+        /// Overwrite local edits to the installed files
+        #[arg(long)]
+        force: bool,
+    },
     /// Scan unmanaged skills in app skills dirs
     ScanUnmanaged,
     /// Import unmanaged skills from app skills dirs into SSOT
@@ -83,12 +155,47 @@ pub fn execute(cmd: SkillsCommand, app: Option<AppType>) -> Result<(), AppError>
 
     match cmd {
         SkillsCommand::List => list_installed(),
-        SkillsCommand::Discover { query } => discover_skills(query.as_deref()),
-        SkillsCommand::Install { spec } => install_skill(&app_type, &spec),
+        SkillsCommand::Discover { query, offline } => discover_skills(query.as_deref(), offline),
+        SkillsCommand::Install {
+            spec,
+            from_lock,
+            allow_lint_errors,
+        } => match (spec, from_lock) {
+            (_, Some(path)) => install_from_lock(&path),
+            (Some(spec), None) => install_skill(&app_type, &spec, allow_lint_errors),
+            (None, None) => Err(AppError::InvalidInput(
+                "Provide a skill spec or --from-lock <FILE>".to_string(),
+            )),
+        },
+        SkillsCommand::Lock { output } => write_lock(&output),
+        SkillsCommand::New {
+            name,
+            description,
+            scripts,
+            references,
+            apps,
+        } => new_skill(NewSkillOptions {
+            directory: name,
+            description,
+            with_scripts: scripts,
+            with_references: references,
+            apps: if apps.is_empty() {
+                vec![app_type]
+            } else {
+                apps
+            },
+        }),
+        SkillsCommand::Pack { spec, output } => pack_skill(&spec, &output),
         SkillsCommand::Uninstall { spec } => uninstall_skill(&spec),
-        SkillsCommand::Enable { spec } => toggle_skill(&app_type, &spec, true),
+        SkillsCommand::Lint { spec } => lint_skill(&spec),
+        SkillsCommand::Enable {
+            spec,
+            allow_lint_errors,
+        } => enable_skill(&app_type, &spec, allow_lint_errors),
         SkillsCommand::Disable { spec } => toggle_skill(&app_type, &spec, false),
         SkillsCommand::Sync => sync_skills(app.as_ref()),
+        SkillsCommand::Outdated { spec } => outdated_skills(spec.as_deref()),
+        SkillsCommand::Update { spec, force, .. } => update_skills(spec.as_deref(), force),
         SkillsCommand::ScanUnmanaged => scan_unmanaged(),
         SkillsCommand::ImportFromApps { directories } => import_from_apps(directories),
         SkillsCommand::Info { spec } => show_skill_info(&spec),
@@ -129,9 +236,11 @@ fn list_installed() -> Result<(), AppError> {
     Ok(())
 }
 
-fn discover_skills(query: Option<&str>) -> Result<(), AppError> {
-    let service = SkillService::new()?;
+fn discover_skills(query: Option<&str>, offline: bool) -> Result<(), AppError> {
+    let mut service = SkillService::new()?;
+    service.set_offline(offline);
     let mut skills = run_async(service.list_skills())?;
+    print_repo_cache_ages(offline)?;
 
     if let Some(query) = query.map(str::trim).filter(|q| !q.is_empty()) {
         let q = query.to_lowercase();
@@ -158,8 +267,43 @@ fn discover_skills(query: Option<&str>) -> Result<(), AppError> {
     Ok(())
 }
 
-fn install_skill(app_type: &AppType, spec: &str) -> Result<(), AppError> {
-    let service = SkillService::new()?;
+/// Show how old the repo index data behind this listing is.
+fn print_repo_cache_ages(offline: bool) -> Result<(), AppError> {
+    for repo in SkillService::list_repos()?.iter().filter(|r| r.enabled) {
+        let label = format!("{}/{}", repo.owner, repo.name);
+        match SkillService::cached_repo_index(repo) {
+            Some(cached) if offline || cached.age_secs() >= 60 => println!(
+                "{}",
+                info(&format!(
+                    "{label}: cached index from {}",
+                    format_age(cached.age_secs())
+                ))
+            ),
+            Some(_) => {}
+            None if offline => println!(
+                "{}",
+                warning(&format!(
+                    "{label}: no cached index; run without --offline first"
+                ))
+            ),
+            None => println!("{}", warning(&format!("{label}: could not be fetched"))),
+        }
+    }
+    Ok(())
+}
+
+fn format_age(secs: i64) -> String {
+    match secs {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86_400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86_400),
+    }
+}
+
+fn install_skill(app_type: &AppType, spec: &str, allow_lint_errors: bool) -> Result<(), AppError> {
+    let mut service = SkillService::new()?;
+    service.allow_lint_errors(allow_lint_errors);
     let installed = run_async(service.install(spec, app_type))?;
     println!(
         "{}",
@@ -172,12 +316,186 @@ fn install_skill(app_type: &AppType, spec: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+fn install_from_lock(path: &Path) -> Result<(), AppError> {
+    let service = SkillService::new()?;
+    let results = run_async(service.install_from_lock(path))?;
+
+    let mut failed = 0;
+    for result in &results {
+        match &result.outcome {
+            SkillLockOutcome::Installed => {
+                println!(
+                    "{}",
+                    success(&format!("✓ Installed '{}'", result.directory))
+                );
+            }
+            SkillLockOutcome::Unchanged => {
+                println!(
+                    "{}",
+                    info(&format!(
+                        "= '{}' already matches the lock",
+                        result.directory
+                    ))
+                );
+            }
+            SkillLockOutcome::Failed(e) => {
+                failed += 1;
+                println!("{}", error(&format!("✗ '{}': {e}", result.directory)));
+            }
+        }
+    }
+
+    let locked: Vec<&str> = results.iter().map(|r| r.directory.as_str()).collect();
+    let extra: Vec<String> = SkillService::list_installed()?
+        .into_iter()
+        .map(|s| s.directory)
+        .filter(|d| !locked.contains(&d.as_str()))
+        .collect();
+    if !extra.is_empty() {
+        println!(
+            "{}",
+            warning(&format!("Installed but not in lock: {}", extra.join(", ")))
+        );
+    }
+
+    if failed > 0 {
+        return Err(AppError::Message(format!(
+            "{failed} of {} locked skill(s) could not be installed",
+            results.len()
+        )));
+    }
+    Ok(())
+}
+
+fn write_lock(output: &Path) -> Result<(), AppError> {
+    let lock = SkillService::write_lock(output)?;
+    println!(
+        "{}",
+        success(&format!(
+            "✓ Locked {} skill(s) to {}",
+            lock.skills.len(),
+            output.display()
+        ))
+    );
+    let local: Vec<&str> = lock
+        .skills
+        .iter()
+        .filter(|s| s.source.starts_with("local:"))
+        .map(|s| s.directory.as_str())
+        .collect();
+    if !local.is_empty() {
+        println!(
+            "{}",
+            warning(&format!(
+                "Local skills cannot be fetched on another machine: {}",
+                local.join(", ")
+            ))
+        );
+    }
+    Ok(())
+}
+
+fn new_skill(options: NewSkillOptions) -> Result<(), AppError> {
+    let skill = SkillService::create_local(&options)?;
+    let dir = SkillService::get_ssot_dir()?.join(&skill.directory);
+    let apps: Vec<&str> = options.apps.iter().map(|a| a.as_str()).collect();
+    println!(
+        "{}",
+        success(&format!(
+            "✓ Created skill '{}' (enabled for {})",
+            skill.directory,
+            apps.join(", ")
+        ))
+    );
+    println!(
+        "  {}",
+        highlight(&dir.join("SKILL.md").display().to_string())
+    );
+    println!(
+        "{}",
+        info("Edit the files there, then run 'cc-switch skills sync' to update copy-mode apps.")
+    );
+    Ok(())
+}
+
+fn pack_skill(spec: &str, output: &Path) -> Result<(), AppError> {
+    let report = SkillService::lint_installed(spec)?;
+    print_lint_issues(&report);
+    let path = SkillService::pack(spec, output)?;
+    println!(
+        "{}",
+        success(&format!("✓ Packed '{spec}' to {}", path.display()))
+    );
+    if report.has_errors() {
+        println!(
+            "{}",
+            warning(
+                "The archive has lint errors; installs will refuse it without --allow-lint-errors."
+            )
+        );
+    }
+    Ok(())
+}
+
 fn uninstall_skill(spec: &str) -> Result<(), AppError> {
     SkillService::uninstall(spec)?;
     println!("{}", success(&format!("✓ Uninstalled skill '{spec}'")));
     Ok(())
 }
 
+fn print_lint_issues(report: &LintReport) {
+    for issue in &report.issues {
+        let location = issue
+            .file
+            .as_deref()
+            .map(|f| format!("{f}: "))
+            .unwrap_or_default();
+        let line = format!("[{}] {location}{}", issue.rule, issue.message);
+        match issue.severity {
+            LintSeverity::Error => println!("{}", error(&format!("✗ {line}"))),
+            LintSeverity::Warning => println!("{}", warning(&format!("! {line}"))),
+        }
+    }
+}
+
+fn lint_skill(spec: &str) -> Result<(), AppError> {
+    let service = SkillService::new()?;
+    let (label, report) = run_async(service.lint(spec))?;
+
+    print_lint_issues(&report);
+    if report.has_errors() {
+        return Err(AppError::Message(format!(
+            "'{label}': {} error(s), {} warning(s)",
+            report.error_count(),
+            report.warning_count()
+        )));
+    }
+    if report.issues.is_empty() {
+        println!("{}", success(&format!("✓ '{label}' passed lint")));
+    } else {
+        println!(
+            "{}",
+            success(&format!(
+                "✓ '{label}' passed lint with {} warning(s)",
+                report.warning_count()
+            ))
+        );
+    }
+    Ok(())
+}
+
+fn enable_skill(app_type: &AppType, spec: &str, allow_lint_errors: bool) -> Result<(), AppError> {
+    let report = SkillService::lint_installed(spec)?;
+    print_lint_issues(&report);
+    if report.has_errors() && !allow_lint_errors {
+        return Err(AppError::Message(format!(
+            "'{spec}' has {} lint error(s); fix them or pass --allow-lint-errors",
+            report.error_count()
+        )));
+    }
+    toggle_skill(app_type, spec, true)
+}
+
 fn toggle_skill(app_type: &AppType, spec: &str, enabled: bool) -> Result<(), AppError> {
     SkillService::toggle_app(spec, app_type, enabled)?;
     println!(
@@ -198,6 +516,113 @@ fn sync_skills(app: Option<&AppType>) -> Result<(), AppError> {
     Ok(())
 }
 
+fn short_commit(commit: Option<&str>) -> String {
+    commit
+        .map(|c| c.chars().take(7).collect())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn version_status(check: &SkillVersionCheck) -> String {
+    match (check.is_outdated(), check.locally_modified) {
+        (true, true) => format!("outdated ({} files), local edits", check.changes.len()),
+        (true, false) => format!("outdated ({} files)", check.changes.len()),
+        (false, true) => "local edits".to_string(),
+        (false, false) => "up to date".to_string(),
+    }
+}
+
+fn print_file_changes(check: &SkillVersionCheck) {
+    for change in &check.changes {
+        let marker = match change.kind {
+            SkillFileChangeKind::Added => "+",
+            SkillFileChangeKind::Removed => "-",
+            SkillFileChangeKind::Modified => "~",
+        };
+        println!("  {} {}", marker, change.path);
+    }
+}
+
+fn outdated_skills(spec: Option<&str>) -> Result<(), AppError> {
+    let service = SkillService::new()?;
+    let checks = run_async(service.check_updates(spec))?;
+
+    if checks.is_empty() {
+        println!("{}", info("No skills installed from repositories."));
+        return Ok(());
+    }
+
+    let mut table = create_table();
+    table.set_header(vec!["Directory", "Installed", "Latest", "Status"]);
+    let outdated = checks.iter().filter(|c| c.is_outdated()).count();
+    for check in &checks {
+        table.add_row(vec![
+            check.directory.clone(),
+            short_commit(check.installed_commit.as_deref()),
+            short_commit(check.latest_commit.as_deref()),
+            version_status(check),
+        ]);
+    }
+    println!("{}", table);
+
+    if outdated > 0 {
+        println!(
+            "{}",
+            info(&format!(
+                "{outdated} skill(s) can be updated: cc-switch skills update <directory> | --all"
+            ))
+        );
+    }
+    Ok(())
+}
+
+fn update_skills(spec: Option<&str>, force: bool) -> Result<(), AppError> {
+    let service = SkillService::new()?;
+    let results = run_async(service.update(spec, force))?;
+
+    if results.is_empty() {
+        println!("{}", info("No skills installed from repositories."));
+        return Ok(());
+    }
+
+    let mut skipped = 0usize;
+    for result in &results {
+        let check = &result.check;
+        match result.outcome {
+            SkillUpdateOutcome::UpToDate => {
+                println!("{}", info(&format!("'{}' is up to date", check.directory)));
+            }
+            SkillUpdateOutcome::Updated => {
+                println!(
+                    "{}",
+                    success(&format!(
+                        "✓ Updated '{}' ({} -> {})",
+                        check.directory,
+                        short_commit(check.installed_commit.as_deref()),
+                        short_commit(check.latest_commit.as_deref())
+                    ))
+                );
+                print_file_changes(check);
+            }
+            SkillUpdateOutcome::SkippedLocalChanges => {
+                skipped += 1;
+                println!(
+                    "{}",
+                    warning(&format!(
+                        "⚠ Skipped '{}': installed files were edited locally; these files would change:",
+                        check.directory
+                    ))
+                );
+                print_file_changes(check);
+            }
+        }
+    }
+
+    if skipped > 0 {
+        println!("{}", info("Re-run with --force to overwrite local edits."));
+    }
+    Ok(())
+}
+
 fn scan_unmanaged() -> Result<(), AppError> {
     let skills = SkillService::scan_unmanaged()?;
     if skills.is_empty() {
@@ -252,6 +677,23 @@ fn show_skill_info(spec: &str) -> Result<(), AppError> {
         "Enabled:   claude={} codex={} gemini={}",
         record.apps.claude, record.apps.codex, record.apps.gemini
     );
+    if let (Some(owner), Some(name)) = (&record.repo_owner, &record.repo_name) {
+        println!(
+            "Repo:      {}/{}@{}",
+            owner,
+            name,
+            record.repo_branch.as_deref().unwrap_or("main")
+        );
+    }
+    if let Some(source) = &record.source {
+        println!("Source:    {}", source);
+    }
+    if let Some(commit) = &record.source_commit {
+        println!("Commit:    {}", commit);
+    }
+    if let Some(hash) = &record.content_hash {
+        println!("Hash:      sha256:{}", hash);
+    }
 
     Ok(())
 }
@@ -273,12 +715,16 @@ fn list_repos() -> Result<(), AppError> {
     }
 
     let mut table = create_table();
-    table.set_header(vec!["Enabled", "Repo", "Branch"]);
+    table.set_header(vec!["Enabled", "Repo", "Branch", "Index cached"]);
     for repo in repos {
+        let cached = SkillService::cached_repo_index(&repo)
+            .map(|c| format_age(c.age_secs()))
+            .unwrap_or_else(|| "-".to_string());
         table.add_row(vec![
             if repo.enabled { "✓" } else { " " }.to_string(),
             format!("{}/{}", repo.owner, repo.name),
             repo.branch,
+            cached,
         ]);
     }
     println!("{}", table);
@@ -0,0 +1,204 @@
+use clap::Subcommand;
+
+use crate::app_config::AppType;
+use crate::backup_crypto;
+use crate::cli::commands::mcp::confirm_overwrite_drift;
+use crate::cli::ui::{create_table, error, highlight, info, success, warning};
+use crate::error::AppError;
+use crate::services::{
+    ConflictResolution, SecretsMode, SyncChange, SyncChangeKind, SyncReport, SyncService,
+};
+use crate::store::AppState;
+
+#[derive(Subcommand)]
+pub enum SyncCommand {
+    /// Set the sync target: a git remote (URL or local/bare repo path) or a plain directory
+    Init {
+        /// Git remote or directory
+        target: String,
+        /// Git branch to sync (default: the remote's default branch)
+        #[arg(long)]
+        branch: Option<String>,
+    },
+    /// Show the sync target and local changes since the last sync
+    Status,
+    /// Publish local providers, MCP servers, prompts and skills
+    Push {
+        /// Encrypt secrets into secrets.enc (automatic when CC_SWITCH_BACKUP_PASSPHRASE is set)
+        #[arg(long)]
+        encrypt_secrets: bool,
+    },
+    /// Merge remote changes into the local configuration
+    Pull {
+        /// Resolve items changed on both sides
+        #[arg(long, value_enum)]
+        resolve: Option<ConflictResolution>,
+        /// Overwrite manual MCP edits in live files without asking
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+pub fn execute(cmd: SyncCommand) -> Result<(), AppError> {
+    match cmd {
+        SyncCommand::Init { target, branch } => init(&target, branch.as_deref()),
+        SyncCommand::Status => status(),
+        SyncCommand::Push { encrypt_secrets } => push(encrypt_secrets),
+        SyncCommand::Pull { resolve, force } => pull(resolve, force),
+    }
+}
+
+fn init(target: &str, branch: Option<&str>) -> Result<(), AppError> {
+    let target = SyncService::init(target, branch)?;
+    println!(
+        "{}",
+        success(&format!("✓ Sync target set to {}", target.describe()))
+    );
+    println!(
+        "{}",
+        info(
+            "Run `cc-switch sync pull` to merge existing remote data, then `cc-switch sync push`."
+        )
+    );
+    Ok(())
+}
+
+fn status() -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    let (config, changes) = SyncService::status(&state)?;
+
+    println!("{}", highlight("Sync"));
+    println!("Target:      {}", config.target.describe());
+    let last = config
+        .last_synced_at
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    println!("Last sync:   {}", last);
+    println!();
+
+    if changes.is_empty() {
+        println!("{}", success("✓ No local changes since the last sync."));
+    } else {
+        println!("{}", info("Local changes not yet pushed:"));
+        print_changes(&changes);
+    }
+    Ok(())
+}
+
+fn push(encrypt_secrets: bool) -> Result<(), AppError> {
+    let passphrase = match backup_crypto::passphrase_from_env() {
+        Some(passphrase) => Some(passphrase),
+        None if encrypt_secrets => Some(
+            inquire::Password::new("Secrets passphrase:")
+                .with_custom_confirmation_message("Confirm passphrase:")
+                .with_display_mode(inquire::PasswordDisplayMode::Masked)
+                .prompt()
+                .map_err(|e| AppError::Message(format!("Prompt failed: {}", e)))?,
+        ),
+        None => None,
+    };
+
+    let state = AppState::try_new()?;
+    let report = SyncService::push(&state, passphrase.as_deref())?;
+
+    if report.changes.is_empty() {
+        println!("{}", success("✓ Remote is already up to date."));
+    } else {
+        print_changes(&report.changes);
+        println!(
+            "{}",
+            success(&format!("✓ Pushed {} change(s)", report.changes.len()))
+        );
+    }
+    print_secrets_mode(&report);
+    Ok(())
+}
+
+fn pull(resolve: Option<ConflictResolution>, force: bool) -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    let apps = [AppType::Claude, AppType::Codex, AppType::Gemini];
+    if !force && !confirm_overwrite_drift(&state, &apps, None)? {
+        println!("{}", info("Cancelled."));
+        return Ok(());
+    }
+    let passphrase = backup_crypto::passphrase_from_env();
+    let report = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| AppError::Message(format!("Failed to create runtime: {e}")))?
+        .block_on(SyncService::pull(&state, resolve, passphrase.as_deref()))?;
+
+    if report.changes.is_empty() {
+        println!("{}", success("✓ Already up to date."));
+    } else {
+        print_changes(&report.changes);
+        println!(
+            "{}",
+            success(&format!("✓ Applied {} change(s)", report.changes.len()))
+        );
+    }
+    if !report.local_changes.is_empty() {
+        println!(
+            "{}",
+            info(&format!(
+                "{} local change(s) not yet pushed; run `cc-switch sync push`.",
+                report.local_changes.len()
+            ))
+        );
+    }
+    for (path, e) in &report.failed {
+        println!("{}", error(&format!("✗ {path}: {e}")));
+    }
+    for path in &report.missing_secrets {
+        println!(
+            "{}",
+            warning(&format!(
+                "⚠ {path}: secrets not available, left as placeholders"
+            ))
+        );
+    }
+    if !report.conflicts.is_empty() {
+        println!();
+        println!(
+            "{}",
+            warning("⚠ Changed on both sides (local version kept):")
+        );
+        for path in &report.conflicts {
+            println!("  {path}");
+        }
+        println!(
+            "{}",
+            info("Re-run with `--resolve ours` or `--resolve theirs` to settle them.")
+        );
+    }
+    print_secrets_mode(&report);
+    Ok(())
+}
+
+fn print_changes(changes: &[SyncChange]) {
+    let mut table = create_table();
+    table.set_header(vec!["", "Path"]);
+    for change in changes {
+        let marker = match change.kind {
+            SyncChangeKind::Added => "+",
+            SyncChangeKind::Updated => "~",
+            SyncChangeKind::Deleted => "-",
+        };
+        table.add_row(vec![marker.to_string(), change.path.clone()]);
+    }
+    println!("{}", table);
+}
+
+fn print_secrets_mode(report: &SyncReport) {
+    let note = match report.secrets {
+        SecretsMode::Encrypted => "Secrets are synced encrypted in secrets.enc.",
+        SecretsMode::Excluded => {
+            "Secrets were not synced; set CC_SWITCH_BACKUP_PASSPHRASE to sync them encrypted."
+        }
+        SecretsMode::Locked => {
+            "The remote has encrypted secrets; set CC_SWITCH_BACKUP_PASSPHRASE to restore them."
+        }
+    };
+    println!("{}", info(note));
+}
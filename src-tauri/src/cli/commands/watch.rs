@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use crate::app_config::AppType;
+use crate::cli::ui::{error, highlight, info, success, warning};
+use crate::error::AppError;
+use crate::services::{
+    LiveWatcher, WatchEvent, WatchOptions, WatchOutcome, WatchPolicy, WatchService,
+};
+use crate::store::AppState;
+use crate::sync_policy::should_sync_live;
+
+pub fn execute(
+    app: Option<AppType>,
+    provider: WatchPolicy,
+    mcp: WatchPolicy,
+    interval: u64,
+    debounce: u64,
+    once: bool,
+) -> Result<(), AppError> {
+    let state = AppState::try_new()?;
+    let options = WatchOptions {
+        apps: match app {
+            Some(app) => vec![app],
+            None => vec![AppType::Claude, AppType::Codex, AppType::Gemini],
+        },
+        provider,
+        mcp,
+        debounce: Duration::from_millis(debounce),
+    };
+
+    if once {
+        let mut found = 0;
+        for app in &options.apps {
+            for event in WatchService::check(&state, app, &options)? {
+                print_event(&event);
+                found += 1;
+            }
+        }
+        if found == 0 {
+            println!("{}", success("✓ Live configs match the store"));
+        }
+        return Ok(());
+    }
+
+    println!("{}", highlight("Watching live config files"));
+    for app in &options.apps {
+        if !should_sync_live(app) {
+            println!("  {}: not set up, skipped", app.as_str());
+            continue;
+        }
+        for path in WatchService::watched_paths(app) {
+            println!("  {}: {}", app.as_str(), path.display());
+        }
+    }
+    println!(
+        "{}",
+        info(&format!(
+            "Provider policy: {}, MCP policy: {}. Press Ctrl+C to stop.",
+            policy_name(provider),
+            policy_name(mcp)
+        ))
+    );
+
+    let mut watcher = LiveWatcher::new(options);
+    let interval = Duration::from_millis(interval.max(100));
+    loop {
+        std::thread::sleep(interval);
+        for event in watcher.poll(&state, Instant::now()) {
+            print_event(&event);
+        }
+    }
+}
+
+fn print_event(event: &WatchEvent) {
+    let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+    let text = event.describe();
+    let line = match event.outcome {
+        WatchOutcome::Reported => warning(&text),
+        WatchOutcome::Adopted | WatchOutcome::Reapplied => success(&text),
+        WatchOutcome::Failed(_) => error(&text),
+    };
+    println!("[{time}] {line}");
+}
+
+fn policy_name(policy: WatchPolicy) -> &'static str {
+    match policy {
+        WatchPolicy::Report => "report",
+        WatchPolicy::Adopt => "adopt",
+        WatchPolicy::Reapply => "reapply",
+    }
+}
@@ -4612,9 +4612,9 @@ pub mod texts {
 
     pub fn prompt_cleared_note() -> &'static str {
         if is_chinese() {
-            "实时文件已清空"
+            "已从实时文件中移除该提示词段落"
         } else {
-            "Live prompt file has been cleared"
+            "The prompt's section has been removed from the live prompt file"
         }
     }
 
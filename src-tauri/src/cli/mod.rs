@@ -9,6 +9,7 @@ pub mod tui;
 pub mod ui;
 
 use crate::app_config::AppType;
+use crate::services::WatchPolicy;
 
 #[derive(Parser)]
 #[command(
@@ -60,6 +61,64 @@ pub enum Commands {
     #[command(subcommand)]
     Env(commands::env::EnvCommand),
 
+    /// Sync configuration across machines via a git repository or directory
+    #[command(subcommand)]
+    Sync(commands::sync::SyncCommand),
+
+    /// Manage profiles: named sets of provider, MCP servers, prompts and skills per app
+    #[command(subcommand)]
+    Profile(commands::profile::ProfileCommand),
+
+    /// Apply a provider and MCP selection per directory from .cc-switch.toml (shell hook, like direnv)
+    #[command(subcommand)]
+    Hook(commands::hook::HookCommand),
+
+    /// Show recent changes to providers, MCP servers, prompts and common config
+    History {
+        /// Number of change sets to show
+        #[arg(long, short = 'n', default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Revert the most recent change sets through the normal services (live files are re-synced)
+    Undo {
+        /// Number of change sets to revert
+        #[arg(default_value_t = 1)]
+        count: usize,
+        /// Revert even if an entry was modified outside cc-switch since
+        #[arg(long)]
+        force: bool,
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Check the database, live config files, MCP commands, skill links and environment
+    Doctor {
+        /// Apply safe repairs (rewrite broken live files from the current provider, remove dangling skill links)
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Watch live config files and report, adopt or revert manual edits (runs in the foreground)
+    Watch {
+        /// What to do when the current provider's live settings change
+        #[arg(long, value_enum, default_value_t = WatchPolicy::Report)]
+        provider: WatchPolicy,
+        /// What to do when live MCP servers differ from the store
+        #[arg(long, value_enum, default_value_t = WatchPolicy::Report)]
+        mcp: WatchPolicy,
+        /// Polling interval in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        interval: u64,
+        /// Wait until files have been unchanged for this many milliseconds before checking
+        #[arg(long, default_value_t = 1500)]
+        debounce: u64,
+        /// Check once and exit instead of watching
+        #[arg(long)]
+        once: bool,
+    },
+
     /// Enter interactive mode
     #[command(alias = "ui")]
     Interactive,
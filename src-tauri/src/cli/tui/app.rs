@@ -2936,6 +2936,8 @@ mod tests {
                 homepage: None,
                 docs: None,
                 tags: vec![],
+                codex_toml: None,
+                tools: Default::default(),
             },
         });
 
@@ -2982,6 +2984,8 @@ mod tests {
                 homepage: None,
                 docs: None,
                 tags: vec![],
+                codex_toml: None,
+                tools: Default::default(),
             },
         });
 
@@ -3016,6 +3020,8 @@ mod tests {
                 homepage: None,
                 docs: None,
                 tags: vec![],
+                codex_toml: None,
+                tools: Default::default(),
             },
         });
 
@@ -3053,6 +3059,8 @@ mod tests {
                 homepage: None,
                 docs: None,
                 tags: vec![],
+                codex_toml: None,
+                tools: Default::default(),
             },
         });
 
@@ -3079,6 +3087,7 @@ mod tests {
                 enabled: false,
                 created_at: None,
                 updated_at: None,
+                stack_order: None,
             },
         });
 
@@ -3166,6 +3175,7 @@ mod tests {
                 enabled: false,
                 created_at: None,
                 updated_at: None,
+                stack_order: None,
             },
         });
 
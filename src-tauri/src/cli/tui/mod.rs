@@ -59,7 +59,7 @@ enum SkillsMsg {
     },
     InstallFinished {
         spec: String,
-        result: Result<crate::services::skill::InstalledSkill, String>,
+        result: Result<Box<crate::services::skill::InstalledSkill>, String>,
     },
 }
 
@@ -1261,6 +1261,7 @@ fn skills_worker_loop(rx: mpsc::Receiver<SkillsReq>, tx: mpsc::Sender<SkillsMsg>
                 let app_clone = app.clone();
                 let result = rt
                     .block_on(async { service.install(&spec_clone, &app_clone).await })
+                    .map(Box::new)
                     .map_err(|e| e.to_string());
                 let _ = tx.send(SkillsMsg::InstallFinished { spec, result });
             }
@@ -3404,6 +3404,9 @@ mod tests {
                 opencode: false,
             },
             installed_at: 1,
+            source: None,
+            source_commit: None,
+            content_hash: None,
         }
     }
 
@@ -3715,6 +3718,7 @@ mod tests {
             path: std::path::PathBuf::from("/tmp/b1.json"),
             timestamp: "20260131_000000".to_string(),
             display_name: "backup".to_string(),
+            encrypted: false,
         }];
 
         let buf = render(&app, &data);
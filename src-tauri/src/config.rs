@@ -133,6 +133,13 @@ pub fn write_text_file(path: &Path, data: &str) -> Result<(), AppError> {
 
 /// 原子写入：写入临时文件后 rename 替换，避免半写状态
 pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), AppError> {
+    atomic_write_with_mode(path, data, None)
+}
+
+/// 原子写入并指定 Unix 权限（如 0o600）：临时文件在写入前即以该权限创建，
+/// 内容不会以默认 umask 权限短暂落盘。`mode` 为 None 时沿用目标文件原有权限；
+/// 非 Unix 平台忽略 `mode`。
+pub fn atomic_write_with_mode(path: &Path, data: &[u8], mode: Option<u32>) -> Result<(), AppError> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
     }
@@ -153,13 +160,22 @@ pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), AppError> {
     tmp.push(format!("{file_name}.tmp.{ts}"));
 
     {
-        let mut f = fs::File::create(&tmp).map_err(|e| AppError::io(&tmp, e))?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.create_new(true).mode(mode);
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        let mut f = options.open(&tmp).map_err(|e| AppError::io(&tmp, e))?;
         f.write_all(data).map_err(|e| AppError::io(&tmp, e))?;
         f.flush().map_err(|e| AppError::io(&tmp, e))?;
     }
 
     #[cfg(unix)]
-    {
+    if mode.is_none() {
         use std::os::unix::fs::PermissionsExt;
         if let Ok(meta) = fs::metadata(path) {
             let perm = meta.permissions().mode();
@@ -1,8 +1,9 @@
 //! 数据库备份和恢复
 //!
-//! 提供 SQL 导出/导入和二进制快照备份功能。
+//! 提供 SQL 导出/导入（可选口令加密）和二进制快照备份功能。
 
 use super::{lock_conn, Database, DB_BACKUP_RETAIN};
+use crate::backup_crypto;
 use crate::config::get_app_config_dir;
 use crate::error::AppError;
 use chrono::Utc;
@@ -10,10 +11,12 @@ use rusqlite::backup::Backup;
 use rusqlite::types::ValueRef;
 use rusqlite::Connection;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use tempfile::NamedTempFile;
 
 const CC_SWITCH_SQL_EXPORT_HEADER: &str = "-- CC Switch SQLite 导出";
+/// 设置口令时导入前快照的扩展名（加密的 SQL 导出）
+const ENCRYPTED_SNAPSHOT_EXT: &str = "sql.enc";
 
 impl Database {
     /// 导出为 SQLite 兼容的 SQL 文本
@@ -28,8 +31,36 @@ impl Database {
         crate::config::atomic_write(target_path, dump.as_bytes())
     }
 
+    /// 导出为口令加密的 SQL（见 [`backup_crypto`]）
+    pub fn export_sql_encrypted(
+        &self,
+        target_path: &Path,
+        passphrase: &str,
+    ) -> Result<(), AppError> {
+        let snapshot = self.snapshot_to_memory()?;
+        let dump = Self::dump_sql(&snapshot)?;
+        let encrypted = backup_crypto::encrypt(dump.as_bytes(), passphrase)?;
+
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+
+        crate::config::atomic_write(target_path, &encrypted)
+    }
+
     /// 从 SQL 文件导入，返回生成的备份 ID（若无备份则为空字符串）
+    ///
+    /// 加密文件使用环境变量中的口令解密。
     pub fn import_sql(&self, source_path: &Path) -> Result<String, AppError> {
+        self.import_sql_with_passphrase(source_path, None)
+    }
+
+    /// 从 SQL 文件导入；加密文件优先使用 `passphrase`，其次读取环境变量
+    pub fn import_sql_with_passphrase(
+        &self,
+        source_path: &Path,
+        passphrase: Option<&str>,
+    ) -> Result<String, AppError> {
         if !source_path.exists() {
             return Err(AppError::InvalidInput(format!(
                 "SQL 文件不存在: {}",
@@ -37,12 +68,40 @@ impl Database {
             )));
         }
 
-        let sql_raw = fs::read_to_string(source_path).map_err(|e| AppError::io(source_path, e))?;
+        let raw = fs::read(source_path).map_err(|e| AppError::io(source_path, e))?;
+        let passphrase = passphrase
+            .map(str::to_string)
+            .or_else(backup_crypto::passphrase_from_env);
+        let raw = if backup_crypto::is_encrypted(&raw) {
+            let passphrase = passphrase.as_deref().ok_or_else(|| {
+                AppError::localized(
+                    "backup.encrypted.passphrase_required",
+                    format!(
+                        "备份文件已加密，请提供口令（或设置 {}）",
+                        backup_crypto::PASSPHRASE_ENV
+                    ),
+                    format!(
+                        "The backup is encrypted; provide the passphrase (or set {})",
+                        backup_crypto::PASSPHRASE_ENV
+                    ),
+                )
+            })?;
+            backup_crypto::decrypt(&raw, passphrase)?
+        } else {
+            raw
+        };
+        let sql_raw = String::from_utf8(raw).map_err(|_| {
+            AppError::localized(
+                "backup.sql.invalid_format",
+                "仅支持导入由 CC Switch 导出的 SQL 备份文件。",
+                "Only SQL backups exported by CC Switch are supported.",
+            )
+        })?;
         let sql_content = sql_raw.trim_start_matches('\u{feff}');
         Self::validate_cc_switch_sql_export(sql_content)?;
 
-        // 导入前备份现有数据库
-        let backup_path = self.backup_database_file()?;
+        // 导入前备份现有数据库（设置了口令时加密保存）
+        let backup_id = self.backup_database_file(passphrase.as_deref())?;
 
         // 在临时数据库执行导入，确保失败不会污染主库
         let temp_file = NamedTempFile::new().map_err(|e| AppError::IoContext {
@@ -72,11 +131,7 @@ impl Database {
                 .map_err(|e| AppError::Database(e.to_string()))?;
         }
 
-        let backup_id = backup_path
-            .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
-            .unwrap_or_default();
-
-        Ok(backup_id)
+        Ok(backup_id.unwrap_or_default())
     }
 
     /// 创建内存快照以避免长时间持有数据库锁
@@ -109,8 +164,11 @@ impl Database {
         ))
     }
 
-    /// 生成一致性快照备份，返回备份文件路径（不存在主库时返回 None）
-    fn backup_database_file(&self) -> Result<Option<PathBuf>, AppError> {
+    /// 生成一致性快照备份，返回备份 ID（不存在主库时返回 None）
+    ///
+    /// 未提供口令时保存为二进制 `<id>.db`；提供口令时保存为加密的 SQL 导出
+    /// `<id>.sql.enc`（可用 `config import` 恢复），避免明文快照与加密备份放在一起。
+    fn backup_database_file(&self, passphrase: Option<&str>) -> Result<Option<String>, AppError> {
         let db_path = get_app_config_dir().join("cc-switch.db");
         if !db_path.exists() {
             return Ok(None);
@@ -123,17 +181,24 @@ impl Database {
 
         fs::create_dir_all(&backup_dir).map_err(|e| AppError::io(&backup_dir, e))?;
 
+        let extension = if passphrase.is_some() {
+            ENCRYPTED_SNAPSHOT_EXT
+        } else {
+            "db"
+        };
         let base_id = format!("db_backup_{}", Utc::now().format("%Y%m%d_%H%M%S"));
         let mut backup_id = base_id.clone();
-        let mut backup_path = backup_dir.join(format!("{backup_id}.db"));
+        let mut backup_path = backup_dir.join(format!("{backup_id}.{extension}"));
         let mut counter = 1;
         while backup_path.exists() {
             backup_id = format!("{base_id}_{counter}");
-            backup_path = backup_dir.join(format!("{backup_id}.db"));
+            backup_path = backup_dir.join(format!("{backup_id}.{extension}"));
             counter += 1;
         }
 
-        {
+        if let Some(passphrase) = passphrase {
+            self.export_sql_encrypted(&backup_path, passphrase)?;
+        } else {
             let conn = lock_conn!(self.conn);
             let mut dest_conn =
                 Connection::open(&backup_path).map_err(|e| AppError::Database(e.to_string()))?;
@@ -145,7 +210,7 @@ impl Database {
         }
 
         Self::cleanup_db_backups(&backup_dir)?;
-        Ok(Some(backup_path))
+        Ok(Some(backup_id))
     }
 
     /// 清理旧的数据库备份，保留最新的 N 个
@@ -154,11 +219,8 @@ impl Database {
             Ok(iter) => iter
                 .filter_map(|entry| entry.ok())
                 .filter(|entry| {
-                    entry
-                        .path()
-                        .extension()
-                        .map(|ext| ext == "db")
-                        .unwrap_or(false)
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    name.ends_with(".db") || name.ends_with(&format!(".{ENCRYPTED_SNAPSHOT_EXT}"))
                 })
                 .collect::<Vec<_>>(),
             Err(_) => return Ok(()),
@@ -181,7 +243,7 @@ impl Database {
     }
 
     /// 基础状态校验
-    fn validate_basic_state(conn: &Connection) -> Result<(), AppError> {
+    pub(crate) fn validate_basic_state(conn: &Connection) -> Result<(), AppError> {
         let provider_count: i64 = conn
             .query_row("SELECT COUNT(*) FROM providers", [], |row| row.get(0))
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -0,0 +1,171 @@
+//! 变更日志数据访问对象
+//!
+//! 只追加的 `change_journal` 表：每次保存配置产生一个变更集，
+//! 记录供应商、MCP 服务器、提示词与通用配置变更前后的 JSON。
+
+use crate::database::{lock_conn, to_json_string, Database};
+use crate::error::AppError;
+use rusqlite::{params, Row};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// 变更日志条目
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub id: i64,
+    /// 同一次保存产生的条目共享变更集编号
+    pub change_set: i64,
+    /// Unix 时间戳（秒）
+    pub created_at: i64,
+    /// 执行变更的系统用户
+    pub actor: String,
+    /// 触发变更的命令
+    pub source: String,
+    /// provider / current_provider / mcp_server / prompt / common_config / undo
+    pub entity_type: String,
+    pub app_type: Option<String>,
+    pub entity_id: String,
+    /// create / update / delete
+    pub action: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    /// 撤销标记条目：所在变更集撤销的变更集
+    pub reverts: Option<i64>,
+}
+
+const ENTRY_COLUMNS: &str = "id, change_set, created_at, actor, source, entity_type, app_type, \
+     entity_id, action, before_json, after_json, reverts";
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<JournalEntry> {
+    let parse = |text: Option<String>| text.and_then(|t| serde_json::from_str(&t).ok());
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        change_set: row.get(1)?,
+        created_at: row.get(2)?,
+        actor: row.get(3)?,
+        source: row.get(4)?,
+        entity_type: row.get(5)?,
+        app_type: row.get(6)?,
+        entity_id: row.get(7)?,
+        action: row.get(8)?,
+        before: parse(row.get(9)?),
+        after: parse(row.get(10)?),
+        reverts: row.get(11)?,
+    })
+}
+
+impl Database {
+    /// 下一个可用的变更集编号
+    pub fn next_journal_change_set(&self) -> Result<i64, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.query_row(
+            "SELECT COALESCE(MAX(change_set), 0) + 1 FROM change_journal",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 追加日志条目（忽略条目中的 id）
+    pub fn append_journal_entries(&self, entries: &[JournalEntry]) -> Result<(), AppError> {
+        let mut conn = lock_conn!(self.conn);
+        let tx = conn
+            .transaction()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        for entry in entries {
+            let before = entry.before.as_ref().map(to_json_string).transpose()?;
+            let after = entry.after.as_ref().map(to_json_string).transpose()?;
+            tx.execute(
+                "INSERT INTO change_journal (
+                    change_set, created_at, actor, source, entity_type, app_type, entity_id,
+                    action, before_json, after_json, reverts
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    entry.change_set,
+                    entry.created_at,
+                    entry.actor,
+                    entry.source,
+                    entry.entity_type,
+                    entry.app_type,
+                    entry.entity_id,
+                    entry.action,
+                    before,
+                    after,
+                    entry.reverts,
+                ],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        tx.commit().map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 最近 `limit` 个变更集的全部条目（变更集降序，集内按写入顺序）
+    pub fn get_journal_entries(&self, limit: usize) -> Result<Vec<JournalEntry>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let sql = format!(
+            "SELECT {ENTRY_COLUMNS} FROM change_journal
+             WHERE change_set IN (
+                SELECT DISTINCT change_set FROM change_journal ORDER BY change_set DESC LIMIT ?1
+             )
+             ORDER BY change_set DESC, id ASC"
+        );
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![limit as i64], entry_from_row)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 指定变更集的全部条目（按写入顺序）
+    pub fn get_journal_change_set(&self, change_set: i64) -> Result<Vec<JournalEntry>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let sql =
+            format!("SELECT {ENTRY_COLUMNS} FROM change_journal WHERE change_set = ?1 ORDER BY id");
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![change_set], entry_from_row)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 尚未撤销、且本身不是撤销操作（不含撤销标记）的最近 `limit` 个变更集（降序）
+    pub fn get_undoable_change_sets(&self, limit: usize) -> Result<Vec<i64>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT change_set FROM change_journal
+                 WHERE change_set NOT IN (
+                       SELECT change_set FROM change_journal WHERE reverts IS NOT NULL
+                   )
+                   AND change_set NOT IN (
+                       SELECT reverts FROM change_journal WHERE reverts IS NOT NULL
+                   )
+                 ORDER BY change_set DESC LIMIT ?1",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(params![limit as i64], |row| row.get(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 已被撤销的变更集
+    pub fn get_reverted_change_sets(&self) -> Result<HashSet<i64>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT reverts FROM change_journal WHERE reverts IS NOT NULL")
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<HashSet<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+}
@@ -13,7 +13,7 @@ impl Database {
     pub fn get_all_mcp_servers(&self) -> Result<IndexMap<String, McpServer>, AppError> {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn.prepare(
-            "SELECT id, name, server_config, description, homepage, docs, tags, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode
+            "SELECT id, name, server_config, description, homepage, docs, tags, enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, codex_toml, tool_filters
              FROM mcp_servers
              ORDER BY name ASC, id ASC"
         ).map_err(|e| AppError::Database(e.to_string()))?;
@@ -31,9 +31,12 @@ impl Database {
                 let enabled_codex: bool = row.get(8)?;
                 let enabled_gemini: bool = row.get(9)?;
                 let enabled_opencode: bool = row.get(10)?;
+                let codex_toml: Option<String> = row.get(11)?;
+                let tool_filters_str: String = row.get(12)?;
 
                 let server = serde_json::from_str(&server_config_str).unwrap_or_default();
                 let tags = serde_json::from_str(&tags_str).unwrap_or_default();
+                let tools = serde_json::from_str(&tool_filters_str).unwrap_or_default();
 
                 Ok((
                     id.clone(),
@@ -51,6 +54,8 @@ impl Database {
                         homepage,
                         docs,
                         tags,
+                        codex_toml,
+                        tools,
                     },
                 ))
             })
@@ -70,8 +75,9 @@ impl Database {
         conn.execute(
             "INSERT OR REPLACE INTO mcp_servers (
                 id, name, server_config, description, homepage, docs, tags,
-                enabled_claude, enabled_codex, enabled_gemini, enabled_opencode
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                enabled_claude, enabled_codex, enabled_gemini, enabled_opencode, codex_toml,
+                tool_filters
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 server.id,
                 server.name,
@@ -87,6 +93,10 @@ impl Database {
                 server.apps.codex,
                 server.apps.gemini,
                 server.apps.opencode,
+                server.codex_toml,
+                serde_json::to_string(&server.tools).map_err(|e| AppError::Database(format!(
+                    "Failed to serialize tool filters: {e}"
+                )))?,
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -3,7 +3,9 @@
 //! Database access operations for each domain
 
 pub mod failover;
+pub mod journal;
 pub mod mcp;
+pub mod profiles;
 pub mod prompts;
 pub mod providers;
 pub mod settings;
@@ -16,3 +18,4 @@ pub mod skills;
 // 所有 DAO 方法都通过 Database impl 提供，无需单独导出
 // 导出 FailoverQueueItem 供外部使用
 pub use failover::FailoverQueueItem;
+pub use journal::JournalEntry;
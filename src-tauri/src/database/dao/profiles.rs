@@ -0,0 +1,119 @@
+//! 配置档案数据访问对象
+//!
+//! `profiles` 表：每个档案一行，各应用的选择以 JSON 保存在 `apps_json` 中。
+
+use crate::database::{lock_conn, to_json_string, Database};
+use crate::error::AppError;
+use crate::profile::Profile;
+use rusqlite::{params, Row};
+
+/// 最近一次应用的档案名称（settings 表）
+const ACTIVE_PROFILE_KEY: &str = "active_profile";
+
+fn profile_from_row(row: &Row) -> rusqlite::Result<(Profile, String)> {
+    Ok((
+        Profile {
+            name: row.get(0)?,
+            description: row.get(1)?,
+            apps: Default::default(),
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
+        },
+        row.get(2)?,
+    ))
+}
+
+fn parse_apps((mut profile, apps_json): (Profile, String)) -> Result<Profile, AppError> {
+    profile.apps = serde_json::from_str(&apps_json)
+        .map_err(|e| AppError::Database(format!("解析配置档案 {} 失败: {e}", profile.name)))?;
+    Ok(profile)
+}
+
+impl Database {
+    /// 获取所有配置档案（按名称排序）
+    pub fn get_profiles(&self) -> Result<Vec<Profile>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT name, description, apps_json, created_at, updated_at
+             FROM profiles ORDER BY name ASC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map([], profile_from_row)
+            .map_err(|e| AppError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        rows.into_iter().map(parse_apps).collect()
+    }
+
+    /// 按名称获取配置档案
+    pub fn get_profile(&self, name: &str) -> Result<Option<Profile>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let row = conn
+            .query_row(
+                "SELECT name, description, apps_json, created_at, updated_at
+             FROM profiles WHERE name = ?1",
+                params![name],
+                profile_from_row,
+            )
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(AppError::Database(e.to_string())),
+            })?;
+
+        row.map(parse_apps).transpose()
+    }
+
+    /// 保存配置档案（同名覆盖）
+    pub fn save_profile(&self, profile: &Profile) -> Result<(), AppError> {
+        let apps_json = to_json_string(&profile.apps)?;
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO profiles (name, description, apps_json, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                profile.name,
+                profile.description,
+                apps_json,
+                profile.created_at,
+                profile.updated_at,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 删除配置档案，返回是否存在
+    pub fn delete_profile(&self, name: &str) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let affected = conn
+            .execute("DELETE FROM profiles WHERE name = ?1", params![name])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(affected > 0)
+    }
+
+    /// 最近一次应用的档案名称
+    pub fn get_active_profile(&self) -> Result<Option<String>, AppError> {
+        self.get_setting(ACTIVE_PROFILE_KEY)
+    }
+
+    /// 记录最近一次应用的档案；None 时清除
+    pub fn set_active_profile(&self, name: Option<&str>) -> Result<(), AppError> {
+        match name {
+            Some(name) => self.set_setting(ACTIVE_PROFILE_KEY, name),
+            None => {
+                let conn = lock_conn!(self.conn);
+                conn.execute(
+                    "DELETE FROM settings WHERE key = ?1",
+                    params![ACTIVE_PROFILE_KEY],
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+                Ok(())
+            }
+        }
+    }
+}
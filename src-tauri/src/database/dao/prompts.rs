@@ -1,10 +1,10 @@
 //! 提示词数据访问对象
 //!
-//! 提供提示词（Prompt）的 CRUD 操作。
+//! 提供提示词（Prompt）及项目级提示词绑定的 CRUD 操作。
 
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
-use crate::prompt::Prompt;
+use crate::prompt::{Prompt, PromptProject};
 use indexmap::IndexMap;
 use rusqlite::params;
 
@@ -14,7 +14,7 @@ impl Database {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn
             .prepare(
-                "SELECT id, name, content, description, enabled, created_at, updated_at
+                "SELECT id, name, content, description, enabled, created_at, updated_at, stack_order
              FROM prompts WHERE app_type = ?1
              ORDER BY created_at ASC, id ASC",
             )
@@ -29,6 +29,7 @@ impl Database {
                 let enabled: bool = row.get(4)?;
                 let created_at: Option<i64> = row.get(5)?;
                 let updated_at: Option<i64> = row.get(6)?;
+                let stack_order: Option<i64> = row.get(7)?;
 
                 Ok((
                     id.clone(),
@@ -40,6 +41,7 @@ impl Database {
                         enabled,
                         created_at,
                         updated_at,
+                        stack_order,
                     },
                 ))
             })
@@ -58,8 +60,9 @@ impl Database {
         let conn = lock_conn!(self.conn);
         conn.execute(
             "INSERT OR REPLACE INTO prompts (
-                id, app_type, name, content, description, enabled, created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                id, app_type, name, content, description, enabled, created_at, updated_at,
+                stack_order
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 prompt.id,
                 app_type,
@@ -69,6 +72,7 @@ impl Database {
                 prompt.enabled,
                 prompt.created_at,
                 prompt.updated_at,
+                prompt.stack_order,
             ],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -85,4 +89,66 @@ impl Database {
         .map_err(|e| AppError::Database(e.to_string()))?;
         Ok(())
     }
+
+    /// 获取项目级提示词绑定；`app_type` 为 None 时返回全部应用
+    pub fn get_prompt_projects(
+        &self,
+        app_type: Option<&str>,
+    ) -> Result<Vec<PromptProject>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT app_type, project_path, prompt_id, activated_at
+             FROM prompt_projects WHERE ?1 IS NULL OR app_type = ?1
+             ORDER BY project_path ASC, app_type ASC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(params![app_type], |row| {
+                Ok(PromptProject {
+                    app_type: row.get(0)?,
+                    project_path: row.get(1)?,
+                    prompt_id: row.get(2)?,
+                    activated_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 保存项目级提示词绑定（每个项目每个应用一个预设）
+    pub fn save_prompt_project(&self, project: &PromptProject) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO prompt_projects (app_type, project_path, prompt_id, activated_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                project.app_type,
+                project.project_path,
+                project.prompt_id,
+                project.activated_at,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 删除项目级提示词绑定，返回是否存在
+    pub fn delete_prompt_project(
+        &self,
+        app_type: &str,
+        project_path: &str,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let affected = conn
+            .execute(
+                "DELETE FROM prompt_projects WHERE app_type = ?1 AND project_path = ?2",
+                params![app_type, project_path],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(affected > 0)
+    }
 }
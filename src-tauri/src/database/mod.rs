@@ -17,6 +17,7 @@
 //! ├── migration.rs  - JSON → SQLite 数据迁移
 //! └── dao/          - 数据访问对象
 //!     ├── providers.rs
+//!     ├── journal.rs
 //!     ├── mcp.rs
 //!     ├── prompts.rs
 //!     ├── skills.rs
@@ -32,7 +33,7 @@ mod schema;
 mod tests;
 
 // DAO 类型导出供外部使用
-pub use dao::FailoverQueueItem;
+pub use dao::{FailoverQueueItem, JournalEntry};
 
 use crate::config::get_app_config_dir;
 use crate::error::AppError;
@@ -47,7 +48,7 @@ const DB_BACKUP_RETAIN: usize = 10;
 
 /// 当前 Schema 版本号
 /// 每次修改表结构时递增，并在 schema.rs 中添加相应的迁移逻辑
-pub(crate) const SCHEMA_VERSION: i32 = 5;
+pub(crate) const SCHEMA_VERSION: i32 = 13;
 
 /// 安全地序列化 JSON，避免 unwrap panic
 pub(crate) fn to_json_string<T: Serialize>(value: &T) -> Result<String, AppError> {
@@ -129,6 +130,33 @@ impl Database {
         Ok(count == 0)
     }
 
+    /// 当前 Schema 版本（`PRAGMA user_version`）
+    pub fn schema_version(&self) -> Result<i32, AppError> {
+        let conn = lock_conn!(self.conn);
+        Self::get_user_version(&conn)
+    }
+
+    /// SQLite 快速完整性检查，返回发现的问题（为空表示正常）
+    pub fn integrity_problems(&self) -> Result<Vec<String>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare("PRAGMA quick_check;")
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let messages = rows
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(messages.into_iter().filter(|m| m != "ok").collect())
+    }
+
+    /// 基础状态校验：至少存在供应商或 MCP 服务器
+    pub fn validate_state(&self) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        Self::validate_basic_state(&conn)
+    }
+
     /// 检查提示词表是否为空
     pub fn is_prompts_table_empty(&self) -> Result<bool, AppError> {
         let conn = lock_conn!(self.conn);
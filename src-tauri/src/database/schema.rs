@@ -58,7 +58,8 @@ impl Database {
             id TEXT PRIMARY KEY, name TEXT NOT NULL, server_config TEXT NOT NULL,
             description TEXT, homepage TEXT, docs TEXT, tags TEXT NOT NULL DEFAULT '[]',
             enabled_claude BOOLEAN NOT NULL DEFAULT 0, enabled_codex BOOLEAN NOT NULL DEFAULT 0,
-            enabled_gemini BOOLEAN NOT NULL DEFAULT 0, enabled_opencode BOOLEAN NOT NULL DEFAULT 0
+            enabled_gemini BOOLEAN NOT NULL DEFAULT 0, enabled_opencode BOOLEAN NOT NULL DEFAULT 0,
+            codex_toml TEXT, tool_filters TEXT NOT NULL DEFAULT '{}'
         )",
             [],
         )
@@ -68,9 +69,19 @@ impl Database {
         conn.execute("CREATE TABLE IF NOT EXISTS prompts (
             id TEXT NOT NULL, app_type TEXT NOT NULL, name TEXT NOT NULL, content TEXT NOT NULL,
             description TEXT, enabled BOOLEAN NOT NULL DEFAULT 1, created_at INTEGER, updated_at INTEGER,
+            stack_order INTEGER,
             PRIMARY KEY (id, app_type)
         )", []).map_err(|e| AppError::Database(e.to_string()))?;
 
+        // 4.1 项目级提示词绑定表（项目目录 -> 预设）
+        Self::create_prompt_projects_table(conn)?;
+
+        // 4.2 变更日志表（只追加）
+        Self::create_change_journal_table(conn)?;
+
+        // 4.3 配置档案表
+        Self::create_profiles_table(conn)?;
+
         // 5. Skills 表（v3.10.0+ 统一结构）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS skills (
@@ -86,7 +97,10 @@ impl Database {
             enabled_codex BOOLEAN NOT NULL DEFAULT 0,
             enabled_gemini BOOLEAN NOT NULL DEFAULT 0,
             enabled_opencode BOOLEAN NOT NULL DEFAULT 0,
-            installed_at INTEGER NOT NULL DEFAULT 0
+            installed_at INTEGER NOT NULL DEFAULT 0,
+            source TEXT,
+            source_commit TEXT,
+            content_hash TEXT
         )",
             [],
         )
@@ -360,6 +374,46 @@ impl Database {
                         Self::migrate_v4_to_v5(conn)?;
                         Self::set_user_version(conn, 5)?;
                     }
+                    5 => {
+                        log::info!("迁移数据库从 v5 到 v6（Codex MCP 原始 TOML 片段）");
+                        Self::migrate_v5_to_v6(conn)?;
+                        Self::set_user_version(conn, 6)?;
+                    }
+                    6 => {
+                        log::info!("迁移数据库从 v6 到 v7（MCP 工具过滤规则）");
+                        Self::migrate_v6_to_v7(conn)?;
+                        Self::set_user_version(conn, 7)?;
+                    }
+                    7 => {
+                        log::info!("迁移数据库从 v7 到 v8（Skill 版本信息）");
+                        Self::migrate_v7_to_v8(conn)?;
+                        Self::set_user_version(conn, 8)?;
+                    }
+                    8 => {
+                        log::info!("迁移数据库从 v8 到 v9（Skill 安装来源）");
+                        Self::migrate_v8_to_v9(conn)?;
+                        Self::set_user_version(conn, 9)?;
+                    }
+                    9 => {
+                        log::info!("迁移数据库从 v9 到 v10（提示词叠加栈）");
+                        Self::migrate_v9_to_v10(conn)?;
+                        Self::set_user_version(conn, 10)?;
+                    }
+                    10 => {
+                        log::info!("迁移数据库从 v10 到 v11（项目级提示词）");
+                        Self::migrate_v10_to_v11(conn)?;
+                        Self::set_user_version(conn, 11)?;
+                    }
+                    11 => {
+                        log::info!("迁移数据库从 v11 到 v12（变更日志）");
+                        Self::migrate_v11_to_v12(conn)?;
+                        Self::set_user_version(conn, 12)?;
+                    }
+                    12 => {
+                        log::info!("迁移数据库从 v12 到 v13（配置档案）");
+                        Self::migrate_v12_to_v13(conn)?;
+                        Self::set_user_version(conn, 13)?;
+                    }
                     _ => {
                         return Err(AppError::Database(format!(
                             "未知的数据库版本 {version}，无法迁移到 {SCHEMA_VERSION}"
@@ -914,6 +968,126 @@ impl Database {
         Ok(())
     }
 
+    /// v5 -> v6 迁移：为 mcp_servers 表添加 codex_toml 列
+    fn migrate_v5_to_v6(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "mcp_servers")? {
+            Self::add_column_if_missing(conn, "mcp_servers", "codex_toml", "TEXT")?;
+        }
+
+        log::info!("v5 -> v6 迁移完成：已添加 Codex MCP 原始 TOML 字段");
+        Ok(())
+    }
+
+    /// v6 -> v7 迁移：为 mcp_servers 表添加 tool_filters 列
+    fn migrate_v6_to_v7(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "mcp_servers")? {
+            Self::add_column_if_missing(
+                conn,
+                "mcp_servers",
+                "tool_filters",
+                "TEXT NOT NULL DEFAULT '{}'",
+            )?;
+        }
+
+        log::info!("v6 -> v7 迁移完成：已添加 MCP 工具过滤规则字段");
+        Ok(())
+    }
+
+    /// v7 -> v8 迁移：记录 Skill 安装来源的提交 SHA 与内容哈希
+    fn migrate_v7_to_v8(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "skills")? {
+            Self::add_column_if_missing(conn, "skills", "source_commit", "TEXT")?;
+            Self::add_column_if_missing(conn, "skills", "content_hash", "TEXT")?;
+        }
+
+        log::info!("v7 -> v8 迁移完成：已添加 Skill 提交 SHA 与内容哈希字段");
+        Ok(())
+    }
+
+    /// v8 -> v9 迁移：记录非仓库 Skill 的安装来源（file://、git+https://、ZIP URL）
+    fn migrate_v8_to_v9(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "skills")? {
+            Self::add_column_if_missing(conn, "skills", "source", "TEXT")?;
+        }
+
+        log::info!("v8 -> v9 迁移完成：已添加 Skill 安装来源字段");
+        Ok(())
+    }
+
+    /// v9 -> v10 迁移：提示词支持多层叠加，记录每层在栈中的顺序
+    fn migrate_v9_to_v10(conn: &Connection) -> Result<(), AppError> {
+        if Self::table_exists(conn, "prompts")? {
+            Self::add_column_if_missing(conn, "prompts", "stack_order", "INTEGER")?;
+        }
+
+        log::info!("v9 -> v10 迁移完成：已添加提示词栈顺序字段");
+        Ok(())
+    }
+
+    /// v10 -> v11 迁移：记录各项目目录使用的提示词预设
+    fn migrate_v10_to_v11(conn: &Connection) -> Result<(), AppError> {
+        Self::create_prompt_projects_table(conn)?;
+
+        log::info!("v10 -> v11 迁移完成：已添加项目级提示词绑定表");
+        Ok(())
+    }
+
+    /// v11 -> v12 迁移：记录配置变更历史，支持撤销
+    fn migrate_v11_to_v12(conn: &Connection) -> Result<(), AppError> {
+        Self::create_change_journal_table(conn)?;
+
+        log::info!("v11 -> v12 迁移完成：已添加变更日志表");
+        Ok(())
+    }
+
+    /// v12 -> v13 迁移：按名称保存的配置档案
+    fn migrate_v12_to_v13(conn: &Connection) -> Result<(), AppError> {
+        Self::create_profiles_table(conn)?;
+
+        log::info!("v12 -> v13 迁移完成：已添加配置档案表");
+        Ok(())
+    }
+
+    fn create_change_journal_table(conn: &Connection) -> Result<(), AppError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS change_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            change_set INTEGER NOT NULL, created_at INTEGER NOT NULL,
+            actor TEXT NOT NULL, source TEXT NOT NULL,
+            entity_type TEXT NOT NULL, app_type TEXT, entity_id TEXT NOT NULL,
+            action TEXT NOT NULL, before_json TEXT, after_json TEXT, reverts INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_change_journal_set ON change_journal(change_set);",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn create_profiles_table(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS profiles (
+            name TEXT PRIMARY KEY, description TEXT, apps_json TEXT NOT NULL,
+            created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn create_prompt_projects_table(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS prompt_projects (
+            app_type TEXT NOT NULL, project_path TEXT NOT NULL, prompt_id TEXT NOT NULL,
+            activated_at INTEGER NOT NULL,
+            PRIMARY KEY (app_type, project_path)
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
     /// 插入默认模型定价数据
     /// 格式: (model_id, display_name, input, output, cache_read, cache_creation)
     /// 注意: model_id 使用短横线格式（如 claude-haiku-4-5），与 API 返回的模型名称标准化后一致
@@ -327,6 +327,244 @@ fn schema_migration_v4_adds_pricing_model_columns() {
     );
 }
 
+#[test]
+fn schema_migration_v5_adds_codex_toml_and_tool_filter_columns() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE mcp_servers (id TEXT PRIMARY KEY, name TEXT NOT NULL, server_config TEXT NOT NULL);
+        INSERT INTO mcp_servers (id, name, server_config) VALUES ('fetch', 'Fetch', '{}');
+        "#,
+    )
+    .expect("seed v5 schema");
+
+    Database::set_user_version(&conn, 5).expect("set user_version=5");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let codex_toml = get_column_info(&conn, "mcp_servers", "codex_toml");
+    assert_eq!(codex_toml.r#type, "TEXT");
+    assert_eq!(codex_toml.notnull, 0);
+
+    let tool_filters = get_column_info(&conn, "mcp_servers", "tool_filters");
+    assert_eq!(tool_filters.notnull, 1);
+    assert_eq!(
+        normalize_default(&tool_filters.default).as_deref(),
+        Some("{}")
+    );
+
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT codex_toml FROM mcp_servers WHERE id = 'fetch'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("read existing row");
+    assert_eq!(existing, None);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v7_adds_skill_version_and_source_columns() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE skills (id TEXT PRIMARY KEY, name TEXT NOT NULL, directory TEXT NOT NULL);
+        INSERT INTO skills (id, name, directory) VALUES ('local:pdf', 'PDF', 'pdf');
+        "#,
+    )
+    .expect("seed v7 schema");
+
+    Database::set_user_version(&conn, 7).expect("set user_version=7");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    for column in ["source", "source_commit", "content_hash"] {
+        let info = get_column_info(&conn, "skills", column);
+        assert_eq!(info.r#type, "TEXT");
+        assert_eq!(info.notnull, 0);
+    }
+
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT content_hash FROM skills WHERE id = 'local:pdf'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("read existing row");
+    assert_eq!(existing, None);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v9_adds_prompt_stack_order_column() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    conn.execute_batch(
+        r#"
+        CREATE TABLE prompts (
+            id TEXT NOT NULL, app_type TEXT NOT NULL, name TEXT NOT NULL, content TEXT NOT NULL,
+            description TEXT, enabled BOOLEAN NOT NULL DEFAULT 1, created_at INTEGER, updated_at INTEGER,
+            PRIMARY KEY (id, app_type)
+        );
+        INSERT INTO prompts (id, app_type, name, content, enabled)
+        VALUES ('team', 'claude', 'Team', 'Be kind.', 1);
+        "#,
+    )
+    .expect("seed v9 schema");
+
+    Database::set_user_version(&conn, 9).expect("set user_version=9");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    let info = get_column_info(&conn, "prompts", "stack_order");
+    assert_eq!(info.r#type, "INTEGER");
+    assert_eq!(info.notnull, 0);
+
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT stack_order FROM prompts WHERE id = 'team'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("read existing row");
+    assert_eq!(existing, None);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v10_adds_prompt_projects_table() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 10).expect("set user_version=10");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    assert!(Database::table_exists(&conn, "prompt_projects").expect("check table"));
+    let info = get_column_info(&conn, "prompt_projects", "activated_at");
+    assert_eq!(info.r#type, "INTEGER");
+    assert_eq!(info.notnull, 1);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v11_adds_change_journal_table() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 11).expect("set user_version=11");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    assert!(Database::table_exists(&conn, "change_journal").expect("check table"));
+    let info = get_column_info(&conn, "change_journal", "change_set");
+    assert_eq!(info.r#type, "INTEGER");
+    assert_eq!(info.notnull, 1);
+    let info = get_column_info(&conn, "change_journal", "before_json");
+    assert_eq!(info.notnull, 0);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn schema_migration_v12_adds_profiles_table() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 12).expect("set user_version=12");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+
+    assert!(Database::table_exists(&conn, "profiles").expect("check table"));
+    let info = get_column_info(&conn, "profiles", "apps_json");
+    assert_eq!(info.r#type, "TEXT");
+    assert_eq!(info.notnull, 1);
+
+    assert_eq!(
+        Database::get_user_version(&conn).expect("version after migration"),
+        SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn profiles_round_trip() {
+    use crate::profile::{Profile, ProfileApp};
+
+    let db = Database::memory().expect("memory db");
+    let mut profile = Profile {
+        name: "work".to_string(),
+        description: Some("Company relay".to_string()),
+        apps: [(
+            "claude".to_string(),
+            ProfileApp {
+                provider: Some("relay".to_string()),
+                mcp_servers: vec!["jira".to_string()],
+                prompts: vec!["strict".to_string()],
+                skills: vec!["review".to_string()],
+            },
+        )]
+        .into(),
+        created_at: 1,
+        updated_at: 1,
+    };
+    db.save_profile(&profile).expect("save");
+    assert_eq!(db.get_profile("work").expect("get"), Some(profile.clone()));
+
+    profile.updated_at = 2;
+    profile.apps.clear();
+    db.save_profile(&profile).expect("overwrite");
+    assert_eq!(db.get_profiles().expect("list"), [profile]);
+
+    assert!(db.delete_profile("work").expect("delete"));
+    assert!(!db.delete_profile("work").expect("delete again"));
+    assert_eq!(db.get_profile("work").expect("get"), None);
+}
+
+#[test]
+fn journal_tracks_undoable_change_sets() {
+    let db = Database::memory().expect("memory db");
+    let entry = |change_set: i64, reverts: Option<i64>| JournalEntry {
+        id: 0,
+        change_set,
+        created_at: 0,
+        actor: "tester".to_string(),
+        source: "cc-switch".to_string(),
+        entity_type: "provider".to_string(),
+        app_type: Some("claude".to_string()),
+        entity_id: "p1".to_string(),
+        action: "update".to_string(),
+        before: Some(json!({ "name": "a" })),
+        after: Some(json!({ "name": "b" })),
+        reverts,
+    };
+
+    assert_eq!(db.next_journal_change_set().expect("next"), 1);
+    db.append_journal_entries(&[entry(1, None), entry(1, None), entry(2, None)])
+        .expect("append");
+    db.append_journal_entries(&[entry(3, Some(2))])
+        .expect("append revert");
+
+    assert_eq!(db.next_journal_change_set().expect("next"), 4);
+    assert_eq!(db.get_undoable_change_sets(10).expect("undoable"), [1]);
+    assert!(db
+        .get_reverted_change_sets()
+        .expect("reverted")
+        .contains(&2));
+
+    let recent = db.get_journal_entries(2).expect("entries");
+    let sets: Vec<i64> = recent.iter().map(|e| e.change_set).collect();
+    assert_eq!(sets, [3, 2]);
+    assert_eq!(recent[1].after, Some(json!({ "name": "b" })));
+    assert_eq!(db.get_journal_change_set(1).expect("set").len(), 2);
+}
+
 #[test]
 fn schema_create_tables_repairs_legacy_proxy_config_singleton_to_per_app() {
     let conn = Connection::open_in_memory().expect("open memory db");
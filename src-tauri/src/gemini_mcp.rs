@@ -50,40 +50,46 @@ pub fn read_mcp_json() -> Result<Option<String>, AppError> {
 
 /// 读取 Gemini settings.json 中的 mcpServers 映射
 pub fn read_mcp_servers_map() -> Result<std::collections::HashMap<String, Value>, AppError> {
-    let path = user_config_path();
+    read_servers_map_at(&user_config_path())
+}
+
+/// 项目级 Gemini 配置文件路径（<project>/.gemini/settings.json）
+pub fn get_project_settings_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(".gemini").join("settings.json")
+}
+
+/// 读取项目级 Gemini settings.json 中的 mcpServers 映射
+pub fn read_project_mcp_servers_map(
+    project_dir: &Path,
+) -> Result<std::collections::HashMap<String, Value>, AppError> {
+    read_servers_map_at(&get_project_settings_path(project_dir))
+}
+
+/// 将 MCP 服务器映射写入项目级 Gemini settings.json（不存在且为空时不创建）
+pub fn set_project_mcp_servers_map(
+    project_dir: &Path,
+    servers: &std::collections::HashMap<String, Value>,
+) -> Result<(), AppError> {
+    let path = get_project_settings_path(project_dir);
+    if servers.is_empty() && !path.exists() {
+        return Ok(());
+    }
+    set_servers_map_at(&path, servers)
+}
+
+fn read_servers_map_at(path: &Path) -> Result<std::collections::HashMap<String, Value>, AppError> {
     if !path.exists() {
         return Ok(std::collections::HashMap::new());
     }
 
-    let root = read_json_value(&path)?;
+    let root = read_json_value(path)?;
     let mut servers: std::collections::HashMap<String, Value> = std::collections::HashMap::new();
     let Some(obj) = root.get("mcpServers").and_then(|v| v.as_object()) else {
         return Ok(servers);
     };
 
     for (id, raw_spec) in obj {
-        let mut spec = raw_spec.clone();
-
-        // Reverse conversion (align upstream):
-        // - httpUrl -> url + type:"http"
-        // - if no type: command => "stdio", url => "sse"
-        if let Some(spec_obj) = spec.as_object_mut() {
-            if let Some(http_url_value) = spec_obj.remove("httpUrl") {
-                spec_obj.insert("url".to_string(), http_url_value);
-                spec_obj.insert("type".to_string(), Value::String("http".to_string()));
-            }
-
-            let has_type = spec_obj.get("type").and_then(|v| v.as_str()).is_some();
-            if !has_type {
-                if spec_obj.get("command").and_then(|v| v.as_str()).is_some() {
-                    spec_obj.insert("type".to_string(), Value::String("stdio".to_string()));
-                } else if spec_obj.get("url").and_then(|v| v.as_str()).is_some() {
-                    spec_obj.insert("type".to_string(), Value::String("sse".to_string()));
-                }
-            }
-        }
-
-        servers.insert(id.clone(), spec);
+        servers.insert(id.clone(), from_gemini_entry(raw_spec));
     }
 
     Ok(servers)
@@ -94,9 +100,15 @@ pub fn read_mcp_servers_map() -> Result<std::collections::HashMap<String, Value>
 pub fn set_mcp_servers_map(
     servers: &std::collections::HashMap<String, Value>,
 ) -> Result<(), AppError> {
-    let path = user_config_path();
+    set_servers_map_at(&user_config_path(), servers)
+}
+
+fn set_servers_map_at(
+    path: &Path,
+    servers: &std::collections::HashMap<String, Value>,
+) -> Result<(), AppError> {
     let mut root = if path.exists() {
-        read_json_value(&path)?
+        read_json_value(path)?
     } else {
         serde_json::json!({})
     };
@@ -104,82 +116,7 @@ pub fn set_mcp_servers_map(
     // 构建 mcpServers 对象：移除 UI 辅助字段（enabled/source），仅保留实际 MCP 规范
     let mut out: Map<String, Value> = Map::new();
     for (id, spec) in servers.iter() {
-        let mut obj = if let Some(map) = spec.as_object() {
-            map.clone()
-        } else {
-            return Err(AppError::McpValidation(format!(
-                "MCP 服务器 '{id}' 不是对象"
-            )));
-        };
-
-        // 提取 server 字段（如果存在）
-        if let Some(server_val) = obj.remove("server") {
-            let server_obj = server_val.as_object().cloned().ok_or_else(|| {
-                AppError::McpValidation(format!("MCP 服务器 '{id}' server 字段不是对象"))
-            })?;
-            obj = server_obj;
-        }
-
-        // Gemini CLI 格式转换：
-        // - Gemini 不使用 "type" 字段（从字段名推断传输类型）
-        // - HTTP 使用 "httpUrl" 字段，SSE 使用 "url" 字段
-        let transport_type = obj.get("type").and_then(|v| v.as_str());
-        if transport_type == Some("http") {
-            // HTTP streaming: 将 "url" 重命名为 "httpUrl"
-            if let Some(url_value) = obj.remove("url") {
-                obj.insert("httpUrl".to_string(), url_value);
-            }
-        }
-        // SSE 保持 "url" 字段不变
-
-        // Timeout conversion:
-        // - CC-Switch/Codex/Claude may use startup_timeout_* / tool_timeout_*.
-        // - Gemini CLI uses a single timeout field (ms).
-        // Derive Gemini timeout by taking the maximum of:
-        //   - existing `timeout` (if any)
-        //   - startup timeout (default 10s)
-        //   - tool timeout (default 60s)
-        const DEFAULT_STARTUP_MS: u64 = 10_000;
-        const DEFAULT_TOOL_MS: u64 = 60_000;
-
-        let existing_timeout_ms = obj
-            .get("timeout")
-            .and_then(|val| val.as_u64().or_else(|| val.as_f64().map(|f| f as u64)));
-
-        let extract_timeout =
-            |obj: &mut Map<String, Value>, key: &str, multiplier: u64| -> Option<u64> {
-                obj.remove(key).and_then(|val| {
-                    val.as_u64()
-                        .map(|n| n.saturating_mul(multiplier))
-                        .or_else(|| val.as_f64().map(|f| (f * multiplier as f64) as u64))
-                })
-            };
-
-        let startup_ms = extract_timeout(&mut obj, "startup_timeout_sec", 1000)
-            .or_else(|| extract_timeout(&mut obj, "startup_timeout_ms", 1))
-            .unwrap_or(DEFAULT_STARTUP_MS);
-        let tool_ms = extract_timeout(&mut obj, "tool_timeout_sec", 1000)
-            .or_else(|| extract_timeout(&mut obj, "tool_timeout_ms", 1))
-            .unwrap_or(DEFAULT_TOOL_MS);
-
-        let derived_timeout_ms = startup_ms.max(tool_ms);
-        let final_timeout_ms = existing_timeout_ms.unwrap_or(0).max(derived_timeout_ms);
-        obj.insert(
-            "timeout".to_string(),
-            Value::Number(final_timeout_ms.into()),
-        );
-
-        // 移除 UI 辅助字段和 type 字段（Gemini 不需要）
-        obj.remove("type");
-        obj.remove("enabled");
-        obj.remove("source");
-        obj.remove("id");
-        obj.remove("name");
-        obj.remove("description");
-        obj.remove("tags");
-        obj.remove("homepage");
-        obj.remove("docs");
-
+        let obj = to_gemini_entry(id, spec)?;
         out.insert(id.clone(), Value::Object(obj));
     }
 
@@ -190,6 +127,113 @@ pub fn set_mcp_servers_map(
         obj.insert("mcpServers".into(), Value::Object(out));
     }
 
-    write_json_value(&path, &root)?;
+    write_json_value(path, &root)?;
     Ok(())
 }
+
+/// 将统一格式的服务器规范转换为 Gemini settings.json 中的条目
+pub(crate) fn to_gemini_entry(id: &str, spec: &Value) -> Result<Map<String, Value>, AppError> {
+    let mut obj = if let Some(map) = spec.as_object() {
+        map.clone()
+    } else {
+        return Err(AppError::McpValidation(format!(
+            "MCP 服务器 '{id}' 不是对象"
+        )));
+    };
+
+    // 提取 server 字段（如果存在）
+    if let Some(server_val) = obj.remove("server") {
+        let server_obj = server_val.as_object().cloned().ok_or_else(|| {
+            AppError::McpValidation(format!("MCP 服务器 '{id}' server 字段不是对象"))
+        })?;
+        obj = server_obj;
+    }
+
+    // Gemini CLI 格式转换：
+    // - Gemini 不使用 "type" 字段（从字段名推断传输类型）
+    // - HTTP 使用 "httpUrl" 字段，SSE 使用 "url" 字段
+    let transport_type = obj.get("type").and_then(|v| v.as_str());
+    if transport_type == Some("http") {
+        // HTTP streaming: 将 "url" 重命名为 "httpUrl"
+        if let Some(url_value) = obj.remove("url") {
+            obj.insert("httpUrl".to_string(), url_value);
+        }
+    }
+    // SSE 保持 "url" 字段不变
+
+    // Timeout conversion:
+    // - CC-Switch/Codex/Claude may use startup_timeout_* / tool_timeout_*.
+    // - Gemini CLI uses a single timeout field (ms).
+    // Derive Gemini timeout by taking the maximum of:
+    //   - existing `timeout` (if any)
+    //   - startup timeout (default 10s)
+    //   - tool timeout (default 60s)
+    const DEFAULT_STARTUP_MS: u64 = 10_000;
+    const DEFAULT_TOOL_MS: u64 = 60_000;
+
+    let existing_timeout_ms = obj
+        .get("timeout")
+        .and_then(|val| val.as_u64().or_else(|| val.as_f64().map(|f| f as u64)));
+
+    let extract_timeout =
+        |obj: &mut Map<String, Value>, key: &str, multiplier: u64| -> Option<u64> {
+            obj.remove(key).and_then(|val| {
+                val.as_u64()
+                    .map(|n| n.saturating_mul(multiplier))
+                    .or_else(|| val.as_f64().map(|f| (f * multiplier as f64) as u64))
+            })
+        };
+
+    let startup_ms = extract_timeout(&mut obj, "startup_timeout_sec", 1000)
+        .or_else(|| extract_timeout(&mut obj, "startup_timeout_ms", 1))
+        .unwrap_or(DEFAULT_STARTUP_MS);
+    let tool_ms = extract_timeout(&mut obj, "tool_timeout_sec", 1000)
+        .or_else(|| extract_timeout(&mut obj, "tool_timeout_ms", 1))
+        .unwrap_or(DEFAULT_TOOL_MS);
+
+    let derived_timeout_ms = startup_ms.max(tool_ms);
+    let final_timeout_ms = existing_timeout_ms.unwrap_or(0).max(derived_timeout_ms);
+    obj.insert(
+        "timeout".to_string(),
+        Value::Number(final_timeout_ms.into()),
+    );
+
+    // 移除 UI 辅助字段和 type 字段（Gemini 不需要）
+    obj.remove("type");
+    obj.remove("enabled");
+    obj.remove("source");
+    obj.remove("id");
+    obj.remove("name");
+    obj.remove("description");
+    obj.remove("tags");
+    obj.remove("homepage");
+    obj.remove("docs");
+
+    Ok(obj)
+}
+
+/// 将 Gemini settings.json 中的条目还原为统一格式的服务器规范
+pub(crate) fn from_gemini_entry(raw_spec: &Value) -> Value {
+    let mut spec = raw_spec.clone();
+
+    // Reverse conversion (align upstream):
+    // - httpUrl -> url + type:"http"
+    // - if no type: command => "stdio", url => "sse"
+    if let Some(spec_obj) = spec.as_object_mut() {
+        if let Some(http_url_value) = spec_obj.remove("httpUrl") {
+            spec_obj.insert("url".to_string(), http_url_value);
+            spec_obj.insert("type".to_string(), Value::String("http".to_string()));
+        }
+
+        let has_type = spec_obj.get("type").and_then(|v| v.as_str()).is_some();
+        if !has_type {
+            if spec_obj.get("command").and_then(|v| v.as_str()).is_some() {
+                spec_obj.insert("type".to_string(), Value::String("stdio".to_string()));
+            } else if spec_obj.get("url").and_then(|v| v.as_str()).is_some() {
+                spec_obj.insert("type".to_string(), Value::String("sse".to_string()));
+            }
+        }
+    }
+
+    spec
+}
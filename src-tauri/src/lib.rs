@@ -1,5 +1,6 @@
 // Core modules
 mod app_config;
+mod backup_crypto;
 mod claude_mcp;
 mod claude_plugin;
 mod codex_config;
@@ -12,11 +13,14 @@ mod gemini_mcp;
 mod import_export;
 mod init_status;
 mod mcp;
+mod profile;
 mod prompt;
 mod prompt_files;
+mod prompt_template;
 mod provider;
 mod provider_defaults;
 mod proxy;
+mod secrets;
 mod services;
 mod settings;
 mod store;
@@ -27,10 +31,10 @@ mod usage_script;
 pub mod cli;
 
 // Public exports
-pub use app_config::{AppType, McpApps, McpServer, MultiAppConfig};
+pub use app_config::{AppType, McpApps, McpServer, McpToolFilter, McpToolFilters, MultiAppConfig};
 pub use codex_config::{get_codex_auth_path, get_codex_config_path, write_codex_live_atomic};
 pub use config::{get_claude_mcp_path, get_claude_settings_path, read_json_file};
-pub use database::{Database, FailoverQueueItem};
+pub use database::{Database, FailoverQueueItem, JournalEntry};
 pub use deeplink::{import_provider_from_deeplink, parse_deeplink_url, DeepLinkImportRequest};
 pub use error::AppError;
 pub use import_export::export_config_to_file;
@@ -38,12 +42,32 @@ pub use mcp::{
     import_from_claude, import_from_codex, import_from_gemini, remove_server_from_claude,
     remove_server_from_codex, remove_server_from_gemini, sync_enabled_to_claude,
     sync_enabled_to_codex, sync_enabled_to_gemini, sync_single_server_to_claude,
-    sync_single_server_to_codex, sync_single_server_to_gemini,
+    sync_single_server_to_codex, sync_single_server_to_gemini, McpDriftKind, McpFieldChange,
+    McpProjectScope, McpReconcileChoice, McpServerDrift,
 };
+pub use profile::{Profile, ProfileApp};
+pub use prompt::Prompt;
+pub use prompt_template::{RenderedPrompt, TemplateContext};
 pub use provider::{Provider, ProviderMeta};
+pub use secrets::SecretStore;
+pub use services::skill::{DiscoverableSkill, SkillRepo};
+pub use services::skill_lint::{LintIssue, LintReport, LintSeverity};
+pub use services::skill_repo_cache::{CachedRepoIndex, RepoIndexCache};
 pub use services::{
-    ConfigService, EndpointLatency, McpService, PromptService, ProviderService, SkillService,
-    SpeedtestService,
+    BundleAction, BundleChange, BundleImportReport, BundleItemKind, BundleSelection, BundleService,
+    ConfigBundle, ConfigService, ConflictResolution, ConflictStrategy, DoctorFinding, DoctorFix,
+    DoctorFixOutcome, DoctorReport, DoctorService, DoctorSeverity, EndpointLatency,
+    HistoryChangeSet, HistoryEntity, HistoryService, HookEnv, HookOutcome, HookService, HookShell,
+    LiveDrift, LiveKeyChange, LiveWatcher, McpService, NewSkillOptions, ProfileService,
+    PromptService, ProviderService, RemoteBackupService, RemoteSnapshot, RemoteUploadOutcome,
+    ResolvedSelection, SelectionSource, SkillFileChange, SkillFileChangeKind, SkillLock,
+    SkillLockEntry, SkillLockOutcome, SkillLockResult, SkillService, SkillUpdateOutcome,
+    SkillUpdateResult, SkillVersionCheck, SpeedtestService, SyncChange, SyncChangeKind, SyncConfig,
+    SyncReport, SyncService, SyncTarget, WatchEvent, WatchOptions, WatchOutcome, WatchPolicy,
+    WatchService, WatchSubject,
+};
+pub use settings::{
+    update_settings, AppSettings, DirectoryAppSelection, DirectorySelection, RemoteBackupKind,
+    RemoteBackupTarget,
 };
-pub use settings::{update_settings, AppSettings};
 pub use store::AppState;
@@ -22,7 +22,34 @@ fn main() {
     }
 }
 
+/// 变更日志中记录的来源，如 `cc-switch provider switch work`（不含选项，避免记录密钥）
+fn command_summary() -> String {
+    let mut words = vec!["cc-switch".to_string()];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--app" || arg == "-a" {
+            args.next();
+            continue;
+        }
+        if arg.starts_with('-') {
+            if words.len() > 1 {
+                break;
+            }
+            continue;
+        }
+        words.push(arg);
+        if words.len() == 4 {
+            break;
+        }
+    }
+    if words.len() == 1 {
+        words.push("interactive".to_string());
+    }
+    words.join(" ")
+}
+
 fn run(cli: Cli) -> Result<(), AppError> {
+    cc_switch_lib::HistoryService::set_source(command_summary());
     match cli.command {
         // Default to interactive mode if no command is provided
         None | Some(Commands::Interactive) => cc_switch_lib::cli::interactive::run(cli.app),
@@ -36,6 +63,25 @@ fn run(cli: Cli) -> Result<(), AppError> {
         Some(Commands::Skills(cmd)) => cc_switch_lib::cli::commands::skills::execute(cmd, cli.app),
         Some(Commands::Config(cmd)) => cc_switch_lib::cli::commands::config::execute(cmd, cli.app),
         Some(Commands::Env(cmd)) => cc_switch_lib::cli::commands::env::execute(cmd, cli.app),
+        Some(Commands::Sync(cmd)) => cc_switch_lib::cli::commands::sync::execute(cmd),
+        Some(Commands::Profile(cmd)) => {
+            cc_switch_lib::cli::commands::profile::execute(cmd, cli.app)
+        }
+        Some(Commands::Hook(cmd)) => cc_switch_lib::cli::commands::hook::execute(cmd, cli.app),
+        Some(Commands::History { limit }) => cc_switch_lib::cli::commands::history::history(limit),
+        Some(Commands::Undo { count, force, yes }) => {
+            cc_switch_lib::cli::commands::history::undo(count, force, yes)
+        }
+        Some(Commands::Doctor { fix }) => cc_switch_lib::cli::commands::doctor::execute(fix),
+        Some(Commands::Watch {
+            provider,
+            mcp,
+            interval,
+            debounce,
+            once,
+        }) => cc_switch_lib::cli::commands::watch::execute(
+            cli.app, provider, mcp, interval, debounce, once,
+        ),
         Some(Commands::Completions { shell }) => {
             cc_switch_lib::cli::generate_completions(shell);
             Ok(())
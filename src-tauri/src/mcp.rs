@@ -1,7 +1,8 @@
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
 
-use crate::app_config::{AppType, McpConfig, MultiAppConfig};
+use crate::app_config::{AppType, McpConfig, McpServer, McpToolFilter, MultiAppConfig};
 use crate::error::AppError;
 
 /// 基础校验：允许 stdio/http/sse；或省略 type（视为 stdio）。对应必填字段存在
@@ -197,8 +198,8 @@ fn extract_server_spec(entry: &Value) -> Result<Value, AppError> {
     Ok(server.clone())
 }
 
-/// 返回已启用的 MCP 服务器（过滤 enabled==true）
-fn collect_enabled_servers(cfg: &McpConfig) -> HashMap<String, Value> {
+/// 返回已启用的 MCP 服务器（过滤 enabled==true），并替换密钥占位符
+fn collect_enabled_servers(cfg: &McpConfig) -> Result<HashMap<String, Value>, AppError> {
     let mut out = HashMap::new();
     for (id, entry) in cfg.servers.iter() {
         let enabled = entry
@@ -210,16 +211,117 @@ fn collect_enabled_servers(cfg: &McpConfig) -> HashMap<String, Value> {
         }
         match extract_server_spec(entry) {
             Ok(spec) => {
-                out.insert(id.clone(), spec);
+                out.insert(id.clone(), resolve_server_secrets(id, &spec)?);
             }
             Err(err) => {
                 log::warn!("跳过无效的 MCP 条目 '{id}': {err}");
             }
         }
     }
+    Ok(out)
+}
+
+/// 工具过滤规则在服务器规范中的字段名（允许, 拒绝）
+///
+/// Claude 不在服务器规范中表达工具过滤，而是写入 settings.json 的权限规则。
+fn tool_filter_keys(app: &AppType) -> Option<(&'static str, &'static str)> {
+    match app {
+        AppType::Claude => None,
+        AppType::Codex => Some(("enabled_tools", "disabled_tools")),
+        AppType::Gemini => Some(("includeTools", "excludeTools")),
+    }
+}
+
+fn tool_filter_for<'a>(
+    config: &'a MultiAppConfig,
+    id: &str,
+    app: &AppType,
+) -> Option<&'a McpToolFilter> {
+    config
+        .mcp
+        .servers
+        .as_ref()
+        .and_then(|servers| servers.get(id))
+        .map(|server| server.tools.for_app(app))
+}
+
+/// 将工具过滤规则写入 Codex / Gemini 的服务器规范（非空列表覆盖规范中的同名字段）
+fn with_tool_filter(app: &AppType, spec: &Value, filter: Option<&McpToolFilter>) -> Value {
+    let mut out = spec.clone();
+    let (Some((allow_key, deny_key)), Some(filter), Some(obj)) =
+        (tool_filter_keys(app), filter, out.as_object_mut())
+    else {
+        return out;
+    };
+    if !filter.allow.is_empty() {
+        obj.insert(allow_key.to_string(), json!(filter.allow));
+    }
+    if !filter.deny.is_empty() {
+        obj.insert(deny_key.to_string(), json!(filter.deny));
+    }
     out
 }
 
+/// 将 live 中对工具列表字段的改动转回工具过滤规则，返回其余字段改动
+///
+/// 用于调和时保留 live 版本：否则存储中的过滤规则会在下次同步时覆盖 live 的改动。
+/// 规范中同名的字段一并移除，以过滤规则为准。
+pub fn absorb_tool_filter_changes(
+    app: &AppType,
+    server: &mut McpServer,
+    changes: &[McpFieldChange],
+) -> Vec<McpFieldChange> {
+    let Some((allow_key, deny_key)) = tool_filter_keys(app) else {
+        return changes.to_vec();
+    };
+
+    let mut rest = Vec::new();
+    for change in changes {
+        let filter = server.tools.for_app_mut(app);
+        let list = match change.path.as_slice() {
+            [key] if key == allow_key => &mut filter.allow,
+            [key] if key == deny_key => &mut filter.deny,
+            _ => {
+                rest.push(change.clone());
+                continue;
+            }
+        };
+        *list = change
+            .live
+            .as_ref()
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(obj) = server.server.as_object_mut() {
+            obj.remove(&change.path[0]);
+        }
+    }
+    rest
+}
+
+/// 写入 live 配置前替换 `${secret:NAME}` 占位符
+///
+/// 未定义的密钥保持占位符原样写入，并记录警告（调用方可通过
+/// `McpService::unresolved_secrets` 在写入前提示用户）。
+fn resolve_server_secrets(id: &str, spec: &Value) -> Result<Value, AppError> {
+    if crate::secrets::find_placeholders(spec).is_empty() {
+        return Ok(spec.clone());
+    }
+    let store = crate::secrets::SecretStore::load()?;
+    let (resolved, unresolved) = crate::secrets::resolve_placeholders(spec, &store);
+    if !unresolved.is_empty() {
+        log::warn!(
+            "MCP 服务器 '{id}' 引用了未定义的密钥，占位符将原样写入: {}",
+            unresolved.join(", ")
+        );
+    }
+    Ok(resolved)
+}
+
 #[allow(dead_code)] // v3.7.0: 旧的分应用 API，保留用于未来可能的迁移
 pub fn get_servers_snapshot_for(
     config: &mut MultiAppConfig,
@@ -329,7 +431,7 @@ pub fn set_enabled_flag_for(
 
 /// 将 config.json 中 enabled==true 的项投影写入 ~/.claude.json
 pub fn sync_enabled_to_claude(config: &MultiAppConfig) -> Result<(), AppError> {
-    let enabled = collect_enabled_servers(&config.mcp.claude);
+    let enabled = collect_enabled_servers(&config.mcp.claude)?;
     crate::claude_mcp::set_mcp_servers_map(&enabled)
 }
 
@@ -389,6 +491,8 @@ pub fn import_from_claude(config: &mut MultiAppConfig) -> Result<usize, AppError
                     homepage: None,
                     docs: None,
                     tags: Vec::new(),
+                    codex_toml: None,
+                    tools: Default::default(),
                 },
             );
             changed += 1;
@@ -421,6 +525,12 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
     let root: toml::Table = toml::from_str(&text)
         .map_err(|e| AppError::McpValidation(format!("解析 ~/.codex/config.toml 失败: {e}")))?;
 
+    // 原始片段：保留 JSON 无法表达的字段与注释，同步时作为基础
+    let fragments = text
+        .parse::<toml_edit::DocumentMut>()
+        .map(|doc| codex_fragments_in(&doc))
+        .unwrap_or_default();
+
     // 确保新结构存在
     if config.mcp.servers.is_none() {
         config.mcp.servers = Some(HashMap::new());
@@ -437,145 +547,10 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
                 continue;
             };
 
-            // type 缺省为 stdio
-            let typ = entry_tbl
-                .get("type")
-                .and_then(|v| v.as_str())
-                .unwrap_or("stdio");
-
-            // 构建 JSON 规范
-            let mut spec = serde_json::Map::new();
-            spec.insert("type".into(), json!(typ));
-
-            // 核心字段（需要手动处理的字段）
-            let core_fields = match typ {
-                "stdio" => vec!["type", "command", "args", "env", "cwd"],
-                "http" | "sse" => vec!["type", "url", "http_headers"],
-                _ => vec!["type"],
+            let Some(spec_v) = codex_table_to_spec(id, entry_tbl) else {
+                continue;
             };
 
-            // 1. 处理核心字段（强类型）
-            match typ {
-                "stdio" => {
-                    if let Some(cmd) = entry_tbl.get("command").and_then(|v| v.as_str()) {
-                        spec.insert("command".into(), json!(cmd));
-                    }
-                    if let Some(args) = entry_tbl.get("args").and_then(|v| v.as_array()) {
-                        let arr = args
-                            .iter()
-                            .filter_map(|x| x.as_str())
-                            .map(|s| json!(s))
-                            .collect::<Vec<_>>();
-                        if !arr.is_empty() {
-                            spec.insert("args".into(), serde_json::Value::Array(arr));
-                        }
-                    }
-                    if let Some(cwd) = entry_tbl.get("cwd").and_then(|v| v.as_str()) {
-                        if !cwd.trim().is_empty() {
-                            spec.insert("cwd".into(), json!(cwd));
-                        }
-                    }
-                    if let Some(env_tbl) = entry_tbl.get("env").and_then(|v| v.as_table()) {
-                        let mut env_json = serde_json::Map::new();
-                        for (k, v) in env_tbl.iter() {
-                            if let Some(sv) = v.as_str() {
-                                env_json.insert(k.clone(), json!(sv));
-                            }
-                        }
-                        if !env_json.is_empty() {
-                            spec.insert("env".into(), serde_json::Value::Object(env_json));
-                        }
-                    }
-                }
-                "http" | "sse" => {
-                    if let Some(url) = entry_tbl.get("url").and_then(|v| v.as_str()) {
-                        spec.insert("url".into(), json!(url));
-                    }
-                    // Read from http_headers (correct Codex format) or headers (legacy) with priority to http_headers
-                    let headers_tbl = entry_tbl
-                        .get("http_headers")
-                        .and_then(|v| v.as_table())
-                        .or_else(|| entry_tbl.get("headers").and_then(|v| v.as_table()));
-
-                    if let Some(headers_tbl) = headers_tbl {
-                        let mut headers_json = serde_json::Map::new();
-                        for (k, v) in headers_tbl.iter() {
-                            if let Some(sv) = v.as_str() {
-                                headers_json.insert(k.clone(), json!(sv));
-                            }
-                        }
-                        if !headers_json.is_empty() {
-                            spec.insert("headers".into(), serde_json::Value::Object(headers_json));
-                        }
-                    }
-                }
-                _ => {
-                    log::warn!("跳过未知类型 '{typ}' 的 Codex MCP 项 '{id}'");
-                    return changed;
-                }
-            }
-
-            // 2. 处理扩展字段和其他未知字段（通用 TOML → JSON 转换）
-            for (key, toml_val) in entry_tbl.iter() {
-                // 跳过已处理的核心字段
-                if core_fields.contains(&key.as_str()) {
-                    continue;
-                }
-
-                // 通用 TOML 值到 JSON 值转换
-                let json_val = match toml_val {
-                    toml::Value::String(s) => Some(json!(s)),
-                    toml::Value::Integer(i) => Some(json!(i)),
-                    toml::Value::Float(f) => Some(json!(f)),
-                    toml::Value::Boolean(b) => Some(json!(b)),
-                    toml::Value::Array(arr) => {
-                        // 只支持简单类型数组
-                        let json_arr: Vec<serde_json::Value> = arr
-                            .iter()
-                            .filter_map(|item| match item {
-                                toml::Value::String(s) => Some(json!(s)),
-                                toml::Value::Integer(i) => Some(json!(i)),
-                                toml::Value::Float(f) => Some(json!(f)),
-                                toml::Value::Boolean(b) => Some(json!(b)),
-                                _ => None,
-                            })
-                            .collect();
-                        if !json_arr.is_empty() {
-                            Some(serde_json::Value::Array(json_arr))
-                        } else {
-                            log::debug!("跳过复杂数组字段 '{key}' (TOML → JSON)");
-                            None
-                        }
-                    }
-                    toml::Value::Table(tbl) => {
-                        // 浅层表转为 JSON 对象（仅支持字符串值）
-                        let mut json_obj = serde_json::Map::new();
-                        for (k, v) in tbl.iter() {
-                            if let Some(s) = v.as_str() {
-                                json_obj.insert(k.clone(), json!(s));
-                            }
-                        }
-                        if !json_obj.is_empty() {
-                            Some(serde_json::Value::Object(json_obj))
-                        } else {
-                            log::debug!("跳过复杂对象字段 '{key}' (TOML → JSON)");
-                            None
-                        }
-                    }
-                    toml::Value::Datetime(_) => {
-                        log::debug!("跳过日期时间字段 '{key}' (TOML → JSON)");
-                        None
-                    }
-                };
-
-                if let Some(val) = json_val {
-                    spec.insert(key.clone(), val);
-                    log::debug!("导入扩展字段 '{key}' = {toml_val:?}");
-                }
-            }
-
-            let spec_v = serde_json::Value::Object(spec);
-
             // 校验：单项失败继续处理
             if let Err(e) = validate_server_spec(&spec_v) {
                 log::warn!("跳过无效 Codex MCP 项 '{id}': {e}");
@@ -586,6 +561,9 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
                 // 已存在：仅启用 Codex 应用
                 if !existing.apps.codex {
                     existing.apps.codex = true;
+                    if existing.codex_toml.is_none() {
+                        existing.codex_toml = fragments.get(id).cloned();
+                    }
                     changed += 1;
                     log::info!("MCP 服务器 '{id}' 已启用 Codex 应用");
                 }
@@ -607,6 +585,8 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
                         homepage: None,
                         docs: None,
                         tags: Vec::new(),
+                        codex_toml: fragments.get(id).cloned(),
+                        tools: Default::default(),
                     },
                 );
                 changed += 1;
@@ -637,6 +617,150 @@ pub fn import_from_codex(config: &mut MultiAppConfig) -> Result<usize, AppError>
     Ok(changed_total)
 }
 
+/// 将 Codex config.toml 中的单个 `[mcp_servers.<id>]` 表转换为统一 JSON 规范
+///
+/// 未知传输类型返回 None。
+fn codex_table_to_spec(id: &str, entry_tbl: &toml::value::Table) -> Option<Value> {
+    // type 缺省为 stdio
+    let typ = entry_tbl
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("stdio");
+
+    // 构建 JSON 规范
+    let mut spec = serde_json::Map::new();
+    spec.insert("type".into(), json!(typ));
+
+    // 核心字段（需要手动处理的字段）
+    let core_fields = match typ {
+        "stdio" => vec!["type", "command", "args", "env", "cwd"],
+        "http" | "sse" => vec!["type", "url", "http_headers"],
+        _ => vec!["type"],
+    };
+
+    // 1. 处理核心字段（强类型）
+    match typ {
+        "stdio" => {
+            if let Some(cmd) = entry_tbl.get("command").and_then(|v| v.as_str()) {
+                spec.insert("command".into(), json!(cmd));
+            }
+            if let Some(args) = entry_tbl.get("args").and_then(|v| v.as_array()) {
+                let arr = args
+                    .iter()
+                    .filter_map(|x| x.as_str())
+                    .map(|s| json!(s))
+                    .collect::<Vec<_>>();
+                if !arr.is_empty() {
+                    spec.insert("args".into(), serde_json::Value::Array(arr));
+                }
+            }
+            if let Some(cwd) = entry_tbl.get("cwd").and_then(|v| v.as_str()) {
+                if !cwd.trim().is_empty() {
+                    spec.insert("cwd".into(), json!(cwd));
+                }
+            }
+            if let Some(env_tbl) = entry_tbl.get("env").and_then(|v| v.as_table()) {
+                let mut env_json = serde_json::Map::new();
+                for (k, v) in env_tbl.iter() {
+                    if let Some(sv) = v.as_str() {
+                        env_json.insert(k.clone(), json!(sv));
+                    }
+                }
+                if !env_json.is_empty() {
+                    spec.insert("env".into(), serde_json::Value::Object(env_json));
+                }
+            }
+        }
+        "http" | "sse" => {
+            if let Some(url) = entry_tbl.get("url").and_then(|v| v.as_str()) {
+                spec.insert("url".into(), json!(url));
+            }
+            // Read from http_headers (correct Codex format) or headers (legacy) with priority to http_headers
+            let headers_tbl = entry_tbl
+                .get("http_headers")
+                .and_then(|v| v.as_table())
+                .or_else(|| entry_tbl.get("headers").and_then(|v| v.as_table()));
+
+            if let Some(headers_tbl) = headers_tbl {
+                let mut headers_json = serde_json::Map::new();
+                for (k, v) in headers_tbl.iter() {
+                    if let Some(sv) = v.as_str() {
+                        headers_json.insert(k.clone(), json!(sv));
+                    }
+                }
+                if !headers_json.is_empty() {
+                    spec.insert("headers".into(), serde_json::Value::Object(headers_json));
+                }
+            }
+        }
+        _ => {
+            log::warn!("跳过未知类型 '{typ}' 的 Codex MCP 项 '{id}'");
+            return None;
+        }
+    }
+
+    // 2. 处理扩展字段和其他未知字段（通用 TOML → JSON 转换）
+    for (key, toml_val) in entry_tbl.iter() {
+        // 跳过已处理的核心字段
+        if core_fields.contains(&key.as_str()) {
+            continue;
+        }
+
+        // 通用 TOML 值到 JSON 值转换
+        let json_val = match toml_val {
+            toml::Value::String(s) => Some(json!(s)),
+            toml::Value::Integer(i) => Some(json!(i)),
+            toml::Value::Float(f) => Some(json!(f)),
+            toml::Value::Boolean(b) => Some(json!(b)),
+            toml::Value::Array(arr) => {
+                // 只支持简单类型数组
+                let json_arr: Vec<serde_json::Value> = arr
+                    .iter()
+                    .filter_map(|item| match item {
+                        toml::Value::String(s) => Some(json!(s)),
+                        toml::Value::Integer(i) => Some(json!(i)),
+                        toml::Value::Float(f) => Some(json!(f)),
+                        toml::Value::Boolean(b) => Some(json!(b)),
+                        _ => None,
+                    })
+                    .collect();
+                if !json_arr.is_empty() {
+                    Some(serde_json::Value::Array(json_arr))
+                } else {
+                    log::debug!("跳过复杂数组字段 '{key}' (TOML → JSON)");
+                    None
+                }
+            }
+            toml::Value::Table(tbl) => {
+                // 浅层表转为 JSON 对象（仅支持字符串值）
+                let mut json_obj = serde_json::Map::new();
+                for (k, v) in tbl.iter() {
+                    if let Some(s) = v.as_str() {
+                        json_obj.insert(k.clone(), json!(s));
+                    }
+                }
+                if !json_obj.is_empty() {
+                    Some(serde_json::Value::Object(json_obj))
+                } else {
+                    log::debug!("跳过复杂对象字段 '{key}' (TOML → JSON)");
+                    None
+                }
+            }
+            toml::Value::Datetime(_) => {
+                log::debug!("跳过日期时间字段 '{key}' (TOML → JSON)");
+                None
+            }
+        };
+
+        if let Some(val) = json_val {
+            spec.insert(key.clone(), val);
+            log::debug!("导入扩展字段 '{key}' = {toml_val:?}");
+        }
+    }
+
+    Some(Value::Object(spec))
+}
+
 /// 将 config.json 中 Codex 的 enabled==true 项以 TOML 形式写入 ~/.codex/config.toml
 ///
 /// 格式策略：
@@ -649,7 +773,7 @@ pub fn sync_enabled_to_codex(config: &MultiAppConfig) -> Result<(), AppError> {
     use toml_edit::{Item, Table};
 
     // 1) 收集启用项（Codex 维度）
-    let enabled = collect_enabled_servers(&config.mcp.codex);
+    let enabled = collect_enabled_servers(&config.mcp.codex)?;
 
     // 2) 读取现有 config.toml 文本；保持无效 TOML 的错误返回（不覆盖文件）
     let base_text = crate::codex_config::read_and_validate_codex_config_text()?;
@@ -684,8 +808,13 @@ pub fn sync_enabled_to_codex(config: &MultiAppConfig) -> Result<(), AppError> {
         ids.sort();
         for id in ids {
             let spec = enabled.get(&id).expect("spec must exist");
+            let spec = with_tool_filter(
+                &AppType::Codex,
+                spec,
+                tool_filter_for(config, &id, &AppType::Codex),
+            );
             // 复用通用转换函数（已包含扩展字段支持）
-            match json_server_to_toml_table(spec) {
+            match codex_server_table(&id, &spec, codex_raw_for(config, &id)) {
                 Ok(table) => {
                     servers_tbl[&id[..]] = Item::Table(table);
                 }
@@ -707,7 +836,7 @@ pub fn sync_enabled_to_codex(config: &MultiAppConfig) -> Result<(), AppError> {
 
 /// 将 config.json 中 enabled==true 的项投影写入 ~/.gemini/settings.json
 pub fn sync_enabled_to_gemini(config: &MultiAppConfig) -> Result<(), AppError> {
-    let enabled = collect_enabled_servers(&config.mcp.gemini);
+    let enabled = collect_enabled_servers(&config.mcp.gemini)?;
     crate::gemini_mcp::set_mcp_servers_map(&enabled)
 }
 
@@ -763,6 +892,8 @@ pub fn import_from_gemini(config: &mut MultiAppConfig) -> Result<usize, AppError
                     homepage: None,
                     docs: None,
                     tags: Vec::new(),
+                    codex_toml: None,
+                    tools: Default::default(),
                 },
             );
             changed += 1;
@@ -792,7 +923,7 @@ pub fn sync_single_server_to_claude(
 
     // 创建新的 HashMap，包含现有的所有服务器 + 当前要同步的服务器
     let mut updated = current;
-    updated.insert(id.to_string(), server_spec.clone());
+    updated.insert(id.to_string(), resolve_server_secrets(id, server_spec)?);
 
     // 写回
     crate::claude_mcp::set_mcp_servers_map(&updated)
@@ -1025,10 +1156,248 @@ fn json_server_to_toml_table(spec: &Value) -> Result<toml_edit::Table, AppError>
     Ok(t)
 }
 
+// ----------------------------------------------------------------------------
+// Codex 原始 TOML 片段：保留 JSON 模型无法表达的字段（嵌套表、日期、注释等）
+// ----------------------------------------------------------------------------
+
+/// 复制 TOML 表并清除文档位置，使其插入到另一份文档时紧跟父表输出
+fn detach_toml_table(src: &toml_edit::Table) -> toml_edit::Table {
+    use toml_edit::{ArrayOfTables, Item, Table};
+
+    let mut out = Table::new();
+    out.set_implicit(src.is_implicit());
+    out.set_dotted(src.is_dotted());
+    *out.decor_mut() = src.decor().clone();
+
+    for (key, item) in src.iter() {
+        let Some((key, _)) = src.get_key_value(key) else {
+            continue;
+        };
+        let item = match item {
+            Item::Table(t) => Item::Table(detach_toml_table(t)),
+            Item::ArrayOfTables(arr) => {
+                let mut out_arr = ArrayOfTables::new();
+                for t in arr.iter() {
+                    out_arr.push(detach_toml_table(t));
+                }
+                Item::ArrayOfTables(out_arr)
+            }
+            other => other.clone(),
+        };
+        out.insert_formatted(key, item);
+    }
+
+    out
+}
+
+/// 将 `[mcp_servers.<id>]` 表序列化为独立片段（子表使用相对表头，如 `[env]`）
+fn codex_table_to_fragment(table: &toml_edit::Table) -> String {
+    toml_edit::DocumentMut::from(detach_toml_table(table)).to_string()
+}
+
+/// 通过 toml 解析将 toml_edit 表转换为统一 JSON 规范（与导入逻辑一致）
+fn toml_edit_table_to_spec(id: &str, table: &toml_edit::Table) -> Option<Value> {
+    let text = codex_table_to_fragment(table);
+    let parsed: toml::Table = toml::from_str(&text).ok()?;
+    codex_table_to_spec(id, &parsed)
+}
+
+/// 收集 config.toml 中每个服务器的原始片段（同时读取 [mcp.servers] 与 [mcp_servers]）
+///
+/// live 中 `env` / `http_headers` 的字符串值是解析后的明文密钥，片段中替换为占位值；
+/// 键、非字符串值与注释照常保留，写入时字符串值由 JSON 规范补回。
+fn codex_fragments_in(doc: &toml_edit::DocumentMut) -> HashMap<String, String> {
+    let mut fragments = HashMap::new();
+    let tables = [
+        doc.get("mcp").and_then(|m| m.get("servers")),
+        doc.get("mcp_servers"),
+    ];
+    for tbl in tables
+        .into_iter()
+        .flatten()
+        .filter_map(|i| i.as_table_like())
+    {
+        for (id, item) in tbl.iter() {
+            if let Ok(mut table) = item.clone().into_table() {
+                redact_codex_secrets(&mut table);
+                fragments.insert(id.to_string(), codex_table_to_fragment(&table));
+            }
+        }
+    }
+    fragments
+}
+
+/// 可能包含密钥的 Codex 服务器子表，其字符串值不随原始片段保存
+const CODEX_SECRET_KEYS: [&str; 3] = ["env", "http_headers", "headers"];
+
+/// 原始片段中替代密钥字符串值的占位值
+const CODEX_REDACTED_VALUE: &str = "<redacted>";
+
+fn redact_codex_secrets(table: &mut toml_edit::Table) {
+    for key in CODEX_SECRET_KEYS {
+        let Some(sub) = table.get_mut(key).and_then(|i| i.as_table_like_mut()) else {
+            continue;
+        };
+        for (_, item) in sub.iter_mut() {
+            if let Some(value) = item.as_value_mut().filter(|v| v.is_str()) {
+                let decor = value.decor().clone();
+                *value = toml_edit::Value::from(CODEX_REDACTED_VALUE);
+                *value.decor_mut() = decor;
+            }
+        }
+    }
+}
+
+/// 将 JSON 规范中的 `env` / `http_headers` 逐项写回原始片段的同名子表
+///
+/// 字符串值全部取自规范（规范中已删除的键随之移除），非字符串值、注释与键顺序保持不变。
+/// `toml_key` 不是密钥子表或片段中没有该子表时返回 false，由调用方按普通字段处理。
+fn merge_codex_secret_table(
+    table: &mut toml_edit::Table,
+    toml_key: &str,
+    generated: Option<&toml_edit::Item>,
+) -> bool {
+    if !CODEX_SECRET_KEYS.contains(&toml_key) {
+        return false;
+    }
+    let has_table = |key: &str| table.get(key).is_some_and(|i| i.is_table_like());
+    let base_key = if has_table(toml_key) {
+        toml_key
+    } else if toml_key == "http_headers" && has_table("headers") {
+        "headers"
+    } else {
+        return false;
+    };
+    let Some(mut item) = table.remove(base_key) else {
+        return false;
+    };
+    let Some(sub) = item.as_table_like_mut() else {
+        return false;
+    };
+
+    let generated = generated.and_then(|g| g.as_table_like());
+    let stale: Vec<String> = sub
+        .iter()
+        .filter(|(key, value)| {
+            value.as_str().is_some() && !generated.is_some_and(|g| g.contains_key(key))
+        })
+        .map(|(key, _)| key.to_string())
+        .collect();
+    for key in stale {
+        sub.remove(&key);
+    }
+    for (key, new) in generated
+        .into_iter()
+        .flat_map(|g| g.iter())
+        .filter_map(|(key, item)| Some((key, item.as_value()?)))
+    {
+        match sub.get_mut(key).and_then(|i| i.as_value_mut()) {
+            Some(old) => {
+                let decor = old.decor().clone();
+                *old = new.clone();
+                *old.decor_mut() = decor;
+            }
+            None => {
+                sub.insert(key, toml_edit::Item::Value(new.clone()));
+            }
+        }
+    }
+
+    if !sub.is_empty() {
+        table.insert(toml_key, item);
+    }
+    true
+}
+
+/// 读取 live config.toml 中指定服务器的原始 TOML 片段
+pub fn read_codex_live_fragment(id: &str) -> Result<Option<String>, AppError> {
+    let text = crate::codex_config::read_and_validate_codex_config_text()?;
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let doc = text
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| AppError::McpValidation(format!("解析 ~/.codex/config.toml 失败: {e}")))?;
+    Ok(codex_fragments_in(&doc).remove(id))
+}
+
+fn codex_raw_for<'a>(config: &'a MultiAppConfig, id: &str) -> Option<&'a str> {
+    config
+        .mcp
+        .servers
+        .as_ref()
+        .and_then(|servers| servers.get(id))
+        .and_then(|server| server.codex_toml.as_deref())
+}
+
+/// 生成写入 Codex 的服务器表
+///
+/// 存在原始片段时以片段为基础（保留未知字段、格式与注释），
+/// 仅覆盖 JSON 规范中值已改变的字段，并移除 JSON 规范中已删除的字段。
+fn codex_server_table(
+    id: &str,
+    spec: &Value,
+    raw: Option<&str>,
+) -> Result<toml_edit::Table, AppError> {
+    let generated = json_server_to_toml_table(spec)?;
+    let Some(raw) = raw.filter(|r| !r.trim().is_empty()) else {
+        return Ok(generated);
+    };
+
+    let mut table = match raw.parse::<toml_edit::DocumentMut>() {
+        Ok(doc) => detach_toml_table(doc.as_table()),
+        Err(e) => {
+            log::warn!("忽略 MCP 服务器 '{id}' 无效的 Codex 原始 TOML: {e}");
+            return Ok(generated);
+        }
+    };
+
+    let raw_spec = toml_edit_table_to_spec(id, &table);
+    let generated_spec = toml_edit_table_to_spec(id, &generated);
+    let (Some(Value::Object(raw_obj)), Some(Value::Object(gen_obj))) = (raw_spec, generated_spec)
+    else {
+        return Ok(generated);
+    };
+
+    // JSON 中的 headers 在 Codex 中写作 http_headers，其余键名一致
+    let toml_key = |key: &str| -> String {
+        if key == "headers" {
+            "http_headers".to_string()
+        } else {
+            key.to_string()
+        }
+    };
+
+    for key in raw_obj.keys().filter(|k| !gen_obj.contains_key(*k)) {
+        if merge_codex_secret_table(&mut table, &toml_key(key), None) {
+            continue;
+        }
+        table.remove(&toml_key(key));
+        if key == "headers" {
+            table.remove("headers");
+        }
+    }
+
+    for (key, value) in gen_obj.iter() {
+        let toml_key = toml_key(key);
+        if merge_codex_secret_table(&mut table, &toml_key, generated.get(&toml_key)) {
+            continue;
+        }
+        if raw_obj.get(key) == Some(value) {
+            continue;
+        }
+        if let Some(item) = generated.get(&toml_key) {
+            table.insert(&toml_key, item.clone());
+        }
+    }
+
+    Ok(table)
+}
+
 /// 将单个 MCP 服务器同步到 Codex live 配置
 /// 始终使用 Codex 官方格式 [mcp_servers]，并清理可能存在的错误格式 [mcp.servers]
 pub fn sync_single_server_to_codex(
-    _config: &MultiAppConfig,
+    config: &MultiAppConfig,
     id: &str,
     server_spec: &Value,
 ) -> Result<(), AppError> {
@@ -1063,7 +1432,12 @@ pub fn sync_single_server_to_codex(
     }
 
     // 将 JSON 服务器规范转换为 TOML 表
-    let toml_table = json_server_to_toml_table(server_spec)?;
+    let spec = with_tool_filter(
+        &AppType::Codex,
+        &resolve_server_secrets(id, server_spec)?,
+        tool_filter_for(config, id, &AppType::Codex),
+    );
+    let toml_table = codex_server_table(id, &spec, codex_raw_for(config, id))?;
 
     // 使用唯一正确的格式：[mcp_servers]
     doc["mcp_servers"][id] = Item::Table(toml_table);
@@ -1112,7 +1486,7 @@ pub fn remove_server_from_codex(id: &str) -> Result<(), AppError> {
 
 /// 将单个 MCP 服务器同步到 Gemini live 配置
 pub fn sync_single_server_to_gemini(
-    _config: &MultiAppConfig,
+    config: &MultiAppConfig,
     id: &str,
     server_spec: &Value,
 ) -> Result<(), AppError> {
@@ -1125,7 +1499,15 @@ pub fn sync_single_server_to_gemini(
 
     // 创建新的 HashMap，包含现有的所有服务器 + 当前要同步的服务器
     let mut updated = current;
-    updated.insert(id.to_string(), server_spec.clone());
+    let spec = resolve_server_secrets(id, server_spec)?;
+    updated.insert(
+        id.to_string(),
+        with_tool_filter(
+            &AppType::Gemini,
+            &spec,
+            tool_filter_for(config, id, &AppType::Gemini),
+        ),
+    );
 
     // 写回
     crate::gemini_mcp::set_mcp_servers_map(&updated)
@@ -1146,3 +1528,433 @@ pub fn remove_server_from_gemini(id: &str) -> Result<(), AppError> {
     // 写回
     crate::gemini_mcp::set_mcp_servers_map(&current)
 }
+
+// ============================================================================
+// 项目级 MCP：Claude `.mcp.json` / `~/.claude.json` projects 段，Gemini `.gemini/settings.json`
+// ============================================================================
+
+/// 项目级 MCP 写入位置（仅 Claude 区分；Gemini 固定使用 `<project>/.gemini/settings.json`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum McpProjectScope {
+    /// `<project>/.mcp.json`，可随仓库共享
+    #[default]
+    Shared,
+    /// `~/.claude.json` 中 `projects.<path>.mcpServers`，仅本机生效
+    Local,
+}
+
+fn project_unsupported_error(app: &AppType) -> AppError {
+    AppError::localized(
+        "mcp.project_unsupported",
+        format!("{} 不支持项目级 MCP 配置", app.as_str()),
+        format!(
+            "{} does not support project-scoped MCP configuration",
+            app.as_str()
+        ),
+    )
+}
+
+/// 读取项目级 MCP 服务器映射
+pub fn read_project_servers(
+    app: &AppType,
+    project_dir: &Path,
+    scope: McpProjectScope,
+) -> Result<HashMap<String, Value>, AppError> {
+    match app {
+        AppType::Claude => crate::claude_mcp::read_project_mcp_servers_map(project_dir, scope),
+        AppType::Gemini => crate::gemini_mcp::read_project_mcp_servers_map(project_dir),
+        AppType::Codex => Err(project_unsupported_error(app)),
+    }
+}
+
+fn write_project_servers(
+    app: &AppType,
+    project_dir: &Path,
+    scope: McpProjectScope,
+    servers: &HashMap<String, Value>,
+) -> Result<(), AppError> {
+    match app {
+        AppType::Claude => {
+            crate::claude_mcp::set_project_mcp_servers_map(project_dir, scope, servers)
+        }
+        AppType::Gemini => crate::gemini_mcp::set_project_mcp_servers_map(project_dir, servers),
+        AppType::Codex => Err(project_unsupported_error(app)),
+    }
+}
+
+/// 将单个 MCP 服务器写入项目级配置
+pub fn sync_single_server_to_project(
+    app: &AppType,
+    project_dir: &Path,
+    scope: McpProjectScope,
+    id: &str,
+    server_spec: &Value,
+    filter: Option<&McpToolFilter>,
+) -> Result<(), AppError> {
+    validate_server_spec(server_spec)?;
+
+    let mut updated = read_project_servers(app, project_dir, scope)?;
+    let spec = with_tool_filter(app, server_spec, filter);
+    updated.insert(id.to_string(), resolve_server_secrets(id, &spec)?);
+    write_project_servers(app, project_dir, scope, &updated)?;
+    if *app == AppType::Claude {
+        crate::claude_mcp::update_project_tool_permissions(project_dir, scope, id, filter)?;
+    }
+    Ok(())
+}
+
+/// 从项目级配置中移除单个 MCP 服务器，返回是否存在
+pub fn remove_server_from_project(
+    app: &AppType,
+    project_dir: &Path,
+    scope: McpProjectScope,
+    id: &str,
+) -> Result<bool, AppError> {
+    let mut current = read_project_servers(app, project_dir, scope)?;
+    if current.remove(id).is_none() {
+        return Ok(false);
+    }
+    write_project_servers(app, project_dir, scope, &current)?;
+    if *app == AppType::Claude {
+        crate::claude_mcp::update_project_tool_permissions(project_dir, scope, id, None)?;
+    }
+    Ok(true)
+}
+
+/// 从项目级配置导入 MCP 到统一结构
+///
+/// 依次读取 `.mcp.json`、`~/.claude.json` 的项目段与 `.gemini/settings.json`。
+/// 项目级服务器不应写入用户级配置，因此新建条目不启用任何应用；已存在的服务器保持不变。
+pub fn import_from_project(
+    config: &mut MultiAppConfig,
+    project_dir: &Path,
+) -> Result<usize, AppError> {
+    use crate::app_config::{McpApps, McpServer};
+
+    let sources = [
+        (AppType::Claude, McpProjectScope::Shared),
+        (AppType::Claude, McpProjectScope::Local),
+        (AppType::Gemini, McpProjectScope::Shared),
+    ];
+
+    let servers = config.mcp.servers.get_or_insert_with(HashMap::new);
+    let mut changed = 0;
+
+    for (app, scope) in sources {
+        let map = read_project_servers(&app, project_dir, scope)?;
+        let mut ids: Vec<_> = map.keys().cloned().collect();
+        ids.sort();
+
+        for id in ids {
+            let spec = &map[&id];
+            if let Err(e) = validate_server_spec(spec) {
+                log::warn!("跳过无效的项目级 MCP 服务器 '{id}': {e}");
+                continue;
+            }
+            if servers.contains_key(&id) {
+                continue;
+            }
+
+            servers.insert(
+                id.clone(),
+                McpServer {
+                    id: id.clone(),
+                    name: id.clone(),
+                    server: spec.clone(),
+                    apps: McpApps::default(),
+                    description: None,
+                    homepage: None,
+                    docs: None,
+                    tags: Vec::new(),
+                    codex_toml: None,
+                    tools: Default::default(),
+                },
+            );
+            changed += 1;
+            log::info!(
+                "从项目 {} 导入 MCP 服务器 '{id}'（{}）",
+                project_dir.display(),
+                app.as_str()
+            );
+        }
+    }
+
+    Ok(changed)
+}
+
+// ============================================================================
+// Live 配置漂移检测与调和（mcp diff / mcp reconcile）
+// ============================================================================
+
+/// 单个服务器在 live 配置与统一存储之间的差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum McpDriftKind {
+    /// 仅存在于 live 配置（手动新增，或 cc-switch 中未对该应用启用）
+    Added,
+    /// cc-switch 中已对该应用启用，但 live 配置中缺失（手动删除）
+    Removed,
+    /// 两侧均存在但字段不同（手动修改）
+    Changed,
+}
+
+/// 字段级差异；`path` 为从规范根开始的键路径（如 `["env", "API_KEY"]`）
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpFieldChange {
+    pub path: Vec<String>,
+    pub stored: Option<Value>,
+    pub live: Option<Value>,
+}
+
+impl McpFieldChange {
+    /// 以点号连接的字段路径，用于展示
+    pub fn path_label(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpServerDrift {
+    pub id: String,
+    pub kind: McpDriftKind,
+    /// 与统一存储的字段差异（live 独有的新服务器为空）
+    pub fields: Vec<McpFieldChange>,
+    /// live 配置中的服务器规范（与已知密钥值相同的字段已还原为占位符）
+    pub live: Option<Value>,
+}
+
+/// 调和时保留哪一侧的版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum McpReconcileChoice {
+    /// 保留 live 配置中的版本，写回统一存储
+    Live,
+    /// 保留统一存储中的版本，重写 live 配置
+    Stored,
+}
+
+/// 读取指定应用 live 配置中的 MCP 服务器（统一 JSON 格式）
+pub fn read_live_servers(app: &AppType) -> Result<HashMap<String, Value>, AppError> {
+    match app {
+        AppType::Claude => crate::claude_mcp::read_mcp_servers_map(),
+        AppType::Codex => read_codex_live_servers(),
+        AppType::Gemini => crate::gemini_mcp::read_mcp_servers_map(),
+    }
+}
+
+fn read_codex_live_servers() -> Result<HashMap<String, Value>, AppError> {
+    let mut servers = HashMap::new();
+    let text = crate::codex_config::read_and_validate_codex_config_text()?;
+    if text.trim().is_empty() {
+        return Ok(servers);
+    }
+
+    let root: toml::Table = toml::from_str(&text)
+        .map_err(|e| AppError::McpValidation(format!("解析 ~/.codex/config.toml 失败: {e}")))?;
+
+    // 与导入一致：同时读取错误格式 [mcp.servers] 与正确格式 [mcp_servers]（后者优先）
+    let tables = [
+        root.get("mcp").and_then(|v| v.get("servers")),
+        root.get("mcp_servers"),
+    ];
+    for tbl in tables.into_iter().flatten().filter_map(|v| v.as_table()) {
+        for (id, entry) in tbl {
+            if let Some(spec) = entry.as_table().and_then(|t| codex_table_to_spec(id, t)) {
+                servers.insert(id.clone(), spec);
+            }
+        }
+    }
+
+    Ok(servers)
+}
+
+/// 计算统一存储中的规范写入指定应用后、再读回时的形态
+///
+/// 各应用的写入都会做格式转换（Codex TOML、Gemini httpUrl/timeout），
+/// 只有在同一形态下比较才不会把转换本身误报为漂移。
+fn project_live_form(
+    app: &AppType,
+    id: &str,
+    spec: &Value,
+    codex_toml: Option<&str>,
+) -> Result<Value, AppError> {
+    match app {
+        AppType::Claude => Ok(spec.clone()),
+        AppType::Codex => {
+            let table = codex_server_table(id, spec, codex_toml)?;
+            Ok(toml_edit_table_to_spec(id, &table).unwrap_or_else(|| spec.clone()))
+        }
+        AppType::Gemini => Ok(crate::gemini_mcp::from_gemini_entry(&Value::Object(
+            crate::gemini_mcp::to_gemini_entry(id, spec)?,
+        ))),
+    }
+}
+
+/// 将 live 中与密钥解析结果一致的字符串还原为占位符，避免密钥值进入 diff 或存储
+fn restore_placeholders(live: &Value, expected: &Value, resolved: &Value) -> Value {
+    match (live, expected, resolved) {
+        (Value::String(l), Value::String(e), Value::String(r)) if l == r && e != r => {
+            expected.clone()
+        }
+        (Value::Object(l), Value::Object(e), Value::Object(r)) => Value::Object(
+            l.iter()
+                .map(|(k, v)| {
+                    let restored = match (e.get(k), r.get(k)) {
+                        (Some(ev), Some(rv)) => restore_placeholders(v, ev, rv),
+                        _ => v.clone(),
+                    };
+                    (k.clone(), restored)
+                })
+                .collect(),
+        ),
+        (Value::Array(l), Value::Array(e), Value::Array(r))
+            if l.len() == e.len() && e.len() == r.len() =>
+        {
+            Value::Array(
+                l.iter()
+                    .zip(e.iter().zip(r.iter()))
+                    .map(|(lv, (ev, rv))| restore_placeholders(lv, ev, rv))
+                    .collect(),
+            )
+        }
+        _ => live.clone(),
+    }
+}
+
+/// 逐字段比较（对象递归到叶子，数组整体比较）
+fn diff_fields(stored: &Value, live: &Value) -> Vec<McpFieldChange> {
+    let mut changes = Vec::new();
+    collect_field_changes(&mut Vec::new(), Some(stored), Some(live), &mut changes);
+    changes
+}
+
+fn collect_field_changes(
+    path: &mut Vec<String>,
+    stored: Option<&Value>,
+    live: Option<&Value>,
+    changes: &mut Vec<McpFieldChange>,
+) {
+    if let (Some(Value::Object(s)), Some(Value::Object(l))) = (stored, live) {
+        let keys: BTreeSet<&String> = s.keys().chain(l.keys()).collect();
+        for key in keys {
+            path.push(key.clone());
+            collect_field_changes(path, s.get(key), l.get(key), changes);
+            path.pop();
+        }
+        return;
+    }
+
+    if stored != live {
+        changes.push(McpFieldChange {
+            path: path.clone(),
+            stored: stored.cloned(),
+            live: live.cloned(),
+        });
+    }
+}
+
+/// 将 live 侧的字段差异应用到统一存储中的规范上（保留 live 无法表达的字段）
+pub fn apply_field_changes(spec: &mut Value, changes: &[McpFieldChange]) {
+    for change in changes {
+        set_value_at_path(spec, &change.path, change.live.as_ref());
+    }
+}
+
+fn set_value_at_path(target: &mut Value, path: &[String], value: Option<&Value>) {
+    let Some(obj) = target.as_object_mut() else {
+        return;
+    };
+    match path {
+        [] => {}
+        [last] => match value {
+            Some(v) => {
+                obj.insert(last.clone(), v.clone());
+            }
+            None => {
+                obj.remove(last);
+            }
+        },
+        [head, rest @ ..] => {
+            if value.is_none() && !obj.contains_key(head) {
+                return;
+            }
+            let child = obj.entry(head.clone()).or_insert_with(|| json!({}));
+            if !child.is_object() {
+                *child = json!({});
+            }
+            set_value_at_path(child, rest, value);
+        }
+    }
+}
+
+/// 比较指定应用的 live 配置与统一存储，按服务器 ID 排序返回差异
+///
+/// 应用未初始化（sync_policy 不允许写 live）时返回空列表。
+pub fn diff_live_servers(
+    config: &MultiAppConfig,
+    app: &AppType,
+) -> Result<Vec<McpServerDrift>, AppError> {
+    if !crate::sync_policy::should_sync_live(app) {
+        return Ok(Vec::new());
+    }
+
+    let live = read_live_servers(app)?;
+    let stored = config.mcp.servers.clone().unwrap_or_default();
+    let store = crate::secrets::SecretStore::load()?;
+
+    let ids: BTreeSet<&String> = live
+        .keys()
+        .chain(
+            stored
+                .iter()
+                .filter(|(_, s)| s.apps.is_enabled_for(app))
+                .map(|(id, _)| id),
+        )
+        .collect();
+
+    let mut drifts = Vec::new();
+    for id in ids {
+        let server = stored.get(id);
+        let enabled = server.is_some_and(|s| s.apps.is_enabled_for(app));
+
+        match (live.get(id), server) {
+            (Some(live_spec), Some(server)) => {
+                let codex_toml = server.codex_toml.as_deref();
+                let filter = Some(server.tools.for_app(app));
+                let spec = with_tool_filter(app, &server.server, filter);
+                let expected = project_live_form(app, id, &spec, codex_toml)?;
+                let (resolved, _) = crate::secrets::resolve_placeholders(&spec, &store);
+                let expected_resolved = project_live_form(app, id, &resolved, codex_toml)?;
+                let live_spec = restore_placeholders(live_spec, &expected, &expected_resolved);
+                let fields = diff_fields(&expected, &live_spec);
+
+                let kind = if !enabled {
+                    McpDriftKind::Added
+                } else if !fields.is_empty() {
+                    McpDriftKind::Changed
+                } else {
+                    continue;
+                };
+                drifts.push(McpServerDrift {
+                    id: id.clone(),
+                    kind,
+                    fields,
+                    live: Some(live_spec),
+                });
+            }
+            (Some(live_spec), None) => drifts.push(McpServerDrift {
+                id: id.clone(),
+                kind: McpDriftKind::Added,
+                fields: Vec::new(),
+                live: Some(live_spec.clone()),
+            }),
+            (None, Some(_)) => drifts.push(McpServerDrift {
+                id: id.clone(),
+                kind: McpDriftKind::Removed,
+                fields: Vec::new(),
+                live: None,
+            }),
+            (None, None) => {}
+        }
+    }
+
+    Ok(drifts)
+}
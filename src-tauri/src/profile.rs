@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 配置档案中单个应用的选择
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileApp {
+    /// 当前供应商 ID；None 表示应用档案时不切换供应商
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// 对该应用启用的 MCP 服务器，其余服务器会被停用
+    #[serde(default)]
+    pub mcp_servers: Vec<String>,
+    /// 提示词栈（按写入文件的顺序）
+    #[serde(default)]
+    pub prompts: Vec<String>,
+    /// 启用的 Skills（安装目录名）
+    #[serde(default)]
+    pub skills: Vec<String>,
+}
+
+/// 命名的配置档案：按应用记录供应商、MCP 服务器、提示词与 Skills
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 应用（`claude` / `codex` / `gemini`）-> 选择；未列出的应用不受影响
+    pub apps: BTreeMap<String, ProfileApp>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
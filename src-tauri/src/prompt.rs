@@ -13,4 +13,22 @@ pub struct Prompt {
     pub created_at: Option<i64>,
     #[serde(rename = "updatedAt", skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<i64>,
+    /// 在已启用提示词栈中的位置（越小越靠前）；未启用时为 None
+    #[serde(
+        rename = "stackOrder",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stack_order: Option<i64>,
+}
+
+/// 项目目录使用的提示词预设（写入 `<project>/CLAUDE.md` 等文件）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptProject {
+    pub app_type: String,
+    /// 项目目录的绝对路径
+    pub project_path: String,
+    pub prompt_id: String,
+    pub activated_at: i64,
 }
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::app_config::AppType;
 use crate::codex_config::get_codex_auth_path;
@@ -14,13 +14,133 @@ pub fn prompt_file_path(app: &AppType) -> Result<PathBuf, AppError> {
         AppType::Gemini => get_gemini_dir(),
     };
 
-    let filename = match app {
+    Ok(base_dir.join(prompt_file_name(app)))
+}
+
+/// 返回项目目录中对应应用的提示词文件路径（`<project>/CLAUDE.md` 等）。
+pub fn project_prompt_file_path(app: &AppType, project_dir: &Path) -> PathBuf {
+    project_dir.join(prompt_file_name(app))
+}
+
+fn prompt_file_name(app: &AppType) -> &'static str {
+    match app {
         AppType::Claude => "CLAUDE.md",
         AppType::Codex => "AGENTS.md",
         AppType::Gemini => "GEMINI.md",
-    };
+    }
+}
 
-    Ok(base_dir.join(filename))
+/// 托管段落起始标记前缀：`<!-- cc-switch:begin <id> -->`
+const SECTION_BEGIN: &str = "<!-- cc-switch:begin ";
+/// 托管段落结束标记前缀：`<!-- cc-switch:end <id> -->`
+const SECTION_END: &str = "<!-- cc-switch:end ";
+const MARKER_SUFFIX: &str = " -->";
+
+/// 提示词文件中由 cc-switch 托管的一个段落（对应提示词栈中的一层）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedSection {
+    pub id: String,
+    pub content: String,
+}
+
+/// 按托管标记拆分后的提示词文件
+///
+/// 标记以外的内容属于用户，重写提示词栈时原样保留：第一个托管段落之前的内容保持在前，
+/// 其余内容（包括段落之间的内容）放在托管区之后。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PromptFileLayout {
+    pub before: String,
+    pub sections: Vec<ManagedSection>,
+    pub after: String,
+}
+
+impl PromptFileLayout {
+    /// 解析提示词文件；未闭合的起始标记及其后内容视为用户内容
+    pub fn parse(content: &str) -> Self {
+        let mut layout = Self::default();
+        let mut current: Option<(String, String)> = None;
+        let mut pending = String::new();
+
+        for line in content.split_inclusive('\n') {
+            let trimmed = line.trim();
+            match current.as_mut() {
+                Some((id, body)) => {
+                    if marker_id(trimmed, SECTION_END) == Some(id.as_str()) {
+                        let (id, body) = current.take().unwrap_or_default();
+                        let content = body.strip_suffix('\n').unwrap_or(&body).to_string();
+                        layout.sections.push(ManagedSection { id, content });
+                    } else {
+                        body.push_str(line);
+                    }
+                }
+                None => {
+                    if let Some(id) = marker_id(trimmed, SECTION_BEGIN) {
+                        current = Some((id.to_string(), String::new()));
+                        // 记录起始标记，段落未闭合时按原文还原
+                        pending.clear();
+                        pending.push_str(line);
+                        continue;
+                    }
+                    if layout.sections.is_empty() {
+                        layout.before.push_str(line);
+                    } else {
+                        layout.after.push_str(line);
+                    }
+                }
+            }
+        }
+
+        if let Some((_, body)) = current {
+            let target = if layout.sections.is_empty() {
+                &mut layout.before
+            } else {
+                &mut layout.after
+            };
+            target.push_str(&pending);
+            target.push_str(&body);
+        }
+        layout
+    }
+
+    /// 用户内容（标记以外）是否为空
+    pub fn has_user_content(&self) -> bool {
+        !self.before.trim().is_empty() || !self.after.trim().is_empty()
+    }
+
+    /// 组装文件内容：托管段落依次排列，段落之间空一行，用户内容保持在原位置
+    pub fn render(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        let before = self.before.trim_end();
+        if !before.is_empty() {
+            parts.push(before.to_string());
+        }
+        for section in &self.sections {
+            parts.push(format!(
+                "{SECTION_BEGIN}{id}{MARKER_SUFFIX}\n{content}\n{SECTION_END}{id}{MARKER_SUFFIX}",
+                id = section.id,
+                content = section.content.trim_end_matches('\n'),
+            ));
+        }
+        let after = self.after.trim_matches('\n');
+        if !after.trim().is_empty() {
+            parts.push(after.to_string());
+        }
+
+        if parts.is_empty() {
+            String::new()
+        } else {
+            let mut out = parts.join("\n\n");
+            out.push('\n');
+            out
+        }
+    }
+}
+
+fn marker_id<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    line.strip_prefix(prefix)?
+        .strip_suffix(MARKER_SUFFIX)
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
 }
 
 fn get_base_dir_with_fallback(
@@ -39,3 +159,42 @@ fn get_base_dir_with_fallback(
             )
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn managed_sections_round_trip_and_keep_user_content() {
+        let file = "# My notes\n\n<!-- cc-switch:begin team -->\nBe kind.\n<!-- cc-switch:end team -->\n\n<!-- cc-switch:begin rust -->\nUse clippy.\n<!-- cc-switch:end rust -->\n\nTrailing note\n";
+        let layout = PromptFileLayout::parse(file);
+        assert_eq!(layout.before, "# My notes\n\n");
+        assert_eq!(
+            layout.sections,
+            vec![
+                ManagedSection {
+                    id: "team".to_string(),
+                    content: "Be kind.".to_string()
+                },
+                ManagedSection {
+                    id: "rust".to_string(),
+                    content: "Use clippy.".to_string()
+                },
+            ]
+        );
+        assert!(layout.has_user_content());
+        assert_eq!(layout.render(), file);
+
+        let mut emptied = layout.clone();
+        emptied.sections.clear();
+        assert_eq!(emptied.render(), "# My notes\n\nTrailing note\n");
+    }
+
+    #[test]
+    fn unterminated_section_is_kept_as_user_content() {
+        let file = "<!-- cc-switch:begin team -->\nhalf written\n";
+        let layout = PromptFileLayout::parse(file);
+        assert!(layout.sections.is_empty());
+        assert_eq!(layout.before, file);
+    }
+}
@@ -0,0 +1,471 @@
+//! 提示词模板渲染
+//!
+//! 提示词在激活（写入 CLAUDE.md / AGENTS.md / GEMINI.md）时渲染，支持：
+//! - `{{var}}`：变量（`user`、`project`、`cwd`、`os`、`date`、`app`）
+//! - `{{> other-id}}`：包含同一应用下的其他提示词（递归渲染，检测循环引用）
+//! - `{{#app codex gemini}} ... {{else}} ... {{/app}}`：按应用输出的条件段落，
+//!   `{{^app codex}} ... {{/app}}` 为取反
+//! - `\{{`：输出字面量 `{{`
+//!
+//! 不含 `{{` 的提示词原样输出；未定义的变量保持原样并在结果中报告。无法识别的标签
+//! （如 JSX 的 `style={{color: 'red'}}`、Helm 的 `{{ .Values.x }}`）与未闭合的 `{{` 按字面量输出。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::prompt::Prompt;
+
+/// 渲染所需的变量与目标应用
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub app: AppType,
+    pub vars: BTreeMap<String, String>,
+}
+
+impl TemplateContext {
+    /// 以当前环境填充内置变量
+    pub fn for_app(app: &AppType) -> Self {
+        let mut vars = BTreeMap::new();
+        if let Some(user) = std::env::var("USER")
+            .ok()
+            .or_else(|| std::env::var("USERNAME").ok())
+            .filter(|u| !u.is_empty())
+        {
+            vars.insert("user".to_string(), user);
+        }
+        if let Ok(cwd) = std::env::current_dir() {
+            if let Some(name) = cwd.file_name() {
+                vars.insert("project".to_string(), name.to_string_lossy().to_string());
+            }
+            vars.insert("cwd".to_string(), cwd.display().to_string());
+        }
+        vars.insert("os".to_string(), std::env::consts::OS.to_string());
+        vars.insert(
+            "date".to_string(),
+            chrono::Local::now().format("%Y-%m-%d").to_string(),
+        );
+        vars.insert("app".to_string(), app.as_str().to_string());
+
+        Self {
+            app: app.clone(),
+            vars,
+        }
+    }
+
+    /// 项目级提示词：`project`、`cwd` 指向项目目录而不是当前目录
+    pub fn for_project(app: &AppType, project_dir: &Path) -> Self {
+        let mut ctx = Self::for_app(app);
+        if let Some(name) = project_dir.file_name() {
+            ctx.vars
+                .insert("project".to_string(), name.to_string_lossy().to_string());
+        }
+        ctx.vars
+            .insert("cwd".to_string(), project_dir.display().to_string());
+        ctx
+    }
+
+    pub fn with_var(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.vars.insert(name.to_string(), value.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedPrompt {
+    pub content: String,
+    /// 未定义的变量名（去重、排序）
+    pub unresolved: Vec<String>,
+}
+
+/// 内容是否使用了模板语法
+pub fn is_template(content: &str) -> bool {
+    content.contains("{{")
+}
+
+/// 渲染已保存的提示词 `id`
+pub fn render_stored(
+    id: &str,
+    prompts: &HashMap<String, Prompt>,
+    ctx: &TemplateContext,
+) -> Result<RenderedPrompt, AppError> {
+    let prompt = prompts
+        .get(id)
+        .ok_or_else(|| AppError::InvalidInput(format!("提示词 {id} 不存在")))?;
+    let mut unresolved = BTreeSet::new();
+    let mut stack = vec![id.to_string()];
+    let content = render_content(&prompt.content, ctx, prompts, &mut stack, &mut unresolved)?;
+    Ok(RenderedPrompt {
+        content,
+        unresolved: unresolved.into_iter().collect(),
+    })
+}
+
+fn render_content(
+    content: &str,
+    ctx: &TemplateContext,
+    prompts: &HashMap<String, Prompt>,
+    stack: &mut Vec<String>,
+    unresolved: &mut BTreeSet<String>,
+) -> Result<String, AppError> {
+    if !is_template(content) {
+        return Ok(content.to_string());
+    }
+    let nodes = parse(content)?;
+    let mut out = String::with_capacity(content.len());
+    render_nodes(&nodes, ctx, prompts, stack, unresolved, &mut out)?;
+    Ok(out)
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var {
+        name: String,
+        raw: String,
+    },
+    Include(String),
+    App {
+        apps: Vec<String>,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+enum Tag {
+    Var(String),
+    Include(String),
+    /// 无法识别的标签，按原文输出
+    Literal,
+    AppOpen {
+        apps: Vec<String>,
+        negate: bool,
+    },
+    Else,
+    AppClose,
+}
+
+impl Tag {
+    fn is_block(&self) -> bool {
+        matches!(self, Tag::AppOpen { .. } | Tag::Else | Tag::AppClose)
+    }
+}
+
+fn classify(inner: &str) -> Result<Tag, AppError> {
+    let inner = inner.trim();
+    if let Some(id) = inner.strip_prefix('>') {
+        let id = id.trim();
+        if id.is_empty() {
+            return Err(AppError::InvalidInput(
+                "模板包含为空：应为 {{> prompt-id}}".to_string(),
+            ));
+        }
+        return Ok(Tag::Include(id.to_string()));
+    }
+
+    let open = inner
+        .strip_prefix("#app")
+        .map(|rest| (rest, false))
+        .or_else(|| inner.strip_prefix("^app").map(|rest| (rest, true)));
+    if let Some((rest, negate)) = open {
+        let apps: Vec<String> = rest
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_lowercase())
+            .collect();
+        if apps.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "模板条件缺少应用名: {{{{{inner}}}}}"
+            )));
+        }
+        if let Some(bad) = apps
+            .iter()
+            .find(|a| !matches!(a.as_str(), "claude" | "codex" | "gemini"))
+        {
+            return Err(AppError::InvalidInput(format!(
+                "模板条件中的未知应用: {bad}（可选 claude、codex、gemini）"
+            )));
+        }
+        return Ok(Tag::AppOpen { apps, negate });
+    }
+
+    match inner {
+        "else" => Ok(Tag::Else),
+        "/app" => Ok(Tag::AppClose),
+        name if is_var_name(name) => Ok(Tag::Var(name.to_string())),
+        _ => Ok(Tag::Literal),
+    }
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn parse(content: &str) -> Result<Vec<Node>, AppError> {
+    let mut tags: Vec<(String, Option<(Tag, String)>)> = Vec::new();
+    let mut text = String::new();
+    let mut cursor = 0;
+
+    while let Some(found) = content[cursor..].find("{{") {
+        let start = cursor + found;
+        if start > 0 && content.as_bytes()[start - 1] == b'\\' {
+            text.push_str(&content[cursor..start - 1]);
+            text.push_str("{{");
+            cursor = start + 2;
+            continue;
+        }
+        let Some(len) = content[start + 2..].find("}}") else {
+            break;
+        };
+        let tag = classify(&content[start + 2..start + 2 + len])?;
+        let mut end = start + 2 + len + 2;
+        text.push_str(&content[cursor..start]);
+        if matches!(tag, Tag::Literal) {
+            text.push_str(&content[start..end]);
+            cursor = end;
+            continue;
+        }
+
+        // 独占一行的块标签连同所在行一起移除，避免渲染结果出现空行
+        if tag.is_block() {
+            let line_start = content[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let line_end = content[end..]
+                .find('\n')
+                .map(|i| end + i + 1)
+                .unwrap_or(content.len());
+            let before = &content[line_start..start];
+            let after = &content[end..line_end];
+            if before.chars().all(|c| c == ' ' || c == '\t') && after.trim().is_empty() {
+                text.truncate(text.len() - before.len());
+                end = line_end;
+            }
+        }
+
+        tags.push((
+            std::mem::take(&mut text),
+            Some((tag, content[start..end].to_string())),
+        ));
+        cursor = end;
+    }
+    text.push_str(&content[cursor..]);
+    tags.push((text, None));
+
+    // 按块结构组装语法树
+    struct Frame {
+        apps: Vec<String>,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Option<Vec<Node>>,
+    }
+    let mut root: Vec<Node> = Vec::new();
+    let mut frames: Vec<Frame> = Vec::new();
+
+    fn current<'a>(root: &'a mut Vec<Node>, frames: &'a mut [Frame]) -> &'a mut Vec<Node> {
+        match frames.last_mut() {
+            Some(frame) => frame.otherwise.as_mut().unwrap_or(&mut frame.then),
+            None => root,
+        }
+    }
+
+    for (text, tag) in tags {
+        if !text.is_empty() {
+            current(&mut root, &mut frames).push(Node::Text(text));
+        }
+        let Some((tag, raw)) = tag else {
+            continue;
+        };
+        match tag {
+            Tag::Literal => {}
+            Tag::Var(name) => current(&mut root, &mut frames).push(Node::Var { name, raw }),
+            Tag::Include(id) => current(&mut root, &mut frames).push(Node::Include(id)),
+            Tag::AppOpen { apps, negate } => frames.push(Frame {
+                apps,
+                negate,
+                then: Vec::new(),
+                otherwise: None,
+            }),
+            Tag::Else => match frames.last_mut() {
+                Some(frame) if frame.otherwise.is_none() => frame.otherwise = Some(Vec::new()),
+                _ => {
+                    return Err(AppError::InvalidInput(
+                        "模板中的 {{else}} 没有对应的 {{#app}}".to_string(),
+                    ))
+                }
+            },
+            Tag::AppClose => {
+                let Some(frame) = frames.pop() else {
+                    return Err(AppError::InvalidInput(
+                        "模板中的 {{/app}} 没有对应的 {{#app}}".to_string(),
+                    ));
+                };
+                current(&mut root, &mut frames).push(Node::App {
+                    apps: frame.apps,
+                    negate: frame.negate,
+                    then: frame.then,
+                    otherwise: frame.otherwise.unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    if !frames.is_empty() {
+        return Err(AppError::InvalidInput(
+            "模板中的 {{#app}} 缺少 {{/app}}".to_string(),
+        ));
+    }
+    Ok(root)
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    ctx: &TemplateContext,
+    prompts: &HashMap<String, Prompt>,
+    stack: &mut Vec<String>,
+    unresolved: &mut BTreeSet<String>,
+    out: &mut String,
+) -> Result<(), AppError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { name, raw } => match ctx.vars.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    unresolved.insert(name.clone());
+                    out.push_str(raw);
+                }
+            },
+            Node::Include(id) => {
+                if stack.contains(id) {
+                    let mut chain = stack.clone();
+                    chain.push(id.clone());
+                    return Err(AppError::InvalidInput(format!(
+                        "提示词循环包含: {}",
+                        chain.join(" -> ")
+                    )));
+                }
+                let included = prompts
+                    .get(id)
+                    .ok_or_else(|| AppError::InvalidInput(format!("包含的提示词 {id} 不存在")))?;
+                stack.push(id.clone());
+                let rendered = render_content(&included.content, ctx, prompts, stack, unresolved)?;
+                stack.pop();
+                out.push_str(&rendered);
+            }
+            Node::App {
+                apps,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let matched = apps.iter().any(|a| a == ctx.app.as_str()) != *negate;
+                let branch = if matched { then } else { otherwise };
+                render_nodes(branch, ctx, prompts, stack, unresolved, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(id: &str, content: &str) -> (String, Prompt) {
+        (
+            id.to_string(),
+            Prompt {
+                id: id.to_string(),
+                name: id.to_string(),
+                content: content.to_string(),
+                description: None,
+                enabled: false,
+                created_at: None,
+                updated_at: None,
+                stack_order: None,
+            },
+        )
+    }
+
+    fn ctx(app: AppType) -> TemplateContext {
+        TemplateContext {
+            app,
+            vars: BTreeMap::new(),
+        }
+        .with_var("user", "ada")
+    }
+
+    #[test]
+    fn renders_variables_includes_and_app_sections() {
+        let prompts: HashMap<String, Prompt> = [
+            prompt("base", "Be concise, {{user}}.\n"),
+            prompt(
+                "main",
+                "{{> base}}\n{{#app codex}}\nUse apply_patch.\n{{else}}\nUse the edit tool.\n{{/app}}\n{{^app gemini}}\nNo web search.\n{{/app}}\nTeam: {{team}} \\{{literal}}\n",
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let codex = render_stored("main", &prompts, &ctx(AppType::Codex)).expect("render");
+        assert_eq!(
+            codex.content,
+            "Be concise, ada.\n\nUse apply_patch.\nNo web search.\nTeam: {{team}} {{literal}}\n"
+        );
+        assert_eq!(codex.unresolved, vec!["team".to_string()]);
+
+        let gemini = render_stored("main", &prompts, &ctx(AppType::Gemini)).expect("render");
+        assert_eq!(
+            gemini.content,
+            "Be concise, ada.\n\nUse the edit tool.\nTeam: {{team}} {{literal}}\n"
+        );
+    }
+
+    #[test]
+    fn plain_prompts_are_returned_verbatim() {
+        let prompts: HashMap<String, Prompt> =
+            [prompt("plain", "# Rules\n\nUse } and { freely.\n")]
+                .into_iter()
+                .collect();
+        let rendered = render_stored("plain", &prompts, &ctx(AppType::Claude)).expect("render");
+        assert_eq!(rendered.content, "# Rules\n\nUse } and { freely.\n");
+    }
+
+    #[test]
+    fn unknown_tags_and_unclosed_braces_are_literal() {
+        let content = "<div style={{color: 'red'}}>{{ user }}</div>\n\
+                       image: {{ .Values.image }}\n{{ item.name }} {{ open";
+        let prompts: HashMap<String, Prompt> = [prompt("mixed", content)].into_iter().collect();
+        let rendered = render_stored("mixed", &prompts, &ctx(AppType::Claude)).expect("render");
+        assert_eq!(
+            rendered.content,
+            "<div style={{color: 'red'}}>ada</div>\nimage: {{ .Values.image }}\n{{ item.name }} {{ open"
+        );
+        assert_eq!(rendered.unresolved, vec!["item.name".to_string()]);
+    }
+
+    #[test]
+    fn rejects_cycles_missing_includes_and_unbalanced_sections() {
+        let prompts: HashMap<String, Prompt> = [
+            prompt("a", "{{> b}}"),
+            prompt("b", "{{> a}}"),
+            prompt("missing", "{{> nope}}"),
+            prompt("open", "{{#app claude}}x"),
+            prompt("unknown", "{{#app cursor}}x{{/app}}"),
+        ]
+        .into_iter()
+        .collect();
+        let claude = ctx(AppType::Claude);
+
+        let err = render_stored("a", &prompts, &claude).expect_err("cycle");
+        assert!(err.to_string().contains("a -> b -> a"), "{err}");
+        assert!(render_stored("missing", &prompts, &claude).is_err());
+        assert!(render_stored("open", &prompts, &claude).is_err());
+        assert!(render_stored("unknown", &prompts, &claude).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! MCP 密钥存储
+//!
+//! 密钥保存在 `~/.cc-switch/secrets.json`（Unix 下权限为 0600），独立于 SQLite 数据库，
+//! 因此不会出现在 `config export`、SQL 备份或 `mcp_servers` 表中。
+//!
+//! MCP 服务器规范通过 `${secret:NAME}` 占位符引用密钥，占位符只在写入 live 配置时替换。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::config::{atomic_write_with_mode, get_app_config_dir};
+use crate::error::AppError;
+
+fn placeholder_re() -> &'static Regex {
+    static PLACEHOLDER_RE: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER_RE.get_or_init(|| {
+        Regex::new(r"\$\{secret:([A-Za-z0-9_.-]+)\}").expect("PLACEHOLDER_RE must compile")
+    })
+}
+
+/// 密钥名称仅允许字母、数字、`_`、`.`、`-`
+pub fn is_valid_secret_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretStore {
+    #[serde(default = "default_version")]
+    version: u32,
+    #[serde(default)]
+    secrets: BTreeMap<String, String>,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl SecretStore {
+    /// 密钥文件路径（~/.cc-switch/secrets.json）
+    pub fn path() -> PathBuf {
+        get_app_config_dir().join("secrets.json")
+    }
+
+    /// 加载密钥存储；文件不存在时返回空存储
+    pub fn load() -> Result<Self, AppError> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        serde_json::from_str(&content).map_err(|e| AppError::json(&path, e))
+    }
+
+    /// 持久化密钥存储，文件权限为仅当前用户可读写（Unix 下为 0600）
+    pub fn save(&self) -> Result<(), AppError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::JsonSerialize { source: e })?;
+        atomic_write_with_mode(&Self::path(), json.as_bytes(), Some(0o600))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.secrets.get(name).map(|s| s.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.secrets.contains_key(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), AppError> {
+        if !is_valid_secret_name(name) {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid secret name '{name}': only letters, digits, '_', '.' and '-' are allowed"
+            )));
+        }
+        self.secrets.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.secrets.remove(name).is_some()
+    }
+
+    /// 所有密钥名称（按字母序）
+    pub fn names(&self) -> Vec<String> {
+        self.secrets.keys().cloned().collect()
+    }
+}
+
+/// 收集 JSON 值中引用的全部密钥名称（去重、排序）
+pub fn find_placeholders(value: &Value) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    collect_placeholders(value, &mut names);
+    names.into_iter().collect()
+}
+
+fn collect_placeholders(value: &Value, names: &mut BTreeSet<String>) {
+    match value {
+        Value::String(s) => {
+            for caps in placeholder_re().captures_iter(s) {
+                names.insert(caps[1].to_string());
+            }
+        }
+        Value::Array(arr) => arr.iter().for_each(|v| collect_placeholders(v, names)),
+        Value::Object(obj) => obj.values().for_each(|v| collect_placeholders(v, names)),
+        _ => {}
+    }
+}
+
+/// 替换 JSON 值中的 `${secret:NAME}` 占位符
+///
+/// 返回替换后的值与未能解析的密钥名称；未解析的占位符保持原样。
+pub fn resolve_placeholders(value: &Value, store: &SecretStore) -> (Value, Vec<String>) {
+    let mut unresolved = BTreeSet::new();
+    let resolved = resolve_value(value, store, &mut unresolved);
+    (resolved, unresolved.into_iter().collect())
+}
+
+fn resolve_value(value: &Value, store: &SecretStore, unresolved: &mut BTreeSet<String>) -> Value {
+    match value {
+        Value::String(s) => {
+            let replaced = placeholder_re().replace_all(s, |caps: &regex::Captures| {
+                let name = &caps[1];
+                match store.get(name) {
+                    Some(secret) => secret.to_string(),
+                    None => {
+                        unresolved.insert(name.to_string());
+                        caps[0].to_string()
+                    }
+                }
+            });
+            Value::String(replaced.into_owned())
+        }
+        Value::Array(arr) => Value::Array(
+            arr.iter()
+                .map(|v| resolve_value(v, store, unresolved))
+                .collect(),
+        ),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), resolve_value(v, store, unresolved)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store_with(pairs: &[(&str, &str)]) -> SecretStore {
+        let mut store = SecretStore::default();
+        for (name, value) in pairs {
+            store.set(name, value).expect("valid secret name");
+        }
+        store
+    }
+
+    #[test]
+    fn resolves_nested_placeholders_and_reports_missing() {
+        let store = store_with(&[("GITHUB_PAT", "ghp_123")]);
+        let spec = json!({
+            "command": "npx",
+            "args": ["--token=${secret:GITHUB_PAT}"],
+            "env": {
+                "GITHUB_TOKEN": "${secret:GITHUB_PAT}",
+                "DB_URL": "postgres://app:${secret:DB_PASSWORD}@localhost/app"
+            }
+        });
+
+        let (resolved, unresolved) = resolve_placeholders(&spec, &store);
+
+        assert_eq!(resolved["args"][0], json!("--token=ghp_123"));
+        assert_eq!(resolved["env"]["GITHUB_TOKEN"], json!("ghp_123"));
+        assert_eq!(
+            resolved["env"]["DB_URL"],
+            json!("postgres://app:${secret:DB_PASSWORD}@localhost/app")
+        );
+        assert_eq!(unresolved, vec!["DB_PASSWORD".to_string()]);
+        assert_eq!(
+            find_placeholders(&spec),
+            vec!["DB_PASSWORD".to_string(), "GITHUB_PAT".to_string()]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_secret_names() {
+        let mut store = SecretStore::default();
+        assert!(store.set("bad name", "x").is_err());
+        assert!(store.set("}", "x").is_err());
+        assert!(store.set("OK_name-1.v2", "x").is_ok());
+    }
+}
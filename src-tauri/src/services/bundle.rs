@@ -0,0 +1,475 @@
+//! 结构化配置包（JSON / YAML）
+//!
+//! 与整库 SQL 导出不同，配置包可按应用、供应商、MCP、提示词与 Skills 选择性导出，
+//! 导入时合并到现有数据库，ID 冲突按策略跳过、覆盖或重命名。
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::ConfigService;
+use super::mcp::McpService;
+use super::prompt::PromptService;
+use super::provider::ProviderService;
+use super::skill::{SkillLockEntry, SkillService};
+use crate::app_config::{AppType, McpServer};
+use crate::backup_crypto;
+use crate::error::AppError;
+use crate::prompt::Prompt;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+pub const BUNDLE_FORMAT: &str = "cc-switch-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+const ALL_APPS: [AppType; 3] = [AppType::Claude, AppType::Codex, AppType::Gemini];
+
+/// 配置包内容；供应商与提示词按应用分组，Skills 以 lock 条目形式记录来源与内容哈希
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, Vec<Provider>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mcp_servers: Vec<McpServer>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prompts: BTreeMap<String, Vec<Prompt>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<SkillLockEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Json,
+    Yaml,
+}
+
+impl BundleFormat {
+    /// 按扩展名识别配置包（`.json` / `.yaml` / `.yml`），其他扩展名视为 SQL
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// 导出范围；未选择任何类别时导出全部
+#[derive(Debug, Clone, Default)]
+pub struct BundleSelection {
+    /// 仅导出该应用的供应商、提示词，以及对其启用的 MCP 服务器与 Skills
+    pub app: Option<AppType>,
+    /// `Some(空)` 表示全部供应商，`Some(ids)` 表示指定供应商
+    pub providers: Option<Vec<String>>,
+    pub mcp: bool,
+    pub prompts: bool,
+    pub skills: bool,
+}
+
+impl BundleSelection {
+    fn select_all(&self) -> bool {
+        self.providers.is_none() && !self.mcp && !self.prompts && !self.skills
+    }
+
+    fn apps(&self) -> Vec<AppType> {
+        match &self.app {
+            Some(app) => vec![app.clone()],
+            None => ALL_APPS.to_vec(),
+        }
+    }
+}
+
+/// 导入时的 ID 冲突处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ConflictStrategy {
+    /// 保留现有条目
+    #[default]
+    Skip,
+    /// 用配置包中的条目替换现有条目
+    Overwrite,
+    /// 以新 ID 导入（`<id>-2`、`<id>-3` …）
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleItemKind {
+    Provider,
+    McpServer,
+    Prompt,
+    Skill,
+}
+
+impl BundleItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Provider => "provider",
+            Self::McpServer => "mcp",
+            Self::Prompt => "prompt",
+            Self::Skill => "skill",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleAction {
+    Add,
+    Overwrite,
+    /// 以新 ID 导入
+    Rename(String),
+    /// ID 冲突，保留现有条目
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleChange {
+    pub kind: BundleItemKind,
+    /// MCP 服务器与 Skills 不区分应用
+    pub app: Option<AppType>,
+    pub id: String,
+    pub action: BundleAction,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BundleImportReport {
+    pub changes: Vec<BundleChange>,
+    /// 需要下载安装的 Skills（由调用方通过 `SkillService::install_lock_entries` 安装）
+    pub pending_skills: Vec<SkillLockEntry>,
+    /// 导入前创建的备份 ID（dry-run 时为 None）
+    pub backup_id: Option<String>,
+}
+
+impl BundleImportReport {
+    pub fn count(&self, predicate: impl Fn(&BundleAction) -> bool) -> usize {
+        self.changes.iter().filter(|c| predicate(&c.action)).count()
+    }
+}
+
+pub struct BundleService;
+
+impl BundleService {
+    /// 按选择范围构建配置包
+    pub fn build(state: &AppState, selection: &BundleSelection) -> Result<ConfigBundle, AppError> {
+        let all = selection.select_all();
+        let apps = selection.apps();
+        let mut bundle = ConfigBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            providers: BTreeMap::new(),
+            mcp_servers: Vec::new(),
+            prompts: BTreeMap::new(),
+            skills: Vec::new(),
+        };
+
+        if all || selection.providers.is_some() {
+            let wanted = selection.providers.clone().unwrap_or_default();
+            let mut found = HashSet::new();
+            for app in &apps {
+                let providers: Vec<Provider> = ProviderService::list(state, app.clone())?
+                    .into_values()
+                    .filter(|p| wanted.is_empty() || wanted.contains(&p.id))
+                    .collect();
+                found.extend(providers.iter().map(|p| p.id.clone()));
+                if !providers.is_empty() {
+                    bundle.providers.insert(app.as_str().to_string(), providers);
+                }
+            }
+            let missing: Vec<&str> = wanted
+                .iter()
+                .filter(|id| !found.contains(*id))
+                .map(String::as_str)
+                .collect();
+            if !missing.is_empty() {
+                return Err(AppError::localized(
+                    "bundle.provider_not_found",
+                    format!("供应商不存在: {}", missing.join(", ")),
+                    format!("Provider not found: {}", missing.join(", ")),
+                ));
+            }
+        }
+
+        if all || selection.mcp {
+            let mut servers: Vec<McpServer> = McpService::get_all_servers(state)?
+                .into_values()
+                .filter(|s| {
+                    selection
+                        .app
+                        .as_ref()
+                        .is_none_or(|app| s.apps.is_enabled_for(app))
+                })
+                .collect();
+            servers.sort_by(|a, b| a.id.cmp(&b.id));
+            bundle.mcp_servers = servers;
+        }
+
+        if all || selection.prompts {
+            for app in &apps {
+                let mut prompts: Vec<Prompt> = PromptService::get_prompts(state, app.clone())?
+                    .into_values()
+                    .collect();
+                prompts.sort_by(|a, b| a.id.cmp(&b.id));
+                if !prompts.is_empty() {
+                    bundle.prompts.insert(app.as_str().to_string(), prompts);
+                }
+            }
+        }
+
+        if all || selection.skills {
+            bundle.skills = SkillService::build_lock()?
+                .skills
+                .into_iter()
+                .filter(|s| {
+                    selection
+                        .app
+                        .as_ref()
+                        .is_none_or(|app| s.apps.is_enabled_for(app))
+                })
+                .collect();
+        }
+
+        Ok(bundle)
+    }
+
+    /// 写入配置包；给定口令时加密整个文件
+    pub fn write(
+        bundle: &ConfigBundle,
+        path: &Path,
+        passphrase: Option<&str>,
+    ) -> Result<(), AppError> {
+        let text = match BundleFormat::from_path(path) {
+            Some(BundleFormat::Yaml) => serde_yaml::to_string(bundle)
+                .map_err(|e| AppError::Message(format!("序列化配置包失败: {e}")))?,
+            _ => serde_json::to_string_pretty(bundle)
+                .map_err(|e| AppError::JsonSerialize { source: e })?,
+        };
+        let data = match passphrase {
+            Some(passphrase) => backup_crypto::encrypt(text.as_bytes(), passphrase)?,
+            None => text.into_bytes(),
+        };
+        crate::config::atomic_write(path, &data)
+    }
+
+    /// 读取配置包（自动识别加密文件）；加密文件优先使用 `passphrase`，其次读取环境变量
+    pub fn read(path: &Path, passphrase: Option<&str>) -> Result<ConfigBundle, AppError> {
+        let raw = fs::read(path).map_err(|e| AppError::io(path, e))?;
+        let raw = if backup_crypto::is_encrypted(&raw) {
+            let passphrase = passphrase
+                .map(str::to_string)
+                .or_else(backup_crypto::passphrase_from_env)
+                .ok_or_else(|| {
+                    AppError::localized(
+                        "backup.encrypted.passphrase_required",
+                        format!(
+                            "备份文件已加密，请提供口令（或设置 {}）",
+                            backup_crypto::PASSPHRASE_ENV
+                        ),
+                        format!(
+                            "The backup is encrypted; provide the passphrase (or set {})",
+                            backup_crypto::PASSPHRASE_ENV
+                        ),
+                    )
+                })?;
+            backup_crypto::decrypt(&raw, &passphrase)?
+        } else {
+            raw
+        };
+        let text = String::from_utf8(raw).map_err(|_| {
+            AppError::InvalidInput(format!("配置包不是有效的 UTF-8: {}", path.display()))
+        })?;
+
+        let bundle: ConfigBundle = match BundleFormat::from_path(path) {
+            Some(BundleFormat::Yaml) => serde_yaml::from_str(&text)
+                .map_err(|e| AppError::InvalidInput(format!("解析配置包失败: {e}")))?,
+            _ => serde_json::from_str(&text).map_err(|e| AppError::json(path, e))?,
+        };
+
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(AppError::InvalidInput(format!(
+                "{} 不是 cc-switch 配置包",
+                path.display()
+            )));
+        }
+        if bundle.version > BUNDLE_VERSION {
+            return Err(AppError::Message(format!(
+                "{} 的版本 {} 高于当前支持的版本 {BUNDLE_VERSION}，请升级 cc-switch",
+                path.display(),
+                bundle.version
+            )));
+        }
+        for app in bundle.providers.keys().chain(bundle.prompts.keys()) {
+            app.parse::<AppType>()?;
+        }
+        Ok(bundle)
+    }
+
+    /// 计算合并结果但不写入（dry-run）
+    pub fn plan(
+        state: &AppState,
+        bundle: &ConfigBundle,
+        strategy: ConflictStrategy,
+    ) -> Result<BundleImportReport, AppError> {
+        let mut report = BundleImportReport::default();
+
+        for (app_key, providers) in &bundle.providers {
+            let app: AppType = app_key.parse()?;
+            let mut taken: HashSet<String> = ProviderService::list(state, app.clone())?
+                .into_keys()
+                .collect();
+            for provider in providers {
+                report.changes.push(BundleChange {
+                    kind: BundleItemKind::Provider,
+                    app: Some(app.clone()),
+                    id: provider.id.clone(),
+                    action: resolve(&provider.id, &mut taken, strategy),
+                });
+            }
+        }
+
+        let mut taken: HashSet<String> = McpService::get_all_servers(state)?.into_keys().collect();
+        for server in &bundle.mcp_servers {
+            report.changes.push(BundleChange {
+                kind: BundleItemKind::McpServer,
+                app: None,
+                id: server.id.clone(),
+                action: resolve(&server.id, &mut taken, strategy),
+            });
+        }
+
+        for (app_key, prompts) in &bundle.prompts {
+            let app: AppType = app_key.parse()?;
+            let mut taken: HashSet<String> = PromptService::get_prompts(state, app.clone())?
+                .into_keys()
+                .collect();
+            for prompt in prompts {
+                report.changes.push(BundleChange {
+                    kind: BundleItemKind::Prompt,
+                    app: Some(app.clone()),
+                    id: prompt.id.clone(),
+                    action: resolve(&prompt.id, &mut taken, strategy),
+                });
+            }
+        }
+
+        // Skill 目录名即安装位置，无法重命名；冲突时仅支持跳过或覆盖
+        let installed: HashSet<String> = SkillService::list_installed()?
+            .into_iter()
+            .map(|s| s.directory)
+            .collect();
+        for skill in &bundle.skills {
+            let action = if !installed.contains(&skill.directory) {
+                BundleAction::Add
+            } else if strategy == ConflictStrategy::Overwrite {
+                BundleAction::Overwrite
+            } else {
+                BundleAction::Skip
+            };
+            if action != BundleAction::Skip {
+                report.pending_skills.push(skill.clone());
+            }
+            report.changes.push(BundleChange {
+                kind: BundleItemKind::Skill,
+                app: None,
+                id: skill.directory.clone(),
+                action,
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// 合并导入配置包：先创建备份，再逐项写入供应商、MCP 服务器与提示词。
+    ///
+    /// Skills 需要联网下载，记录在 `pending_skills` 中由调用方安装。
+    pub fn import(
+        state: &AppState,
+        bundle: &ConfigBundle,
+        strategy: ConflictStrategy,
+    ) -> Result<BundleImportReport, AppError> {
+        let mut report = Self::plan(state, bundle, strategy)?;
+        if report.count(|a| *a != BundleAction::Skip) == 0 {
+            return Ok(report);
+        }
+
+        let backup_id = ConfigService::create_backup(&crate::config::get_app_config_path(), None)?;
+        report.backup_id = Some(backup_id).filter(|id| !id.is_empty());
+
+        let mut changes = report.changes.iter();
+        for (app_key, providers) in &bundle.providers {
+            let app: AppType = app_key.parse()?;
+            for (provider, change) in providers.iter().zip(changes.by_ref()) {
+                let mut provider = provider.clone();
+                match &change.action {
+                    BundleAction::Skip => {}
+                    BundleAction::Overwrite => {
+                        ProviderService::update(state, app.clone(), provider)?;
+                    }
+                    BundleAction::Add | BundleAction::Rename(_) => {
+                        if let BundleAction::Rename(new_id) = &change.action {
+                            provider.id = new_id.clone();
+                        }
+                        ProviderService::add(state, app.clone(), provider)?;
+                    }
+                }
+            }
+        }
+
+        for (server, change) in bundle.mcp_servers.iter().zip(changes.by_ref()) {
+            let mut server = server.clone();
+            match &change.action {
+                BundleAction::Skip => continue,
+                BundleAction::Rename(new_id) => server.id = new_id.clone(),
+                BundleAction::Add | BundleAction::Overwrite => {}
+            }
+            McpService::upsert_server(state, server)?;
+        }
+
+        for (app_key, prompts) in &bundle.prompts {
+            let app: AppType = app_key.parse()?;
+            let existing = PromptService::get_prompts(state, app.clone())?;
+            for (prompt, change) in prompts.iter().zip(changes.by_ref()) {
+                let mut prompt = prompt.clone();
+                // 导入不改变当前启用的提示词栈：覆盖时沿用现有启用状态，新增时不启用
+                let active = existing
+                    .get(&prompt.id)
+                    .filter(|_| change.action == BundleAction::Overwrite)
+                    .map(|p| (p.enabled, p.stack_order));
+                (prompt.enabled, prompt.stack_order) = active.unwrap_or((false, None));
+                match &change.action {
+                    BundleAction::Skip => continue,
+                    BundleAction::Rename(new_id) => prompt.id = new_id.clone(),
+                    BundleAction::Add | BundleAction::Overwrite => {}
+                }
+                let id = prompt.id.clone();
+                PromptService::upsert_prompt(state, app.clone(), &id, prompt)?;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// 根据冲突策略决定单个条目的处理方式，并登记最终占用的 ID
+fn resolve(id: &str, taken: &mut HashSet<String>, strategy: ConflictStrategy) -> BundleAction {
+    if taken.insert(id.to_string()) {
+        return BundleAction::Add;
+    }
+    match strategy {
+        ConflictStrategy::Skip => BundleAction::Skip,
+        ConflictStrategy::Overwrite => BundleAction::Overwrite,
+        ConflictStrategy::Rename => {
+            let new_id = (2..)
+                .map(|n| format!("{id}-{n}"))
+                .find(|candidate| !taken.contains(candidate))
+                .expect("unbounded candidates");
+            taken.insert(new_id.clone());
+            BundleAction::Rename(new_id)
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use super::provider::ProviderService;
+use super::remote_backup::{RemoteBackupService, RemoteUploadOutcome, IMPLICIT_UPLOAD_DEADLINE};
 use crate::app_config::{AppType, MultiAppConfig};
+use crate::backup_crypto;
 use crate::database::Database;
 use crate::error::AppError;
 use crate::provider::Provider;
@@ -8,6 +10,7 @@ use chrono::Utc;
 use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 const MAX_BACKUPS: usize = 10;
 
@@ -22,6 +25,8 @@ pub struct BackupInfo {
     pub timestamp: String,
     /// 显示名称（用于 UI）
     pub display_name: String,
+    /// 是否为口令加密的备份
+    pub encrypted: bool,
 }
 
 /// 配置导入导出相关业务逻辑
@@ -37,13 +42,51 @@ impl ConfigService {
     /// # 命名规则
     /// - 有自定义名称：`{custom_name}_{timestamp}.sql`
     /// - 无自定义名称：`backup_{timestamp}.sql`
+    ///
+    /// 设置了 `CC_SWITCH_BACKUP_PASSPHRASE` 时备份会加密。
     pub fn create_backup(
         config_path: &Path,
         custom_name: Option<String>,
     ) -> Result<String, AppError> {
+        Self::create_backup_with_passphrase(
+            config_path,
+            custom_name,
+            backup_crypto::passphrase_from_env().as_deref(),
+        )
+    }
+
+    /// 创建 SQL 备份；提供口令时加密。远程目标上传失败或超时只记录日志
+    pub fn create_backup_with_passphrase(
+        config_path: &Path,
+        custom_name: Option<String>,
+        passphrase: Option<&str>,
+    ) -> Result<String, AppError> {
+        let (backup_id, uploads) = Self::create_backup_with_uploads(
+            config_path,
+            custom_name,
+            passphrase,
+            Some(IMPLICIT_UPLOAD_DEADLINE),
+        )?;
+        for outcome in uploads {
+            if let Err(err) = outcome.result {
+                log::warn!("上传备份到远程目标 {} 失败: {}", outcome.target, err);
+            }
+        }
+        Ok(backup_id)
+    }
+
+    /// 创建 SQL 备份并上传到设置中的全部远程目标，返回备份 ID 与各目标的上传结果
+    ///
+    /// 未加密的备份包含全部 API Key，不会上传到远程目标。
+    pub fn create_backup_with_uploads(
+        config_path: &Path,
+        custom_name: Option<String>,
+        passphrase: Option<&str>,
+        upload_deadline: Option<Duration>,
+    ) -> Result<(String, Vec<RemoteUploadOutcome>), AppError> {
         let db_path = crate::config::get_app_config_dir().join("cc-switch.db");
         if !db_path.exists() {
-            return Ok(String::new());
+            return Ok((String::new(), Vec::new()));
         }
 
         let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
@@ -63,11 +106,19 @@ impl ConfigService {
 
         let backup_path = backup_dir.join(format!("{backup_id}.sql"));
         let db = Database::init()?;
-        db.export_sql(&backup_path)?;
+        match passphrase {
+            Some(passphrase) => db.export_sql_encrypted(&backup_path, passphrase)?,
+            None => db.export_sql(&backup_path)?,
+        }
 
         Self::cleanup_old_backups(&backup_dir, MAX_BACKUPS)?;
 
-        Ok(backup_id)
+        let uploads = if passphrase.is_some() {
+            RemoteBackupService::upload_all(&backup_path, upload_deadline)
+        } else {
+            RemoteBackupService::skip_unencrypted()
+        };
+        Ok((backup_id, uploads))
     }
 
     /// 列出所有可用的备份
@@ -102,11 +153,14 @@ impl ConfigService {
                 // 生成显示名称
                 let display_name = Self::format_display_name(&filename, &timestamp);
 
+                let encrypted = backup_crypto::is_encrypted_file(&path).unwrap_or(false);
+
                 Some(BackupInfo {
                     id: filename.clone(),
                     path: path.clone(),
                     timestamp,
                     display_name,
+                    encrypted,
                 })
             })
             .collect();
@@ -119,6 +173,21 @@ impl ConfigService {
 
     /// 根据备份 ID 恢复配置
     pub fn restore_from_backup_id(backup_id: &str, state: &AppState) -> Result<String, AppError> {
+        Self::restore_from_backup_id_with_passphrase(backup_id, state, None)
+    }
+
+    /// 根据备份 ID 恢复配置；加密备份使用给定口令（缺省时读取环境变量）
+    pub fn restore_from_backup_id_with_passphrase(
+        backup_id: &str,
+        state: &AppState,
+        passphrase: Option<&str>,
+    ) -> Result<String, AppError> {
+        let backup_path = Self::backup_path(backup_id)?;
+        Self::import_config_from_path_with_passphrase(&backup_path, state, passphrase)
+    }
+
+    /// 备份 ID 对应的文件路径（文件不存在时报错）
+    pub fn backup_path(backup_id: &str) -> Result<PathBuf, AppError> {
         let config_path = crate::config::get_app_config_path();
         let backup_dir = config_path
             .parent()
@@ -131,11 +200,11 @@ impl ConfigService {
             return Err(AppError::Message(format!("备份文件不存在: {}", backup_id)));
         }
 
-        Self::import_config_from_path(&backup_path, state)
+        Ok(backup_path)
     }
 
     /// 从文件名提取时间戳字符串
-    fn extract_timestamp(filename: &str) -> Option<String> {
+    pub(crate) fn extract_timestamp(filename: &str) -> Option<String> {
         // 尝试匹配格式：xxx_YYYYMMDD_HHMMSS
         let parts: Vec<&str> = filename.rsplitn(3, '_').collect();
         if parts.len() >= 2 {
@@ -147,7 +216,7 @@ impl ConfigService {
     }
 
     /// 格式化显示名称
-    fn format_display_name(filename: &str, timestamp: &str) -> String {
+    pub(crate) fn format_display_name(filename: &str, timestamp: &str) -> String {
         // 从时间戳格式 YYYYMMDD_HHMMSS 转换为可读格式
         if timestamp.len() == 15 {
             // YYYYMMDD_HHMMSS
@@ -230,17 +299,40 @@ impl ConfigService {
         db.export_sql(target_path)
     }
 
+    /// 导出口令加密的 SQL
+    pub fn export_config_to_path_encrypted(
+        target_path: &Path,
+        passphrase: &str,
+    ) -> Result<(), AppError> {
+        let db = Database::init()?;
+        db.export_sql_encrypted(target_path, passphrase)
+    }
+
     pub fn import_config_from_path(file_path: &Path, state: &AppState) -> Result<String, AppError> {
+        Self::import_config_from_path_with_passphrase(file_path, state, None)
+    }
+
+    /// 导入 SQL（自动识别加密文件）；加密文件使用给定口令，缺省时读取环境变量
+    pub fn import_config_from_path_with_passphrase(
+        file_path: &Path,
+        state: &AppState,
+        passphrase: Option<&str>,
+    ) -> Result<String, AppError> {
         let db_path = crate::config::get_app_config_dir().join("cc-switch.db");
         if !db_path.exists() {
             return Err(AppError::Config("数据库不存在，无法导入".to_string()));
         }
 
-        // Pre-import backup (SQL).
-        let backup_id = Self::create_backup(&db_path, None)?;
+        // Pre-import backup (SQL), encrypted with the same passphrase as the import.
+        let passphrase = passphrase
+            .map(str::to_string)
+            .or_else(backup_crypto::passphrase_from_env);
+        let backup_id = Self::create_backup_with_passphrase(&db_path, None, passphrase.as_deref())?;
 
-        // Import SQL into DB (also performs an internal binary snapshot backup).
-        state.db.import_sql(file_path)?;
+        // Import SQL into DB (also performs an internal snapshot backup).
+        state
+            .db
+            .import_sql_with_passphrase(file_path, passphrase.as_deref())?;
 
         Ok(backup_id)
     }
@@ -0,0 +1,573 @@
+//! Health checks across everything cc-switch manages (`cc-switch doctor`)
+//!
+//! - database: schema version, SQLite integrity, stored data present
+//! - live files per app: `settings.json`, `~/.claude.json`, `config.toml`, `auth.json`, `.env`
+//! - the stored current provider still matches the live config
+//! - MCP servers: stdio commands on PATH, live configs in sync with the store
+//! - skills: no dangling symlinks in the app skill directories
+//! - shell/system environment variables that override the live config
+//!
+//! Checks never modify anything. Findings that have a safe repair carry a [`DoctorFix`],
+//! applied by [`DoctorService::fix`]: a broken live file is moved aside and the current
+//! provider re-applied, and dangling skill links are removed.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app_config::AppType;
+use crate::claude_mcp::validate_command_in_path;
+use crate::codex_config::{get_codex_auth_path, get_codex_config_path, validate_config_toml};
+use crate::config::{get_claude_mcp_path, get_claude_settings_path};
+use crate::database::SCHEMA_VERSION;
+use crate::error::AppError;
+use crate::gemini_config::{get_gemini_env_path, get_gemini_settings_path, parse_env_file_strict};
+use crate::provider::Provider;
+use crate::services::env_checker::check_env_conflicts;
+use crate::services::skill_adapter::GeminiExtensionAdapter;
+use crate::services::{McpService, ProviderService, SkillService};
+use crate::store::AppState;
+use crate::sync_policy::should_sync_live;
+
+/// Parses a live file's contents, returning the parse error
+type FileCheck = fn(&str) -> Result<(), String>;
+
+const APPS: [AppType; 3] = [AppType::Claude, AppType::Codex, AppType::Gemini];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DoctorSeverity {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// A safe repair for a finding.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DoctorFix {
+    /// Move the broken live files aside (`<file>.broken-<timestamp>`) and re-apply the
+    /// stored current provider, which rewrites the live files.
+    ReapplyProvider {
+        app: AppType,
+        provider_id: String,
+        broken: Vec<PathBuf>,
+    },
+    /// Remove a symlink whose target no longer exists.
+    RemoveDanglingLink { path: PathBuf },
+}
+
+impl DoctorFix {
+    pub fn describe(&self) -> String {
+        match self {
+            DoctorFix::ReapplyProvider {
+                app, provider_id, ..
+            } => format!("re-apply provider '{provider_id}' for {}", app.as_str()),
+            DoctorFix::RemoveDanglingLink { path } => {
+                format!("remove dangling link {}", path.display())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DoctorFinding {
+    /// `database`, `claude`, `codex`, `gemini`, `mcp`, `skills` or `env`
+    pub area: String,
+    pub severity: DoctorSeverity,
+    pub message: String,
+    /// What the user can do about it when there is no automatic fix
+    pub hint: Option<String>,
+    pub fix: Option<DoctorFix>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub findings: Vec<DoctorFinding>,
+}
+
+impl DoctorReport {
+    pub fn error_count(&self) -> usize {
+        self.count(DoctorSeverity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(DoctorSeverity::Warning)
+    }
+
+    pub fn fixes(&self) -> impl Iterator<Item = &DoctorFix> {
+        self.findings.iter().filter_map(|f| f.fix.as_ref())
+    }
+
+    fn count(&self, severity: DoctorSeverity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    fn push(
+        &mut self,
+        area: &str,
+        severity: DoctorSeverity,
+        message: String,
+    ) -> &mut DoctorFinding {
+        self.findings.push(DoctorFinding {
+            area: area.to_string(),
+            severity,
+            message,
+            hint: None,
+            fix: None,
+        });
+        self.findings.last_mut().expect("finding pushed above")
+    }
+
+    fn ok(&mut self, area: &str, message: String) {
+        self.push(area, DoctorSeverity::Ok, message);
+    }
+
+    fn warning(&mut self, area: &str, message: String) -> &mut DoctorFinding {
+        self.push(area, DoctorSeverity::Warning, message)
+    }
+
+    fn error(&mut self, area: &str, message: String) -> &mut DoctorFinding {
+        self.push(area, DoctorSeverity::Error, message)
+    }
+}
+
+impl DoctorFinding {
+    fn hint(&mut self, hint: impl Into<String>) -> &mut Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    fn fix(&mut self, fix: Option<DoctorFix>) -> &mut Self {
+        self.fix = fix;
+        self
+    }
+}
+
+/// Result of applying one fix
+#[derive(Debug)]
+pub struct DoctorFixOutcome {
+    pub fix: DoctorFix,
+    pub result: Result<(), AppError>,
+}
+
+pub struct DoctorService;
+
+impl DoctorService {
+    /// Run every check. `state` is the result of opening the database; when that failed,
+    /// the file checks still run and the error is reported under `database`.
+    pub fn diagnose(state: Result<&AppState, &AppError>) -> DoctorReport {
+        let mut report = DoctorReport::default();
+        match state {
+            Ok(state) => Self::check_database(state, &mut report),
+            Err(e) => {
+                report
+                    .error("database", format!("cannot open the database: {e}"))
+                    .hint("Restore a backup with `cc-switch config restore`.");
+            }
+        }
+        let state = state.ok();
+
+        for app in APPS {
+            if !should_sync_live(&app) {
+                report.ok(
+                    app.as_str(),
+                    format!("{} is not set up on this machine, skipped", app.as_str()),
+                );
+                continue;
+            }
+            let current = state.and_then(|s| current_provider(s, &app));
+            let live_ok = Self::check_live_files(&app, current.as_ref(), &mut report);
+            if let (true, Some(state)) = (live_ok, state) {
+                Self::check_current_provider(state, &app, &mut report);
+            }
+        }
+
+        if let Some(state) = state {
+            Self::check_mcp(state, &mut report);
+        }
+        Self::check_skill_links(&mut report);
+        for app in APPS.iter().filter(|app| should_sync_live(app)) {
+            Self::check_env(app, &mut report);
+        }
+        report
+    }
+
+    /// Apply every fix in the report, continuing past failures.
+    pub fn fix(state: &AppState, report: &DoctorReport) -> Vec<DoctorFixOutcome> {
+        let mut outcomes = Vec::new();
+        let mut removed_links = false;
+        for fix in report.fixes() {
+            let result = match fix {
+                DoctorFix::ReapplyProvider {
+                    app,
+                    provider_id,
+                    broken,
+                } => quarantine(broken)
+                    .and_then(|_| ProviderService::switch(state, app.clone(), provider_id)),
+                DoctorFix::RemoveDanglingLink { path } => {
+                    removed_links = true;
+                    SkillService::remove_path(path)
+                }
+            };
+            outcomes.push(DoctorFixOutcome {
+                fix: fix.clone(),
+                result,
+            });
+        }
+        if removed_links {
+            // Recreate links for skills that are still installed and enabled.
+            if let Err(e) = SkillService::sync_all_enabled_best_effort() {
+                log::warn!("re-syncing skills after doctor --fix failed: {e}");
+            }
+        }
+        outcomes
+    }
+
+    fn check_database(state: &AppState, report: &mut DoctorReport) {
+        match state.db.schema_version() {
+            Ok(version) if version == SCHEMA_VERSION => {
+                report.ok("database", format!("schema version {version}"));
+            }
+            Ok(version) => {
+                report
+                    .error(
+                        "database",
+                        format!("schema version {version}, expected {SCHEMA_VERSION}"),
+                    )
+                    .hint("Upgrade cc-switch or restore a backup with `cc-switch config restore`.");
+            }
+            Err(e) => {
+                report.error("database", format!("cannot read the schema version: {e}"));
+            }
+        }
+
+        match state.db.integrity_problems() {
+            Ok(problems) if problems.is_empty() => {
+                report.ok("database", "integrity check passed".to_string());
+            }
+            Ok(problems) => {
+                report
+                    .error(
+                        "database",
+                        format!("integrity check failed: {}", problems.join("; ")),
+                    )
+                    .hint("Restore a backup with `cc-switch config restore`.");
+            }
+            Err(e) => {
+                report.error("database", format!("integrity check failed: {e}"));
+            }
+        }
+
+        if state.db.validate_state().is_err() {
+            report
+                .warning(
+                    "database",
+                    "no providers or MCP servers are stored yet".to_string(),
+                )
+                .hint("Add one with `cc-switch provider add` or import the live config.");
+        }
+    }
+
+    /// Returns whether every live file parsed.
+    fn check_live_files(
+        app: &AppType,
+        current: Option<&Provider>,
+        report: &mut DoctorReport,
+    ) -> bool {
+        let area = app.as_str();
+        let reapply = |broken: Vec<PathBuf>| {
+            current.map(|p| DoctorFix::ReapplyProvider {
+                app: app.clone(),
+                provider_id: p.id.clone(),
+                broken,
+            })
+        };
+        let no_fix_hint =
+            "Fix the file by hand, or set a current provider so doctor --fix can rewrite it.";
+
+        let files: Vec<(PathBuf, FileCheck)> = match app {
+            AppType::Claude => vec![(get_claude_settings_path(), check_json_object)],
+            AppType::Codex => vec![
+                (get_codex_config_path(), check_toml),
+                (get_codex_auth_path(), check_json_object),
+            ],
+            AppType::Gemini => vec![
+                (get_gemini_env_path(), check_env_file),
+                (get_gemini_settings_path(), check_json_object),
+            ],
+        };
+
+        let mut all_ok = true;
+        let mut missing = Vec::new();
+        for (path, check) in files {
+            if !path.exists() {
+                missing.push(path);
+                continue;
+            }
+            let result = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| check(&text));
+            match result {
+                Ok(()) => report.ok(area, format!("{} is valid", path.display())),
+                Err(e) => {
+                    all_ok = false;
+                    let fix = reapply(vec![path.clone()]);
+                    let finding = report.error(area, format!("{} is invalid: {e}", path.display()));
+                    finding.fix(fix.clone());
+                    if fix.is_none() {
+                        finding.hint(no_fix_hint);
+                    }
+                }
+            }
+        }
+
+        // Gemini keeps settings.json optional; the other files are written on every switch.
+        missing.retain(|p| *p != get_gemini_settings_path());
+        if !missing.is_empty() {
+            let names: Vec<String> = missing.iter().map(|p| p.display().to_string()).collect();
+            match reapply(Vec::new()) {
+                Some(fix) => {
+                    all_ok = false;
+                    report
+                        .warning(area, format!("missing live file(s): {}", names.join(", ")))
+                        .fix(Some(fix));
+                }
+                None => report.ok(
+                    area,
+                    format!("no current provider, not written yet: {}", names.join(", ")),
+                ),
+            }
+        }
+
+        if *app == AppType::Claude {
+            let path = get_claude_mcp_path();
+            if path.exists() {
+                let result = fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| check_json_object(&text));
+                match result {
+                    Ok(()) => report.ok(area, format!("{} is valid", path.display())),
+                    Err(e) => {
+                        report
+                            .error(area, format!("{} is invalid: {e}", path.display()))
+                            .hint("This file also holds Claude Code's own state; fix it by hand.");
+                    }
+                }
+            }
+        }
+        all_ok
+    }
+
+    fn check_current_provider(state: &AppState, app: &AppType, report: &mut DoctorReport) {
+        let area = app.as_str();
+        let drift = match ProviderService::live_drift(state, app.clone()) {
+            Ok(Some(drift)) => drift,
+            Ok(None) => return,
+            Err(e) => {
+                report.warning(area, format!("cannot compare the live config: {e}"));
+                return;
+            }
+        };
+        if drift.is_clean() {
+            report.ok(
+                area,
+                format!(
+                    "live config matches current provider '{}'",
+                    drift.provider_id
+                ),
+            );
+        } else {
+            let keys: Vec<&str> = drift.changes.iter().map(|c| c.key.as_str()).collect();
+            report
+                .warning(
+                    area,
+                    format!(
+                        "live config differs from current provider '{}': {}",
+                        drift.provider_id,
+                        keys.join(", ")
+                    ),
+                )
+                .hint(format!(
+                    "Review with `cc-switch --app {area} provider status` to adopt the live values, save them as a new provider, or re-apply the stored one."
+                ));
+        }
+    }
+
+    fn check_env(app: &AppType, report: &mut DoctorReport) {
+        let area = app.as_str();
+        match check_env_conflicts(area) {
+            Ok(conflicts) if conflicts.is_empty() => {
+                report.ok("env", format!("no variables override the {area} config"));
+            }
+            Ok(conflicts) => {
+                let mut vars: Vec<String> = conflicts
+                    .iter()
+                    .map(|c| format!("{} ({})", c.var_name, c.source_path))
+                    .collect();
+                vars.dedup();
+                report
+                    .warning(
+                        "env",
+                        format!(
+                            "environment variables override the {area} config: {}",
+                            vars.join(", ")
+                        ),
+                    )
+                    .hint(format!("See `cc-switch --app {area} env check`."));
+            }
+            Err(e) => {
+                report.warning("env", format!("cannot check environment variables: {e}"));
+            }
+        }
+    }
+
+    fn check_mcp(state: &AppState, report: &mut DoctorReport) {
+        let servers = match McpService::get_all_servers(state) {
+            Ok(servers) => servers,
+            Err(e) => {
+                report.error("mcp", format!("cannot read MCP servers: {e}"));
+                return;
+            }
+        };
+
+        let mut missing = BTreeMap::new();
+        for server in servers.values() {
+            if !APPS.iter().any(|app| server.apps.is_enabled_for(app)) {
+                continue;
+            }
+            let Some(command) = server.server.get("command").and_then(Value::as_str) else {
+                continue;
+            };
+            // Secret placeholders are only substituted when live configs are written.
+            if command.contains("${") {
+                continue;
+            }
+            if !validate_command_in_path(command).unwrap_or(false) {
+                missing.insert(server.id.clone(), command.to_string());
+            }
+        }
+        if missing.is_empty() {
+            report.ok("mcp", "all MCP server commands are on PATH".to_string());
+        }
+        for (id, command) in missing {
+            report
+                .warning(
+                    "mcp",
+                    format!("server '{id}': command '{command}' not found in PATH"),
+                )
+                .hint("Install it, or use an absolute path in the server spec.");
+        }
+
+        for app in APPS {
+            if !should_sync_live(&app) {
+                continue;
+            }
+            match McpService::diff_live(state, &app) {
+                Ok(drift) if drift.is_empty() => {}
+                Ok(drift) => {
+                    let ids: Vec<&str> = drift.iter().map(|d| d.id.as_str()).collect();
+                    report
+                        .warning(
+                            "mcp",
+                            format!(
+                                "{} live config differs from the store: {}",
+                                app.as_str(),
+                                ids.join(", ")
+                            ),
+                        )
+                        .hint(format!(
+                            "Review with `cc-switch --app {} mcp diff`, then `mcp reconcile`.",
+                            app.as_str()
+                        ));
+                }
+                Err(e) => {
+                    report.warning(
+                        "mcp",
+                        format!("cannot read the {} MCP config: {e}", app.as_str()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_skill_links(report: &mut DoctorReport) {
+        let mut roots = Vec::new();
+        for app in [AppType::Claude, AppType::Codex] {
+            if let Ok(dir) = SkillService::get_app_skills_dir(&app) {
+                roots.push(dir);
+            }
+        }
+        // Gemini extensions link supporting files one level further down.
+        roots.push(GeminiExtensionAdapter::extensions_dir());
+
+        let mut dangling = Vec::new();
+        for root in roots {
+            collect_dangling_links(&root, 2, &mut dangling);
+        }
+        if dangling.is_empty() {
+            report.ok("skills", "no dangling skill links".to_string());
+        }
+        for path in dangling {
+            report
+                .warning("skills", format!("dangling link {}", path.display()))
+                .fix(Some(DoctorFix::RemoveDanglingLink { path }));
+        }
+    }
+}
+
+fn current_provider(state: &AppState, app: &AppType) -> Option<Provider> {
+    let config = state.config.read().ok()?;
+    let manager = config.get_manager(app)?;
+    manager.providers.get(&manager.current).cloned()
+}
+
+fn check_json_object(text: &str) -> Result<(), String> {
+    match serde_json::from_str::<Value>(text) {
+        Ok(Value::Object(_)) => Ok(()),
+        Ok(_) => Err("expected a JSON object".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn check_toml(text: &str) -> Result<(), String> {
+    validate_config_toml(text).map_err(|e| e.to_string())
+}
+
+fn check_env_file(text: &str) -> Result<(), String> {
+    parse_env_file_strict(text)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn collect_dangling_links(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if SkillService::is_symlink(&path) {
+            if !path.exists() {
+                out.push(path);
+            }
+        } else if depth > 1 && path.is_dir() {
+            collect_dangling_links(&path, depth - 1, out);
+        }
+    }
+}
+
+fn quarantine(paths: &[PathBuf]) -> Result<(), AppError> {
+    let stamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+    for path in paths {
+        if !path.exists() {
+            continue;
+        }
+        let mut target = path.clone().into_os_string();
+        target.push(format!(".broken-{stamp}"));
+        fs::rename(path, &target).map_err(|e| AppError::io(path, e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,505 @@
+//! 配置变更历史与撤销
+//!
+//! `AppState::save()` 持久化前后各读取一次数据库快照，差异按实体写入只追加的
+//! `change_journal` 表；同一次保存的条目组成一个变更集。撤销时按变更前的值
+//! 调用各业务服务，从而同步重写 live 配置文件。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::mcp::McpService;
+use super::prompt::PromptService;
+use super::provider::ProviderService;
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::database::{Database, JournalEntry};
+use crate::error::AppError;
+use crate::store::AppState;
+
+/// 日志记录的实体类型（顺序即同一变更集内条目的写入顺序，撤销时逆序处理）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HistoryEntity {
+    Provider,
+    CurrentProvider,
+    Prompt,
+    CommonConfig,
+    McpServer,
+    /// 撤销标记：记录该变更集撤销了哪个变更集
+    Undo,
+}
+
+impl HistoryEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Provider => "provider",
+            Self::CurrentProvider => "current_provider",
+            Self::Prompt => "prompt",
+            Self::CommonConfig => "common_config",
+            Self::McpServer => "mcp_server",
+            Self::Undo => "undo",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "provider" => Self::Provider,
+            "current_provider" => Self::CurrentProvider,
+            "prompt" => Self::Prompt,
+            "common_config" => Self::CommonConfig,
+            "mcp_server" => Self::McpServer,
+            "undo" => Self::Undo,
+            _ => return None,
+        })
+    }
+}
+
+/// 一次保存产生的变更
+#[derive(Debug, Clone)]
+pub struct HistoryChangeSet {
+    pub change_set: i64,
+    pub created_at: i64,
+    pub actor: String,
+    pub source: String,
+    /// 实体变更（不含撤销标记）
+    pub entries: Vec<JournalEntry>,
+    /// 本变更集撤销的变更集
+    pub reverts: Option<i64>,
+    /// 是否已被撤销
+    pub undone: bool,
+}
+
+type EntityKey = (HistoryEntity, Option<String>, String);
+
+#[derive(Default)]
+struct JournalContext {
+    source: Option<String>,
+    /// 撤销期间固定变更集编号，使多次保存归入同一变更集
+    change_set: Option<i64>,
+}
+
+fn context() -> &'static Mutex<JournalContext> {
+    static CONTEXT: OnceLock<Mutex<JournalContext>> = OnceLock::new();
+    CONTEXT.get_or_init(|| Mutex::new(JournalContext::default()))
+}
+
+/// 撤销结束（包括出错）时解除变更集编号的固定
+struct PinnedChangeSet;
+
+impl PinnedChangeSet {
+    fn pin(change_set: i64) -> Self {
+        if let Ok(mut ctx) = context().lock() {
+            ctx.change_set = Some(change_set);
+        }
+        Self
+    }
+}
+
+impl Drop for PinnedChangeSet {
+    fn drop(&mut self) {
+        if let Ok(mut ctx) = context().lock() {
+            ctx.change_set = None;
+        }
+    }
+}
+
+fn actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// 按实体展开配置，用于比较与冲突检测
+fn snapshot(config: &MultiAppConfig) -> Result<BTreeMap<EntityKey, Value>, AppError> {
+    let mut map = BTreeMap::new();
+
+    for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        let app_key = Some(app.as_str().to_string());
+        if let Some(manager) = config.get_manager(&app) {
+            for (id, provider) in &manager.providers {
+                map.insert(
+                    (HistoryEntity::Provider, app_key.clone(), id.clone()),
+                    to_value(provider)?,
+                );
+            }
+            if !manager.current.is_empty() {
+                map.insert(
+                    (
+                        HistoryEntity::CurrentProvider,
+                        app_key.clone(),
+                        "current".to_string(),
+                    ),
+                    Value::String(manager.current.clone()),
+                );
+            }
+        }
+
+        let prompts = match app {
+            AppType::Claude => &config.prompts.claude.prompts,
+            AppType::Codex => &config.prompts.codex.prompts,
+            AppType::Gemini => &config.prompts.gemini.prompts,
+        };
+        for (id, prompt) in prompts {
+            map.insert(
+                (HistoryEntity::Prompt, app_key.clone(), id.clone()),
+                to_value(prompt)?,
+            );
+        }
+
+        if let Some(snippet) = config
+            .common_config_snippets
+            .get(&app)
+            .filter(|s| !s.trim().is_empty())
+        {
+            map.insert(
+                (HistoryEntity::CommonConfig, app_key, "common".to_string()),
+                Value::String(snippet.clone()),
+            );
+        }
+    }
+
+    for (id, server) in config.mcp.servers.iter().flatten() {
+        map.insert(
+            (HistoryEntity::McpServer, None, id.clone()),
+            to_value(server)?,
+        );
+    }
+
+    Ok(map)
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, AppError> {
+    serde_json::to_value(value).map_err(|source| AppError::JsonSerialize { source })
+}
+
+/// 比较保存前后的数据库快照并写入日志；由 `AppState::save()` 调用
+pub(crate) fn record_changes(
+    db: &Database,
+    before: &MultiAppConfig,
+    after: &MultiAppConfig,
+) -> Result<(), AppError> {
+    let before = snapshot(before)?;
+    let after = snapshot(after)?;
+    let keys: BTreeSet<&EntityKey> = before.keys().chain(after.keys()).collect();
+
+    let (source, pinned) = {
+        let ctx = context().lock().map_err(AppError::from)?;
+        (
+            ctx.source
+                .clone()
+                .unwrap_or_else(|| "cc-switch".to_string()),
+            ctx.change_set,
+        )
+    };
+    let created_at = chrono::Utc::now().timestamp();
+    let actor = actor();
+
+    let mut entries = Vec::new();
+    for key in keys {
+        let (old, new) = (before.get(key), after.get(key));
+        if old == new {
+            continue;
+        }
+        let action = match (old, new) {
+            (None, _) => "create",
+            (_, None) => "delete",
+            _ => "update",
+        };
+        let (entity, app, id) = key;
+        entries.push(JournalEntry {
+            id: 0,
+            change_set: 0,
+            created_at,
+            actor: actor.clone(),
+            source: source.clone(),
+            entity_type: entity.as_str().to_string(),
+            app_type: app.clone(),
+            entity_id: id.clone(),
+            action: action.to_string(),
+            before: old.cloned(),
+            after: new.cloned(),
+            reverts: None,
+        });
+    }
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let change_set = match pinned {
+        Some(change_set) => change_set,
+        None => db.next_journal_change_set()?,
+    };
+    for entry in &mut entries {
+        entry.change_set = change_set;
+    }
+    db.append_journal_entries(&entries)
+}
+
+/// 将闭包内的多次保存归入同一变更集，使一次多步操作可以一次撤销
+///
+/// 外层已固定变更集（如撤销期间）时沿用外层的编号。
+pub(crate) fn in_single_change_set<R>(
+    db: &Database,
+    f: impl FnOnce() -> Result<R, AppError>,
+) -> Result<R, AppError> {
+    let pinned = context()
+        .lock()
+        .map_err(AppError::from)?
+        .change_set
+        .is_some();
+    if pinned {
+        return f();
+    }
+    let _pinned = PinnedChangeSet::pin(db.next_journal_change_set()?);
+    f()
+}
+
+/// 变更历史业务
+pub struct HistoryService;
+
+impl HistoryService {
+    /// 设置后续变更记录的来源（通常为 CLI 命令）
+    pub fn set_source(source: impl Into<String>) {
+        if let Ok(mut ctx) = context().lock() {
+            ctx.source = Some(source.into());
+        }
+    }
+
+    /// 最近的变更集（最新的在前）
+    pub fn list(state: &AppState, limit: usize) -> Result<Vec<HistoryChangeSet>, AppError> {
+        let undone = state.db.get_reverted_change_sets()?;
+        let mut sets: Vec<HistoryChangeSet> = Vec::new();
+        for entry in state.db.get_journal_entries(limit)? {
+            if sets.last().map(|s| s.change_set) != Some(entry.change_set) {
+                sets.push(HistoryChangeSet {
+                    change_set: entry.change_set,
+                    created_at: entry.created_at,
+                    actor: entry.actor.clone(),
+                    source: entry.source.clone(),
+                    entries: Vec::new(),
+                    reverts: None,
+                    undone: undone.contains(&entry.change_set),
+                });
+            }
+            let set = sets.last_mut().expect("change set pushed above");
+            if entry.reverts.is_some() {
+                set.reverts = entry.reverts;
+            } else {
+                set.entries.push(entry);
+            }
+        }
+        Ok(sets)
+    }
+
+    /// 将被 `undo(count)` 撤销的变更集（最新的在前）
+    pub fn undo_candidates(
+        state: &AppState,
+        count: usize,
+    ) -> Result<Vec<HistoryChangeSet>, AppError> {
+        let ids = state.db.get_undoable_change_sets(count)?;
+        let mut sets = Vec::new();
+        for id in ids {
+            let entries = state.db.get_journal_change_set(id)?;
+            let Some(first) = entries.first() else {
+                continue;
+            };
+            sets.push(HistoryChangeSet {
+                change_set: id,
+                created_at: first.created_at,
+                actor: first.actor.clone(),
+                source: first.source.clone(),
+                entries,
+                reverts: None,
+                undone: false,
+            });
+        }
+        Ok(sets)
+    }
+
+    /// 依次撤销最近的 `count` 个变更集
+    ///
+    /// 实体在变更后又被修改过（例如直接恢复了 SQL 备份）时拒绝撤销，除非 `force`。
+    /// 每次撤销本身也记录为一个变更集，撤销过的变更集不会再次被选中。
+    pub fn undo(
+        state: &AppState,
+        count: usize,
+        force: bool,
+    ) -> Result<Vec<HistoryChangeSet>, AppError> {
+        let sets = Self::undo_candidates(state, count)?;
+        if sets.is_empty() {
+            return Err(AppError::InvalidInput("没有可撤销的变更".to_string()));
+        }
+
+        for set in &sets {
+            if !force {
+                let conflicts = Self::conflicts(state, &set.entries)?;
+                if !conflicts.is_empty() {
+                    return Err(AppError::InvalidInput(format!(
+                        "变更集 #{} 之后以下条目已被修改，可使用 --force 强制撤销: {}",
+                        set.change_set,
+                        conflicts.join(", ")
+                    )));
+                }
+            }
+
+            let revert_set = state.db.next_journal_change_set()?;
+            {
+                let _pinned = PinnedChangeSet::pin(revert_set);
+                Self::revert(state, &set.entries)?;
+            }
+            let source = context()
+                .lock()
+                .ok()
+                .and_then(|ctx| ctx.source.clone())
+                .unwrap_or_else(|| "cc-switch".to_string());
+            state.db.append_journal_entries(&[JournalEntry {
+                id: 0,
+                change_set: revert_set,
+                created_at: chrono::Utc::now().timestamp(),
+                actor: actor(),
+                source,
+                entity_type: HistoryEntity::Undo.as_str().to_string(),
+                app_type: None,
+                entity_id: set.change_set.to_string(),
+                action: "undo".to_string(),
+                before: None,
+                after: None,
+                reverts: Some(set.change_set),
+            }])?;
+        }
+
+        Ok(sets)
+    }
+
+    /// 当前值与变更后的值不一致的条目
+    fn conflicts(state: &AppState, entries: &[JournalEntry]) -> Result<Vec<String>, AppError> {
+        let current = snapshot(&crate::store::export_db_to_multi_app_config(&state.db)?)?;
+        Ok(entries
+            .iter()
+            .filter_map(|entry| {
+                let entity = HistoryEntity::parse(&entry.entity_type)?;
+                let key = (entity, entry.app_type.clone(), entry.entity_id.clone());
+                (current.get(&key) != entry.after.as_ref()).then(|| describe_entry(entry))
+            })
+            .collect())
+    }
+
+    fn revert(state: &AppState, entries: &[JournalEntry]) -> Result<(), AppError> {
+        let mut restored_prompt_apps = BTreeSet::new();
+        for entry in entries.iter().rev() {
+            let Some(entity) = HistoryEntity::parse(&entry.entity_type) else {
+                continue;
+            };
+            let app = entry
+                .app_type
+                .as_deref()
+                .map(AppType::from_str)
+                .transpose()?;
+            let require_app = || {
+                app.clone().ok_or_else(|| {
+                    AppError::Message(format!("日志条目 #{} 缺少应用类型", entry.id))
+                })
+            };
+
+            match entity {
+                HistoryEntity::Provider => {
+                    let app = require_app()?;
+                    let exists =
+                        ProviderService::list(state, app.clone())?.contains_key(&entry.entity_id);
+                    match (&entry.before, exists) {
+                        (None, true) => ProviderService::delete(state, app, &entry.entity_id)?,
+                        (None, false) => {}
+                        (Some(before), exists) => {
+                            let provider = parse_value(before)?;
+                            if exists {
+                                ProviderService::update(state, app, provider)?;
+                            } else {
+                                ProviderService::add(state, app, provider)?;
+                            }
+                        }
+                    }
+                }
+                HistoryEntity::CurrentProvider => {
+                    let app = require_app()?;
+                    match entry.before.as_ref().and_then(Value::as_str) {
+                        Some(id) => ProviderService::switch(state, app, id)?,
+                        None => {
+                            {
+                                let mut config = state.config.write()?;
+                                if let Some(manager) = config.get_manager_mut(&app) {
+                                    manager.current.clear();
+                                }
+                            }
+                            state.save()?;
+                        }
+                    }
+                }
+                HistoryEntity::Prompt => {
+                    let app = require_app()?;
+                    if !restored_prompt_apps.insert(app.as_str()) {
+                        continue;
+                    }
+                    // 同一应用的提示词一次性恢复，保证栈顺序与 live 文件一致
+                    let mut changes = Vec::new();
+                    for e in entries.iter().filter(|e| {
+                        e.entity_type == entry.entity_type && e.app_type == entry.app_type
+                    }) {
+                        let prompt = e.before.as_ref().map(parse_value).transpose()?;
+                        changes.push((e.entity_id.clone(), prompt));
+                    }
+                    PromptService::restore_prompts(state, app, changes)?;
+                }
+                HistoryEntity::CommonConfig => {
+                    let app = require_app()?;
+                    let snippet = entry
+                        .before
+                        .as_ref()
+                        .and_then(Value::as_str)
+                        .map(str::to_string);
+                    {
+                        let mut config = state.config.write()?;
+                        config.common_config_snippets.set(&app, snippet);
+                    }
+                    state.save()?;
+                    let current = ProviderService::current(state, app.clone())?;
+                    if !current.is_empty() {
+                        ProviderService::switch(state, app, &current)?;
+                    }
+                }
+                HistoryEntity::McpServer => match &entry.before {
+                    Some(before) => McpService::upsert_server(state, parse_value(before)?)?,
+                    None => {
+                        McpService::delete_server(state, &entry.entity_id)?;
+                    }
+                },
+                HistoryEntity::Undo => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, AppError> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| AppError::Message(format!("无法解析历史记录中的数据: {e}")))
+}
+
+/// 条目的简短描述，如 `provider claude/work`
+pub fn describe_entry(entry: &JournalEntry) -> String {
+    let entity = match HistoryEntity::parse(&entry.entity_type) {
+        Some(HistoryEntity::Provider) => "provider",
+        Some(HistoryEntity::CurrentProvider) => "current provider",
+        Some(HistoryEntity::Prompt) => "prompt",
+        Some(HistoryEntity::CommonConfig) => "common config",
+        Some(HistoryEntity::McpServer) => "mcp server",
+        Some(HistoryEntity::Undo) => "undo",
+        None => entry.entity_type.as_str(),
+    };
+    match (entry.entity_type.as_str(), &entry.app_type) {
+        ("current_provider" | "common_config", Some(app)) => format!("{entity} {app}"),
+        (_, Some(app)) => format!("{entity} {app}/{}", entry.entity_id),
+        (_, None) => format!("{entity} {}", entry.entity_id),
+    }
+}
@@ -0,0 +1,594 @@
+//! 按目录自动选择配置（`.cc-switch.toml` 与 `cc-switch hook`）
+//!
+//! 从当前目录向上查找最近的 `.cc-switch.toml`，或设置中登记的目录。shell 集成在切换目录时
+//! 调用 `cc-switch hook apply`：
+//! - 默认模式：通过常规服务切换供应商、MCP 服务器（及档案中的其它部分），离开目录时恢复进入前的选择；
+//! - 环境变量模式（`--env`）：不改动全局 live 文件，只输出需要导出的环境变量。
+//!
+//! 与 direnv 类似，`.cc-switch.toml` 须先通过 `cc-switch hook allow` 允许（按路径记录内容哈希），
+//! 文件内容变化后需重新允许，避免刚克隆的仓库在 `cd` 时静默切换供应商或导出密钥。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::profile::ProfileService;
+use super::provider::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::profile::{Profile, ProfileApp};
+use crate::settings::{self, DirectoryAppSelection, DirectorySelection};
+use crate::store::AppState;
+
+/// 项目目录中的配置文件名
+pub const PROJECT_FILE: &str = ".cc-switch.toml";
+
+/// 默认模式下当前生效的目录配置与恢复用的快照（settings 表）
+const HOOK_STATE_KEY: &str = "hook_state";
+
+/// 环境变量模式下记录当前生效配置与已导出变量名的 shell 变量
+pub const ENV_KEY_VAR: &str = "CC_SWITCH_HOOK_KEY";
+pub const ENV_VARS_VAR: &str = "CC_SWITCH_HOOK_VARS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HookShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// 目录配置的来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionSource {
+    /// `<dir>/.cc-switch.toml`
+    File(PathBuf),
+    /// 设置中的 `directorySelections`
+    Settings,
+}
+
+/// 对某个目录生效的配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSelection {
+    /// 声明配置的目录
+    pub dir: PathBuf,
+    pub source: SelectionSource,
+    pub selection: DirectorySelection,
+    /// 设置中的目录配置总是可信；配置文件须已允许且内容未变
+    pub trusted: bool,
+}
+
+impl ResolvedSelection {
+    /// 目录与配置内容共同决定的标识；配置文件修改后视为新的配置
+    pub fn key(&self) -> String {
+        let content = serde_json::to_string(&self.selection).unwrap_or_default();
+        let digest = hex::encode(Sha256::digest(content.as_bytes()));
+        format!("{}#{}", self.dir.display(), &digest[..12])
+    }
+
+    /// 用于提示的简短描述，如 `profile work; claude: relay`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(profile) = &self.selection.profile {
+            parts.push(format!("profile {profile}"));
+        }
+        for (app, selection) in app_selections(&self.selection) {
+            let mut items = Vec::new();
+            if let Some(provider) = &selection.provider {
+                items.push(provider.clone());
+            }
+            if let Some(mcp) = &selection.mcp {
+                items.push(format!("MCP [{}]", mcp.join(", ")));
+            }
+            if !items.is_empty() {
+                parts.push(format!("{}: {}", app.as_str(), items.join(", ")));
+            }
+        }
+        parts.join("; ")
+    }
+}
+
+/// 一次 `hook apply` 的结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookOutcome {
+    /// 已恢复其进入前状态的目录
+    pub restored: Option<String>,
+    /// 新应用的目录配置
+    pub applied: Option<ResolvedSelection>,
+    /// 未被允许而忽略的配置文件
+    pub blocked: Option<PathBuf>,
+}
+
+/// 环境变量模式的输出
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookEnv {
+    /// 当前目录配置的标识；与上一次相同时无需输出任何内容
+    pub key: Option<String>,
+    pub set: Vec<(String, String)>,
+    pub warnings: Vec<String>,
+    /// 未被允许而忽略的配置文件
+    pub blocked: Option<PathBuf>,
+}
+
+/// 默认模式下持久化的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HookState {
+    key: String,
+    dir: String,
+    /// 应用 -> 进入目录前的选择
+    restore: BTreeMap<String, ProfileApp>,
+}
+
+/// 目录配置业务
+pub struct HookService;
+
+impl HookService {
+    /// 从 `cwd` 向上查找最近的目录配置（同一目录中配置文件优先于设置）
+    pub fn find(cwd: &Path) -> Result<Option<ResolvedSelection>, AppError> {
+        let settings = settings::get_settings();
+        for dir in cwd.ancestors() {
+            let file = dir.join(PROJECT_FILE);
+            if file.is_file() {
+                let text = fs::read_to_string(&file).map_err(|e| AppError::io(&file, e))?;
+                let selection: DirectorySelection = toml::from_str(&text)
+                    .map_err(|e| AppError::Config(format!("解析 {} 失败: {e}", file.display())))?;
+                let trusted = settings
+                    .trusted_project_files
+                    .get(file.to_string_lossy().as_ref())
+                    .is_some_and(|digest| *digest == content_digest(&text));
+                return Ok(Some(ResolvedSelection {
+                    dir: dir.to_path_buf(),
+                    source: SelectionSource::File(file),
+                    selection,
+                    trusted,
+                }));
+            }
+            if let Some(selection) = settings
+                .directory_selections
+                .get(dir.to_string_lossy().as_ref())
+            {
+                return Ok(Some(ResolvedSelection {
+                    dir: dir.to_path_buf(),
+                    source: SelectionSource::Settings,
+                    selection: selection.clone(),
+                    trusted: true,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 允许（`allow = true`）或撤销对 `cwd` 起最近的 `.cc-switch.toml` 的信任，返回该文件路径
+    ///
+    /// 允许时记录当前内容的哈希；文件修改后需重新允许。
+    pub fn set_trusted(cwd: &Path, allow: bool) -> Result<PathBuf, AppError> {
+        let cwd = cwd.canonicalize().map_err(|e| AppError::io(cwd, e))?;
+        let file = cwd
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_FILE))
+            .find(|file| file.is_file())
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!("未找到 {PROJECT_FILE}: {}", cwd.display()))
+            })?;
+        let key = file.to_string_lossy().to_string();
+
+        let mut settings = settings::get_settings();
+        if allow {
+            let text = fs::read_to_string(&file).map_err(|e| AppError::io(&file, e))?;
+            toml::from_str::<DirectorySelection>(&text)
+                .map_err(|e| AppError::Config(format!("解析 {} 失败: {e}", file.display())))?;
+            settings
+                .trusted_project_files
+                .insert(key, content_digest(&text));
+        } else if settings.trusted_project_files.remove(&key).is_none() {
+            return Err(AppError::InvalidInput(format!(
+                "配置文件未被允许: {}",
+                file.display()
+            )));
+        }
+        settings::update_settings(settings)?;
+        Ok(file)
+    }
+
+    /// 拆分出未被允许的配置文件：返回 (可应用的配置, 被忽略的文件)
+    fn find_trusted(cwd: &Path) -> Result<(Option<ResolvedSelection>, Option<PathBuf>), AppError> {
+        match Self::find(cwd)? {
+            Some(found) if !found.trusted => {
+                let file = match found.source {
+                    SelectionSource::File(file) => file,
+                    SelectionSource::Settings => found.dir,
+                };
+                Ok((None, Some(file)))
+            }
+            found => Ok((found, None)),
+        }
+    }
+
+    /// 在设置中登记目录配置（None 时移除），返回规范化后的目录
+    pub fn set_mapping(
+        dir: &Path,
+        selection: Option<DirectorySelection>,
+    ) -> Result<PathBuf, AppError> {
+        let dir = dir.canonicalize().map_err(|e| AppError::io(dir, e))?;
+        let key = dir.to_string_lossy().to_string();
+        let mut settings = settings::get_settings();
+        let changed = match selection {
+            Some(selection) => {
+                settings.directory_selections.insert(key, selection);
+                true
+            }
+            None => settings.directory_selections.remove(&key).is_some(),
+        };
+        if !changed {
+            return Err(AppError::InvalidInput(format!(
+                "目录未登记配置: {}",
+                dir.display()
+            )));
+        }
+        settings::update_settings(settings)?;
+        Ok(dir)
+    }
+
+    /// 默认模式：离开目录时恢复进入前的选择，进入新目录时应用其配置
+    pub fn apply(state: &AppState, cwd: &Path) -> Result<HookOutcome, AppError> {
+        let (found, blocked) = Self::find_trusted(cwd)?;
+        let mut outcome = HookOutcome {
+            blocked,
+            ..Default::default()
+        };
+        let active = Self::load_state(state)?;
+        if active.as_ref().map(|a| &a.key) == found.as_ref().map(|f| f.key()).as_ref() {
+            return Ok(outcome);
+        }
+
+        if let Some(active) = active {
+            // 先清除状态：即使恢复失败也不会在每次提示符时重复尝试
+            state.db.set_setting(HOOK_STATE_KEY, "")?;
+            let targets = active
+                .restore
+                .into_iter()
+                .map(|(app, target)| Ok((app.parse::<AppType>()?, target)))
+                .collect::<Result<Vec<_>, AppError>>()?;
+            ProfileService::apply_targets(state, &targets)?;
+            outcome.restored = Some(active.dir);
+        }
+
+        if let Some(found) = found {
+            let targets = Self::targets(state, &found)?;
+            let restore = targets
+                .iter()
+                .map(|(app, _)| {
+                    Ok((
+                        app.as_str().to_string(),
+                        ProfileService::capture(state, app)?,
+                    ))
+                })
+                .collect::<Result<BTreeMap<_, _>, AppError>>()?;
+            ProfileService::apply_targets(state, &targets)?;
+
+            let hook_state = HookState {
+                key: found.key(),
+                dir: found.dir.display().to_string(),
+                restore,
+            };
+            let json = serde_json::to_string(&hook_state)
+                .map_err(|source| AppError::JsonSerialize { source })?;
+            state.db.set_setting(HOOK_STATE_KEY, &json)?;
+            outcome.applied = Some(found);
+        }
+        Ok(outcome)
+    }
+
+    /// 环境变量模式：计算目录配置中供应商对应的环境变量
+    ///
+    /// MCP 服务器、提示词与 Skills 只能写入配置文件，此模式下忽略并给出提示。
+    pub fn env(state: &AppState, cwd: &Path) -> Result<HookEnv, AppError> {
+        let (found, blocked) = Self::find_trusted(cwd)?;
+        let Some(found) = found else {
+            return Ok(HookEnv {
+                blocked,
+                ..Default::default()
+            });
+        };
+        // 校验引用的条目
+        Self::targets(state, &found)?;
+
+        let mut providers: BTreeMap<String, String> = BTreeMap::new();
+        let mut warnings = Vec::new();
+        if let Some(name) = &found.selection.profile {
+            let profile = ProfileService::get(state, name)?;
+            for (app, target) in &profile.apps {
+                if let Some(provider) = &target.provider {
+                    providers.insert(app.clone(), provider.clone());
+                }
+                if !target.mcp_servers.is_empty()
+                    || !target.prompts.is_empty()
+                    || !target.skills.is_empty()
+                {
+                    warnings.push(format!(
+                        "{app}: MCP servers, prompts and skills of profile '{name}' are not applied in env mode"
+                    ));
+                }
+            }
+        }
+        for (app, selection) in app_selections(&found.selection) {
+            if let Some(provider) = &selection.provider {
+                providers.insert(app.as_str().to_string(), provider.clone());
+            }
+            if selection.mcp.is_some() {
+                warnings.push(format!(
+                    "{}: MCP servers are not applied in env mode",
+                    app.as_str()
+                ));
+            }
+        }
+
+        let mut set = Vec::new();
+        for (app, id) in providers {
+            let app: AppType = app.parse()?;
+            let provider = ProviderService::list(state, app.clone())?
+                .shift_remove(&id)
+                .ok_or_else(|| AppError::InvalidInput(format!("供应商不存在: {id}")))?;
+            let settings = &provider.settings_config;
+            match app {
+                AppType::Claude | AppType::Gemini => {
+                    for (key, value) in settings
+                        .get("env")
+                        .and_then(Value::as_object)
+                        .into_iter()
+                        .flatten()
+                    {
+                        if let Some(value) = env_value(value) {
+                            set.push((key.clone(), value));
+                        }
+                    }
+                }
+                AppType::Codex => {
+                    if let Some(key) = settings
+                        .get("auth")
+                        .and_then(|auth| auth.get("OPENAI_API_KEY"))
+                        .and_then(env_value)
+                    {
+                        set.push(("OPENAI_API_KEY".to_string(), key));
+                    }
+                    warnings.push(format!(
+                        "codex: only OPENAI_API_KEY is exported; base_url and model of '{id}' live in config.toml"
+                    ));
+                }
+            }
+        }
+
+        // 标识同时覆盖导出的值：供应商内容变化后重新导出
+        let mut hasher = Sha256::new();
+        hasher.update(found.key().as_bytes());
+        for (name, value) in &set {
+            hasher.update([0]);
+            hasher.update(name.as_bytes());
+            hasher.update([0]);
+            hasher.update(value.as_bytes());
+        }
+        let digest = hex::encode(hasher.finalize());
+        Ok(HookEnv {
+            key: Some(format!("{}#{}", found.dir.display(), &digest[..12])),
+            set,
+            warnings,
+            blocked: None,
+        })
+    }
+
+    /// 供 shell 求值的环境变量语句：先清除上一次导出的变量，再导出新的变量
+    pub fn env_script(shell: HookShell, env: &HookEnv, previous: &[String]) -> String {
+        let mut lines: Vec<String> = previous
+            .iter()
+            .filter(|name| is_env_name(name))
+            .map(|name| unset_line(shell, name))
+            .collect();
+        let names: Vec<&str> = env
+            .set
+            .iter()
+            .filter(|(name, _)| is_env_name(name))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        for (name, value) in &env.set {
+            if is_env_name(name) {
+                lines.push(export_line(shell, name, value));
+            }
+        }
+        match &env.key {
+            Some(key) => {
+                lines.push(export_line(shell, ENV_KEY_VAR, key));
+                lines.push(export_line(shell, ENV_VARS_VAR, &names.join(" ")));
+            }
+            None => {
+                lines.push(unset_line(shell, ENV_KEY_VAR));
+                lines.push(unset_line(shell, ENV_VARS_VAR));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// shell 集成脚本（`eval "$(cc-switch hook init bash)"`）
+    pub fn init_script(shell: HookShell, exe: &Path, env_mode: bool) -> String {
+        let exe = exe.display().to_string();
+        let flags = if env_mode { " --env" } else { "" };
+        match shell {
+            HookShell::Bash => format!(
+                r#"_cc_switch_hook() {{
+  local previous_exit_status=$?
+  if [[ "$PWD" != "${{_CC_SWITCH_PWD:-}}" ]]; then
+    _CC_SWITCH_PWD="$PWD"
+    eval "$({exe} hook apply bash{flags})"
+  fi
+  return $previous_exit_status
+}}
+if [[ ";${{PROMPT_COMMAND[*]:-}};" != *";_cc_switch_hook;"* ]]; then
+  PROMPT_COMMAND="_cc_switch_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
+fi
+"#,
+                exe = sh_quote(&exe),
+            ),
+            HookShell::Zsh => format!(
+                r#"_cc_switch_hook() {{
+  eval "$({exe} hook apply zsh{flags})"
+}}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _cc_switch_hook
+_cc_switch_hook
+"#,
+                exe = sh_quote(&exe),
+            ),
+            HookShell::Fish => format!(
+                r#"function __cc_switch_hook --on-variable PWD
+    {exe} hook apply fish{flags} | source
+end
+__cc_switch_hook
+"#,
+                exe = fish_quote(&exe),
+            ),
+        }
+    }
+
+    /// 合并档案与各应用的覆盖项，得到要应用的完整选择（未指定的部分取当前值）
+    fn targets(
+        state: &AppState,
+        found: &ResolvedSelection,
+    ) -> Result<Vec<(AppType, ProfileApp)>, AppError> {
+        let mut apps: BTreeMap<String, ProfileApp> = BTreeMap::new();
+        if let Some(name) = &found.selection.profile {
+            apps = ProfileService::get(state, name)?.apps;
+        }
+        for (app, selection) in app_selections(&found.selection) {
+            let target = match apps.remove(app.as_str()) {
+                Some(target) => target,
+                None => ProfileService::capture(state, &app)?,
+            };
+            let target = ProfileApp {
+                provider: selection.provider.clone().or(target.provider),
+                mcp_servers: selection.mcp.clone().unwrap_or(target.mcp_servers),
+                ..target
+            };
+            apps.insert(app.as_str().to_string(), target);
+        }
+
+        let label = match &found.source {
+            SelectionSource::File(path) => path.display().to_string(),
+            SelectionSource::Settings => found.dir.display().to_string(),
+        };
+        ProfileService::resolve(
+            state,
+            &Profile {
+                name: label,
+                description: None,
+                apps,
+                created_at: 0,
+                updated_at: 0,
+            },
+        )
+    }
+
+    fn load_state(state: &AppState) -> Result<Option<HookState>, AppError> {
+        match state.db.get_setting(HOOK_STATE_KEY)? {
+            Some(json) if !json.is_empty() => Ok(serde_json::from_str(&json).ok()),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn app_selections(selection: &DirectorySelection) -> Vec<(AppType, &DirectoryAppSelection)> {
+    [
+        (AppType::Claude, &selection.claude),
+        (AppType::Codex, &selection.codex),
+        (AppType::Gemini, &selection.gemini),
+    ]
+    .into_iter()
+    .filter_map(|(app, selection)| selection.as_ref().map(|s| (app, s)))
+    .collect()
+}
+
+fn content_digest(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+fn env_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn is_env_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn export_line(shell: HookShell, name: &str, value: &str) -> String {
+    match shell {
+        HookShell::Bash | HookShell::Zsh => format!("export {name}={};", sh_quote(value)),
+        HookShell::Fish => format!("set -gx {name} {};", fish_quote(value)),
+    }
+}
+
+fn unset_line(shell: HookShell, name: &str) -> String {
+    match shell {
+        HookShell::Bash | HookShell::Zsh => format!("unset {name};"),
+        HookShell::Fish => format!("set -e {name};"),
+    }
+}
+
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_script_quotes_values_per_shell() {
+        let env = HookEnv {
+            key: Some("/repo#abc".to_string()),
+            set: vec![
+                ("TOKEN".to_string(), "it's".to_string()),
+                ("BAD NAME".to_string(), "x".to_string()),
+            ],
+            warnings: Vec::new(),
+            blocked: None,
+        };
+        assert_eq!(
+            HookService::env_script(HookShell::Bash, &env, &["OLD".to_string()]),
+            "unset OLD;\nexport TOKEN='it'\\''s';\nexport CC_SWITCH_HOOK_KEY='/repo#abc';\nexport CC_SWITCH_HOOK_VARS='TOKEN';"
+        );
+        assert_eq!(
+            HookService::env_script(HookShell::Fish, &env, &[]),
+            "set -gx TOKEN 'it\\'s';\nset -gx CC_SWITCH_HOOK_KEY '/repo#abc';\nset -gx CC_SWITCH_HOOK_VARS 'TOKEN';"
+        );
+        assert_eq!(
+            HookService::env_script(HookShell::Zsh, &HookEnv::default(), &["TOKEN".to_string()]),
+            "unset TOKEN;\nunset CC_SWITCH_HOOK_KEY;\nunset CC_SWITCH_HOOK_VARS;"
+        );
+    }
+
+    #[test]
+    fn project_file_rejects_unknown_keys() {
+        let selection: DirectorySelection = toml::from_str(
+            "profile = \"work\"\n[claude]\nprovider = \"relay\"\nmcp = [\"jira\"]\n",
+        )
+        .expect("parse");
+        assert_eq!(selection.profile.as_deref(), Some("work"));
+        assert_eq!(
+            selection.claude.and_then(|c| c.mcp),
+            Some(vec!["jira".to_string()])
+        );
+        assert!(toml::from_str::<DirectorySelection>("[opencode]\nprovider = \"x\"\n").is_err());
+    }
+}
@@ -1,10 +1,23 @@
 use std::collections::HashMap;
+use std::path::Path;
 
-use crate::app_config::{AppType, McpServer, MultiAppConfig};
+use crate::app_config::{AppType, McpApps, McpServer, McpToolFilters, MultiAppConfig};
+use crate::claude_mcp;
 use crate::error::AppError;
-use crate::mcp;
+use crate::mcp::{self, McpDriftKind, McpProjectScope, McpReconcileChoice, McpServerDrift};
+use crate::secrets::{self, SecretStore};
 use crate::store::AppState;
 
+/// 密钥使用情况（不包含密钥值）
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretUsage {
+    pub name: String,
+    /// 是否已在密钥存储中定义
+    pub defined: bool,
+    /// 引用该密钥的 MCP 服务器 ID（按字母序）
+    pub servers: Vec<String>,
+}
+
 /// MCP 相关业务逻辑（v3.7.0 统一结构）
 pub struct McpService;
 
@@ -28,12 +41,24 @@ impl McpService {
 
     /// 添加或更新 MCP 服务器
     pub fn upsert_server(state: &AppState, server: McpServer) -> Result<(), AppError> {
-        let (server_id, apps_to_remove) = {
+        if !server.tools.claude.allow.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Claude only supports denying MCP tools: permissions.allow would auto-approve them instead of restricting the server"
+                    .to_string(),
+            ));
+        }
+        let (server_id, apps_to_remove, previous_tools) = {
             let mut cfg = state.config.write()?;
 
             let servers = cfg.mcp.servers.get_or_insert_with(HashMap::new);
             let server_id = server.id.clone();
 
+            // 之前写入 Claude 权限规则所依据的过滤器（仅当此前已启用 Claude）
+            let previous_tools = servers
+                .get(&server_id)
+                .filter(|existing| existing.apps.claude)
+                .map(|existing| existing.tools.clone());
+
             let apps_to_remove = servers
                 .get(&server_id)
                 .map(|existing| {
@@ -49,14 +74,22 @@ impl McpService {
             // 插入或更新
             servers.insert(server_id.clone(), server.clone());
 
-            (server_id, apps_to_remove)
+            (server_id, apps_to_remove, previous_tools)
         };
 
         state.save()?;
 
         // 如果是更新：对“由启用变为禁用”的应用，清理对应 live 配置
+        let previous = previous_tools.clone().unwrap_or_default();
         for app in apps_to_remove {
-            Self::remove_server_from_app(state, &server_id, &app)?;
+            Self::remove_server_from_app(state, &server_id, &app, &previous)?;
+        }
+
+        // 过滤器变化时撤销旧的 Claude 权限规则，新规则在同步时写入
+        if let Some(previous) = previous_tools.filter(|_| server.apps.claude) {
+            if previous.claude != server.tools.claude {
+                claude_mcp::update_tool_permissions(&server_id, Some(&previous.claude), None)?;
+            }
         }
 
         // 同步到各个启用的应用
@@ -117,7 +150,7 @@ impl McpService {
             if enabled {
                 Self::sync_server_to_app(state, &server, &app)?;
             } else {
-                Self::remove_server_from_app(state, server_id, &app)?;
+                Self::remove_server_from_app(state, server_id, &app, &server.tools)?;
             }
         }
 
@@ -153,6 +186,7 @@ impl McpService {
         match app {
             AppType::Claude => {
                 mcp::sync_single_server_to_claude(cfg, &server.id, &server.server)?;
+                claude_mcp::update_tool_permissions(&server.id, None, Some(&server.tools.claude))?;
             }
             AppType::Codex => {
                 mcp::sync_single_server_to_codex(cfg, &server.id, &server.server)?;
@@ -172,14 +206,22 @@ impl McpService {
     ) -> Result<(), AppError> {
         // 从所有曾启用的应用中移除
         for app in server.apps.enabled_apps() {
-            Self::remove_server_from_app(state, id, &app)?;
+            Self::remove_server_from_app(state, id, &app, &server.tools)?;
         }
         Ok(())
     }
 
-    fn remove_server_from_app(_state: &AppState, id: &str, app: &AppType) -> Result<(), AppError> {
+    fn remove_server_from_app(
+        _state: &AppState,
+        id: &str,
+        app: &AppType,
+        tools: &McpToolFilters,
+    ) -> Result<(), AppError> {
         match app {
-            AppType::Claude => mcp::remove_server_from_claude(id)?,
+            AppType::Claude => {
+                mcp::remove_server_from_claude(id)?;
+                claude_mcp::update_tool_permissions(id, Some(&tools.claude), None)?;
+            }
             AppType::Codex => mcp::remove_server_from_codex(id)?,
             AppType::Gemini => mcp::remove_server_from_gemini(id)?,
         }
@@ -271,4 +313,316 @@ impl McpService {
         state.save()?;
         Ok(count)
     }
+
+    // ========================================================================
+    // 项目级 MCP
+    // ========================================================================
+
+    /// 将已存储的 MCP 服务器写入项目级配置（不改变全局启用状态）
+    pub fn enable_in_project(
+        state: &AppState,
+        id: &str,
+        app: &AppType,
+        project_dir: &Path,
+        scope: McpProjectScope,
+    ) -> Result<(), AppError> {
+        Self::ensure_project_dir(project_dir)?;
+        Self::ensure_project_scope(app, scope)?;
+        let server = Self::get_all_servers(state)?
+            .remove(id)
+            .ok_or_else(|| AppError::InvalidInput(format!("MCP server '{id}' not found")))?;
+
+        mcp::sync_single_server_to_project(
+            app,
+            project_dir,
+            scope,
+            id,
+            &server.server,
+            Some(server.tools.for_app(app)),
+        )
+    }
+
+    /// 从项目级配置中移除 MCP 服务器，返回是否存在
+    pub fn disable_in_project(
+        id: &str,
+        app: &AppType,
+        project_dir: &Path,
+        scope: McpProjectScope,
+    ) -> Result<bool, AppError> {
+        Self::ensure_project_dir(project_dir)?;
+        Self::ensure_project_scope(app, scope)?;
+        mcp::remove_server_from_project(app, project_dir, scope, id)
+    }
+
+    /// 读取项目级配置中的 MCP 服务器
+    pub fn get_project_servers(
+        app: &AppType,
+        project_dir: &Path,
+        scope: McpProjectScope,
+    ) -> Result<HashMap<String, serde_json::Value>, AppError> {
+        Self::ensure_project_dir(project_dir)?;
+        mcp::read_project_servers(app, project_dir, scope)
+    }
+
+    /// 从项目级配置导入 MCP 到统一结构
+    pub fn import_from_project(state: &AppState, project_dir: &Path) -> Result<usize, AppError> {
+        Self::ensure_project_dir(project_dir)?;
+        let mut cfg = state.config.write()?;
+        let count = mcp::import_from_project(&mut cfg, project_dir)?;
+        drop(cfg);
+        state.save()?;
+        Ok(count)
+    }
+
+    /// 本机项目配置（`--local`）只存在于 Claude 的 `~/.claude.json`
+    fn ensure_project_scope(app: &AppType, scope: McpProjectScope) -> Result<(), AppError> {
+        if scope == McpProjectScope::Local && *app != AppType::Claude {
+            return Err(AppError::localized(
+                "mcp.project_local_unsupported",
+                format!(
+                    "{} 不支持本机项目级 MCP 配置（--local 仅适用于 Claude）",
+                    app.as_str()
+                ),
+                format!(
+                    "{} does not support local project MCP configuration (--local is Claude only)",
+                    app.as_str()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn ensure_project_dir(project_dir: &Path) -> Result<(), AppError> {
+        if project_dir.is_dir() {
+            Ok(())
+        } else {
+            Err(AppError::InvalidInput(format!(
+                "Project directory '{}' does not exist",
+                project_dir.display()
+            )))
+        }
+    }
+
+    // ========================================================================
+    // Live 配置漂移检测与调和
+    // ========================================================================
+
+    /// 比较指定应用的 live 配置与统一存储（不写入任何文件）
+    pub fn diff_live(state: &AppState, app: &AppType) -> Result<Vec<McpServerDrift>, AppError> {
+        let cfg = state.config.read()?;
+        mcp::diff_live_servers(&cfg, app)
+    }
+
+    /// 对单个漂移的服务器选择保留 live 或存储中的版本
+    ///
+    /// - 保留 live：将 live 侧的改动写回统一存储（新增的服务器会被导入，手动删除则禁用该应用），
+    ///   随后照常同步到其它已启用的应用
+    /// - 保留存储：按统一存储重写该应用的 live 配置（live 独有的服务器会被移除）
+    ///
+    /// 服务器没有漂移时返回 false。
+    pub fn reconcile(
+        state: &AppState,
+        app: &AppType,
+        id: &str,
+        choice: McpReconcileChoice,
+    ) -> Result<bool, AppError> {
+        let Some(drift) = Self::diff_live(state, app)?
+            .into_iter()
+            .find(|d| d.id == id)
+        else {
+            return Ok(false);
+        };
+
+        let existing = Self::get_all_servers(state)?.remove(id);
+
+        match (choice, drift.kind) {
+            (McpReconcileChoice::Stored, McpDriftKind::Added) => {
+                let tools = existing.map(|s| s.tools).unwrap_or_default();
+                Self::remove_server_from_app(state, id, app, &tools)?;
+            }
+            (McpReconcileChoice::Stored, _) => {
+                let server = existing
+                    .ok_or_else(|| AppError::Message(format!("MCP server '{id}' not found")))?;
+                Self::sync_server_to_app(state, &server, app)?;
+            }
+            (McpReconcileChoice::Live, McpDriftKind::Removed) => {
+                Self::toggle_app(state, id, app.clone(), false)?;
+            }
+            (McpReconcileChoice::Live, _) => {
+                // 之前写入 Claude 权限规则所依据的过滤器
+                let previous_tools = existing
+                    .as_ref()
+                    .filter(|s| s.apps.is_enabled_for(app))
+                    .map(|s| s.tools.clone());
+                let mut server = match existing {
+                    Some(mut server) => {
+                        // live 中的工具白/黑名单回写到过滤器，而不是服务器规范
+                        let changes =
+                            mcp::absorb_tool_filter_changes(app, &mut server, &drift.fields);
+                        mcp::apply_field_changes(&mut server.server, &changes);
+                        server.apps.set_enabled_for(app, true);
+                        server
+                    }
+                    None => {
+                        let mut apps = McpApps::default();
+                        apps.set_enabled_for(app, true);
+                        McpServer {
+                            id: id.to_string(),
+                            name: id.to_string(),
+                            server: drift.live.unwrap_or_default(),
+                            apps,
+                            description: None,
+                            homepage: None,
+                            docs: None,
+                            tags: Vec::new(),
+                            codex_toml: None,
+                            tools: Default::default(),
+                        }
+                    }
+                };
+                // Codex：同时采用 live 中的原始片段，保留 JSON 无法表达的字段
+                if *app == AppType::Codex {
+                    server.codex_toml = mcp::read_codex_live_fragment(id)?;
+                }
+
+                // 只写回当前应用：其它应用 live 中尚未处理的修改保持不变，仍显示为差异
+                {
+                    let mut cfg = state.config.write()?;
+                    cfg.mcp
+                        .servers
+                        .get_or_insert_with(HashMap::new)
+                        .insert(id.to_string(), server.clone());
+                }
+                state.save()?;
+                if let Some(previous) = previous_tools.filter(|_| *app == AppType::Claude) {
+                    if previous.claude != server.tools.claude {
+                        claude_mcp::update_tool_permissions(id, Some(&previous.claude), None)?;
+                    }
+                }
+                Self::sync_server_to_app(state, &server, app)?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    // ========================================================================
+    // 密钥占位符（${secret:NAME}）
+    // ========================================================================
+
+    /// 查找已启用服务器中引用但未定义的密钥，返回 (服务器 ID, 密钥名称列表)
+    ///
+    /// 这些占位符在同步时会原样写入 live 配置，调用方应在写入前后提示用户。
+    pub fn unresolved_secrets(
+        state: &AppState,
+        only_id: Option<&str>,
+    ) -> Result<Vec<(String, Vec<String>)>, AppError> {
+        let store = SecretStore::load()?;
+        let servers = Self::get_all_servers(state)?;
+
+        let mut result: Vec<(String, Vec<String>)> = servers
+            .values()
+            .filter(|server| only_id.is_none_or(|id| server.id == id))
+            .filter(|server| only_id.is_some() || !server.apps.is_empty())
+            .filter_map(|server| {
+                let missing = Self::missing_in(&server.server, &store);
+                (!missing.is_empty()).then(|| (server.id.clone(), missing))
+            })
+            .collect();
+        result.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(result)
+    }
+
+    /// 服务器规范中引用但未定义的密钥（用于尚未保存的规范）
+    pub fn missing_secrets(spec: &serde_json::Value) -> Result<Vec<String>, AppError> {
+        Ok(Self::missing_in(spec, &SecretStore::load()?))
+    }
+
+    fn missing_in(spec: &serde_json::Value, store: &SecretStore) -> Vec<String> {
+        secrets::find_placeholders(spec)
+            .into_iter()
+            .filter(|name| !store.contains(name))
+            .collect()
+    }
+
+    /// 引用指定密钥的已启用服务器 ID（按字母序）
+    pub fn secret_users(state: &AppState, name: &str) -> Result<Vec<String>, AppError> {
+        let mut ids: Vec<String> = Self::get_all_servers(state)?
+            .into_values()
+            .filter(|server| {
+                !server.apps.is_empty()
+                    && secrets::find_placeholders(&server.server).contains(&name.to_string())
+            })
+            .map(|server| server.id)
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// 列出所有已定义或被引用的密钥（不返回密钥值）
+    pub fn list_secrets(state: &AppState) -> Result<Vec<SecretUsage>, AppError> {
+        let store = SecretStore::load()?;
+        let servers = Self::get_all_servers(state)?;
+
+        let mut usages: Vec<SecretUsage> = store
+            .names()
+            .into_iter()
+            .map(|name| SecretUsage {
+                name,
+                defined: true,
+                servers: Vec::new(),
+            })
+            .collect();
+
+        for server in servers.values() {
+            for name in secrets::find_placeholders(&server.server) {
+                match usages.iter_mut().find(|u| u.name == name) {
+                    Some(usage) => usage.servers.push(server.id.clone()),
+                    None => usages.push(SecretUsage {
+                        name,
+                        defined: false,
+                        servers: vec![server.id.clone()],
+                    }),
+                }
+            }
+        }
+
+        for usage in usages.iter_mut() {
+            usage.servers.sort();
+        }
+        usages.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(usages)
+    }
+
+    /// 设置密钥，并重新同步引用该密钥的已启用服务器，返回重新同步的服务器数量
+    pub fn set_secret(state: &AppState, name: &str, value: &str) -> Result<usize, AppError> {
+        let mut store = SecretStore::load()?;
+        store.set(name, value)?;
+        store.save()?;
+        Self::resync_secret_users(state, name)
+    }
+
+    /// 删除密钥，返回是否存在
+    ///
+    /// 引用该密钥的已启用服务器会被重新同步，使 live 配置不再保留旧值。
+    pub fn remove_secret(state: &AppState, name: &str) -> Result<bool, AppError> {
+        let mut store = SecretStore::load()?;
+        if !store.remove(name) {
+            return Ok(false);
+        }
+        store.save()?;
+        Self::resync_secret_users(state, name)?;
+        Ok(true)
+    }
+
+    fn resync_secret_users(state: &AppState, name: &str) -> Result<usize, AppError> {
+        let users = Self::secret_users(state, name)?;
+        let servers = Self::get_all_servers(state)?;
+        for id in &users {
+            if let Some(server) = servers.get(id) {
+                Self::sync_server_to_apps(state, server)?;
+            }
+        }
+        Ok(users.len())
+    }
 }
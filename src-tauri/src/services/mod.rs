@@ -1,16 +1,50 @@
+pub mod bundle;
 pub mod config;
+pub mod doctor;
 pub mod env_checker;
 pub mod env_manager;
+pub mod history;
+pub mod hook;
 pub mod local_env_check;
 pub mod mcp;
+pub mod profile;
 pub mod prompt;
 pub mod provider;
+pub mod remote_backup;
 pub mod skill;
+pub mod skill_adapter;
+pub mod skill_lint;
+pub mod skill_repo_cache;
+pub mod skill_source;
 pub mod speedtest;
+pub mod sync;
+pub mod watch;
 
+pub use bundle::{
+    BundleAction, BundleChange, BundleImportReport, BundleItemKind, BundleSelection, BundleService,
+    ConfigBundle, ConflictStrategy,
+};
 pub use config::ConfigService;
+pub use doctor::{
+    DoctorFinding, DoctorFix, DoctorFixOutcome, DoctorReport, DoctorService, DoctorSeverity,
+};
+pub use history::{HistoryChangeSet, HistoryEntity, HistoryService};
+pub use hook::{HookEnv, HookOutcome, HookService, HookShell, ResolvedSelection, SelectionSource};
 pub use mcp::McpService;
+pub use profile::ProfileService;
 pub use prompt::PromptService;
-pub use provider::ProviderService;
-pub use skill::SkillService;
+pub use provider::{LiveDrift, LiveKeyChange, ProviderService};
+pub use remote_backup::{RemoteBackupService, RemoteSnapshot, RemoteUploadOutcome};
+pub use skill::{
+    NewSkillOptions, SkillFileChange, SkillFileChangeKind, SkillLock, SkillLockEntry,
+    SkillLockOutcome, SkillLockResult, SkillService, SkillUpdateOutcome, SkillUpdateResult,
+    SkillVersionCheck,
+};
 pub use speedtest::{EndpointLatency, SpeedtestService};
+pub use sync::{
+    ConflictResolution, SecretsMode, SyncChange, SyncChangeKind, SyncConfig, SyncReport,
+    SyncService, SyncTarget,
+};
+pub use watch::{
+    LiveWatcher, WatchEvent, WatchOptions, WatchOutcome, WatchPolicy, WatchService, WatchSubject,
+};
@@ -0,0 +1,233 @@
+//! 配置档案
+//!
+//! 档案按应用记录当前供应商、启用的 MCP 服务器、提示词栈与启用的 Skills。
+//! 应用档案前先校验引用的条目全部存在；任一步骤失败时按应用前的快照恢复，
+//! 所有保存归入同一变更集，可通过 `cc-switch undo` 一次撤销。
+
+use std::str::FromStr;
+
+use super::history::in_single_change_set;
+use super::mcp::McpService;
+use super::prompt::PromptService;
+use super::provider::ProviderService;
+use super::skill::SkillService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::profile::{Profile, ProfileApp};
+use crate::store::AppState;
+
+/// 配置档案业务
+pub struct ProfileService;
+
+impl ProfileService {
+    /// 获取所有档案（按名称排序）
+    pub fn list(state: &AppState) -> Result<Vec<Profile>, AppError> {
+        state.db.get_profiles()
+    }
+
+    pub fn get(state: &AppState, name: &str) -> Result<Profile, AppError> {
+        state
+            .db
+            .get_profile(name)?
+            .ok_or_else(|| AppError::InvalidInput(format!("配置档案不存在: {name}")))
+    }
+
+    /// 最近一次应用的档案名称
+    pub fn active(state: &AppState) -> Result<Option<String>, AppError> {
+        state.db.get_active_profile()
+    }
+
+    /// 读取应用当前的选择
+    pub fn capture(state: &AppState, app: &AppType) -> Result<ProfileApp, AppError> {
+        let provider = ProviderService::current(state, app.clone())?;
+
+        let mut mcp_servers: Vec<String> = McpService::get_all_servers(state)?
+            .into_values()
+            .filter(|s| s.apps.is_enabled_for(app))
+            .map(|s| s.id)
+            .collect();
+        mcp_servers.sort();
+
+        let prompts = PromptService::active_stack(state, app.clone())?
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+
+        let mut skills: Vec<String> = SkillService::list_installed()?
+            .into_iter()
+            .filter(|s| s.apps.is_enabled_for(app))
+            .map(|s| s.directory)
+            .collect();
+        skills.sort();
+
+        Ok(ProfileApp {
+            provider: (!provider.is_empty()).then_some(provider),
+            mcp_servers,
+            prompts,
+            skills,
+        })
+    }
+
+    /// 将指定应用的当前选择保存为档案；同名档案存在时需要 `overwrite`
+    pub fn save_current(
+        state: &AppState,
+        name: &str,
+        description: Option<String>,
+        apps: &[AppType],
+        overwrite: bool,
+    ) -> Result<Profile, AppError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput("配置档案名称不能为空".to_string()));
+        }
+        let existing = state.db.get_profile(name)?;
+        if existing.is_some() && !overwrite {
+            return Err(AppError::InvalidInput(format!("配置档案已存在: {name}")));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut profile = Profile {
+            name: name.to_string(),
+            description: description.or_else(|| existing.as_ref()?.description.clone()),
+            apps: Default::default(),
+            created_at: existing.as_ref().map_or(now, |p| p.created_at),
+            updated_at: now,
+        };
+        for app in apps {
+            profile
+                .apps
+                .insert(app.as_str().to_string(), Self::capture(state, app)?);
+        }
+        state.db.save_profile(&profile)?;
+        Ok(profile)
+    }
+
+    /// 删除档案，返回是否存在
+    pub fn delete(state: &AppState, name: &str) -> Result<bool, AppError> {
+        let deleted = state.db.delete_profile(name)?;
+        if deleted && Self::active(state)?.as_deref() == Some(name) {
+            state.db.set_active_profile(None)?;
+        }
+        Ok(deleted)
+    }
+
+    /// 应用档案：依次切换供应商、MCP 服务器、提示词栈与 Skills
+    ///
+    /// 失败时按应用前的快照恢复已改动的部分，并返回原始错误。
+    pub fn use_profile(state: &AppState, name: &str) -> Result<Profile, AppError> {
+        let profile = Self::get(state, name)?;
+        let targets = Self::resolve(state, &profile)?;
+        Self::apply_targets(state, &targets).map_err(|e| {
+            AppError::Message(format!("应用配置档案 {} 失败，已回滚: {e}", profile.name))
+        })?;
+
+        state.db.set_active_profile(Some(&profile.name))?;
+        Ok(profile)
+    }
+
+    /// 按应用调整为目标选择，所有保存归入同一变更集
+    ///
+    /// 任一步骤失败时按调整前的快照恢复（尽力而为），并返回原始错误。
+    pub(crate) fn apply_targets(
+        state: &AppState,
+        targets: &[(AppType, ProfileApp)],
+    ) -> Result<(), AppError> {
+        let snapshot = targets
+            .iter()
+            .map(|(app, _)| Ok((app.clone(), Self::capture(state, app)?)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        in_single_change_set(&state.db, || {
+            for (app, target) in targets {
+                if let Err(e) = Self::apply_app(state, app, target) {
+                    for (app, before) in &snapshot {
+                        if let Err(err) = Self::apply_app(state, app, before) {
+                            log::warn!("回滚时恢复 {} 失败: {err}", app.as_str());
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// 解析档案中的应用并校验引用的条目全部存在（应用前不做任何改动）
+    pub(crate) fn resolve(
+        state: &AppState,
+        profile: &Profile,
+    ) -> Result<Vec<(AppType, ProfileApp)>, AppError> {
+        let servers = McpService::get_all_servers(state)?;
+        let skills = SkillService::list_installed()?;
+
+        let mut targets = Vec::new();
+        for (key, target) in &profile.apps {
+            let app = AppType::from_str(key)?;
+            let missing = |kind: &str, id: &str| {
+                AppError::InvalidInput(format!(
+                    "配置档案 {} 引用的{kind}不存在: {} {id}",
+                    profile.name,
+                    app.as_str()
+                ))
+            };
+
+            if let Some(id) = &target.provider {
+                if !ProviderService::list(state, app.clone())?.contains_key(id) {
+                    return Err(missing("供应商", id));
+                }
+            }
+            for id in &target.mcp_servers {
+                if !servers.contains_key(id) {
+                    return Err(missing("MCP 服务器", id));
+                }
+            }
+            let prompts = PromptService::get_prompts(state, app.clone())?;
+            for id in &target.prompts {
+                if !prompts.contains_key(id) {
+                    return Err(missing("提示词", id));
+                }
+            }
+            for dir in &target.skills {
+                if !skills.iter().any(|s| &s.directory == dir) {
+                    return Err(missing("Skill", dir));
+                }
+            }
+            targets.push((app, target.clone()));
+        }
+        Ok(targets)
+    }
+
+    /// 将单个应用调整为目标选择，只改动与当前不同的部分
+    fn apply_app(state: &AppState, app: &AppType, target: &ProfileApp) -> Result<(), AppError> {
+        if let Some(id) = &target.provider {
+            if ProviderService::current(state, app.clone())? != *id {
+                ProviderService::switch(state, app.clone(), id)?;
+            }
+        }
+
+        let mut servers: Vec<_> = McpService::get_all_servers(state)?.into_values().collect();
+        servers.sort_by(|a, b| a.id.cmp(&b.id));
+        for server in servers {
+            let wanted = target.mcp_servers.contains(&server.id);
+            if server.apps.is_enabled_for(app) != wanted {
+                McpService::toggle_app(state, &server.id, app.clone(), wanted)?;
+            }
+        }
+
+        let stack: Vec<String> = PromptService::active_stack(state, app.clone())?
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        if stack != target.prompts {
+            PromptService::set_stack(state, app.clone(), &target.prompts)?;
+        }
+
+        for skill in SkillService::list_installed()? {
+            let wanted = target.skills.contains(&skill.directory);
+            if skill.apps.is_enabled_for(app) != wanted {
+                SkillService::toggle_app(&skill.directory, app, wanted)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::app_config::AppType;
+use crate::app_config::{AppType, MultiAppConfig};
 use crate::config::write_text_file;
 use crate::error::AppError;
-use crate::prompt::Prompt;
-use crate::prompt_files::prompt_file_path;
+use crate::prompt::{Prompt, PromptProject};
+use crate::prompt_files::{
+    project_prompt_file_path, prompt_file_path, ManagedSection, PromptFileLayout,
+};
+use crate::prompt_template::{self, RenderedPrompt, TemplateContext};
 use crate::store::AppState;
 
 pub struct PromptService;
@@ -29,41 +33,83 @@ impl PromptService {
         id: &str,
         prompt: Prompt,
     ) -> Result<(), AppError> {
-        // 检查是否为已启用的提示词
-        let is_enabled = prompt.enabled;
+        // 已启用的提示词（或其包含的提示词）发生变化时，同步更新到对应的文件
+        Self::apply_stack(state, &app, |prompts| {
+            prompts.insert(id.to_string(), prompt);
+            Ok(())
+        })?;
+        Self::refresh_projects(state, &app);
+        Ok(())
+    }
 
-        let mut cfg = state.config.write()?;
-        let prompts = match app {
-            AppType::Claude => &mut cfg.prompts.claude.prompts,
-            AppType::Codex => &mut cfg.prompts.codex.prompts,
-            AppType::Gemini => &mut cfg.prompts.gemini.prompts,
-        };
-        prompts.insert(id.to_string(), prompt.clone());
-        drop(cfg);
-        state.save()?;
+    /// 将若干提示词恢复为给定状态（`None` 表示删除），并按恢复后的栈重写 live 文件
+    pub fn restore_prompts(
+        state: &AppState,
+        app: AppType,
+        changes: Vec<(String, Option<Prompt>)>,
+    ) -> Result<(), AppError> {
+        Self::apply_stack(state, &app, |prompts| {
+            for (id, prompt) in changes {
+                match prompt {
+                    Some(prompt) => {
+                        prompts.insert(id, prompt);
+                    }
+                    None => {
+                        prompts.remove(&id);
+                    }
+                }
+            }
+            Ok(())
+        })?;
+        Self::refresh_projects(state, &app);
+        Ok(())
+    }
 
-        // 如果是已启用的提示词，同步更新到对应的文件
-        if is_enabled {
-            let target_path = prompt_file_path(&app)?;
-            write_text_file(&target_path, &prompt.content)?;
-        }
+    /// 预览提示词在指定应用下的渲染结果（不写入文件）
+    pub fn render_prompt(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+    ) -> Result<RenderedPrompt, AppError> {
+        let prompts = Self::get_prompts(state, app.clone())?;
+        prompt_template::render_stored(id, &prompts, &TemplateContext::for_app(&app))
+    }
 
-        Ok(())
+    fn render_logged(
+        id: &str,
+        prompts: &HashMap<String, Prompt>,
+        ctx: &TemplateContext,
+    ) -> Result<RenderedPrompt, AppError> {
+        let rendered = prompt_template::render_stored(id, prompts, ctx)?;
+        if !rendered.unresolved.is_empty() {
+            log::warn!(
+                "提示词 {id} 包含未定义的变量: {}",
+                rendered.unresolved.join(", ")
+            );
+        }
+        Ok(rendered)
     }
 
     pub fn delete_prompt(state: &AppState, app: AppType, id: &str) -> Result<(), AppError> {
         let mut cfg = state.config.write()?;
-        let prompts = match app {
-            AppType::Claude => &mut cfg.prompts.claude.prompts,
-            AppType::Codex => &mut cfg.prompts.codex.prompts,
-            AppType::Gemini => &mut cfg.prompts.gemini.prompts,
-        };
+        let prompts = Self::prompts_mut(&mut cfg, &app);
 
         if let Some(prompt) = prompts.get(id) {
             if prompt.enabled {
                 return Err(AppError::InvalidInput("无法删除已启用的提示词".to_string()));
             }
         }
+        if let Some(project) = state
+            .db
+            .get_prompt_projects(Some(app.as_str()))?
+            .into_iter()
+            .find(|p| p.prompt_id == id)
+        {
+            return Err(AppError::InvalidInput(format!(
+                "无法删除项目正在使用的提示词（{}）",
+                project.project_path
+            )));
+        }
 
         prompts.remove(id);
         drop(cfg);
@@ -71,122 +117,483 @@ impl PromptService {
         Ok(())
     }
 
+    /// 当前应用的提示词栈（按写入文件的顺序）
+    pub fn active_stack(state: &AppState, app: AppType) -> Result<Vec<Prompt>, AppError> {
+        let prompts = Self::get_prompts(state, app)?;
+        Ok(Self::stack_ids(&prompts)
+            .into_iter()
+            .filter_map(|id| prompts.get(&id).cloned())
+            .collect())
+    }
+
+    /// 将提示词加入栈顶（文件末尾）；已在栈中时保持原位置
     pub fn enable_prompt(state: &AppState, app: AppType, id: &str) -> Result<(), AppError> {
-        // 回填当前 live 文件内容到已启用的提示词，或创建备份
-        let target_path = prompt_file_path(&app)?;
-        if target_path.exists() {
-            if let Ok(live_content) = std::fs::read_to_string(&target_path) {
-                if !live_content.trim().is_empty() {
-                    let mut cfg = state.config.write()?;
-                    let prompts = match app {
-                        AppType::Claude => &mut cfg.prompts.claude.prompts,
-                        AppType::Codex => &mut cfg.prompts.codex.prompts,
-                        AppType::Gemini => &mut cfg.prompts.gemini.prompts,
-                    };
-
-                    // 尝试回填到当前已启用的提示词
-                    if let Some((enabled_id, enabled_prompt)) = prompts
-                        .iter_mut()
-                        .find(|(_, p)| p.enabled)
-                        .map(|(id, p)| (id.clone(), p))
-                    {
-                        let timestamp = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs() as i64;
-                        enabled_prompt.content = live_content.clone();
-                        enabled_prompt.updated_at = Some(timestamp);
-                        log::info!("回填 live 提示词内容到已启用项: {enabled_id}");
-                        drop(cfg); // 释放锁后保存，避免死锁
-                        state.save()?; // 第一次保存：回填后立即持久化
-                    } else {
-                        // 没有已启用的提示词，则创建一次备份（避免重复备份）
-                        let content_exists = prompts
-                            .values()
-                            .any(|p| p.content.trim() == live_content.trim());
-                        if !content_exists {
-                            let timestamp = std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs() as i64;
-                            let backup_id = format!("backup-{timestamp}");
-                            let backup_prompt = Prompt {
-                                id: backup_id.clone(),
-                                name: format!(
-                                    "原始提示词 {}",
-                                    chrono::Local::now().format("%Y-%m-%d %H:%M")
-                                ),
-                                content: live_content,
-                                description: Some("自动备份的原始提示词".to_string()),
-                                enabled: false,
-                                created_at: Some(timestamp),
-                                updated_at: Some(timestamp),
-                            };
-                            prompts.insert(backup_id.clone(), backup_prompt);
-                            log::info!("回填 live 提示词内容，创建备份: {backup_id}");
-                            drop(cfg); // 释放锁后保存
-                            state.save()?; // 第一次保存：回填后立即持久化
-                        } else {
-                            // 即使内容已存在，也无需重复备份；但不需要保存任何更改
-                            drop(cfg);
-                        }
-                    }
-                }
+        Self::apply_stack(state, &app, |prompts| {
+            Self::ensure_exists(prompts, id)?;
+            let mut stack = Self::stack_ids(prompts);
+            if !stack.iter().any(|s| s == id) {
+                stack.push(id.to_string());
             }
+            Self::assign_stack(prompts, &stack);
+            Ok(())
+        })
+    }
+
+    /// 将提示词插入栈中的指定位置（从 1 开始，超出范围时放在末尾）；已在栈中时移动到该位置
+    pub fn add_layer(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+        position: Option<usize>,
+    ) -> Result<(), AppError> {
+        Self::apply_stack(state, &app, |prompts| {
+            Self::ensure_exists(prompts, id)?;
+            let mut stack = Self::stack_ids(prompts);
+            stack.retain(|s| s != id);
+            let index = position
+                .map(|p| p.saturating_sub(1).min(stack.len()))
+                .unwrap_or(stack.len());
+            stack.insert(index, id.to_string());
+            Self::assign_stack(prompts, &stack);
+            Ok(())
+        })
+    }
+
+    /// 调整栈中已启用提示词的位置（从 1 开始）
+    pub fn move_layer(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+        position: usize,
+    ) -> Result<(), AppError> {
+        let prompts = Self::get_prompts(state, app.clone())?;
+        Self::ensure_exists(&prompts, id)?;
+        if !prompts.get(id).is_some_and(|p| p.enabled) {
+            return Err(AppError::InvalidInput(format!("提示词 {id} 未激活")));
         }
+        Self::add_layer(state, app, id, Some(position))
+    }
 
-        // 启用目标提示词并写入文件
-        let mut cfg = state.config.write()?;
-        let prompts = match app {
+    /// 用给定的提示词列表替换整个栈（空列表即全部停用）
+    pub fn set_stack(state: &AppState, app: AppType, ids: &[String]) -> Result<(), AppError> {
+        let mut stack: Vec<String> = Vec::with_capacity(ids.len());
+        for id in ids {
+            if stack.contains(id) {
+                return Err(AppError::InvalidInput(format!(
+                    "提示词 {id} 重复出现在栈中"
+                )));
+            }
+            stack.push(id.clone());
+        }
+
+        Self::apply_stack(state, &app, |prompts| {
+            for id in &stack {
+                Self::ensure_exists(prompts, id)?;
+            }
+            Self::assign_stack(prompts, &stack);
+            Ok(())
+        })
+    }
+
+    /// 将提示词移出栈；文件中仅移除对应的托管段落
+    pub fn disable_prompt(state: &AppState, app: AppType, id: &str) -> Result<(), AppError> {
+        Self::apply_stack(state, &app, |prompts| {
+            // 验证提示词是否存在且已启用
+            match prompts.get(id) {
+                Some(prompt) if prompt.enabled => {}
+                Some(_) => return Err(AppError::InvalidInput(format!("提示词 {id} 未激活"))),
+                None => return Err(AppError::InvalidInput(format!("提示词 {id} 不存在"))),
+            }
+            let mut stack = Self::stack_ids(prompts);
+            stack.retain(|s| s != id);
+            Self::assign_stack(prompts, &stack);
+            Ok(())
+        })
+    }
+
+    fn prompts_mut<'a>(
+        cfg: &'a mut MultiAppConfig,
+        app: &AppType,
+    ) -> &'a mut HashMap<String, Prompt> {
+        match app {
             AppType::Claude => &mut cfg.prompts.claude.prompts,
             AppType::Codex => &mut cfg.prompts.codex.prompts,
             AppType::Gemini => &mut cfg.prompts.gemini.prompts,
-        };
-
-        for prompt in prompts.values_mut() {
-            prompt.enabled = false;
         }
+    }
 
-        if let Some(prompt) = prompts.get_mut(id) {
-            prompt.enabled = true;
-            write_text_file(&target_path, &prompt.content)?; // 原子写入
+    fn ensure_exists(prompts: &HashMap<String, Prompt>, id: &str) -> Result<(), AppError> {
+        if prompts.contains_key(id) {
+            Ok(())
         } else {
-            return Err(AppError::InvalidInput(format!("提示词 {id} 不存在")));
+            Err(AppError::InvalidInput(format!("提示词 {id} 不存在")))
         }
+    }
 
-        drop(cfg);
-        state.save()?; // 第二次保存：启用目标提示词并写入文件后
-        Ok(())
+    /// 已启用提示词按栈顺序排列；旧数据没有顺序时按创建时间排在最后
+    fn stack_ids(prompts: &HashMap<String, Prompt>) -> Vec<String> {
+        let mut enabled: Vec<&Prompt> = prompts.values().filter(|p| p.enabled).collect();
+        enabled.sort_by(|a, b| {
+            (a.stack_order.unwrap_or(i64::MAX), a.created_at, &a.id).cmp(&(
+                b.stack_order.unwrap_or(i64::MAX),
+                b.created_at,
+                &b.id,
+            ))
+        });
+        enabled.into_iter().map(|p| p.id.clone()).collect()
     }
 
-    pub fn disable_prompt(state: &AppState, app: AppType, id: &str) -> Result<(), AppError> {
+    fn assign_stack(prompts: &mut HashMap<String, Prompt>, stack: &[String]) {
+        for (prompt_id, prompt) in prompts.iter_mut() {
+            match stack.iter().position(|s| s == prompt_id) {
+                Some(index) => {
+                    prompt.enabled = true;
+                    prompt.stack_order = Some(index as i64 + 1);
+                }
+                None => {
+                    prompt.enabled = false;
+                    prompt.stack_order = None;
+                }
+            }
+        }
+    }
+
+    /// 修改提示词后重写 live 文件
+    ///
+    /// 先把 live 文件中手动修改过的托管段落回填到对应提示词（模板或未知段落另存为备份），
+    /// 再应用 `update`，最后按新的栈渲染所有层并替换托管段落；标记以外的内容原样保留。
+    /// 渲染失败时不会修改文件和配置。
+    fn apply_stack<F>(state: &AppState, app: &AppType, update: F) -> Result<(), AppError>
+    where
+        F: FnOnce(&mut HashMap<String, Prompt>) -> Result<(), AppError>,
+    {
+        let target_path = prompt_file_path(app)?;
+        let live = if target_path.exists() {
+            Some(std::fs::read_to_string(&target_path).map_err(|e| AppError::io(&target_path, e))?)
+        } else {
+            None
+        };
+        let ctx = TemplateContext::for_app(app);
+
         let mut cfg = state.config.write()?;
-        let prompts = match app {
-            AppType::Claude => &mut cfg.prompts.claude.prompts,
-            AppType::Codex => &mut cfg.prompts.codex.prompts,
-            AppType::Gemini => &mut cfg.prompts.gemini.prompts,
+        let prompts = Self::prompts_mut(&mut cfg, app);
+        let mut working = prompts.clone();
+        let mut layout = live
+            .as_deref()
+            .map(PromptFileLayout::parse)
+            .unwrap_or_default();
+        Self::backfill_live(&mut working, &mut layout, &ctx);
+
+        update(&mut working)?;
+
+        // 先渲染全部层：模板错误时保持原有状态
+        let mut sections = Vec::new();
+        for id in Self::stack_ids(&working) {
+            let rendered = Self::render_logged(&id, &working, &ctx)?;
+            sections.push(ManagedSection {
+                id,
+                content: rendered.content,
+            });
+        }
+        layout.sections = sections;
+        let content = layout.render();
+
+        let unchanged = match live.as_deref() {
+            Some(existing) => existing == content,
+            None => content.is_empty(),
         };
+        if !unchanged {
+            write_text_file(&target_path, &content)?; // 原子写入
+        }
 
-        // 验证提示词是否存在且已启用
-        if let Some(prompt) = prompts.get_mut(id) {
-            if !prompt.enabled {
-                return Err(AppError::InvalidInput(format!("提示词 {} 未激活", id)));
+        *prompts = working;
+        drop(cfg); // 释放锁后保存，避免死锁
+        state.save()
+    }
+
+    /// 回填 live 文件中手动修改的内容
+    fn backfill_live(
+        prompts: &mut HashMap<String, Prompt>,
+        layout: &mut PromptFileLayout,
+        ctx: &TemplateContext,
+    ) {
+        let timestamp = chrono::Utc::now().timestamp();
+
+        if !layout.sections.is_empty() {
+            let snapshot = prompts.clone();
+            for section in &layout.sections {
+                let Some(prompt) = prompts.get_mut(&section.id) else {
+                    // 段落对应的提示词已不存在，保留一份备份
+                    Self::insert_backup(prompts, &section.content, timestamp);
+                    continue;
+                };
+                // live 段落与提示词渲染结果一致（或无法渲染比较）时无需回填
+                let unchanged = prompt_template::render_stored(&section.id, &snapshot, ctx)
+                    .map(|r| r.content.trim() == section.content.trim())
+                    .unwrap_or(true);
+                if unchanged {
+                    continue;
+                }
+                if prompt_template::is_template(&prompt.content) {
+                    // 模板提示词不能被渲染结果覆盖，手动修改过的内容改为备份
+                    Self::insert_backup(prompts, &section.content, timestamp);
+                } else {
+                    prompt.content = section.content.clone();
+                    prompt.updated_at = Some(timestamp);
+                    log::info!("回填 live 提示词段落到已启用项: {}", section.id);
+                }
+            }
+            return;
+        }
+
+        // 没有托管标记：旧版本写入的单个提示词，或用户手写的内容
+        let live_content = layout.before.clone();
+        if live_content.trim().is_empty() {
+            return;
+        }
+        let stack = Self::stack_ids(prompts);
+        let [enabled_id] = stack.as_slice() else {
+            // 未启用任何提示词（或已有多层）时，整个文件作为用户内容保留
+            return;
+        };
+        let unchanged = prompt_template::render_stored(enabled_id, prompts, ctx)
+            .is_ok_and(|r| r.content.trim() == live_content.trim());
+        if !unchanged {
+            match prompts.get_mut(enabled_id) {
+                Some(prompt) if !prompt_template::is_template(&prompt.content) => {
+                    prompt.content = live_content.clone();
+                    prompt.updated_at = Some(timestamp);
+                    log::info!("回填 live 提示词内容到已启用项: {enabled_id}");
+                }
+                _ => Self::insert_backup(prompts, &live_content, timestamp),
             }
-            prompt.enabled = false;
+        }
+        // 旧版本的内容已归入提示词，改由托管段落承载
+        layout.before.clear();
+    }
+
+    /// 创建一次备份（内容已存在时不重复备份）
+    fn insert_backup(prompts: &mut HashMap<String, Prompt>, content: &str, timestamp: i64) {
+        if prompts.values().any(|p| p.content.trim() == content.trim()) {
+            return;
+        }
+        let mut backup_id = format!("backup-{timestamp}");
+        let mut suffix = 1;
+        while prompts.contains_key(&backup_id) {
+            suffix += 1;
+            backup_id = format!("backup-{timestamp}-{suffix}");
+        }
+        let backup_prompt = Prompt {
+            id: backup_id.clone(),
+            name: format!(
+                "原始提示词 {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M")
+            ),
+            content: content.to_string(),
+            description: Some("自动备份的原始提示词".to_string()),
+            enabled: false,
+            created_at: Some(timestamp),
+            updated_at: Some(timestamp),
+            stack_order: None,
+        };
+        prompts.insert(backup_id.clone(), backup_prompt);
+        log::info!("回填 live 提示词内容，创建备份: {backup_id}");
+    }
+
+    /// 在项目目录中启用提示词：写入 `<project>/CLAUDE.md` 等文件的托管段落，并记录到数据库
+    pub fn activate_in_project(
+        state: &AppState,
+        app: AppType,
+        id: &str,
+        project_dir: &Path,
+    ) -> Result<PathBuf, AppError> {
+        let project_path = Self::project_path(project_dir)?;
+        Self::sync_project(state, &app, &project_path, Some(id))?;
+        state.db.save_prompt_project(&PromptProject {
+            app_type: app.as_str().to_string(),
+            project_path: project_path.display().to_string(),
+            prompt_id: id.to_string(),
+            activated_at: chrono::Utc::now().timestamp(),
+        })?;
+        Ok(project_prompt_file_path(&app, &project_path))
+    }
+
+    /// 停用项目中的提示词：移除托管段落（保留其余内容），返回项目之前是否有绑定
+    pub fn deactivate_in_project(
+        state: &AppState,
+        app: AppType,
+        project_dir: &Path,
+    ) -> Result<bool, AppError> {
+        let project_path = Self::project_path(project_dir)?;
+        Self::sync_project(state, &app, &project_path, None)?;
+        state
+            .db
+            .delete_prompt_project(app.as_str(), &project_path.display().to_string())
+    }
+
+    /// 列出使用提示词预设的项目；`app` 为 None 时返回全部应用
+    pub fn project_usages(
+        state: &AppState,
+        app: Option<AppType>,
+    ) -> Result<Vec<PromptProject>, AppError> {
+        state
+            .db
+            .get_prompt_projects(app.as_ref().map(|a| a.as_str()))
+    }
+
+    /// 将项目中已有的提示词文件导入为预设（只导入托管段落以外的内容）
+    pub fn import_from_project(
+        state: &AppState,
+        app: AppType,
+        project_dir: &Path,
+    ) -> Result<String, AppError> {
+        let project_path = Self::project_path(project_dir)?;
+        let file_path = project_prompt_file_path(&app, &project_path);
+        if !file_path.exists() {
+            return Err(AppError::Message(format!(
+                "项目提示词文件不存在: {}",
+                file_path.display()
+            )));
+        }
+
+        let content =
+            std::fs::read_to_string(&file_path).map_err(|e| AppError::io(&file_path, e))?;
+        let mut layout = PromptFileLayout::parse(&content);
+        layout.sections.clear();
+        let content = layout.render();
+        if content.trim().is_empty() {
+            return Err(AppError::Message(
+                "提示词文件中没有未托管的内容".to_string(),
+            ));
+        }
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let project_name = project_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| project_path.display().to_string());
+        let mut id = format!("project-{timestamp}");
+        let existing = Self::get_prompts(state, app.clone())?;
+        let mut suffix = 1;
+        while existing.contains_key(&id) {
+            suffix += 1;
+            id = format!("project-{timestamp}-{suffix}");
+        }
+
+        let prompt = Prompt {
+            id: id.clone(),
+            name: format!("项目提示词 {project_name}"),
+            content,
+            description: Some(format!("从项目 {} 导入", project_path.display())),
+            enabled: false,
+            created_at: Some(timestamp),
+            updated_at: Some(timestamp),
+            stack_order: None,
+        };
+
+        Self::upsert_prompt(state, app, &id, prompt)?;
+        Ok(id)
+    }
+
+    fn project_path(project_dir: &Path) -> Result<PathBuf, AppError> {
+        if !project_dir.is_dir() {
+            return Err(AppError::InvalidInput(format!(
+                "项目目录不存在: {}",
+                project_dir.display()
+            )));
+        }
+        std::fs::canonicalize(project_dir).map_err(|e| AppError::io(project_dir, e))
+    }
+
+    /// 重写项目提示词文件中的托管段落
+    ///
+    /// 托管段落被手动修改过时先另存为备份；与预设内容相同的未托管内容（例如刚导入的文件）
+    /// 视为已由预设接管，其余内容原样保留。`id` 为 None 时仅移除托管段落。
+    fn sync_project(
+        state: &AppState,
+        app: &AppType,
+        project_path: &Path,
+        id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let file_path = project_prompt_file_path(app, project_path);
+        let live = if file_path.exists() {
+            Some(std::fs::read_to_string(&file_path).map_err(|e| AppError::io(&file_path, e))?)
         } else {
-            return Err(AppError::InvalidInput(format!("提示词 {} 不存在", id)));
+            None
+        };
+        let ctx = TemplateContext::for_project(app, project_path);
+
+        let mut cfg = state.config.write()?;
+        let prompts = Self::prompts_mut(&mut cfg, app);
+        let mut working = prompts.clone();
+        let mut layout = live
+            .as_deref()
+            .map(PromptFileLayout::parse)
+            .unwrap_or_default();
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let mut backed_up = false;
+        for section in &layout.sections {
+            let unchanged = prompt_template::render_stored(&section.id, &working, &ctx)
+                .map(|r| r.content.trim() == section.content.trim())
+                .unwrap_or(true);
+            if !unchanged {
+                Self::insert_backup(&mut working, &section.content, timestamp);
+                backed_up = true;
+            }
         }
 
-        drop(cfg);
-        state.save()?;
+        layout.sections.clear();
+        if let Some(id) = id {
+            Self::ensure_exists(&working, id)?;
+            let rendered = Self::render_logged(id, &working, &ctx)?;
+            if layout.after.trim().is_empty() && layout.before.trim() == rendered.content.trim() {
+                layout.before.clear();
+            }
+            layout.sections.push(ManagedSection {
+                id: id.to_string(),
+                content: rendered.content,
+            });
+        }
+        let content = layout.render();
 
-        // 清空对应的实时文件
-        let target_path = prompt_file_path(&app)?;
-        write_text_file(&target_path, "")?;
+        match live.as_deref() {
+            Some(existing) if existing == content => {}
+            Some(_) if content.is_empty() => {
+                std::fs::remove_file(&file_path).map_err(|e| AppError::io(&file_path, e))?;
+            }
+            None if content.is_empty() => {}
+            _ => write_text_file(&file_path, &content)?,
+        }
 
+        if backed_up {
+            *prompts = working;
+            drop(cfg);
+            state.save()?;
+        }
         Ok(())
     }
 
+    /// 预设内容变化后同步所有使用它的项目（失败只记录日志）
+    fn refresh_projects(state: &AppState, app: &AppType) {
+        let projects = match state.db.get_prompt_projects(Some(app.as_str())) {
+            Ok(projects) => projects,
+            Err(e) => {
+                log::warn!("读取项目级提示词绑定失败: {e}");
+                return;
+            }
+        };
+        for project in projects {
+            let path = PathBuf::from(&project.project_path);
+            if !path.is_dir() {
+                continue;
+            }
+            if let Err(e) = Self::sync_project(state, app, &path, Some(&project.prompt_id)) {
+                log::warn!("同步项目提示词失败 {}: {e}", project.project_path);
+            }
+        }
+    }
+
     pub fn import_from_file(state: &AppState, app: AppType) -> Result<String, AppError> {
         let file_path = prompt_file_path(&app)?;
 
@@ -196,6 +603,19 @@ impl PromptService {
 
         let content =
             std::fs::read_to_string(&file_path).map_err(|e| AppError::io(&file_path, e))?;
+        // 已由 cc-switch 托管的段落本身就是提示词，只导入标记以外的内容
+        let layout = PromptFileLayout::parse(&content);
+        let content = if layout.sections.is_empty() {
+            content
+        } else if layout.has_user_content() {
+            let mut user = layout.clone();
+            user.sections.clear();
+            user.render()
+        } else {
+            return Err(AppError::Message(
+                "提示词文件中没有未托管的内容".to_string(),
+            ));
+        };
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -213,6 +633,7 @@ impl PromptService {
             enabled: false,
             created_at: Some(timestamp),
             updated_at: Some(timestamp),
+            stack_order: None,
         };
 
         Self::upsert_prompt(state, app, &id, prompt)?;
@@ -0,0 +1,283 @@
+//! 当前供应商的 live 漂移检测
+//!
+//! 用户在 Claude Code 中 `/login`、手动编辑 `~/.codex/config.toml` 等操作会让 live 配置
+//! 与存储的当前供应商不一致，直到下次切换时才由 `backfill_*_current` 回填。
+//! 这里复用回填逻辑计算“若现在回填，供应商会变成什么”，并与存储的版本逐键比较。
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::app_config::{AppType, MultiAppConfig};
+use crate::codex_config::{get_codex_auth_path, get_codex_config_path};
+use crate::config::get_claude_settings_path;
+use crate::error::AppError;
+use crate::gemini_config::get_gemini_env_path;
+use crate::provider::Provider;
+use crate::store::AppState;
+use crate::sync_policy::should_sync_live;
+
+use super::ProviderService;
+
+/// live 配置中与存储不同的单个键
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveKeyChange {
+    /// 以 `.` 连接的键路径，如 `env.ANTHROPIC_AUTH_TOKEN`
+    pub key: String,
+    /// 存储中的值（live 新增的键为 None）
+    pub stored: Option<Value>,
+    /// live 中的值（live 删除的键为 None）
+    pub live: Option<Value>,
+}
+
+/// 当前供应商与 live 配置的比较结果
+#[derive(Debug, Clone)]
+pub struct LiveDrift {
+    pub app_type: AppType,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub changes: Vec<LiveKeyChange>,
+}
+
+impl LiveDrift {
+    pub fn is_clean(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl ProviderService {
+    /// 比较 live 配置与存储的当前供应商（不写入任何文件）
+    ///
+    /// 应用未初始化、没有当前供应商或 live 文件不存在时返回 None。
+    pub fn live_drift(state: &AppState, app_type: AppType) -> Result<Option<LiveDrift>, AppError> {
+        if !should_sync_live(&app_type) || !live_files_exist(&app_type) {
+            return Ok(None);
+        }
+        let config = state.config.read().map_err(AppError::from)?;
+        let Some(stored) = current_provider(&config, &app_type) else {
+            return Ok(None);
+        };
+
+        let mut scratch = config.clone();
+        drop(config);
+        Self::backfill_current(&mut scratch, &app_type)?;
+        let live = current_provider(&scratch, &app_type)
+            .map(|p| p.settings_config)
+            .unwrap_or(Value::Null);
+
+        let stored_keys = flatten(&comparable(&app_type, &stored.settings_config)?);
+        let live_keys = flatten(&comparable(&app_type, &live)?);
+        let mut changes = Vec::new();
+        for (key, value) in &stored_keys {
+            match live_keys.get(key) {
+                Some(live) if live == value => {}
+                live => changes.push(LiveKeyChange {
+                    key: key.clone(),
+                    stored: Some(value.clone()),
+                    live: live.cloned(),
+                }),
+            }
+        }
+        for (key, value) in &live_keys {
+            if !stored_keys.contains_key(key) {
+                changes.push(LiveKeyChange {
+                    key: key.clone(),
+                    stored: None,
+                    live: Some(value.clone()),
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(Some(LiveDrift {
+            app_type,
+            provider_id: stored.id,
+            provider_name: stored.name,
+            changes,
+        }))
+    }
+
+    /// 将 live 配置写回当前供应商（与切换时的回填相同，不改动 live 文件）
+    pub fn adopt_live(state: &AppState, app_type: AppType) -> Result<(), AppError> {
+        Self::run_transaction(state, move |config| {
+            if current_provider(config, &app_type).is_none() {
+                return Err(no_current_provider(&app_type));
+            }
+            Self::backfill_current(config, &app_type)?;
+            Ok(((), None))
+        })
+    }
+
+    /// 将 live 配置另存为新供应商并设为当前，原供应商保持不变
+    pub fn save_live_as(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        name: &str,
+    ) -> Result<(), AppError> {
+        let id = id.trim().to_string();
+        let name = name.trim().to_string();
+        if id.is_empty() || name.is_empty() {
+            return Err(AppError::InvalidInput(
+                "供应商 ID 和名称不能为空".to_string(),
+            ));
+        }
+
+        Self::run_transaction(state, move |config| {
+            let Some(original) = current_provider(config, &app_type) else {
+                return Err(no_current_provider(&app_type));
+            };
+            let exists = config
+                .get_manager(&app_type)
+                .is_some_and(|m| m.providers.contains_key(&id));
+            if exists {
+                return Err(AppError::InvalidInput(format!("供应商已存在: {id}")));
+            }
+
+            Self::backfill_current(config, &app_type)?;
+            let manager = config
+                .get_manager_mut(&app_type)
+                .ok_or_else(|| Self::app_not_found(&app_type))?;
+            let live = manager
+                .providers
+                .insert(original.id.clone(), original.clone())
+                .map(|p| p.settings_config)
+                .unwrap_or(Value::Null);
+
+            let mut provider = Provider::with_id(id.clone(), name, live, None);
+            provider.category = original.category.clone();
+            provider.created_at = Some(chrono::Utc::now().timestamp());
+            manager.providers.insert(id.clone(), provider);
+            manager.current = id;
+            Ok(((), None))
+        })
+    }
+
+    /// 按存储的当前供应商重写 live 配置（丢弃 live 中的改动）
+    pub fn reapply_current(state: &AppState, app_type: AppType) -> Result<(), AppError> {
+        let current = {
+            let config = state.config.read().map_err(AppError::from)?;
+            current_provider(&config, &app_type)
+                .map(|p| p.id)
+                .ok_or_else(|| no_current_provider(&app_type))?
+        };
+        // 目标即当前供应商时切换不会回填，live 直接按存储重写
+        Self::switch(state, app_type, &current)
+    }
+
+    /// 用 live 配置回填当前供应商
+    fn backfill_current(config: &mut MultiAppConfig, app_type: &AppType) -> Result<(), AppError> {
+        // 传入空的目标 ID，使回填逻辑总是作用于当前供应商
+        match app_type {
+            AppType::Claude => Self::backfill_claude_current(config, ""),
+            AppType::Codex => Self::backfill_codex_current(config, ""),
+            AppType::Gemini => Self::backfill_gemini_current(config, ""),
+        }
+    }
+}
+
+fn current_provider(config: &MultiAppConfig, app_type: &AppType) -> Option<Provider> {
+    let manager = config.get_manager(app_type)?;
+    manager.providers.get(&manager.current).cloned()
+}
+
+fn no_current_provider(app_type: &AppType) -> AppError {
+    AppError::InvalidInput(format!("{} 没有当前供应商", app_type.as_str()))
+}
+
+fn live_files_exist(app_type: &AppType) -> bool {
+    match app_type {
+        AppType::Claude => get_claude_settings_path().exists(),
+        AppType::Codex => get_codex_auth_path().exists() || get_codex_config_path().exists(),
+        AppType::Gemini => get_gemini_env_path().exists(),
+    }
+}
+
+/// 去掉不属于供应商、由其他功能写入 live 的部分，并统一 Codex 配置片段的格式
+fn comparable(app_type: &AppType, settings: &Value) -> Result<Value, AppError> {
+    let mut value = settings.clone();
+    match app_type {
+        AppType::Claude => {
+            // MCP 工具过滤生成的权限规则
+            if let Some(permissions) = value.get_mut("permissions").and_then(Value::as_object_mut) {
+                for list in permissions.values_mut() {
+                    if let Some(items) = list.as_array_mut() {
+                        items.retain(|v| v.as_str().is_none_or(|s| !s.starts_with("mcp__")));
+                    }
+                }
+                permissions.retain(|_, v| v.as_array().is_none_or(|a| !a.is_empty()));
+            }
+        }
+        AppType::Codex => {
+            // 存储中可能是旧版的整份 config.toml，按回填时的提取规则归一化后再比较
+            if let Some(text) = value.get("config").and_then(Value::as_str) {
+                let snippet = ProviderService::codex_config_snippet_from_live_config(text)?;
+                let table: toml::Table = toml::from_str(&snippet)
+                    .map_err(|e| AppError::Config(format!("解析 Codex 配置片段失败: {e}")))?;
+                value["config"] = serde_json::to_value(table)
+                    .map_err(|e| AppError::JsonSerialize { source: e })?;
+            }
+        }
+        AppType::Gemini => {
+            // MCP 同步写入的服务器列表
+            if let Some(config) = value.get_mut("config").and_then(Value::as_object_mut) {
+                config.remove("mcpServers");
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// 展开为 `a.b.c -> 叶子值`，数组视为叶子，空对象忽略
+fn flatten(value: &Value) -> BTreeMap<String, Value> {
+    fn walk(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(&path, child, out);
+                }
+            }
+            Value::Null => {}
+            leaf => {
+                out.insert(prefix.to_string(), leaf.clone());
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    walk("", value, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn codex_snippets_compare_by_effective_fields() {
+        let full = json!({ "config": "model_provider = \"relay\"\nmodel = \"gpt-5\"\n\n[model_providers.relay]\nname = \"relay\"\nbase_url = \"https://relay/v1\"\nwire_api = \"responses\"\n" });
+        let snippet = json!({ "config": "base_url = \"https://relay/v1\"\nmodel = \"gpt-5\"\nwire_api = \"responses\"" });
+        assert_eq!(
+            flatten(&comparable(&AppType::Codex, &full).unwrap()),
+            flatten(&comparable(&AppType::Codex, &snippet).unwrap())
+        );
+    }
+
+    #[test]
+    fn claude_ignores_generated_tool_rules() {
+        let live = json!({
+            "env": { "ANTHROPIC_AUTH_TOKEN": "sk" },
+            "permissions": { "allow": ["mcp__fs__read"], "deny": ["Bash(rm:*)"] }
+        });
+        let keys = flatten(&comparable(&AppType::Claude, &live).unwrap());
+        assert_eq!(
+            keys.keys().collect::<Vec<_>>(),
+            ["env.ANTHROPIC_AUTH_TOKEN", "permissions.deny"]
+        );
+    }
+}
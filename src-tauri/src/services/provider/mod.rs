@@ -1,3 +1,4 @@
+mod drift;
 mod endpoints;
 mod gemini_auth;
 mod live;
@@ -17,6 +18,7 @@ use crate::error::AppError;
 use crate::provider::Provider;
 use crate::store::AppState;
 
+pub use drift::{LiveDrift, LiveKeyChange};
 use gemini_auth::GeminiAuthType;
 use live::LiveSnapshot;
 
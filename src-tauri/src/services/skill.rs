@@ -8,7 +8,8 @@ use chrono::{DateTime, Utc};
 use futures::future::join_all;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tokio::time::timeout;
@@ -18,8 +19,15 @@ pub use crate::app_config::{InstalledSkill, SkillApps, UnmanagedSkill};
 use crate::config::get_app_config_dir;
 use crate::database::Database;
 use crate::error::{format_skill_error, AppError};
+use crate::services::skill_adapter;
+use crate::services::skill_lint::{self, LintReport, LintSeverity};
+use crate::services::skill_repo_cache::{CachedRepoIndex, RepoIndexCache};
+use crate::services::skill_source::{
+    self, is_commit_sha, ConditionalDownload, SkillFetcher, SkillSource,
+};
 
 const SKILLS_INDEX_VERSION: u32 = 1;
+const SKILLS_LOCK_VERSION: u32 = 1;
 
 fn default_skills_index_version() -> u32 {
     SKILLS_INDEX_VERSION
@@ -186,6 +194,170 @@ pub struct Skill {
     pub repo_branch: Option<String>,
 }
 
+// ============================================================================
+// Versioning types (upstream change detection)
+// ============================================================================
+
+/// File-level change between the installed copy and the upstream copy of a skill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillFileChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillFileChange {
+    /// Path relative to the skill directory (always `/`-separated).
+    pub path: String,
+    pub kind: SkillFileChangeKind,
+}
+
+/// Result of comparing an installed skill against its upstream source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillVersionCheck {
+    pub directory: String,
+    pub name: String,
+    /// Commit recorded at install time.
+    pub installed_commit: Option<String>,
+    /// Commit of the upstream archive that was compared against.
+    pub latest_commit: Option<String>,
+    /// Upstream content differs from what was installed.
+    pub upstream_changed: bool,
+    /// The SSOT copy differs from the recorded content hash (local edits).
+    pub locally_modified: bool,
+    /// Files that an update would add/remove/modify in the SSOT copy.
+    pub changes: Vec<SkillFileChange>,
+}
+
+impl SkillVersionCheck {
+    pub fn is_outdated(&self) -> bool {
+        self.upstream_changed && !self.changes.is_empty()
+    }
+}
+
+/// Outcome of `skills update` for a single skill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SkillUpdateOutcome {
+    Updated,
+    UpToDate,
+    /// Local edits would be overwritten; re-run with force.
+    SkippedLocalChanges,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillUpdateResult {
+    pub check: SkillVersionCheck,
+    pub outcome: SkillUpdateOutcome,
+}
+
+// ============================================================================
+// Lockfile (skills.lock)
+// ============================================================================
+
+/// `skills.lock`: a reproducible snapshot of the installed skill set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillLock {
+    pub version: u32,
+    pub skills: Vec<SkillLockEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillLockEntry {
+    pub directory: String,
+    pub name: String,
+    /// `owner/name:directory` for repo skills, the install source spec
+    /// (`file://`, `git+https://`, ZIP URL), or `local:directory` for imported skills.
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub content_hash: String,
+    pub apps: SkillApps,
+}
+
+/// Per-entry outcome of `skills install --from-lock`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SkillLockOutcome {
+    Installed,
+    /// Already present with the locked content; only app enablement was applied.
+    Unchanged,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillLockResult {
+    pub directory: String,
+    pub outcome: SkillLockOutcome,
+}
+
+// ============================================================================
+// Authoring (skills new / skills pack)
+// ============================================================================
+
+/// Options for `skills new`.
+#[derive(Debug, Clone, Default)]
+pub struct NewSkillOptions {
+    /// Directory name; also used as the front matter `name`.
+    pub directory: String,
+    pub description: String,
+    /// Add `scripts/` with an example script referenced from SKILL.md.
+    pub with_scripts: bool,
+    /// Add `references/` with a reference doc linked from SKILL.md.
+    pub with_references: bool,
+    /// Apps to enable and sync to.
+    pub apps: Vec<AppType>,
+}
+
+const SKILL_MD_TEMPLATE: &str = "---
+name: {name}
+description: {description}
+---
+
+# {title}
+
+## When to use
+
+Describe the tasks and requests this skill should be used for.
+
+## Instructions
+
+1. Step-by-step guidance for the agent.
+";
+
+const SCRIPTS_SECTION_TEMPLATE: &str = "
+## Scripts
+
+- `scripts/example.sh`: replace with the helper scripts this skill runs.
+";
+
+const REFERENCES_SECTION_TEMPLATE: &str = "
+## References
+
+- [Reference](references/REFERENCE.md): detailed material loaded only when needed.
+";
+
+const EXAMPLE_SCRIPT_TEMPLATE: &str = "#!/usr/bin/env bash
+set -euo pipefail
+
+echo \"example script for {name}\"
+";
+
+const REFERENCE_DOC_TEMPLATE: &str = "# {title} reference
+
+Put detailed documentation, schemas or examples here and link to them from SKILL.md.
+";
+
 /// Skill metadata extracted from SKILL.md YAML front matter.
 #[derive(Debug, Clone, Deserialize)]
 pub struct SkillMetadata {
@@ -199,6 +371,12 @@ pub struct SkillMetadata {
 
 pub struct SkillService {
     http_client: Client,
+    /// Fetchers for non-repo install sources (first match wins).
+    fetchers: Vec<Box<dyn SkillFetcher>>,
+    /// Install even when `skills lint` reports errors.
+    allow_lint_errors: bool,
+    /// Serve repo discovery from the cache only.
+    offline: bool,
 }
 
 impl SkillService {
@@ -215,7 +393,54 @@ impl SkillService {
                 )
             })?;
 
-        Ok(Self { http_client })
+        let fetchers = skill_source::default_fetchers(&http_client);
+        Ok(Self {
+            http_client,
+            fetchers,
+            allow_lint_errors: false,
+            offline: false,
+        })
+    }
+
+    /// Discover skills from the repo index cache without network access.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Skip the lint gate on install (warnings are never blocking).
+    pub fn allow_lint_errors(&mut self, allow: bool) {
+        self.allow_lint_errors = allow;
+    }
+
+    /// Refuse to install a skill whose lint report has errors, unless overridden.
+    fn check_lint(&self, skill_dir: &Path, directory: &str) -> Result<(), AppError> {
+        if self.allow_lint_errors {
+            return Ok(());
+        }
+        let report = skill_lint::lint_skill_dir(skill_dir)?;
+        if !report.has_errors() {
+            return Ok(());
+        }
+        let errors = report
+            .issues
+            .iter()
+            .filter(|i| i.severity == LintSeverity::Error)
+            .map(|i| match &i.file {
+                Some(file) => format!("{file}: {}", i.message),
+                None => i.message.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        Err(AppError::Message(format_skill_error(
+            "SKILL_LINT_FAILED",
+            &[("directory", directory), ("errors", errors.as_str())],
+            Some("fixLintErrors"),
+        )))
+    }
+
+    /// Register an additional source fetcher; it takes precedence over the built-in ones.
+    pub fn register_fetcher(&mut self, fetcher: Box<dyn SkillFetcher>) {
+        self.fetchers.insert(0, fetcher);
     }
 
     // ---------------------------------------------------------------------
@@ -469,6 +694,9 @@ impl SkillService {
                             repo_branch: None,
                             apps,
                             installed_at: Utc::now().timestamp(),
+                            source: None,
+                            source_commit: None,
+                            content_hash: Self::hash_skill_dir(&ssot_path).ok(),
                         },
                     );
                     created += 1;
@@ -487,7 +715,7 @@ impl SkillService {
     // ---------------------------------------------------------------------
 
     #[cfg(unix)]
-    fn create_symlink(src: &Path, dest: &Path) -> Result<(), AppError> {
+    pub(crate) fn create_symlink(src: &Path, dest: &Path) -> Result<(), AppError> {
         std::os::unix::fs::symlink(src, dest).map_err(|e| AppError::IoContext {
             context: format!("创建符号链接失败 ({} -> {})", src.display(), dest.display()),
             source: e,
@@ -495,20 +723,20 @@ impl SkillService {
     }
 
     #[cfg(windows)]
-    fn create_symlink(src: &Path, dest: &Path) -> Result<(), AppError> {
+    pub(crate) fn create_symlink(src: &Path, dest: &Path) -> Result<(), AppError> {
         std::os::windows::fs::symlink_dir(src, dest).map_err(|e| AppError::IoContext {
             context: format!("创建符号链接失败 ({} -> {})", src.display(), dest.display()),
             source: e,
         })
     }
 
-    fn is_symlink(path: &Path) -> bool {
+    pub(crate) fn is_symlink(path: &Path) -> bool {
         path.symlink_metadata()
             .map(|m| m.file_type().is_symlink())
             .unwrap_or(false)
     }
 
-    fn remove_path(path: &Path) -> Result<(), AppError> {
+    pub(crate) fn remove_path(path: &Path) -> Result<(), AppError> {
         if Self::is_symlink(path) {
             #[cfg(unix)]
             fs::remove_file(path).map_err(|e| AppError::io(path, e))?;
@@ -538,39 +766,16 @@ impl SkillService {
             )));
         }
 
-        let app_dir = Self::get_app_skills_dir(app)?;
-        // D5: allow creating target app dirs during skills sync.
-        fs::create_dir_all(&app_dir).map_err(|e| AppError::io(&app_dir, e))?;
-
-        let dest = app_dir.join(directory);
-        if dest.exists() || Self::is_symlink(&dest) {
-            Self::remove_path(&dest)?;
-        }
-
-        match method {
-            SyncMethod::Auto => match Self::create_symlink(&source, &dest) {
-                Ok(()) => Ok(()),
-                Err(err) => {
-                    log::warn!(
-                        "Symlink 创建失败，将回退到文件复制: {} -> {}. 错误: {err}",
-                        source.display(),
-                        dest.display()
-                    );
-                    Self::copy_dir_recursive(&source, &dest)
-                }
-            },
-            SyncMethod::Symlink => Self::create_symlink(&source, &dest),
-            SyncMethod::Copy => Self::copy_dir_recursive(&source, &dest),
-        }
+        skill_adapter::adapter_for(app).sync(directory, &source, method)
     }
 
     pub fn remove_from_app(directory: &str, app: &AppType) -> Result<(), AppError> {
-        let app_dir = Self::get_app_skills_dir(app)?;
-        let path = app_dir.join(directory);
-        if path.exists() || Self::is_symlink(&path) {
-            Self::remove_path(&path)?;
-        }
-        Ok(())
+        skill_adapter::adapter_for(app).remove(directory)
+    }
+
+    /// Where a skill is placed for an app (skills dir entry or Gemini extension dir).
+    pub fn app_skill_path(directory: &str, app: &AppType) -> Result<PathBuf, AppError> {
+        skill_adapter::adapter_for(app).target_path(directory)
     }
 
     pub fn sync_to_app(index: &SkillsIndex, app: &AppType) -> Result<(), AppError> {
@@ -647,7 +852,18 @@ impl SkillService {
 
     pub fn remove_repo(owner: &str, name: &str) -> Result<(), AppError> {
         let db = Database::init()?;
-        db.delete_skill_repo(owner, name)
+        db.delete_skill_repo(owner, name)?;
+
+        let mut cache = RepoIndexCache::load();
+        if cache.remove_repo(owner, name) {
+            cache.save()?;
+        }
+        Ok(())
+    }
+
+    /// Cached discovery metadata for a repo, if any.
+    pub fn cached_repo_index(repo: &SkillRepo) -> Option<CachedRepoIndex> {
+        RepoIndexCache::load().get(repo).cloned()
     }
 
     fn resolve_directory_from_input(index: &SkillsIndex, input: &str) -> Option<String> {
@@ -744,6 +960,10 @@ impl SkillService {
             return Err(AppError::InvalidInput("Skill 不能为空".to_string()));
         }
 
+        if let Some(source) = SkillSource::parse(spec)? {
+            return self.install_from_source(source, app).await;
+        }
+
         let mut index = Self::load_index()?;
         let _ = Self::migrate_ssot_if_pending(&mut index)?;
 
@@ -764,13 +984,16 @@ impl SkillService {
             if !same_repo
                 && (existing.repo_owner.is_some()
                     || existing.repo_name.is_some()
+                    || existing.source.is_some()
                     || existing.id.starts_with("local:"))
             {
-                let existing_repo = format!(
-                    "{}/{}",
-                    existing.repo_owner.as_deref().unwrap_or("unknown"),
-                    existing.repo_name.as_deref().unwrap_or("unknown")
-                );
+                let existing_repo = existing.source.clone().unwrap_or_else(|| {
+                    format!(
+                        "{}/{}",
+                        existing.repo_owner.as_deref().unwrap_or("unknown"),
+                        existing.repo_name.as_deref().unwrap_or("unknown")
+                    )
+                });
                 let new_repo = format!("{}/{}", discoverable.repo_owner, discoverable.repo_name);
 
                 return Err(AppError::Message(format_skill_error(
@@ -796,6 +1019,7 @@ impl SkillService {
         // Ensure SSOT dir and install files.
         let ssot_dir = Self::get_ssot_dir()?;
         let dest = ssot_dir.join(&install_name);
+        let mut source_commit = None;
         if !dest.exists() {
             let repo = SkillRepo {
                 owner: discoverable.repo_owner.clone(),
@@ -804,7 +1028,7 @@ impl SkillService {
                 enabled: true,
             };
 
-            let temp_dir = timeout(
+            let (temp_dir, commit) = timeout(
                 std::time::Duration::from_secs(60),
                 self.download_repo(&repo),
             )
@@ -841,8 +1065,14 @@ impl SkillService {
                 )));
             }
 
+            if let Err(e) = self.check_lint(&source, &install_name) {
+                let _ = fs::remove_dir_all(&temp_dir);
+                return Err(e);
+            }
+
             Self::copy_dir_recursive(&source, &dest)?;
             let _ = fs::remove_dir_all(&temp_dir);
+            source_commit = commit;
         }
 
         let installed = InstalledSkill {
@@ -860,6 +1090,9 @@ impl SkillService {
             repo_branch: Some(discoverable.repo_branch.clone()),
             apps: SkillApps::only(app),
             installed_at: Utc::now().timestamp(),
+            source: None,
+            source_commit,
+            content_hash: Some(Self::hash_skill_dir(&dest)?),
         };
 
         index.skills.insert(install_name.clone(), installed.clone());
@@ -869,6 +1102,199 @@ impl SkillService {
         Ok(installed)
     }
 
+    /// Install from a `file://`, `git+https://` or ZIP URL source.
+    async fn install_from_source(
+        &self,
+        source: SkillSource,
+        app: &AppType,
+    ) -> Result<InstalledSkill, AppError> {
+        let mut index = Self::load_index()?;
+        let _ = Self::migrate_ssot_if_pending(&mut index)?;
+
+        let (temp_dir, skill_dir, commit) = self.fetch_source(&source).await?;
+        let result = self
+            .check_lint(&skill_dir, &source.default_directory())
+            .and_then(|_| {
+                Self::install_fetched(&mut index, &source, &temp_dir, &skill_dir, commit, app)
+            });
+        let _ = fs::remove_dir_all(&temp_dir);
+        result
+    }
+
+    fn install_fetched(
+        index: &mut SkillsIndex,
+        source: &SkillSource,
+        temp_dir: &Path,
+        skill_dir: &Path,
+        commit: Option<String>,
+        app: &AppType,
+    ) -> Result<InstalledSkill, AppError> {
+        let spec = source.to_string();
+        let install_name = if skill_dir == temp_dir {
+            source.default_directory()
+        } else {
+            skill_dir
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| source.default_directory())
+        };
+        if install_name.is_empty() || install_name.starts_with('.') {
+            return Err(AppError::InvalidInput(format!(
+                "Cannot derive a skill directory name from '{spec}'; append #<subdir>"
+            )));
+        }
+
+        if let Some(existing) = index.skills.get(&install_name) {
+            if existing.source.as_deref() != Some(spec.as_str()) {
+                let existing_source = existing.source.clone().unwrap_or_else(|| {
+                    match (&existing.repo_owner, &existing.repo_name) {
+                        (Some(owner), Some(name)) => format!("{owner}/{name}"),
+                        _ => existing.id.clone(),
+                    }
+                });
+                return Err(AppError::Message(format_skill_error(
+                    "SKILL_DIRECTORY_CONFLICT",
+                    &[
+                        ("directory", install_name.as_str()),
+                        ("existing_repo", existing_source.as_str()),
+                        ("new_repo", spec.as_str()),
+                    ],
+                    Some("uninstallFirst"),
+                )));
+            }
+
+            // Already installed from the same source: just enable current app and sync.
+            let mut updated = existing.clone();
+            updated.apps.set_enabled_for(app, true);
+            index.skills.insert(install_name.clone(), updated.clone());
+            Self::save_index(index)?;
+            Self::sync_to_app_dir(&install_name, app, index.sync_method)?;
+            return Ok(updated);
+        }
+
+        // Never clobber a hand-made or unmanaged skill that happens to share the name.
+        let dest = Self::get_ssot_dir()?.join(&install_name);
+        if dest.exists() {
+            return Err(AppError::Message(format!(
+                "Skill 目录 '{}' 已存在但未被 CC Switch 管理，已拒绝覆盖；请先移走该目录后重试",
+                dest.display()
+            )));
+        }
+        Self::copy_dir_recursive(skill_dir, &dest)?;
+
+        let meta = Self::parse_skill_metadata_static(&dest.join("SKILL.md")).ok();
+        let installed = InstalledSkill {
+            id: spec.clone(),
+            name: meta
+                .as_ref()
+                .and_then(|m| m.name.clone())
+                .unwrap_or_else(|| install_name.clone()),
+            description: meta.and_then(|m| m.description),
+            directory: install_name.clone(),
+            readme_url: None,
+            repo_owner: None,
+            repo_name: None,
+            repo_branch: None,
+            apps: SkillApps::only(app),
+            installed_at: Utc::now().timestamp(),
+            source: Some(spec),
+            source_commit: commit,
+            content_hash: Some(Self::hash_skill_dir(&dest)?),
+        };
+
+        index.skills.insert(install_name.clone(), installed.clone());
+        Self::save_index(index)?;
+        Self::sync_to_app_dir(&install_name, app, index.sync_method)?;
+        Ok(installed)
+    }
+
+    /// Fetch a source into a temp dir and locate the skill inside it.
+    ///
+    /// Returns (temp dir, skill dir, commit SHA); the caller removes the temp dir.
+    async fn fetch_source(
+        &self,
+        source: &SkillSource,
+    ) -> Result<(PathBuf, PathBuf, Option<String>), AppError> {
+        let fetcher = self
+            .fetchers
+            .iter()
+            .find(|f| f.supports(source))
+            .ok_or_else(|| AppError::Message(format!("No fetcher for skill source: {source}")))?;
+
+        let temp_path = Self::create_temp_dir()?;
+
+        let source_label = source.to_string();
+        let fetched = match timeout(
+            std::time::Duration::from_secs(120),
+            fetcher.fetch(source, &temp_path),
+        )
+        .await
+        {
+            Ok(Ok(commit)) => {
+                Self::locate_source_skill(source, &temp_path).map(|dir| (dir, commit))
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(AppError::Message(format_skill_error(
+                "DOWNLOAD_TIMEOUT",
+                &[("source", source_label.as_str()), ("timeout", "120")],
+                Some("checkNetwork"),
+            ))),
+        };
+
+        match fetched {
+            Ok((skill_dir, commit)) => Ok((temp_path, skill_dir, commit)),
+            Err(e) => {
+                let _ = fs::remove_dir_all(&temp_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// `#subdir` if given, else the tree root when it is a skill, else its only skill dir.
+    fn locate_source_skill(source: &SkillSource, root: &Path) -> Result<PathBuf, AppError> {
+        if let Some(subdir) = source.subdir() {
+            let dir = root.join(subdir);
+            if subdir.split('/').any(|c| c == "..") || !dir.is_dir() {
+                return Err(AppError::Message(format_skill_error(
+                    "SKILL_DIR_NOT_FOUND",
+                    &[("directory", subdir)],
+                    Some("checkRepoUrl"),
+                )));
+            }
+            return Ok(dir);
+        }
+
+        if root.join("SKILL.md").exists() {
+            return Ok(root.to_path_buf());
+        }
+
+        let mut dirs = Self::scan_skill_dirs(root)?;
+        match dirs.len() {
+            1 => Ok(dirs.remove(0)),
+            0 => {
+                let source_label = source.to_string();
+                Err(AppError::Message(format_skill_error(
+                    "SKILL_DIR_NOT_FOUND",
+                    &[("path", source_label.as_str())],
+                    Some("checkRepoUrl"),
+                )))
+            }
+            _ => {
+                let mut found: Vec<String> = dirs
+                    .iter()
+                    .filter_map(|d| d.strip_prefix(root).ok())
+                    .map(|d| d.to_string_lossy().replace('\\', "/"))
+                    .collect();
+                found.sort();
+                Err(AppError::InvalidInput(format!(
+                    "Source contains {} skills ({}); append #<subdir> to pick one",
+                    found.len(),
+                    found.join(", ")
+                )))
+            }
+        }
+    }
+
     async fn resolve_install_spec(
         &self,
         index: &SkillsIndex,
@@ -897,81 +1323,931 @@ impl SkillService {
     }
 
     // ---------------------------------------------------------------------
-    // Unmanaged scan / import
+    // Versioning (content hash / upstream updates)
     // ---------------------------------------------------------------------
 
-    pub fn scan_unmanaged() -> Result<Vec<UnmanagedSkill>, AppError> {
-        let index = Self::load_index()?;
-        let managed: HashSet<String> = index.skills.keys().cloned().collect();
-
-        let mut unmanaged: HashMap<String, UnmanagedSkill> = HashMap::new();
-
-        for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
-            let app_dir = match Self::get_app_skills_dir(&app) {
-                Ok(d) => d,
-                Err(_) => continue,
-            };
-            if !app_dir.exists() {
-                continue;
-            }
+    /// Per-file sha256 digests keyed by `/`-separated relative path.
+    fn file_digests(root: &Path) -> Result<BTreeMap<String, String>, AppError> {
+        let mut digests = BTreeMap::new();
+        let mut stack = vec![root.to_path_buf()];
 
-            for entry in fs::read_dir(&app_dir).map_err(|e| AppError::io(&app_dir, e))? {
-                let entry = entry.map_err(|e| AppError::io(&app_dir, e))?;
-                let path = entry.path();
-                if !path.is_dir() {
+        while let Some(dir) = stack.pop() {
+            for entry in fs::read_dir(&dir).map_err(|e| AppError::io(&dir, e))? {
+                let entry = entry.map_err(|e| AppError::io(&dir, e))?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name == ".git" || name == ".DS_Store" {
                     continue;
                 }
 
-                let dir_name = entry.file_name().to_string_lossy().to_string();
-                if dir_name.starts_with('.') {
-                    continue;
-                }
-                if managed.contains(&dir_name) {
+                let path = entry.path();
+                if path.is_dir() {
+                    stack.push(path);
                     continue;
                 }
 
-                let skill_md = path.join("SKILL.md");
-                let (name, description) = if skill_md.exists() {
-                    match Self::parse_skill_metadata_static(&skill_md) {
-                        Ok(meta) => (
-                            meta.name.unwrap_or_else(|| dir_name.clone()),
-                            meta.description,
-                        ),
-                        Err(_) => (dir_name.clone(), None),
-                    }
-                } else {
-                    (dir_name.clone(), None)
-                };
-
-                let app_str = match app {
-                    AppType::Claude => "claude",
-                    AppType::Codex => "codex",
-                    AppType::Gemini => "gemini",
-                };
-
-                unmanaged
-                    .entry(dir_name.clone())
-                    .and_modify(|s| s.found_in.push(app_str.to_string()))
-                    .or_insert(UnmanagedSkill {
-                        directory: dir_name,
-                        name,
-                        description,
-                        found_in: vec![app_str.to_string()],
-                    });
+                let bytes = fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+                let relative = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                digests.insert(relative, hex::encode(Sha256::digest(&bytes)));
             }
         }
 
-        Ok(unmanaged.into_values().collect())
+        Ok(digests)
     }
 
-    pub fn import_from_apps(directories: Vec<String>) -> Result<Vec<InstalledSkill>, AppError> {
-        let mut index = Self::load_index()?;
-        let ssot_dir = Self::get_ssot_dir()?;
-        let mut imported = Vec::new();
-
-        for dir_name in directories {
-            let mut source_path: Option<PathBuf> = None;
-            let mut found_in: Vec<AppType> = Vec::new();
+    /// Content hash of a skill directory: sha256 over sorted relative paths and file digests,
+    /// so it is stable across platforms and independent of timestamps.
+    pub fn hash_skill_dir(path: &Path) -> Result<String, AppError> {
+        let mut hasher = Sha256::new();
+        for (relative, digest) in Self::file_digests(path)? {
+            hasher.update(relative.as_bytes());
+            hasher.update([0u8]);
+            hasher.update(digest.as_bytes());
+            hasher.update([b'\n']);
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    /// File-level diff from `current` (may not exist) to `upstream`.
+    pub fn diff_skill_dirs(
+        current: &Path,
+        upstream: &Path,
+    ) -> Result<Vec<SkillFileChange>, AppError> {
+        let before = if current.exists() {
+            Self::file_digests(current)?
+        } else {
+            BTreeMap::new()
+        };
+        let after = Self::file_digests(upstream)?;
+
+        let mut changes = Vec::new();
+        for (path, digest) in &after {
+            let kind = match before.get(path) {
+                None => SkillFileChangeKind::Added,
+                Some(old) if old != digest => SkillFileChangeKind::Modified,
+                Some(_) => continue,
+            };
+            changes.push(SkillFileChange {
+                path: path.clone(),
+                kind,
+            });
+        }
+        for path in before.keys().filter(|p| !after.contains_key(*p)) {
+            changes.push(SkillFileChange {
+                path: path.clone(),
+                kind: SkillFileChangeKind::Removed,
+            });
+        }
+
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(changes)
+    }
+
+    /// Compare an installed skill against an already-fetched upstream copy.
+    pub fn check_against_upstream(
+        record: &InstalledSkill,
+        upstream_dir: &Path,
+        latest_commit: Option<String>,
+    ) -> Result<SkillVersionCheck, AppError> {
+        let local_dir = Self::get_ssot_dir()?.join(&record.directory);
+        let local_hash = if local_dir.exists() {
+            Some(Self::hash_skill_dir(&local_dir)?)
+        } else {
+            None
+        };
+        let upstream_hash = Self::hash_skill_dir(upstream_dir)?;
+
+        // Records from before versioning have no hash: fall back to the current SSOT copy.
+        let baseline = record.content_hash.as_ref().or(local_hash.as_ref());
+        let upstream_changed = baseline != Some(&upstream_hash);
+        let locally_modified = matches!(
+            (&record.content_hash, &local_hash),
+            (Some(recorded), Some(local)) if recorded != local
+        );
+
+        Ok(SkillVersionCheck {
+            directory: record.directory.clone(),
+            name: record.name.clone(),
+            installed_commit: record.source_commit.clone(),
+            latest_commit,
+            upstream_changed,
+            locally_modified,
+            changes: Self::diff_skill_dirs(&local_dir, upstream_dir)?,
+        })
+    }
+
+    /// Replace the SSOT copy of a skill with an already-fetched upstream copy.
+    ///
+    /// Refuses (returns `SkippedLocalChanges`) when the SSOT copy was edited since install,
+    /// unless `force` is set.
+    pub fn apply_upstream(
+        record: &InstalledSkill,
+        upstream_dir: &Path,
+        latest_commit: Option<String>,
+        force: bool,
+    ) -> Result<SkillUpdateResult, AppError> {
+        let check = Self::check_against_upstream(record, upstream_dir, latest_commit.clone())?;
+
+        if !check.upstream_changed {
+            return Ok(SkillUpdateResult {
+                check,
+                outcome: SkillUpdateOutcome::UpToDate,
+            });
+        }
+        if check.locally_modified && !force {
+            return Ok(SkillUpdateResult {
+                check,
+                outcome: SkillUpdateOutcome::SkippedLocalChanges,
+            });
+        }
+
+        let dest = Self::get_ssot_dir()?.join(&record.directory);
+        if dest.exists() {
+            fs::remove_dir_all(&dest).map_err(|e| AppError::io(&dest, e))?;
+        }
+        Self::copy_dir_recursive(upstream_dir, &dest)?;
+
+        let mut updated = record.clone();
+        updated.source_commit = latest_commit.or(updated.source_commit);
+        updated.content_hash = Some(Self::hash_skill_dir(&dest)?);
+        let skill_md = dest.join("SKILL.md");
+        if let Ok(meta) = Self::parse_skill_metadata_static(&skill_md) {
+            if let Some(name) = meta.name {
+                updated.name = name;
+            }
+            if meta.description.is_some() {
+                updated.description = meta.description;
+            }
+        }
+
+        let db = Database::init()?;
+        db.save_skill(&updated)?;
+
+        // Copy-mode app dirs hold stale files; symlinks are simply recreated.
+        let sync_method = crate::settings::get_skill_sync_method();
+        for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            if updated.apps.is_enabled_for(&app) {
+                Self::sync_to_app_dir(&updated.directory, &app, sync_method)?;
+            }
+        }
+
+        Ok(SkillUpdateResult {
+            check,
+            outcome: SkillUpdateOutcome::Updated,
+        })
+    }
+
+    /// Skills installed from a repo or an install source (local imports have no upstream).
+    fn is_updatable(record: &InstalledSkill) -> bool {
+        record.source.is_some() || (record.repo_owner.is_some() && record.repo_name.is_some())
+    }
+
+    /// Installed repo/source skills selected by directory/id (all of them when `target` is None).
+    fn repo_skills_for(
+        index: &SkillsIndex,
+        target: Option<&str>,
+    ) -> Result<Vec<InstalledSkill>, AppError> {
+        let selected: Vec<InstalledSkill> = match target {
+            Some(input) => {
+                let dir = Self::resolve_directory_from_input(index, input)
+                    .ok_or_else(|| AppError::Message(format!("未找到已安装的 Skill: {input}")))?;
+                let record = index.skills[&dir].clone();
+                if !Self::is_updatable(&record) {
+                    return Err(AppError::Message(format!(
+                        "Skill {dir} 不是从仓库安装的，无法检查更新"
+                    )));
+                }
+                vec![record]
+            }
+            None => index
+                .skills
+                .values()
+                .filter(|s| Self::is_updatable(s))
+                .cloned()
+                .collect(),
+        };
+
+        let mut selected = selected;
+        selected.sort_by(|a, b| a.directory.cmp(&b.directory));
+        Ok(selected)
+    }
+
+    /// Download each involved repo archive once and hand every skill's upstream copy to `f`.
+    async fn with_upstream_copies<T>(
+        &self,
+        records: Vec<InstalledSkill>,
+        mut f: impl FnMut(&InstalledSkill, &Path, Option<String>) -> Result<T, AppError>,
+    ) -> Result<Vec<T>, AppError> {
+        let mut out = Vec::new();
+        let mut by_repo: BTreeMap<(String, String, String), Vec<InstalledSkill>> = BTreeMap::new();
+        for record in records {
+            if let Some(spec) = &record.source {
+                let source = SkillSource::parse(spec)?
+                    .ok_or_else(|| AppError::Message(format!("Invalid skill source '{spec}'")))?;
+                let (temp_dir, skill_dir, commit) = self.fetch_source(&source).await?;
+                let result = f(&record, &skill_dir, commit);
+                let _ = fs::remove_dir_all(&temp_dir);
+                out.push(result?);
+                continue;
+            }
+
+            let key = (
+                record.repo_owner.clone().unwrap_or_default(),
+                record.repo_name.clone().unwrap_or_default(),
+                record.repo_branch.clone().unwrap_or_default(),
+            );
+            by_repo.entry(key).or_default().push(record);
+        }
+
+        for ((owner, name, branch), records) in by_repo {
+            let repo = SkillRepo {
+                owner,
+                name,
+                branch,
+                enabled: true,
+            };
+            let (temp_dir, commit) = timeout(
+                std::time::Duration::from_secs(60),
+                self.download_repo(&repo),
+            )
+            .await
+            .map_err(|_| {
+                AppError::Message(format_skill_error(
+                    "DOWNLOAD_TIMEOUT",
+                    &[
+                        ("owner", repo.owner.as_str()),
+                        ("name", repo.name.as_str()),
+                        ("timeout", "60"),
+                    ],
+                    Some("checkNetwork"),
+                ))
+            })??;
+
+            let result = records.iter().try_for_each(|record| {
+                match Self::find_skill_dir_in_repo(&temp_dir, &record.directory)? {
+                    Some(upstream) => out.push(f(record, &upstream, commit.clone())?),
+                    None => log::warn!(
+                        "仓库 {}/{} 中已不存在 Skill 目录 {}，已跳过",
+                        repo.owner,
+                        repo.name,
+                        record.directory
+                    ),
+                }
+                Ok::<(), AppError>(())
+            });
+            let _ = fs::remove_dir_all(&temp_dir);
+            result?;
+        }
+
+        Ok(out)
+    }
+
+    /// Compare installed repo skills with their upstream repo archives.
+    pub async fn check_updates(
+        &self,
+        target: Option<&str>,
+    ) -> Result<Vec<SkillVersionCheck>, AppError> {
+        let mut index = Self::load_index()?;
+        let _ = Self::migrate_ssot_if_pending(&mut index)?;
+        let records = Self::repo_skills_for(&index, target)?;
+        self.with_upstream_copies(records, Self::check_against_upstream)
+            .await
+    }
+
+    /// Update installed repo skills from their upstream repo archives.
+    pub async fn update(
+        &self,
+        target: Option<&str>,
+        force: bool,
+    ) -> Result<Vec<SkillUpdateResult>, AppError> {
+        let mut index = Self::load_index()?;
+        let _ = Self::migrate_ssot_if_pending(&mut index)?;
+        let records = Self::repo_skills_for(&index, target)?;
+        self.with_upstream_copies(records, |record, upstream, commit| {
+            Self::apply_upstream(record, upstream, commit, force)
+        })
+        .await
+    }
+
+    // ---------------------------------------------------------------------
+    // Lint
+    // ---------------------------------------------------------------------
+
+    /// Lint an installed skill's SSOT copy.
+    pub fn lint_installed(directory_or_id: &str) -> Result<LintReport, AppError> {
+        let index = Self::load_index()?;
+        let Some(directory) = Self::resolve_directory_from_input(&index, directory_or_id) else {
+            return Err(AppError::Message(format!(
+                "未找到已安装的 Skill: {directory_or_id}"
+            )));
+        };
+        skill_lint::lint_skill_dir(&Self::get_ssot_dir()?.join(directory))
+    }
+
+    /// Lint a skill given a local directory, an install source spec, an installed skill,
+    /// or a repo skill (`directory` / `owner/name:directory`). Returns the resolved label.
+    pub async fn lint(&self, spec: &str) -> Result<(String, LintReport), AppError> {
+        let spec = spec.trim();
+        let path = Path::new(spec);
+        if !spec.is_empty() && path.is_dir() {
+            return Ok((spec.to_string(), skill_lint::lint_skill_dir(path)?));
+        }
+
+        if let Some(source) = SkillSource::parse(spec)? {
+            let (temp_dir, skill_dir, _) = self.fetch_source(&source).await?;
+            let report = skill_lint::lint_skill_dir(&skill_dir);
+            let _ = fs::remove_dir_all(&temp_dir);
+            return Ok((source.to_string(), report?));
+        }
+
+        let index = Self::load_index()?;
+        if let Some(directory) = Self::resolve_directory_from_input(&index, spec) {
+            let report = Self::lint_installed(&directory)?;
+            return Ok((directory, report));
+        }
+
+        let discoverable = self.resolve_install_spec(&index, spec).await?;
+        let repo = SkillRepo {
+            owner: discoverable.repo_owner.clone(),
+            name: discoverable.repo_name.clone(),
+            branch: discoverable.repo_branch.clone(),
+            enabled: true,
+        };
+        let install_name = Path::new(&discoverable.directory)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| discoverable.directory.clone());
+        let (temp_dir, _) = self.download_repo(&repo).await?;
+        let report = match Self::find_skill_dir_in_repo(&temp_dir, &install_name) {
+            Ok(Some(dir)) => skill_lint::lint_skill_dir(&dir),
+            Ok(None) => Err(AppError::Message(format_skill_error(
+                "SKILL_DIR_NOT_FOUND",
+                &[("directory", install_name.as_str())],
+                Some("checkRepoUrl"),
+            ))),
+            Err(e) => Err(e),
+        };
+        let _ = fs::remove_dir_all(&temp_dir);
+        Ok((discoverable.key, report?))
+    }
+
+    // ---------------------------------------------------------------------
+    // Lockfile (skills.lock)
+    // ---------------------------------------------------------------------
+
+    /// Snapshot the installed skills (sorted by directory).
+    pub fn build_lock() -> Result<SkillLock, AppError> {
+        let mut index = Self::load_index()?;
+        let _ = Self::migrate_ssot_if_pending(&mut index)?;
+        let ssot_dir = Self::get_ssot_dir()?;
+
+        let mut skills = Vec::new();
+        for record in index.skills.values() {
+            let dir = ssot_dir.join(&record.directory);
+            if !dir.exists() {
+                log::warn!("Skill {} 不存在于 SSOT，未写入 lock", record.directory);
+                continue;
+            }
+
+            let source = match (&record.source, &record.repo_owner, &record.repo_name) {
+                (Some(source), _, _) => source.clone(),
+                (None, Some(owner), Some(name)) => {
+                    format!("{owner}/{name}:{}", record.directory)
+                }
+                _ => format!("local:{}", record.directory),
+            };
+            skills.push(SkillLockEntry {
+                directory: record.directory.clone(),
+                name: record.name.clone(),
+                branch: record
+                    .repo_branch
+                    .clone()
+                    .filter(|_| record.source.is_none() && record.repo_owner.is_some()),
+                source,
+                commit: record.source_commit.clone(),
+                // The lock pins what is on disk, including local edits.
+                content_hash: Self::hash_skill_dir(&dir)?,
+                apps: record.apps.clone(),
+            });
+        }
+        skills.sort_by(|a, b| a.directory.cmp(&b.directory));
+
+        Ok(SkillLock {
+            version: SKILLS_LOCK_VERSION,
+            skills,
+        })
+    }
+
+    pub fn write_lock(path: &Path) -> Result<SkillLock, AppError> {
+        let lock = Self::build_lock()?;
+        crate::config::write_json_file(path, &lock)?;
+        Ok(lock)
+    }
+
+    pub fn read_lock(path: &Path) -> Result<SkillLock, AppError> {
+        let lock: SkillLock = crate::config::read_json_file(path)?;
+        if lock.version > SKILLS_LOCK_VERSION {
+            return Err(AppError::Message(format!(
+                "{} 的版本 {} 高于当前支持的版本 {SKILLS_LOCK_VERSION}，请升级 cc-switch",
+                path.display(),
+                lock.version
+            )));
+        }
+        Ok(lock)
+    }
+
+    /// Reproduce the locked skill set: fetch each skill at its locked commit, verify the
+    /// content hash before writing anything, and apply the locked app enablement.
+    pub async fn install_from_lock(&self, path: &Path) -> Result<Vec<SkillLockResult>, AppError> {
+        let lock = Self::read_lock(path)?;
+        self.install_lock_entries(&lock.skills).await
+    }
+
+    /// Install the given lock entries (also used when merging a config bundle).
+    pub async fn install_lock_entries(
+        &self,
+        entries: &[SkillLockEntry],
+    ) -> Result<Vec<SkillLockResult>, AppError> {
+        let mut index = Self::load_index()?;
+        let _ = Self::migrate_ssot_if_pending(&mut index)?;
+
+        let mut results = Vec::new();
+        for entry in entries {
+            let outcome = match self.install_lock_entry(&mut index, entry).await {
+                Ok(outcome) => outcome,
+                Err(e) => SkillLockOutcome::Failed(e.to_string()),
+            };
+            results.push(SkillLockResult {
+                directory: entry.directory.clone(),
+                outcome,
+            });
+        }
+        Ok(results)
+    }
+
+    async fn install_lock_entry(
+        &self,
+        index: &mut SkillsIndex,
+        entry: &SkillLockEntry,
+    ) -> Result<SkillLockOutcome, AppError> {
+        if entry.directory.is_empty()
+            || entry.directory.starts_with('.')
+            || entry.directory.contains(['/', '\\'])
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid skill directory in lock: '{}'",
+                entry.directory
+            )));
+        }
+
+        let dest = Self::get_ssot_dir()?.join(&entry.directory);
+        if dest.exists() && Self::hash_skill_dir(&dest)? == entry.content_hash {
+            let record = index
+                .skills
+                .get(&entry.directory)
+                .cloned()
+                .unwrap_or_else(|| Self::record_from_lock(entry));
+            Self::apply_lock_record(index, record, entry)?;
+            return Ok(SkillLockOutcome::Unchanged);
+        }
+
+        if entry.source.starts_with("local:") {
+            return Err(AppError::Message(format!(
+                "本地 Skill '{}' 无法从 lock 获取，请先将其复制到 ~/.cc-switch/skills/",
+                entry.directory
+            )));
+        }
+
+        let (temp_dir, skill_dir, _) = match SkillSource::parse(&entry.source)? {
+            Some(source) => {
+                let source = Self::pin_lock_source(source, entry)?;
+                let (temp_dir, skill_dir, commit) = self.fetch_source(&source).await?;
+                if let (Some(pinned), Some(fetched)) = (&entry.commit, &commit) {
+                    if !pinned.eq_ignore_ascii_case(fetched) {
+                        let _ = fs::remove_dir_all(&temp_dir);
+                        return Err(AppError::Message(format!(
+                            "Skill '{}' 的来源提交与 lock 不一致（lock: {pinned}，实际: {fetched}），已拒绝安装",
+                            entry.directory
+                        )));
+                    }
+                }
+                (temp_dir, skill_dir, commit)
+            }
+            None => {
+                let (repo, directory) = Self::parse_repo_key(&entry.source, entry)?;
+                let (temp_dir, commit) = timeout(
+                    std::time::Duration::from_secs(60),
+                    self.download_repo_at(&repo, entry.commit.as_deref()),
+                )
+                .await
+                .map_err(|_| {
+                    AppError::Message(format_skill_error(
+                        "DOWNLOAD_TIMEOUT",
+                        &[
+                            ("owner", repo.owner.as_str()),
+                            ("name", repo.name.as_str()),
+                            ("timeout", "60"),
+                        ],
+                        Some("checkNetwork"),
+                    ))
+                })??;
+                match Self::find_skill_dir_in_repo(&temp_dir, &directory) {
+                    Ok(Some(dir)) => (temp_dir, dir, commit),
+                    other => {
+                        let _ = fs::remove_dir_all(&temp_dir);
+                        other?;
+                        return Err(AppError::Message(format_skill_error(
+                            "SKILL_DIR_NOT_FOUND",
+                            &[("directory", directory.as_str())],
+                            Some("checkRepoUrl"),
+                        )));
+                    }
+                }
+            }
+        };
+
+        let result = (|| {
+            let fetched_hash = Self::hash_skill_dir(&skill_dir)?;
+            if fetched_hash != entry.content_hash {
+                let unpinned = if entry.commit.is_none() {
+                    "；该来源没有固定提交，上游内容已变化"
+                } else {
+                    ""
+                };
+                return Err(AppError::Message(format!(
+                    "Skill '{}' 内容哈希不匹配（lock: {}，实际: {}）{unpinned}，已拒绝安装",
+                    entry.directory, entry.content_hash, fetched_hash
+                )));
+            }
+
+            if dest.exists() {
+                fs::remove_dir_all(&dest).map_err(|e| AppError::io(&dest, e))?;
+            }
+            Self::copy_dir_recursive(&skill_dir, &dest)?;
+            Ok(())
+        })();
+        let _ = fs::remove_dir_all(&temp_dir);
+        result?;
+
+        let mut record = index
+            .skills
+            .get(&entry.directory)
+            .cloned()
+            .unwrap_or_else(|| Self::record_from_lock(entry));
+        if let Ok(meta) = Self::parse_skill_metadata_static(&dest.join("SKILL.md")) {
+            record.description = meta.description.or(record.description);
+        }
+        Self::apply_lock_record(index, record, entry)?;
+        Ok(SkillLockOutcome::Installed)
+    }
+
+    fn parse_repo_key(key: &str, entry: &SkillLockEntry) -> Result<(SkillRepo, String), AppError> {
+        let invalid = || AppError::InvalidInput(format!("Invalid skill source in lock: '{key}'"));
+        let (repo, directory) = key.split_once(':').ok_or_else(invalid)?;
+        let (owner, name) = repo.split_once('/').ok_or_else(invalid)?;
+        Ok((
+            SkillRepo {
+                owner: owner.to_string(),
+                name: name.to_string(),
+                branch: entry.branch.clone().unwrap_or_default(),
+                enabled: true,
+            },
+            directory.to_string(),
+        ))
+    }
+
+    /// Point a git source at the commit recorded in the lock.
+    ///
+    /// Git entries without a commit cannot be reproduced once the branch moves, so they are
+    /// rejected before cloning. ZIP and `file://` sources have no ref to pin; they rely on
+    /// the content hash (and on the archive commit when the lock recorded one).
+    fn pin_lock_source(
+        source: SkillSource,
+        entry: &SkillLockEntry,
+    ) -> Result<SkillSource, AppError> {
+        match source {
+            SkillSource::Git { url, subdir, .. } => {
+                let Some(commit) = entry.commit.clone().filter(|c| is_commit_sha(c)) else {
+                    return Err(AppError::Message(format!(
+                        "Skill '{}' 的 lock 条目未记录提交，无法按固定版本安装 {}；请重新安装后再生成 lock",
+                        entry.directory, entry.source
+                    )));
+                };
+                Ok(SkillSource::Git {
+                    url,
+                    reference: Some(commit),
+                    subdir,
+                })
+            }
+            other => Ok(other),
+        }
+    }
+
+    fn record_from_lock(entry: &SkillLockEntry) -> InstalledSkill {
+        let source = SkillSource::parse(&entry.source).ok().flatten();
+        let repo = if source.is_none() && !entry.source.starts_with("local:") {
+            Self::parse_repo_key(&entry.source, entry).ok()
+        } else {
+            None
+        };
+
+        InstalledSkill {
+            id: entry.source.clone(),
+            name: entry.name.clone(),
+            description: None,
+            directory: entry.directory.clone(),
+            readme_url: repo.as_ref().map(|(repo, directory)| {
+                format!(
+                    "https://github.com/{}/{}/tree/{}/{}",
+                    repo.owner,
+                    repo.name,
+                    entry.branch.as_deref().unwrap_or("main"),
+                    directory
+                )
+            }),
+            repo_owner: repo.as_ref().map(|(r, _)| r.owner.clone()),
+            repo_name: repo.as_ref().map(|(r, _)| r.name.clone()),
+            repo_branch: repo
+                .as_ref()
+                .map(|_| entry.branch.clone().unwrap_or_default()),
+            apps: SkillApps::default(),
+            installed_at: Utc::now().timestamp(),
+            source: source.map(|s| s.to_string()),
+            source_commit: None,
+            content_hash: None,
+        }
+    }
+
+    /// Persist a record with the locked commit/hash/apps and sync app dirs accordingly.
+    fn apply_lock_record(
+        index: &mut SkillsIndex,
+        mut record: InstalledSkill,
+        entry: &SkillLockEntry,
+    ) -> Result<(), AppError> {
+        record.source_commit = entry.commit.clone().or(record.source_commit);
+        record.content_hash = Some(entry.content_hash.clone());
+        record.apps = entry.apps.clone();
+
+        index.skills.insert(entry.directory.clone(), record.clone());
+        Database::init()?.save_skill(&record)?;
+
+        for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            if record.apps.is_enabled_for(&app) {
+                Self::sync_to_app_dir(&record.directory, &app, index.sync_method)?;
+            } else {
+                Self::remove_from_app(&record.directory, &app)?;
+            }
+        }
+        Ok(())
+    }
+
+    // ---------------------------------------------------------------------
+    // Authoring (skills new / skills pack)
+    // ---------------------------------------------------------------------
+
+    /// Scaffold a new skill in the SSOT dir, register it as `local:<dir>` and sync it.
+    pub fn create_local(options: &NewSkillOptions) -> Result<InstalledSkill, AppError> {
+        let directory = options.directory.trim();
+        if directory.is_empty()
+            || directory.len() > skill_lint::MAX_NAME_LEN
+            || !directory
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            || directory.starts_with('-')
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid skill name '{directory}': use up to {} lowercase letters, digits and hyphens",
+                skill_lint::MAX_NAME_LEN
+            )));
+        }
+        let description = options.description.trim();
+        if description.is_empty() || description.contains('\n') {
+            return Err(AppError::InvalidInput(
+                "Skill description must be a single non-empty line".to_string(),
+            ));
+        }
+        if description.chars().count() > skill_lint::MAX_DESCRIPTION_LEN {
+            return Err(AppError::InvalidInput(format!(
+                "Skill description exceeds {} characters",
+                skill_lint::MAX_DESCRIPTION_LEN
+            )));
+        }
+
+        let mut index = Self::load_index()?;
+        let _ = Self::migrate_ssot_if_pending(&mut index)?;
+        let dest = Self::get_ssot_dir()?.join(directory);
+        if index.skills.contains_key(directory) || dest.exists() {
+            return Err(AppError::Message(format!("Skill '{directory}' 已存在")));
+        }
+
+        let title = directory
+            .split('-')
+            .filter(|w| !w.is_empty())
+            .map(|w| {
+                let mut chars = w.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Quote the description so YAML special characters (`:`, `#`, ...) stay literal.
+        let quoted =
+            serde_json::to_string(description).map_err(|e| AppError::Message(e.to_string()))?;
+        let render = |template: &str| {
+            template
+                .replace("{name}", directory)
+                .replace("{description}", &quoted)
+                .replace("{title}", &title)
+        };
+
+        let write = |relative: &str, content: &str| -> Result<(), AppError> {
+            let path = dest.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+            }
+            fs::write(&path, content).map_err(|e| AppError::io(&path, e))
+        };
+
+        let mut skill_md = render(SKILL_MD_TEMPLATE);
+        if options.with_scripts {
+            skill_md.push_str(SCRIPTS_SECTION_TEMPLATE);
+            write("scripts/example.sh", &render(EXAMPLE_SCRIPT_TEMPLATE))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let script = dest.join("scripts/example.sh");
+                fs::set_permissions(&script, fs::Permissions::from_mode(0o755))
+                    .map_err(|e| AppError::io(&script, e))?;
+            }
+        }
+        if options.with_references {
+            skill_md.push_str(REFERENCES_SECTION_TEMPLATE);
+            write("references/REFERENCE.md", &render(REFERENCE_DOC_TEMPLATE))?;
+        }
+        write("SKILL.md", &skill_md)?;
+
+        let mut apps = SkillApps::default();
+        for app in &options.apps {
+            apps.set_enabled_for(app, true);
+        }
+        let record = InstalledSkill {
+            id: format!("local:{directory}"),
+            name: directory.to_string(),
+            description: Some(description.to_string()),
+            directory: directory.to_string(),
+            readme_url: None,
+            repo_owner: None,
+            repo_name: None,
+            repo_branch: None,
+            apps,
+            installed_at: Utc::now().timestamp(),
+            source: None,
+            source_commit: None,
+            content_hash: Some(Self::hash_skill_dir(&dest)?),
+        };
+        index.skills.insert(directory.to_string(), record.clone());
+        Self::save_index(&index)?;
+
+        for app in &options.apps {
+            Self::sync_to_app_dir(directory, app, index.sync_method)?;
+        }
+        Ok(record)
+    }
+
+    /// Zip an installed skill as `<directory>/...` for sharing; installable via a ZIP URL
+    /// or `file://` after extracting.
+    pub fn pack(directory_or_id: &str, output: &Path) -> Result<PathBuf, AppError> {
+        let index = Self::load_index()?;
+        let Some(directory) = Self::resolve_directory_from_input(&index, directory_or_id) else {
+            return Err(AppError::Message(format!(
+                "未找到已安装的 Skill: {directory_or_id}"
+            )));
+        };
+        let source = Self::get_ssot_dir()?.join(&directory);
+        if !source.is_dir() {
+            return Err(AppError::Message(format!(
+                "Skill {directory} 不存在于 SSOT: {}",
+                source.display()
+            )));
+        }
+
+        let output = if output.is_dir() {
+            output.join(format!("{directory}.zip"))
+        } else {
+            output.to_path_buf()
+        };
+        let file = fs::File::create(&output).map_err(|e| AppError::io(&output, e))?;
+        let mut writer = zip::ZipWriter::new(file);
+
+        for relative in Self::file_digests(&source)?.keys() {
+            let path = source.join(relative);
+            let mut options = zip::write::SimpleFileOptions::default();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = fs::metadata(&path)
+                    .map_err(|e| AppError::io(&path, e))?
+                    .permissions()
+                    .mode();
+                options = options.unix_permissions(mode & 0o777);
+            }
+            let bytes = fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+            let zip_err = |e: zip::result::ZipError| {
+                AppError::Message(format!("写入 ZIP 失败 {}: {e}", output.display()))
+            };
+            writer
+                .start_file(format!("{directory}/{relative}"), options)
+                .map_err(zip_err)?;
+            std::io::Write::write_all(&mut writer, &bytes).map_err(|e| AppError::io(&output, e))?;
+        }
+        writer
+            .finish()
+            .map_err(|e| AppError::Message(format!("写入 ZIP 失败 {}: {e}", output.display())))?;
+
+        Ok(output)
+    }
+
+    // ---------------------------------------------------------------------
+    // Unmanaged scan / import
+    // ---------------------------------------------------------------------
+
+    pub fn scan_unmanaged() -> Result<Vec<UnmanagedSkill>, AppError> {
+        let index = Self::load_index()?;
+        let managed: HashSet<String> = index.skills.keys().cloned().collect();
+
+        let mut unmanaged: HashMap<String, UnmanagedSkill> = HashMap::new();
+
+        for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            let app_dir = match Self::get_app_skills_dir(&app) {
+                Ok(d) => d,
+                Err(_) => continue,
+            };
+            if !app_dir.exists() {
+                continue;
+            }
+
+            for entry in fs::read_dir(&app_dir).map_err(|e| AppError::io(&app_dir, e))? {
+                let entry = entry.map_err(|e| AppError::io(&app_dir, e))?;
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+
+                let dir_name = entry.file_name().to_string_lossy().to_string();
+                if dir_name.starts_with('.') {
+                    continue;
+                }
+                if managed.contains(&dir_name) {
+                    continue;
+                }
+
+                let skill_md = path.join("SKILL.md");
+                let (name, description) = if skill_md.exists() {
+                    match Self::parse_skill_metadata_static(&skill_md) {
+                        Ok(meta) => (
+                            meta.name.unwrap_or_else(|| dir_name.clone()),
+                            meta.description,
+                        ),
+                        Err(_) => (dir_name.clone(), None),
+                    }
+                } else {
+                    (dir_name.clone(), None)
+                };
+
+                let app_str = match app {
+                    AppType::Claude => "claude",
+                    AppType::Codex => "codex",
+                    AppType::Gemini => "gemini",
+                };
+
+                unmanaged
+                    .entry(dir_name.clone())
+                    .and_modify(|s| s.found_in.push(app_str.to_string()))
+                    .or_insert(UnmanagedSkill {
+                        directory: dir_name,
+                        name,
+                        description,
+                        found_in: vec![app_str.to_string()],
+                    });
+            }
+        }
+
+        Ok(unmanaged.into_values().collect())
+    }
+
+    pub fn import_from_apps(directories: Vec<String>) -> Result<Vec<InstalledSkill>, AppError> {
+        let mut index = Self::load_index()?;
+        let ssot_dir = Self::get_ssot_dir()?;
+        let mut imported = Vec::new();
+
+        for dir_name in directories {
+            let mut source_path: Option<PathBuf> = None;
+            let mut found_in: Vec<AppType> = Vec::new();
 
             for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
                 if let Ok(app_dir) = Self::get_app_skills_dir(&app) {
@@ -1024,6 +2300,9 @@ impl SkillService {
                     repo_branch: None,
                     apps: SkillApps::default(),
                     installed_at: Utc::now().timestamp(),
+                    source: None,
+                    source_commit: None,
+                    content_hash: Self::hash_skill_dir(&dest).ok(),
                 });
 
             record.apps.merge_enabled(&apps);
@@ -1050,16 +2329,47 @@ impl SkillService {
         repos: Vec<SkillRepo>,
     ) -> Result<Vec<DiscoverableSkill>, AppError> {
         let enabled_repos: Vec<SkillRepo> = repos.into_iter().filter(|r| r.enabled).collect();
-        let tasks = enabled_repos
-            .iter()
-            .map(|repo| self.fetch_repo_skills(repo));
-        let results: Vec<Result<Vec<DiscoverableSkill>, AppError>> = join_all(tasks).await;
-
+        let mut cache = RepoIndexCache::load();
         let mut skills = Vec::new();
-        for (repo, result) in enabled_repos.into_iter().zip(results.into_iter()) {
-            match result {
-                Ok(repo_skills) => skills.extend(repo_skills),
-                Err(e) => log::warn!("获取仓库 {}/{} 技能失败: {}", repo.owner, repo.name, e),
+
+        if self.offline {
+            for repo in &enabled_repos {
+                match cache.get(repo) {
+                    Some(cached) => skills.extend(cached.skills.iter().cloned()),
+                    None => {
+                        log::warn!("仓库 {}/{} 尚无缓存，离线模式下跳过", repo.owner, repo.name)
+                    }
+                }
+            }
+        } else {
+            let tasks = enabled_repos
+                .iter()
+                .map(|repo| self.refresh_repo_index(repo, cache.get(repo)));
+            let results: Vec<Result<CachedRepoIndex, AppError>> = join_all(tasks).await;
+
+            for (repo, result) in enabled_repos.iter().zip(results.into_iter()) {
+                match result {
+                    Ok(entry) => {
+                        skills.extend(entry.skills.iter().cloned());
+                        cache.insert(repo, entry);
+                    }
+                    Err(e) => match cache.get(repo) {
+                        Some(cached) => {
+                            log::warn!(
+                                "获取仓库 {}/{} 技能失败，使用缓存: {}",
+                                repo.owner,
+                                repo.name,
+                                e
+                            );
+                            skills.extend(cached.skills.iter().cloned());
+                        }
+                        None => log::warn!("获取仓库 {}/{} 技能失败: {}", repo.owner, repo.name, e),
+                    },
+                }
+            }
+
+            if let Err(e) = cache.save() {
+                log::warn!("保存技能仓库缓存失败: {e}");
             }
         }
 
@@ -1166,26 +2476,105 @@ impl SkillService {
         Ok(())
     }
 
-    async fn fetch_repo_skills(
+    /// Refresh a repo's cached skill metadata, reusing it when the archive ETag is unchanged.
+    async fn refresh_repo_index(
         &self,
         repo: &SkillRepo,
-    ) -> Result<Vec<DiscoverableSkill>, AppError> {
-        let temp_dir = timeout(std::time::Duration::from_secs(60), self.download_repo(repo))
-            .await
-            .map_err(|_| {
-                AppError::Message(format_skill_error(
-                    "DOWNLOAD_TIMEOUT",
-                    &[
-                        ("owner", repo.owner.as_str()),
-                        ("name", repo.name.as_str()),
-                        ("timeout", "60"),
-                    ],
-                    Some("checkNetwork"),
-                ))
-            })??;
+        cached: Option<&CachedRepoIndex>,
+    ) -> Result<CachedRepoIndex, AppError> {
+        timeout(
+            std::time::Duration::from_secs(60),
+            self.refresh_repo_index_inner(repo, cached),
+        )
+        .await
+        .map_err(|_| {
+            AppError::Message(format_skill_error(
+                "DOWNLOAD_TIMEOUT",
+                &[
+                    ("owner", repo.owner.as_str()),
+                    ("name", repo.name.as_str()),
+                    ("timeout", "60"),
+                ],
+                Some("checkNetwork"),
+            ))
+        })?
+    }
+
+    async fn refresh_repo_index_inner(
+        &self,
+        repo: &SkillRepo,
+        cached: Option<&CachedRepoIndex>,
+    ) -> Result<CachedRepoIndex, AppError> {
+        let mut branches: Vec<&str> = Vec::new();
+        if let Some(cached) = cached {
+            branches.push(cached.resolved_branch.as_str());
+        }
+        if !repo.branch.trim().is_empty() {
+            branches.push(repo.branch.as_str());
+        }
+        branches.extend(["main", "master"]);
+        let mut seen = HashSet::new();
+        branches.retain(|b| seen.insert(*b));
+
+        let mut last_error: Option<AppError> = None;
+        for branch in branches {
+            let url = format!(
+                "https://github.com/{}/{}/archive/refs/heads/{}.zip",
+                repo.owner, repo.name, branch
+            );
+            let etag = cached
+                .filter(|c| c.resolved_branch == branch)
+                .and_then(|c| c.etag.as_deref());
+
+            match skill_source::download_if_changed(&self.http_client, &url, etag).await {
+                Ok(ConditionalDownload::NotModified) => {
+                    if let Some(cached) = cached {
+                        return Ok(CachedRepoIndex {
+                            checked_at: Utc::now().timestamp(),
+                            ..cached.clone()
+                        });
+                    }
+                }
+                Ok(ConditionalDownload::Fetched { bytes, etag }) => {
+                    let temp_dir = Self::create_temp_dir()?;
+                    let scanned =
+                        skill_source::extract_zip_archive(&bytes, &temp_dir).and_then(|commit| {
+                            Ok((commit, Self::collect_repo_skills(repo, &temp_dir)?))
+                        });
+                    let _ = fs::remove_dir_all(&temp_dir);
+                    let (commit, skills) = scanned?;
+
+                    return Ok(CachedRepoIndex {
+                        owner: repo.owner.clone(),
+                        name: repo.name.clone(),
+                        branch: repo.branch.clone(),
+                        resolved_branch: branch.to_string(),
+                        etag,
+                        commit,
+                        checked_at: Utc::now().timestamp(),
+                        skills,
+                    });
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
 
+        Err(last_error.unwrap_or_else(|| {
+            AppError::Message(format_skill_error(
+                "DOWNLOAD_FAILED",
+                &[],
+                Some("checkNetwork"),
+            ))
+        }))
+    }
+
+    /// Extract discoverable skill metadata from an extracted repo archive.
+    fn collect_repo_skills(
+        repo: &SkillRepo,
+        temp_dir: &Path,
+    ) -> Result<Vec<DiscoverableSkill>, AppError> {
         let mut skills = Vec::new();
-        let skill_dirs = Self::scan_skill_dirs(&temp_dir)?;
+        let skill_dirs = Self::scan_skill_dirs(temp_dir)?;
         for path in skill_dirs {
             let skill_md = path.join("SKILL.md");
             if !skill_md.exists() {
@@ -1208,7 +2597,7 @@ impl SkillService {
                 continue;
             }
 
-            let relative = path.strip_prefix(&temp_dir).unwrap_or(&path);
+            let relative = path.strip_prefix(temp_dir).unwrap_or(&path);
             let relative_path = relative.to_string_lossy().replace('\\', "/");
             let readme_path = if relative_path.trim().is_empty() {
                 directory.clone()
@@ -1231,7 +2620,6 @@ impl SkillService {
             });
         }
 
-        let _ = fs::remove_dir_all(&temp_dir);
         Ok(skills)
     }
 
@@ -1279,7 +2667,8 @@ impl SkillService {
         Ok(meta)
     }
 
-    async fn download_repo(&self, repo: &SkillRepo) -> Result<PathBuf, AppError> {
+    /// Create a temp dir that outlives this call; callers remove it when done.
+    fn create_temp_dir() -> Result<PathBuf, AppError> {
         let temp_dir = tempfile::tempdir().map_err(|e| {
             AppError::localized(
                 "skills.tempdir_failed",
@@ -1289,6 +2678,37 @@ impl SkillService {
         })?;
         let temp_path = temp_dir.path().to_path_buf();
         let _ = temp_dir.keep();
+        Ok(temp_path)
+    }
+
+    /// Download a repo archive pinned to `commit` (falls back to the branch when unknown).
+    async fn download_repo_at(
+        &self,
+        repo: &SkillRepo,
+        commit: Option<&str>,
+    ) -> Result<(PathBuf, Option<String>), AppError> {
+        let Some(commit) = commit else {
+            return self.download_repo(repo).await;
+        };
+
+        let temp_path = Self::create_temp_dir()?;
+        let url = format!(
+            "https://github.com/{}/{}/archive/{}.zip",
+            repo.owner, repo.name, commit
+        );
+        match self.download_and_extract(&url, &temp_path).await {
+            Ok(found) => Ok((temp_path, found.or_else(|| Some(commit.to_string())))),
+            Err(e) => {
+                let _ = fs::remove_dir_all(&temp_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Download a repo archive into a temp dir; also returns the archive's commit SHA
+    /// (GitHub stores it as the ZIP comment).
+    async fn download_repo(&self, repo: &SkillRepo) -> Result<(PathBuf, Option<String>), AppError> {
+        let temp_path = Self::create_temp_dir()?;
 
         let branches = if repo.branch.trim().is_empty() {
             vec!["main", "master"]
@@ -1304,7 +2724,7 @@ impl SkillService {
             );
 
             match self.download_and_extract(&url, &temp_path).await {
-                Ok(()) => return Ok(temp_path),
+                Ok(commit) => return Ok((temp_path, commit)),
                 Err(e) => {
                     last_error = Some(e);
                     continue;
@@ -1321,97 +2741,13 @@ impl SkillService {
         }))
     }
 
-    async fn download_and_extract(&self, url: &str, dest: &Path) -> Result<(), AppError> {
-        let response = self.http_client.get(url).send().await.map_err(|e| {
-            AppError::localized(
-                "skills.download_failed",
-                format!("下载失败: {e}"),
-                format!("Download failed: {e}"),
-            )
-        })?;
-
-        if !response.status().is_success() {
-            let status = response.status().as_u16().to_string();
-            return Err(AppError::Message(format_skill_error(
-                "DOWNLOAD_FAILED",
-                &[("status", status.as_str())],
-                match status.as_str() {
-                    "403" => Some("http403"),
-                    "404" => Some("http404"),
-                    "429" => Some("http429"),
-                    _ => Some("checkNetwork"),
-                },
-            )));
-        }
-
-        let bytes = response.bytes().await.map_err(|e| {
-            AppError::localized(
-                "skills.download_failed",
-                format!("读取下载内容失败: {e}"),
-                format!("Failed to read download bytes: {e}"),
-            )
-        })?;
-
-        let cursor = std::io::Cursor::new(bytes);
-        let mut archive = zip::ZipArchive::new(cursor).map_err(|e| {
-            AppError::localized(
-                "skills.zip_invalid",
-                format!("ZIP 文件损坏: {e}"),
-                format!("Invalid ZIP: {e}"),
-            )
-        })?;
-
-        let root_name = if !archive.is_empty() {
-            let first_file = archive.by_index(0).map_err(|e| {
-                AppError::localized(
-                    "skills.zip_invalid",
-                    format!("读取 ZIP 失败: {e}"),
-                    format!("Failed to read ZIP: {e}"),
-                )
-            })?;
-            let name = first_file.name();
-            name.split('/').next().unwrap_or("").to_string()
-        } else {
-            return Err(AppError::Message(format_skill_error(
-                "EMPTY_ARCHIVE",
-                &[],
-                Some("checkRepoUrl"),
-            )));
-        };
-
-        for i in 0..archive.len() {
-            let mut file = archive
-                .by_index(i)
-                .map_err(|e| AppError::Message(e.to_string()))?;
-            let file_path = file.name();
-
-            let relative_path =
-                if let Some(stripped) = file_path.strip_prefix(&format!("{root_name}/")) {
-                    stripped
-                } else {
-                    continue;
-                };
-            if relative_path.is_empty() {
-                continue;
-            }
-
-            let outpath = dest.join(relative_path);
-            if file.is_dir() {
-                fs::create_dir_all(&outpath).map_err(|e| AppError::io(&outpath, e))?;
-            } else {
-                if let Some(parent) = outpath.parent() {
-                    fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
-                }
-                let mut outfile =
-                    fs::File::create(&outpath).map_err(|e| AppError::io(&outpath, e))?;
-                std::io::copy(&mut file, &mut outfile).map_err(|e| AppError::IoContext {
-                    context: format!("写入文件失败: {}", outpath.display()),
-                    source: e,
-                })?;
-            }
-        }
-
-        Ok(())
+    async fn download_and_extract(
+        &self,
+        url: &str,
+        dest: &Path,
+    ) -> Result<Option<String>, AppError> {
+        let bytes = skill_source::download_bytes(&self.http_client, url).await?;
+        skill_source::extract_zip_archive(&bytes, dest)
     }
 
     fn scan_skill_dirs(root: &Path) -> Result<Vec<PathBuf>, AppError> {
@@ -1477,7 +2813,7 @@ impl SkillService {
         Ok(matches.into_iter().next())
     }
 
-    fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<(), AppError> {
+    pub(crate) fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<(), AppError> {
         fs::create_dir_all(dest).map_err(|e| AppError::io(dest, e))?;
         for entry in fs::read_dir(src).map_err(|e| AppError::io(src, e))? {
             let entry = entry.map_err(|e| AppError::io(src, e))?;
@@ -0,0 +1,353 @@
+//! Per-app skill packaging
+//!
+//! Skills are stored once in the SSOT dir as `SKILL.md` + supporting files. Each app gets
+//! them in its native layout through a [`SkillAdapter`]:
+//! - Claude: `~/.claude/skills/<dir>`, the SSOT directory as-is (symlink or copy)
+//! - Codex: `~/.codex/skills/<dir>`; SKILL.md front matter is reduced to the `name` and
+//!   single-line `description` Codex accepts, which forces a copy when rewriting is needed
+//! - Gemini: `~/.gemini/extensions/<dir>` with a generated `gemini-extension.json` and a
+//!   `GEMINI.md` context file built from SKILL.md; supporting files are linked or copied
+//!
+//! Adapters also undo their own layout on disable, including leftovers from the old
+//! plain-directory layout.
+
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app_config::AppType;
+use crate::config::write_json_file;
+use crate::error::AppError;
+use crate::services::skill::{SkillService, SyncMethod};
+
+/// Marker file placed in generated layouts so cleanup never touches user-owned directories.
+pub const MANAGED_MARKER: &str = ".cc-switch-skill";
+
+const CODEX_MAX_NAME_LEN: usize = 100;
+const CODEX_MAX_DESCRIPTION_LEN: usize = 500;
+
+/// Translates an SSOT skill into one app's native layout.
+pub trait SkillAdapter: Send + Sync {
+    /// Where the skill lives in the app's layout.
+    fn target_path(&self, directory: &str) -> Result<PathBuf, AppError>;
+
+    /// Install or refresh `source` (the SSOT skill dir) for the app.
+    fn sync(&self, directory: &str, source: &Path, method: SyncMethod) -> Result<(), AppError>;
+
+    /// Remove everything `sync` created for the skill.
+    fn remove(&self, directory: &str) -> Result<(), AppError>;
+}
+
+pub fn adapter_for(app: &AppType) -> Box<dyn SkillAdapter> {
+    match app {
+        AppType::Claude => Box::new(DirectoryAdapter {
+            app: AppType::Claude,
+        }),
+        AppType::Codex => Box::new(CodexAdapter),
+        AppType::Gemini => Box::new(GeminiExtensionAdapter),
+    }
+}
+
+/// Split SKILL.md into its YAML front matter and body.
+pub fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let content = content.trim_start_matches('\u{feff}');
+    let Some(rest) = content.strip_prefix("---") else {
+        return (None, content);
+    };
+    match rest.split_once("\n---") {
+        Some((front_matter, body)) => {
+            // Drop the remainder of the closing delimiter line.
+            let body = body.split_once('\n').map(|(_, b)| b).unwrap_or("");
+            (Some(front_matter), body)
+        }
+        None => (None, content),
+    }
+}
+
+fn read_front_matter(source: &Path) -> Result<(serde_yaml::Mapping, String), AppError> {
+    let path = source.join("SKILL.md");
+    let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+    let (front_matter, body) = split_front_matter(&content);
+    let map = front_matter
+        .and_then(|fm| serde_yaml::from_str::<serde_yaml::Value>(fm).ok())
+        .and_then(|v| v.as_mapping().cloned())
+        .unwrap_or_default();
+    Ok((map, body.to_string()))
+}
+
+fn field(map: &serde_yaml::Mapping, key: &str) -> Option<String> {
+    map.get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|s| !s.is_empty())
+}
+
+fn truncate_chars(value: &str, max: usize) -> String {
+    if value.chars().count() <= max {
+        return value.to_string();
+    }
+    let mut out: String = value.chars().take(max.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+fn clear_path(path: &Path) -> Result<(), AppError> {
+    if path.exists() || SkillService::is_symlink(path) {
+        SkillService::remove_path(path)?;
+    }
+    Ok(())
+}
+
+/// Link (per `method`) or copy one SSOT entry into a generated layout.
+fn place(source: &Path, dest: &Path, method: SyncMethod) -> Result<(), AppError> {
+    let copy = || {
+        if source.is_dir() {
+            SkillService::copy_dir_recursive(source, dest)
+        } else {
+            fs::copy(source, dest)
+                .map(|_| ())
+                .map_err(|e| AppError::io(dest, e))
+        }
+    };
+    match method {
+        SyncMethod::Copy => copy(),
+        SyncMethod::Symlink => SkillService::create_symlink(source, dest),
+        SyncMethod::Auto => SkillService::create_symlink(source, dest).or_else(|err| {
+            log::warn!(
+                "Symlink 创建失败，将回退到文件复制: {} -> {}. 错误: {err}",
+                source.display(),
+                dest.display()
+            );
+            copy()
+        }),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Claude: plain skills directory
+// ----------------------------------------------------------------------------
+
+pub struct DirectoryAdapter {
+    app: AppType,
+}
+
+impl SkillAdapter for DirectoryAdapter {
+    fn target_path(&self, directory: &str) -> Result<PathBuf, AppError> {
+        Ok(SkillService::get_app_skills_dir(&self.app)?.join(directory))
+    }
+
+    fn sync(&self, directory: &str, source: &Path, method: SyncMethod) -> Result<(), AppError> {
+        let dest = self.target_path(directory)?;
+        if let Some(parent) = dest.parent() {
+            // D5: allow creating target app dirs during skills sync.
+            fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+        clear_path(&dest)?;
+        place(source, &dest, method)
+    }
+
+    fn remove(&self, directory: &str) -> Result<(), AppError> {
+        clear_path(&self.target_path(directory)?)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Codex: skills directory with a Codex-compatible SKILL.md
+// ----------------------------------------------------------------------------
+
+pub struct CodexAdapter;
+
+impl CodexAdapter {
+    /// SKILL.md rewritten for Codex, or `None` when the original is already compatible.
+    fn translated_skill_md(directory: &str, source: &Path) -> Result<Option<String>, AppError> {
+        let path = source.join("SKILL.md");
+        let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let (front_matter, body) = split_front_matter(&content);
+        let (map, _) = read_front_matter(source)?;
+
+        let name = truncate_chars(
+            &field(&map, "name").unwrap_or_else(|| directory.to_string()),
+            CODEX_MAX_NAME_LEN,
+        );
+        let description = truncate_chars(
+            &field(&map, "description").unwrap_or_else(|| name.clone()),
+            CODEX_MAX_DESCRIPTION_LEN,
+        );
+
+        let compatible = front_matter.is_some()
+            && map.len() == 2
+            && map.get("name").and_then(|v| v.as_str()) == Some(name.as_str())
+            && map.get("description").and_then(|v| v.as_str()) == Some(description.as_str());
+        if compatible {
+            return Ok(None);
+        }
+
+        let mut front_matter = serde_yaml::Mapping::new();
+        front_matter.insert("name".into(), name.into());
+        front_matter.insert("description".into(), description.into());
+        let front_matter = serde_yaml::to_string(&front_matter)
+            .map_err(|e| AppError::Message(format!("序列化 SKILL.md front matter 失败: {e}")))?;
+        Ok(Some(format!("---\n{front_matter}---\n{body}")))
+    }
+}
+
+impl SkillAdapter for CodexAdapter {
+    fn target_path(&self, directory: &str) -> Result<PathBuf, AppError> {
+        Ok(SkillService::get_app_skills_dir(&AppType::Codex)?.join(directory))
+    }
+
+    fn sync(&self, directory: &str, source: &Path, method: SyncMethod) -> Result<(), AppError> {
+        let Some(skill_md) = Self::translated_skill_md(directory, source)? else {
+            return DirectoryAdapter {
+                app: AppType::Codex,
+            }
+            .sync(directory, source, method);
+        };
+
+        // The rewritten SKILL.md can't be shared with the SSOT copy: link the other entries.
+        let dest = self.target_path(directory)?;
+        clear_path(&dest)?;
+        fs::create_dir_all(&dest).map_err(|e| AppError::io(&dest, e))?;
+        for entry in fs::read_dir(source).map_err(|e| AppError::io(source, e))? {
+            let entry = entry.map_err(|e| AppError::io(source, e))?;
+            let name = entry.file_name();
+            if name == "SKILL.md" {
+                continue;
+            }
+            place(&entry.path(), &dest.join(&name), method)?;
+        }
+        let path = dest.join("SKILL.md");
+        fs::write(&path, skill_md).map_err(|e| AppError::io(&path, e))?;
+        let marker = dest.join(MANAGED_MARKER);
+        fs::write(&marker, directory).map_err(|e| AppError::io(&marker, e))
+    }
+
+    fn remove(&self, directory: &str) -> Result<(), AppError> {
+        clear_path(&self.target_path(directory)?)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Gemini: extension with gemini-extension.json + GEMINI.md
+// ----------------------------------------------------------------------------
+
+pub struct GeminiExtensionAdapter;
+
+impl GeminiExtensionAdapter {
+    pub fn extensions_dir() -> PathBuf {
+        crate::gemini_config::get_gemini_dir().join("extensions")
+    }
+
+    fn context_file(directory: &str, source: &Path) -> Result<String, AppError> {
+        let (map, body) = read_front_matter(source)?;
+        let name = field(&map, "name").unwrap_or_else(|| directory.to_string());
+        let mut out = format!("# {name}\n\n");
+        if let Some(description) = field(&map, "description") {
+            out.push_str(&format!("Use this skill when: {description}\n\n"));
+        }
+        out.push_str(body.trim_start_matches('\n'));
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+impl SkillAdapter for GeminiExtensionAdapter {
+    fn target_path(&self, directory: &str) -> Result<PathBuf, AppError> {
+        Ok(Self::extensions_dir().join(directory))
+    }
+
+    fn sync(&self, directory: &str, source: &Path, method: SyncMethod) -> Result<(), AppError> {
+        let dest = self.target_path(directory)?;
+        if dest.exists() && !dest.join(MANAGED_MARKER).exists() {
+            return Err(AppError::Message(format!(
+                "Gemini 扩展目录已存在且不由 cc-switch 管理: {}",
+                dest.display()
+            )));
+        }
+        clear_path(&dest)?;
+        // Earlier versions synced Gemini skills as plain directories.
+        DirectoryAdapter {
+            app: AppType::Gemini,
+        }
+        .remove(directory)?;
+
+        fs::create_dir_all(&dest).map_err(|e| AppError::io(&dest, e))?;
+        for entry in fs::read_dir(source).map_err(|e| AppError::io(source, e))? {
+            let entry = entry.map_err(|e| AppError::io(source, e))?;
+            let name = entry.file_name();
+            if name == "SKILL.md" || name == "gemini-extension.json" || name == "GEMINI.md" {
+                continue;
+            }
+            place(&entry.path(), &dest.join(&name), method)?;
+        }
+
+        let context = dest.join("GEMINI.md");
+        fs::write(&context, Self::context_file(directory, source)?)
+            .map_err(|e| AppError::io(&context, e))?;
+        write_json_file(
+            &dest.join("gemini-extension.json"),
+            &json!({
+                "name": directory,
+                "version": "1.0.0",
+                "contextFileName": "GEMINI.md",
+            }),
+        )?;
+        let marker = dest.join(MANAGED_MARKER);
+        fs::write(&marker, directory).map_err(|e| AppError::io(&marker, e))
+    }
+
+    fn remove(&self, directory: &str) -> Result<(), AppError> {
+        let dest = self.target_path(directory)?;
+        if dest.join(MANAGED_MARKER).exists() {
+            clear_path(&dest)?;
+        }
+        DirectoryAdapter {
+            app: AppType::Gemini,
+        }
+        .remove(directory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_front_matter_from_body() {
+        let (fm, body) = split_front_matter("---\nname: a\n---\n# Title\n");
+        assert_eq!(fm, Some("\nname: a"));
+        assert_eq!(body, "# Title\n");
+
+        let (fm, body) = split_front_matter("# No front matter\n");
+        assert_eq!(fm, None);
+        assert_eq!(body, "# No front matter\n");
+    }
+
+    #[test]
+    fn codex_translation_keeps_compatible_skill_md() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            dir.path().join("SKILL.md"),
+            "---\nname: pdf\ndescription: Work with PDFs\n---\nBody\n",
+        )
+        .expect("write");
+        assert!(CodexAdapter::translated_skill_md("pdf", dir.path())
+            .expect("translate")
+            .is_none());
+
+        fs::write(
+            dir.path().join("SKILL.md"),
+            "---\nname: pdf\ndescription: |\n  Work with\n  PDFs\nallowed-tools: Bash\n---\nBody\n",
+        )
+        .expect("write");
+        let translated = CodexAdapter::translated_skill_md("pdf", dir.path())
+            .expect("translate")
+            .expect("rewritten");
+        assert_eq!(
+            translated,
+            "---\nname: pdf\ndescription: Work with PDFs\n---\nBody\n"
+        );
+    }
+}
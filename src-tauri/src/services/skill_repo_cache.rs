@@ -0,0 +1,106 @@
+//! Cached skill metadata for discovery repos
+//!
+//! `skills discover` used to download every enabled repo archive on each run. The extracted
+//! skill metadata is now cached in `~/.cc-switch/cache/skill-repos.json`, keyed by
+//! `owner/name@branch`, together with the archive's ETag. Refreshes send `If-None-Match`
+//! and reuse the cached metadata on `304 Not Modified`; `--offline` reads the cache only.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::config::{get_app_config_dir, write_json_file};
+use crate::error::AppError;
+use crate::services::skill::{DiscoverableSkill, SkillRepo};
+
+const REPO_CACHE_VERSION: u32 = 1;
+
+/// Cached discovery result for one repo/branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedRepoIndex {
+    pub owner: String,
+    pub name: String,
+    /// Branch as configured on the repo (may be empty)
+    pub branch: String,
+    /// Branch whose archive was actually downloaded (main/master fallback)
+    pub resolved_branch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Last time the entry was fetched or confirmed unchanged (Unix seconds)
+    pub checked_at: i64,
+    pub skills: Vec<DiscoverableSkill>,
+}
+
+impl CachedRepoIndex {
+    pub fn age_secs(&self) -> i64 {
+        (Utc::now().timestamp() - self.checked_at).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoIndexCache {
+    pub version: u32,
+    #[serde(default)]
+    pub repos: BTreeMap<String, CachedRepoIndex>,
+}
+
+impl Default for RepoIndexCache {
+    fn default() -> Self {
+        Self {
+            version: REPO_CACHE_VERSION,
+            repos: BTreeMap::new(),
+        }
+    }
+}
+
+impl RepoIndexCache {
+    /// 缓存文件路径（~/.cc-switch/cache/skill-repos.json）
+    pub fn path() -> PathBuf {
+        get_app_config_dir().join("cache").join("skill-repos.json")
+    }
+
+    /// 加载缓存；文件不存在、损坏或版本不兼容时返回空缓存
+    pub fn load() -> Self {
+        let path = Self::path();
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&content) {
+            Ok(cache) if cache.version == REPO_CACHE_VERSION => cache,
+            Ok(_) => Self::default(),
+            Err(e) => {
+                log::warn!("技能仓库缓存损坏，已忽略 {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        write_json_file(&Self::path(), self)
+    }
+
+    pub fn key(repo: &SkillRepo) -> String {
+        format!("{}/{}@{}", repo.owner, repo.name, repo.branch.trim()).to_lowercase()
+    }
+
+    pub fn get(&self, repo: &SkillRepo) -> Option<&CachedRepoIndex> {
+        self.repos.get(&Self::key(repo))
+    }
+
+    pub fn insert(&mut self, repo: &SkillRepo, entry: CachedRepoIndex) {
+        self.repos.insert(Self::key(repo), entry);
+    }
+
+    /// Drop every cached branch of a repo (after `skills repos remove`).
+    pub fn remove_repo(&mut self, owner: &str, name: &str) -> bool {
+        let prefix = format!("{owner}/{name}@").to_lowercase();
+        let before = self.repos.len();
+        self.repos.retain(|key, _| !key.starts_with(&prefix));
+        self.repos.len() != before
+    }
+}
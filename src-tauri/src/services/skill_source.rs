@@ -0,0 +1,653 @@
+//! Skill install sources beyond GitHub skill repos
+//!
+//! Supported install specs:
+//! - `file:///path/to/skill` — a local directory (or a directory containing one skill)
+//! - `git+https://host/org/repo[@ref][#subdir]` — any git host (GitLab, Gitea, ...), via `git clone`
+//! - `https://host/path/skill.zip[#subdir]` — a ZIP archive
+//!
+//! Each source type is handled by a [`SkillFetcher`]; `SkillService` keeps a list of fetchers
+//! and picks the first one that supports the parsed source.
+
+use futures::future::BoxFuture;
+use reqwest::Client;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{format_skill_error, AppError};
+use crate::services::skill::SkillService;
+
+/// A parsed non-repo install spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkillSource {
+    /// `file://` directory
+    Local { path: PathBuf },
+    /// `git+<url>[@ref][#subdir]`
+    Git {
+        url: String,
+        reference: Option<String>,
+        subdir: Option<String>,
+    },
+    /// `http(s)://...zip[#subdir]`
+    Zip { url: String, subdir: Option<String> },
+}
+
+impl SkillSource {
+    /// Parse an install spec; returns `None` for plain repo specs (`directory` / `owner/name:directory`).
+    pub fn parse(spec: &str) -> Result<Option<Self>, AppError> {
+        let spec = spec.trim();
+
+        if let Some(rest) = spec.strip_prefix("file://") {
+            if rest.is_empty() {
+                return Err(AppError::InvalidInput(
+                    "file:// source requires a path".to_string(),
+                ));
+            }
+            return Ok(Some(Self::Local {
+                path: PathBuf::from(rest),
+            }));
+        }
+
+        if let Some(rest) = spec.strip_prefix("git+") {
+            let (rest, subdir) = split_fragment(rest);
+            // `@ref` only counts inside the URL path (URLs may contain `user@host`);
+            // refs themselves may contain `/` (e.g. `feature/x`).
+            let path_start = rest.find("://").and_then(|scheme| {
+                let host = scheme + 3;
+                rest[host..].find('/').map(|slash| host + slash)
+            });
+            let (url, reference) =
+                match path_start.and_then(|start| rest[start..].rfind('@').map(|at| start + at)) {
+                    Some(at) if at + 1 < rest.len() => {
+                        (rest[..at].to_string(), Some(rest[at + 1..].to_string()))
+                    }
+                    _ => (rest.to_string(), None),
+                };
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err(AppError::InvalidInput(format!(
+                    "Unsupported git source '{spec}': use git+https://host/org/repo[@ref][#subdir]"
+                )));
+            }
+            return Ok(Some(Self::Git {
+                url,
+                reference,
+                subdir,
+            }));
+        }
+
+        if spec.starts_with("https://") || spec.starts_with("http://") {
+            let (url, subdir) = split_fragment(spec);
+            let path = url.split('?').next().unwrap_or(&url);
+            if !path.to_ascii_lowercase().ends_with(".zip") {
+                return Err(AppError::InvalidInput(format!(
+                    "Unsupported URL '{spec}': expected a .zip archive (use git+{url} for git repositories)"
+                )));
+            }
+            return Ok(Some(Self::Zip { url, subdir }));
+        }
+
+        Ok(None)
+    }
+
+    /// Sub-directory inside the fetched tree that holds the skill.
+    pub fn subdir(&self) -> Option<&str> {
+        match self {
+            Self::Local { .. } => None,
+            Self::Git { subdir, .. } | Self::Zip { subdir, .. } => subdir.as_deref(),
+        }
+    }
+
+    /// Install directory name used when the skill sits at the root of the fetched tree.
+    pub fn default_directory(&self) -> String {
+        let raw = match self {
+            Self::Local { path } => path.to_string_lossy().to_string(),
+            Self::Git { url, .. } | Self::Zip { url, .. } => {
+                url.split('?').next().unwrap_or(url).to_string()
+            }
+        };
+        let last = raw
+            .trim_end_matches(['/', '\\'])
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default();
+        last.trim_end_matches(".zip")
+            .trim_end_matches(".git")
+            .to_string()
+    }
+}
+
+impl fmt::Display for SkillSource {
+    /// Canonical spec string (round-trips through [`SkillSource::parse`]).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local { path } => write!(f, "file://{}", path.display()),
+            Self::Git {
+                url,
+                reference,
+                subdir,
+            } => {
+                write!(f, "git+{url}")?;
+                if let Some(reference) = reference {
+                    write!(f, "@{reference}")?;
+                }
+                if let Some(subdir) = subdir {
+                    write!(f, "#{subdir}")?;
+                }
+                Ok(())
+            }
+            Self::Zip { url, subdir } => {
+                write!(f, "{url}")?;
+                if let Some(subdir) = subdir {
+                    write!(f, "#{subdir}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn split_fragment(raw: &str) -> (String, Option<String>) {
+    match raw.split_once('#') {
+        Some((left, frag)) if !frag.trim_matches('/').is_empty() => {
+            (left.to_string(), Some(frag.trim_matches('/').to_string()))
+        }
+        Some((left, _)) => (left.to_string(), None),
+        None => (raw.to_string(), None),
+    }
+}
+
+// ============================================================================
+// Fetchers
+// ============================================================================
+
+/// Fetches a [`SkillSource`] into a local directory.
+pub trait SkillFetcher: Send + Sync {
+    fn supports(&self, source: &SkillSource) -> bool;
+
+    /// Materialize the source tree under `dest` (an existing empty directory).
+    /// Returns the source commit SHA when known.
+    fn fetch<'a>(
+        &'a self,
+        source: &'a SkillSource,
+        dest: &'a Path,
+    ) -> BoxFuture<'a, Result<Option<String>, AppError>>;
+}
+
+/// `file://` directories.
+pub struct LocalPathFetcher;
+
+impl SkillFetcher for LocalPathFetcher {
+    fn supports(&self, source: &SkillSource) -> bool {
+        matches!(source, SkillSource::Local { .. })
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        source: &'a SkillSource,
+        dest: &'a Path,
+    ) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        Box::pin(async move {
+            let SkillSource::Local { path } = source else {
+                return Err(unsupported(source));
+            };
+            if !path.is_dir() {
+                return Err(AppError::InvalidInput(format!(
+                    "Skill directory '{}' does not exist",
+                    path.display()
+                )));
+            }
+            SkillService::copy_dir_recursive(path, dest)?;
+            let git_dir = dest.join(".git");
+            if git_dir.exists() {
+                fs::remove_dir_all(&git_dir).map_err(|e| AppError::io(&git_dir, e))?;
+            }
+            Ok(None)
+        })
+    }
+}
+
+/// `git+https://` repositories, cloned with the `git` CLI (works with any host).
+pub struct GitFetcher;
+
+impl SkillFetcher for GitFetcher {
+    fn supports(&self, source: &SkillSource) -> bool {
+        matches!(source, SkillSource::Git { .. })
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        source: &'a SkillSource,
+        dest: &'a Path,
+    ) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        Box::pin(async move {
+            let SkillSource::Git { url, reference, .. } = source else {
+                return Err(unsupported(source));
+            };
+
+            // `git` blocks until the clone finishes; keep it off the async workers.
+            let (url, reference, dest) = (url.clone(), reference.clone(), dest.to_path_buf());
+            tokio::task::spawn_blocking(move || clone_git(&url, reference.as_deref(), &dest))
+                .await
+                .map_err(|e| AppError::Message(format!("git clone task failed: {e}")))?
+        })
+    }
+}
+
+/// Clone `url` into `dest` and drop `.git`; returns the checked-out commit SHA.
+///
+/// Branches and tags use a shallow clone. A full commit SHA cannot be passed to
+/// `--branch`, so it is checked out from a full clone instead.
+fn clone_git(url: &str, reference: Option<&str>, dest: &Path) -> Result<Option<String>, AppError> {
+    let mut clone = Command::new("git");
+    clone.args(["clone", "--quiet"]);
+    match reference {
+        Some(commit) if is_commit_sha(commit) => {
+            clone.arg("--no-checkout");
+        }
+        Some(reference) => {
+            clone.args(["--depth", "1", "--branch", reference]);
+        }
+        None => {
+            clone.args(["--depth", "1"]);
+        }
+    }
+    clone.arg(url).arg(dest);
+    run_git(&mut clone)?;
+
+    if let Some(commit) = reference.filter(|r| is_commit_sha(r)) {
+        run_git(
+            Command::new("git")
+                .arg("-C")
+                .arg(dest)
+                .args(["checkout", "--quiet", commit]),
+        )?;
+    }
+
+    let head = run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(dest)
+            .args(["rev-parse", "HEAD"]),
+    )?;
+    let git_dir = dest.join(".git");
+    fs::remove_dir_all(&git_dir).map_err(|e| AppError::io(&git_dir, e))?;
+
+    let head = head.trim().to_ascii_lowercase();
+    Ok((!head.is_empty()).then_some(head))
+}
+
+/// A full 40-character commit SHA.
+pub(crate) fn is_commit_sha(value: &str) -> bool {
+    value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub(crate) fn run_git(cmd: &mut Command) -> Result<String, AppError> {
+    let output = cmd.output().map_err(|e| AppError::IoContext {
+        context: "运行 git 失败（请确认已安装 git）".to_string(),
+        source: e,
+    })?;
+    if !output.status.success() {
+        return Err(AppError::Message(format!(
+            "git failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `http(s)://...zip` archives.
+pub struct ZipUrlFetcher {
+    http_client: Client,
+}
+
+impl ZipUrlFetcher {
+    pub fn new(http_client: Client) -> Self {
+        Self { http_client }
+    }
+}
+
+impl SkillFetcher for ZipUrlFetcher {
+    fn supports(&self, source: &SkillSource) -> bool {
+        matches!(source, SkillSource::Zip { .. })
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        source: &'a SkillSource,
+        dest: &'a Path,
+    ) -> BoxFuture<'a, Result<Option<String>, AppError>> {
+        Box::pin(async move {
+            let SkillSource::Zip { url, .. } = source else {
+                return Err(unsupported(source));
+            };
+            let bytes = download_bytes(&self.http_client, url).await?;
+            extract_zip_archive(&bytes, dest)
+        })
+    }
+}
+
+fn unsupported(source: &SkillSource) -> AppError {
+    AppError::Message(format!("No fetcher for skill source: {source}"))
+}
+
+/// Default fetchers for every built-in source type.
+pub fn default_fetchers(http_client: &Client) -> Vec<Box<dyn SkillFetcher>> {
+    vec![
+        Box::new(LocalPathFetcher),
+        Box::new(GitFetcher),
+        Box::new(ZipUrlFetcher::new(http_client.clone())),
+    ]
+}
+
+// ============================================================================
+// Shared helpers (also used by GitHub repo downloads)
+// ============================================================================
+
+pub(crate) async fn download_bytes(client: &Client, url: &str) -> Result<Vec<u8>, AppError> {
+    match download_if_changed(client, url, None).await? {
+        ConditionalDownload::Fetched { bytes, .. } => Ok(bytes),
+        ConditionalDownload::NotModified => Err(AppError::Message(format_skill_error(
+            "DOWNLOAD_FAILED",
+            &[("status", "304")],
+            Some("checkNetwork"),
+        ))),
+    }
+}
+
+/// Result of a download conditioned on a previously seen ETag.
+pub(crate) enum ConditionalDownload {
+    NotModified,
+    Fetched {
+        bytes: Vec<u8>,
+        etag: Option<String>,
+    },
+}
+
+/// GET `url`, sending `If-None-Match` when an ETag is known.
+pub(crate) async fn download_if_changed(
+    client: &Client,
+    url: &str,
+    etag: Option<&str>,
+) -> Result<ConditionalDownload, AppError> {
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+    let response = request.send().await.map_err(|e| {
+        AppError::localized(
+            "skills.download_failed",
+            format!("下载失败: {e}"),
+            format!("Download failed: {e}"),
+        )
+    })?;
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(ConditionalDownload::NotModified);
+    }
+    if !response.status().is_success() {
+        let status = response.status().as_u16().to_string();
+        return Err(AppError::Message(format_skill_error(
+            "DOWNLOAD_FAILED",
+            &[("status", status.as_str())],
+            match status.as_str() {
+                "403" => Some("http403"),
+                "404" => Some("http404"),
+                "429" => Some("http429"),
+                _ => Some("checkNetwork"),
+            },
+        )));
+    }
+
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let bytes = response.bytes().await.map_err(|e| {
+        AppError::localized(
+            "skills.download_failed",
+            format!("读取下载内容失败: {e}"),
+            format!("Failed to read download bytes: {e}"),
+        )
+    })?;
+    Ok(ConditionalDownload::Fetched {
+        bytes: bytes.to_vec(),
+        etag,
+    })
+}
+
+/// Extract a ZIP archive into `dest`, stripping the top-level directory when every entry
+/// shares one (GitHub/GitLab/Gitea archives). Returns the commit SHA stored in the ZIP
+/// comment by `git archive`, if any.
+pub(crate) fn extract_zip_archive(bytes: &[u8], dest: &Path) -> Result<Option<String>, AppError> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).map_err(|e| {
+        AppError::localized(
+            "skills.zip_invalid",
+            format!("ZIP 文件损坏: {e}"),
+            format!("Invalid ZIP: {e}"),
+        )
+    })?;
+    if archive.is_empty() {
+        return Err(AppError::Message(format_skill_error(
+            "EMPTY_ARCHIVE",
+            &[],
+            Some("checkRepoUrl"),
+        )));
+    }
+    let commit = commit_from_zip_comment(archive.comment());
+
+    let names: Vec<PathBuf> = (0..archive.len())
+        .filter_map(|i| archive.by_index(i).ok()?.enclosed_name())
+        .collect();
+    let root = names
+        .first()
+        .and_then(|p| p.components().next())
+        .map(|c| PathBuf::from(c.as_os_str()));
+    let strip_root = root.filter(|root| {
+        names.iter().all(|p| p.starts_with(root)) && names.iter().any(|p| p != root)
+    });
+
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| AppError::Message(e.to_string()))?;
+        // enclosed_name() rejects absolute paths and `..` traversal.
+        let Some(name) = file.enclosed_name() else {
+            continue;
+        };
+        let relative = match &strip_root {
+            Some(root) => name.strip_prefix(root).unwrap_or(&name).to_path_buf(),
+            None => name,
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let outpath = dest.join(relative);
+        if file.is_dir() {
+            fs::create_dir_all(&outpath).map_err(|e| AppError::io(&outpath, e))?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+            }
+            let mut outfile = fs::File::create(&outpath).map_err(|e| AppError::io(&outpath, e))?;
+            std::io::copy(&mut file, &mut outfile).map_err(|e| AppError::IoContext {
+                context: format!("写入文件失败: {}", outpath.display()),
+                source: e,
+            })?;
+        }
+    }
+
+    Ok(commit)
+}
+
+fn commit_from_zip_comment(comment: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(comment).ok()?.trim();
+    is_commit_sha(text).then(|| text.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// 返回带 ETag 的响应；请求携带匹配的 If-None-Match 时返回 304
+    fn serve_with_etag(etag: &'static str, requests: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind local server");
+        let addr = listener.local_addr().expect("local addr");
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let Ok(mut stream) = stream else { continue };
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let response = if request.contains(&format!("if-none-match: {etag}")) {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    format!("HTTP/1.1 200 OK\r\nETag: {etag}\r\nContent-Length: 4\r\nConnection: close\r\n\r\nbody")
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{addr}/archive.zip")
+    }
+
+    #[test]
+    fn conditional_download_reuses_matching_etag() {
+        let url = serve_with_etag("\"v1\"", 2);
+        let client = Client::new();
+        let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+
+        let ConditionalDownload::Fetched { bytes, etag } = runtime
+            .block_on(download_if_changed(&client, &url, None))
+            .expect("first download")
+        else {
+            panic!("expected a full download");
+        };
+        assert_eq!(bytes, b"body");
+        assert_eq!(etag.as_deref(), Some("\"v1\""));
+
+        let second = runtime
+            .block_on(download_if_changed(&client, &url, etag.as_deref()))
+            .expect("conditional download");
+        assert!(matches!(second, ConditionalDownload::NotModified));
+    }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        run_git(Command::new("git").arg("-C").arg(dir).args(args)).expect("run git")
+    }
+
+    #[test]
+    fn clone_checks_out_a_pinned_commit() {
+        let upstream = tempfile::tempdir().expect("tempdir");
+        let repo = upstream.path();
+        git(repo, &["init", "--quiet"]);
+        let commit = |content: &str| {
+            fs::write(repo.join("SKILL.md"), content).expect("write skill");
+            git(repo, &["add", "."]);
+            git(
+                repo,
+                &[
+                    "-c",
+                    "user.name=test",
+                    "-c",
+                    "user.email=test@example.com",
+                    "commit",
+                    "--quiet",
+                    "-m",
+                    content,
+                ],
+            );
+            git(repo, &["rev-parse", "HEAD"]).trim().to_string()
+        };
+        let pinned = commit("v1");
+        commit("v2");
+
+        let url = repo.to_string_lossy().to_string();
+        let dest = tempfile::tempdir().expect("tempdir");
+        let head = clone_git(&url, Some(&pinned), &dest.path().join("pinned")).expect("clone");
+        assert_eq!(head.as_deref(), Some(pinned.as_str()));
+        let content = fs::read_to_string(dest.path().join("pinned").join("SKILL.md"));
+        assert_eq!(content.expect("read skill"), "v1");
+        assert!(!dest.path().join("pinned").join(".git").exists());
+
+        clone_git(&url, None, &dest.path().join("tip")).expect("clone tip");
+        let content = fs::read_to_string(dest.path().join("tip").join("SKILL.md"));
+        assert_eq!(content.expect("read skill"), "v2");
+    }
+
+    #[test]
+    fn parses_and_round_trips_source_specs() {
+        let cases = [
+            (
+                "git+https://git.example.com/team/skills.git@v1.2#tools/pdf",
+                SkillSource::Git {
+                    url: "https://git.example.com/team/skills.git".to_string(),
+                    reference: Some("v1.2".to_string()),
+                    subdir: Some("tools/pdf".to_string()),
+                },
+            ),
+            (
+                "git+https://git.example.com/team/skills.git@feature/x",
+                SkillSource::Git {
+                    url: "https://git.example.com/team/skills.git".to_string(),
+                    reference: Some("feature/x".to_string()),
+                    subdir: None,
+                },
+            ),
+            (
+                "git+https://deploy@git.example.com/team/skills",
+                SkillSource::Git {
+                    url: "https://deploy@git.example.com/team/skills".to_string(),
+                    reference: None,
+                    subdir: None,
+                },
+            ),
+            (
+                "git+https://git.example.com/team/skills",
+                SkillSource::Git {
+                    url: "https://git.example.com/team/skills".to_string(),
+                    reference: None,
+                    subdir: None,
+                },
+            ),
+            (
+                "https://files.example.com/pdf-tools.zip",
+                SkillSource::Zip {
+                    url: "https://files.example.com/pdf-tools.zip".to_string(),
+                    subdir: None,
+                },
+            ),
+            (
+                "file:///mnt/share/skills/pdf",
+                SkillSource::Local {
+                    path: PathBuf::from("/mnt/share/skills/pdf"),
+                },
+            ),
+        ];
+
+        for (spec, expected) in cases {
+            let parsed = SkillSource::parse(spec).unwrap().expect("source spec");
+            assert_eq!(parsed, expected);
+            assert_eq!(parsed.to_string(), spec);
+        }
+
+        assert_eq!(SkillSource::parse("anthropics/skills:pdf").unwrap(), None);
+        assert!(SkillSource::parse("https://github.com/org/repo").is_err());
+        assert_eq!(
+            SkillSource::parse("git+https://h/org/skills.git")
+                .unwrap()
+                .unwrap()
+                .default_directory(),
+            "skills"
+        );
+    }
+}
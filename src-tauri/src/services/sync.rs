@@ -0,0 +1,846 @@
+//! 跨设备同步
+//!
+//! 将供应商、MCP 服务器、提示词与 Skills 元数据序列化为稳定、便于 diff 的目录结构
+//! （每个条目一个 JSON 文件），通过 git 仓库（含本地裸仓库）或普通目录推送与拉取。
+//! 密钥字段以占位符写入；设置口令时另存为加密的 `secrets.enc`。
+//! 拉取时以上次同步的内容哈希为基线做三方合并，双方都修改的条目视为冲突。
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::mcp::McpService;
+use super::prompt::PromptService;
+use super::provider::ProviderService;
+use super::skill::{SkillLockEntry, SkillLockOutcome, SkillService};
+use super::skill_source::run_git;
+use crate::app_config::{AppType, McpServer};
+use crate::backup_crypto;
+use crate::error::AppError;
+use crate::prompt::Prompt;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+const SYNC_CONFIG_FILE: &str = "sync.json";
+const SYNC_REPO_DIR: &str = "sync-repo";
+const MANIFEST_FILE: &str = "cc-switch-sync.json";
+const SECRETS_FILE: &str = "secrets.enc";
+const LAYOUT_FORMAT: &str = "cc-switch-sync";
+const LAYOUT_VERSION: u32 = 1;
+const TREE_ROOTS: [&str; 4] = ["providers", "mcp", "prompts", "skills"];
+
+/// 同步文件中替代密钥值的占位符
+pub const SECRET_PLACEHOLDER: &str = "<secret>";
+
+/// 同步目标
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SyncTarget {
+    /// git 远端（URL 或本地仓库路径），工作副本位于 `~/.cc-switch/sync-repo`
+    Git { remote: String, branch: String },
+    /// 普通目录（如网盘同步目录）
+    Directory { path: PathBuf },
+}
+
+impl SyncTarget {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Git { remote, branch } => format!("git {remote} ({branch})"),
+            Self::Directory { path } => format!("directory {}", path.display()),
+        }
+    }
+}
+
+/// `~/.cc-switch/sync.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConfig {
+    pub target: SyncTarget,
+    /// 上次同步时各文件的内容哈希（三方合并的基线）
+    #[serde(default)]
+    pub base: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_synced_at: Option<i64>,
+}
+
+/// 拉取时冲突条目的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConflictResolution {
+    /// 保留本地版本，下次推送时覆盖远端
+    Ours,
+    /// 使用远端版本
+    Theirs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncChangeKind {
+    Added,
+    Updated,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncChange {
+    /// 同步目录中的相对路径（如 `providers/claude/work.json`）
+    pub path: String,
+    pub kind: SyncChangeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecretsMode {
+    /// 未设置口令：密钥不离开本机
+    #[default]
+    Excluded,
+    /// 密钥以口令加密写入 `secrets.enc`
+    Encrypted,
+    /// 远端存在 `secrets.enc`，但未提供口令
+    Locked,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    /// 推送：写入远端的变更；拉取：应用到本地的变更
+    pub changes: Vec<SyncChange>,
+    /// 拉取：尚未推送的本地变更
+    pub local_changes: Vec<String>,
+    /// 拉取：双方都修改且未解决的条目（保留本地版本）
+    pub conflicts: Vec<String>,
+    pub failed: Vec<(String, String)>,
+    /// 拉取：无法还原密钥（仍为占位符）的条目
+    pub missing_secrets: Vec<String>,
+    pub secrets: SecretsMode,
+}
+
+/// 路径 -> JSON Pointer -> 密钥值
+type SecretMap = BTreeMap<String, BTreeMap<String, String>>;
+
+/// 本地配置序列化后的目录树：路径 -> 文件内容（密钥已替换为占位符）
+struct Tree {
+    files: BTreeMap<String, String>,
+    secrets: SecretMap,
+}
+
+pub struct SyncService;
+
+impl SyncService {
+    pub fn config_path() -> PathBuf {
+        crate::config::get_app_config_dir().join(SYNC_CONFIG_FILE)
+    }
+
+    fn repo_dir() -> PathBuf {
+        crate::config::get_app_config_dir().join(SYNC_REPO_DIR)
+    }
+
+    pub fn load_config() -> Result<Option<SyncConfig>, AppError> {
+        let path = Self::config_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        crate::config::read_json_file(&path).map(Some)
+    }
+
+    fn require_config() -> Result<SyncConfig, AppError> {
+        Self::load_config()?.ok_or_else(|| {
+            AppError::localized(
+                "sync.not_initialized",
+                "尚未配置同步，请先执行 `cc-switch sync init <远端或目录>`",
+                "Sync is not set up; run `cc-switch sync init <remote-or-dir>` first",
+            )
+        })
+    }
+
+    fn save_config(config: &SyncConfig) -> Result<(), AppError> {
+        crate::config::write_json_file(&Self::config_path(), config)
+    }
+
+    /// 配置同步目标：git 远端会被克隆到工作副本，普通目录不存在时自动创建。
+    ///
+    /// 重新初始化会清空同步基线，下一次拉取按首次同步处理。
+    pub fn init(target: &str, branch: Option<&str>) -> Result<SyncTarget, AppError> {
+        let target = target.trim();
+        if target.is_empty() {
+            return Err(AppError::InvalidInput("同步目标不能为空".to_string()));
+        }
+
+        let local = Path::new(target);
+        let target = if local.is_dir() && !is_git_repo(local) {
+            SyncTarget::Directory {
+                path: local.canonicalize().map_err(|e| AppError::io(local, e))?,
+            }
+        } else if local.is_dir() || looks_like_git_url(target) {
+            let remote = if local.is_dir() {
+                local
+                    .canonicalize()
+                    .map_err(|e| AppError::io(local, e))?
+                    .to_string_lossy()
+                    .to_string()
+            } else {
+                target.to_string()
+            };
+            let repo = Self::repo_dir();
+            if repo.exists() {
+                fs::remove_dir_all(&repo).map_err(|e| AppError::io(&repo, e))?;
+            }
+            run_git(
+                Command::new("git")
+                    .args(["clone", "--quiet"])
+                    .arg(&remote)
+                    .arg(&repo),
+            )?;
+            let branch = match branch {
+                Some(branch) => branch.to_string(),
+                None => run_git(git(&repo).args(["symbolic-ref", "--short", "HEAD"]))?
+                    .trim()
+                    .to_string(),
+            };
+            SyncTarget::Git { remote, branch }
+        } else {
+            fs::create_dir_all(local).map_err(|e| AppError::io(local, e))?;
+            SyncTarget::Directory {
+                path: local.canonicalize().map_err(|e| AppError::io(local, e))?,
+            }
+        };
+
+        Self::save_config(&SyncConfig {
+            target: target.clone(),
+            base: BTreeMap::new(),
+            last_synced_at: None,
+        })?;
+        Ok(target)
+    }
+
+    /// 自上次同步以来的本地变更（不访问远端）
+    pub fn status(state: &AppState) -> Result<(SyncConfig, Vec<SyncChange>), AppError> {
+        let config = Self::require_config()?;
+        let local = hashes(&local_tree(state)?.files);
+        let changes = diff(&config.base, &local);
+        Ok((config, changes))
+    }
+
+    /// 推送本地配置。远端自上次同步后有变更时拒绝推送，需先拉取。
+    pub fn push(state: &AppState, passphrase: Option<&str>) -> Result<SyncReport, AppError> {
+        let mut config = Self::require_config()?;
+        let dir = refresh(&config.target)?;
+        check_manifest(&dir)?;
+
+        let remote = hashes(&read_tree(&dir)?);
+        if remote != config.base {
+            return Err(AppError::localized(
+                "sync.remote_changed",
+                "远端自上次同步后有新的变更，请先执行 `cc-switch sync pull`",
+                "The remote has changed since the last sync; run `cc-switch sync pull` first",
+            ));
+        }
+
+        let tree = local_tree(state)?;
+        let local = hashes(&tree.files);
+        let mut report = SyncReport {
+            changes: diff(&remote, &local),
+            ..Default::default()
+        };
+
+        for root in TREE_ROOTS {
+            let path = dir.join(root);
+            if path.exists() {
+                fs::remove_dir_all(&path).map_err(|e| AppError::io(&path, e))?;
+            }
+        }
+        for (relative, content) in &tree.files {
+            let path = dir.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+            }
+            crate::config::write_text_file(&path, content)?;
+        }
+        let manifest = serde_json::json!({ "format": LAYOUT_FORMAT, "version": LAYOUT_VERSION });
+        crate::config::write_json_file(&dir.join(MANIFEST_FILE), &manifest)?;
+
+        if let Some(passphrase) = passphrase {
+            write_secrets(&dir, &tree.secrets, passphrase)?;
+            report.secrets = SecretsMode::Encrypted;
+        }
+
+        if let SyncTarget::Git { branch, .. } = &config.target {
+            run_git(git(&dir).args(["add", "-A"]))?;
+            let status = run_git(git(&dir).args(["status", "--porcelain"]))?;
+            if !status.trim().is_empty() {
+                let message = format!("cc-switch sync: {} change(s)", report.changes.len());
+                run_git(identity(git(&dir), &dir).args(["commit", "--quiet", "-m", &message]))?;
+                run_git(
+                    git(&dir)
+                        .args(["push", "--quiet", "origin"])
+                        .arg(format!("HEAD:refs/heads/{branch}")),
+                )?;
+            }
+        }
+
+        config.base = local;
+        config.last_synced_at = Some(chrono::Utc::now().timestamp());
+        Self::save_config(&config)?;
+        Ok(report)
+    }
+
+    /// 拉取远端配置并与本地合并。
+    ///
+    /// - 仅远端修改：应用到本地
+    /// - 仅本地修改：保留，等待推送
+    /// - 双方都修改：按 `resolution` 处理；未指定时保留本地并报告冲突（推送会被拒绝）
+    pub async fn pull(
+        state: &AppState,
+        resolution: Option<ConflictResolution>,
+        passphrase: Option<&str>,
+    ) -> Result<SyncReport, AppError> {
+        let mut config = Self::require_config()?;
+        let dir = refresh(&config.target)?;
+        check_manifest(&dir)?;
+
+        let remote_files = read_tree(&dir)?;
+        let remote = hashes(&remote_files);
+        let local_tree = local_tree(state)?;
+        let local = hashes(&local_tree.files);
+
+        let mut report = SyncReport::default();
+        let remote_secrets = match (dir.join(SECRETS_FILE).exists(), passphrase) {
+            (false, _) => None,
+            (true, None) => {
+                report.secrets = SecretsMode::Locked;
+                None
+            }
+            (true, Some(passphrase)) => {
+                report.secrets = SecretsMode::Encrypted;
+                Some(read_secrets(&dir, passphrase)?)
+            }
+        };
+
+        let paths: BTreeSet<String> = remote
+            .keys()
+            .chain(local.keys())
+            .chain(config.base.keys())
+            .cloned()
+            .collect();
+        let mut to_apply = Vec::new();
+        for path in paths {
+            let (b, l, r) = (config.base.get(&path), local.get(&path), remote.get(&path));
+            if l == r {
+                // 内容一致，但远端密钥可能已更新
+                let secrets_changed = remote_secrets
+                    .as_ref()
+                    .and_then(|s| s.get(&path))
+                    .is_some_and(|s| local_tree.secrets.get(&path) != Some(s));
+                if secrets_changed {
+                    to_apply.push(path.clone());
+                }
+                set_base(&mut config.base, &path, r);
+            } else if l == b {
+                to_apply.push(path.clone());
+            } else if r == b {
+                report.local_changes.push(path.clone());
+            } else {
+                match resolution {
+                    Some(ConflictResolution::Theirs) => to_apply.push(path.clone()),
+                    Some(ConflictResolution::Ours) => set_base(&mut config.base, &path, r),
+                    None => report.conflicts.push(path.clone()),
+                }
+            }
+        }
+
+        let mut pending_skills = Vec::new();
+        for path in to_apply {
+            let remote_content = remote_files.get(&path);
+            let kind = match (local.contains_key(&path), remote_content.is_some()) {
+                (_, false) => SyncChangeKind::Deleted,
+                (false, true) => SyncChangeKind::Added,
+                (true, true) => SyncChangeKind::Updated,
+            };
+            let result = match remote_content {
+                Some(content) => {
+                    let mut value: Value = serde_json::from_str(content)
+                        .map_err(|e| AppError::json(dir.join(&path), e))?;
+                    let mut missing = false;
+                    restore_secrets(
+                        &mut value,
+                        "",
+                        remote_secrets.as_ref().and_then(|s| s.get(&path)),
+                        local_tree.secrets.get(&path),
+                        &mut missing,
+                    );
+                    if missing {
+                        report.missing_secrets.push(path.clone());
+                    }
+                    apply_item(state, &path, value, &mut pending_skills)
+                }
+                None => delete_item(state, &path, &local_tree.files[&path]),
+            };
+            match result {
+                Ok(()) => {
+                    set_base(&mut config.base, &path, remote.get(&path));
+                    report.changes.push(SyncChange { path, kind });
+                }
+                Err(e) => report.failed.push((path, e.to_string())),
+            }
+        }
+
+        // Skills 需要下载，逐个安装；失败的条目不更新基线，下次拉取时重试
+        if !pending_skills.is_empty() {
+            let entries: Vec<SkillLockEntry> = pending_skills
+                .iter()
+                .map(|(_, entry)| entry.clone())
+                .collect();
+            let results = SkillService::new()?.install_lock_entries(&entries).await?;
+            for ((path, _), result) in pending_skills.iter().zip(results) {
+                if let SkillLockOutcome::Failed(e) = result.outcome {
+                    config.base.remove(path);
+                    report.changes.retain(|c| &c.path != path);
+                    report.failed.push((path.clone(), e));
+                }
+            }
+        }
+
+        config.last_synced_at = Some(chrono::Utc::now().timestamp());
+        Self::save_config(&config)?;
+        Ok(report)
+    }
+}
+
+fn git(dir: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(dir);
+    cmd
+}
+
+/// 未配置 git 身份时使用默认提交者，避免提交失败
+fn identity(mut cmd: Command, dir: &Path) -> Command {
+    if run_git(git(dir).args(["config", "user.email"])).is_err() {
+        cmd.args([
+            "-c",
+            "user.name=cc-switch",
+            "-c",
+            "user.email=cc-switch@localhost",
+        ]);
+    }
+    cmd
+}
+
+fn is_git_repo(path: &Path) -> bool {
+    path.join(".git").exists() || (path.join("HEAD").is_file() && path.join("objects").is_dir())
+}
+
+fn looks_like_git_url(target: &str) -> bool {
+    target.contains("://") || target.ends_with(".git") || {
+        // scp 风格：user@host:path
+        let (head, _) = target.split_once(':').unwrap_or(("", ""));
+        head.contains('@') && !head.contains('/')
+    }
+}
+
+/// 准备同步目录：git 目标会获取远端并将工作副本重置到远端分支
+fn refresh(target: &SyncTarget) -> Result<PathBuf, AppError> {
+    match target {
+        SyncTarget::Directory { path } => {
+            fs::create_dir_all(path).map_err(|e| AppError::io(path, e))?;
+            Ok(path.clone())
+        }
+        SyncTarget::Git { branch, .. } => {
+            let dir = SyncService::repo_dir();
+            if !dir.join(".git").exists() {
+                return Err(AppError::Message(format!(
+                    "同步工作副本不存在: {}，请重新执行 `cc-switch sync init`",
+                    dir.display()
+                )));
+            }
+            run_git(git(&dir).args(["fetch", "--quiet", "--prune", "origin"]))?;
+            let remote_ref = format!("refs/remotes/origin/{branch}");
+            if run_git(git(&dir).args(["rev-parse", "--verify", "--quiet", &remote_ref])).is_ok() {
+                run_git(git(&dir).args([
+                    "checkout",
+                    "--quiet",
+                    "--force",
+                    "-B",
+                    branch,
+                    &remote_ref,
+                ]))?;
+                run_git(git(&dir).args(["clean", "-fdq"]))?;
+            } else {
+                // 远端尚无该分支：视为空
+                for root in TREE_ROOTS {
+                    let path = dir.join(root);
+                    if path.exists() {
+                        fs::remove_dir_all(&path).map_err(|e| AppError::io(&path, e))?;
+                    }
+                }
+            }
+            Ok(dir)
+        }
+    }
+}
+
+fn check_manifest(dir: &Path) -> Result<(), AppError> {
+    let path = dir.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(());
+    }
+    let manifest: Value = crate::config::read_json_file(&path)?;
+    let version = manifest.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > u64::from(LAYOUT_VERSION) {
+        return Err(AppError::Message(format!(
+            "同步目录的格式版本 {version} 高于当前支持的版本 {LAYOUT_VERSION}，请升级 cc-switch"
+        )));
+    }
+    Ok(())
+}
+
+fn local_tree(state: &AppState) -> Result<Tree, AppError> {
+    let mut tree = Tree {
+        files: BTreeMap::new(),
+        secrets: SecretMap::new(),
+    };
+
+    for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        for provider in ProviderService::list(state, app.clone())?.into_values() {
+            let path = format!(
+                "providers/{}/{}.json",
+                app.as_str(),
+                file_stem(&provider.id)
+            );
+            tree.insert(path, &provider)?;
+        }
+        for mut prompt in PromptService::get_prompts(state, app.clone())?.into_values() {
+            // 启用状态属于本机，不参与同步
+            prompt.enabled = false;
+            prompt.stack_order = None;
+            let path = format!("prompts/{}/{}.json", app.as_str(), file_stem(&prompt.id));
+            tree.insert(path, &prompt)?;
+        }
+    }
+    for server in McpService::get_all_servers(state)?.into_values() {
+        let path = format!("mcp/{}.json", file_stem(&server.id));
+        tree.insert(path, &server)?;
+    }
+    for skill in SkillService::build_lock()?.skills {
+        let path = format!("skills/{}.json", file_stem(&skill.directory));
+        tree.insert(path, &skill)?;
+    }
+    Ok(tree)
+}
+
+impl Tree {
+    fn insert(&mut self, path: String, item: &impl Serialize) -> Result<(), AppError> {
+        let mut value =
+            serde_json::to_value(item).map_err(|e| AppError::JsonSerialize { source: e })?;
+        let mut secrets = BTreeMap::new();
+        redact(&mut value, "", secret_scopes(&path), &mut secrets);
+        if !secrets.is_empty() {
+            self.secrets.insert(path.clone(), secrets);
+        }
+        let content = serde_json::to_string_pretty(&value)
+            .map_err(|e| AppError::JsonSerialize { source: e })?;
+        self.files.insert(path, content + "\n");
+        Ok(())
+    }
+}
+
+fn read_tree(dir: &Path) -> Result<BTreeMap<String, String>, AppError> {
+    fn walk(
+        dir: &Path,
+        relative: &str,
+        files: &mut BTreeMap<String, String>,
+    ) -> Result<(), AppError> {
+        for entry in fs::read_dir(dir).map_err(|e| AppError::io(dir, e))? {
+            let entry = entry.map_err(|e| AppError::io(dir, e))?;
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let child = format!("{relative}/{name}");
+            if path.is_dir() {
+                walk(&path, &child, files)?;
+            } else if name.ends_with(".json") {
+                let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+                files.insert(child, content);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = BTreeMap::new();
+    for root in TREE_ROOTS {
+        let path = dir.join(root);
+        if path.is_dir() {
+            walk(&path, root, &mut files)?;
+        }
+    }
+    Ok(files)
+}
+
+fn hashes(files: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    files
+        .iter()
+        .map(|(path, content)| {
+            (
+                path.clone(),
+                hex::encode(Sha256::digest(content.as_bytes())),
+            )
+        })
+        .collect()
+}
+
+fn diff(from: &BTreeMap<String, String>, to: &BTreeMap<String, String>) -> Vec<SyncChange> {
+    let paths: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let kind = match (from.get(path), to.get(path)) {
+                (None, Some(_)) => SyncChangeKind::Added,
+                (Some(_), None) => SyncChangeKind::Deleted,
+                (Some(a), Some(b)) if a != b => SyncChangeKind::Updated,
+                _ => return None,
+            };
+            Some(SyncChange {
+                path: path.clone(),
+                kind,
+            })
+        })
+        .collect()
+}
+
+fn set_base(base: &mut BTreeMap<String, String>, path: &str, hash: Option<&String>) {
+    match hash {
+        Some(hash) => base.insert(path.to_string(), hash.clone()),
+        None => base.remove(path),
+    };
+}
+
+/// ID 转为文件名：保留字母数字与 `._-`，其余字符按字节转义为 `%XX`
+fn file_stem(id: &str) -> String {
+    let mut stem = String::new();
+    for (i, byte) in id.bytes().enumerate() {
+        let keep = byte.is_ascii_alphanumeric() || b"_-".contains(&byte) || (byte == b'.' && i > 0);
+        if keep {
+            stem.push(byte as char);
+        } else {
+            stem.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    stem
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    ["KEY", "TOKEN", "SECRET", "PASSWORD"]
+        .iter()
+        .any(|word| key.contains(word))
+}
+
+/// 按条目类型整体视为密钥的位置（JSON Pointer）：其下所有字符串都会被替换
+///
+/// 仅凭字段名无法识别 `Authorization` 请求头、Codex TOML 片段中的令牌等，因此按结构处理。
+fn secret_scopes(path: &str) -> &'static [&'static str] {
+    if path.starts_with("providers/codex/") {
+        &[
+            "/settingsConfig/env",
+            "/settingsConfig/auth",
+            "/settingsConfig/config",
+        ]
+    } else if path.starts_with("providers/") {
+        &["/settingsConfig/env", "/settingsConfig/auth"]
+    } else if path.starts_with("mcp/") {
+        &[
+            "/server/env",
+            "/server/headers",
+            "/server/http_headers",
+            "/codex_toml",
+        ]
+    } else {
+        &[]
+    }
+}
+
+fn pointer_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// 将密钥字段替换为占位符，原值按 JSON Pointer 记录到 `out`
+///
+/// 密钥包括 `scopes` 下的全部值、名称像密钥的字段，以及带查询参数的 MCP `url`（常含令牌）。
+fn redact(value: &mut Value, pointer: &str, scopes: &[&str], out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let child_pointer = format!("{pointer}/{}", pointer_segment(key));
+                if scopes.contains(&child_pointer.as_str()) {
+                    redact_all(child, &child_pointer, out);
+                    continue;
+                }
+                match child {
+                    Value::String(s)
+                        if is_secret_key(key)
+                            || (child_pointer == "/server/url" && s.contains('?')) =>
+                    {
+                        redact_string(s, &child_pointer, out);
+                    }
+                    _ => redact(child, &child_pointer, scopes, out),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter_mut().enumerate() {
+                redact(child, &format!("{pointer}/{i}"), scopes, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 替换 `value` 下的所有字符串
+fn redact_all(value: &mut Value, pointer: &str, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::String(s) => redact_string(s, pointer, out),
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                redact_all(child, &format!("{pointer}/{}", pointer_segment(key)), out);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter_mut().enumerate() {
+                redact_all(child, &format!("{pointer}/{i}"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn redact_string(s: &mut String, pointer: &str, out: &mut BTreeMap<String, String>) {
+    if !s.is_empty() && s != SECRET_PLACEHOLDER {
+        out.insert(
+            pointer.to_string(),
+            std::mem::replace(s, SECRET_PLACEHOLDER.to_string()),
+        );
+    }
+}
+
+/// 还原占位符：优先使用远端加密的密钥，其次沿用本地同一位置的值
+fn restore_secrets(
+    value: &mut Value,
+    pointer: &str,
+    remote: Option<&BTreeMap<String, String>>,
+    local: Option<&BTreeMap<String, String>>,
+    missing: &mut bool,
+) {
+    match value {
+        Value::String(s) if s == SECRET_PLACEHOLDER => {
+            let secret = remote
+                .and_then(|m| m.get(pointer))
+                .or_else(|| local.and_then(|m| m.get(pointer)));
+            match secret {
+                Some(secret) => *s = secret.clone(),
+                None => *missing = true,
+            }
+        }
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let child_pointer = format!("{pointer}/{}", pointer_segment(key));
+                restore_secrets(child, &child_pointer, remote, local, missing);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter_mut().enumerate() {
+                restore_secrets(child, &format!("{pointer}/{i}"), remote, local, missing);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 写入加密的密钥文件；内容未变化时保留原文件，避免每次推送都产生 diff
+fn write_secrets(dir: &Path, secrets: &SecretMap, passphrase: &str) -> Result<(), AppError> {
+    let path = dir.join(SECRETS_FILE);
+    if path.exists() && read_secrets(dir, passphrase).ok().as_ref() == Some(secrets) {
+        return Ok(());
+    }
+    let plaintext =
+        serde_json::to_vec(secrets).map_err(|e| AppError::JsonSerialize { source: e })?;
+    crate::config::atomic_write(&path, &backup_crypto::encrypt(&plaintext, passphrase)?)
+}
+
+fn read_secrets(dir: &Path, passphrase: &str) -> Result<SecretMap, AppError> {
+    let path = dir.join(SECRETS_FILE);
+    let raw = fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+    let plaintext = backup_crypto::decrypt(&raw, passphrase)?;
+    serde_json::from_slice(&plaintext).map_err(|e| AppError::json(&path, e))
+}
+
+fn app_from_path(path: &str) -> Result<AppType, AppError> {
+    path.split('/').nth(1).unwrap_or_default().parse()
+}
+
+fn parse<T: serde::de::DeserializeOwned>(path: &str, value: Value) -> Result<T, AppError> {
+    serde_json::from_value(value)
+        .map_err(|e| AppError::InvalidInput(format!("无效的同步文件 {path}: {e}")))
+}
+
+fn apply_item(
+    state: &AppState,
+    path: &str,
+    value: Value,
+    pending_skills: &mut Vec<(String, SkillLockEntry)>,
+) -> Result<(), AppError> {
+    match path.split('/').next() {
+        Some("providers") => {
+            let app = app_from_path(path)?;
+            let provider: Provider = parse(path, value)?;
+            if ProviderService::list(state, app.clone())?.contains_key(&provider.id) {
+                ProviderService::update(state, app, provider)?;
+            } else {
+                ProviderService::add(state, app, provider)?;
+            }
+        }
+        Some("mcp") => {
+            let server: McpServer = parse(path, value)?;
+            McpService::upsert_server(state, server)?;
+        }
+        Some("prompts") => {
+            let app = app_from_path(path)?;
+            let mut prompt: Prompt = parse(path, value)?;
+            if let Some(existing) = PromptService::get_prompts(state, app.clone())?.get(&prompt.id)
+            {
+                prompt.enabled = existing.enabled;
+                prompt.stack_order = existing.stack_order;
+            }
+            let id = prompt.id.clone();
+            PromptService::upsert_prompt(state, app, &id, prompt)?;
+        }
+        Some("skills") => {
+            let entry: SkillLockEntry = parse(path, value)?;
+            pending_skills.push((path.to_string(), entry));
+        }
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "无法识别的同步文件: {path}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn delete_item(state: &AppState, path: &str, local_content: &str) -> Result<(), AppError> {
+    let value: Value = serde_json::from_str(local_content)
+        .map_err(|e| AppError::InvalidInput(format!("无效的同步文件 {path}: {e}")))?;
+    let field = if path.starts_with("skills/") {
+        "directory"
+    } else {
+        "id"
+    };
+    let id = value.get(field).and_then(Value::as_str).unwrap_or_default();
+    match path.split('/').next() {
+        Some("providers") => ProviderService::delete(state, app_from_path(path)?, id),
+        Some("mcp") => McpService::delete_server(state, id).map(|_| ()),
+        Some("prompts") => PromptService::delete_prompt(state, app_from_path(path)?, id),
+        Some("skills") => SkillService::uninstall(id),
+        _ => Err(AppError::InvalidInput(format!(
+            "无法识别的同步文件: {path}"
+        ))),
+    }
+}
@@ -0,0 +1,349 @@
+//! Watch live config files and react to manual edits (`cc-switch watch`)
+//!
+//! Live files are polled for changes (size and modification time). Once an app's files
+//! have been quiet for the debounce period, the stored current provider and the MCP
+//! servers are compared with the live config, and each drift is reported, adopted into
+//! the store or overwritten from the store according to the [`WatchPolicy`].
+//!
+//! The in-memory config is reloaded from the database before every check, so changes
+//! made by other cc-switch invocations while the watcher runs are never overwritten.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app_config::AppType;
+use crate::codex_config::{get_codex_auth_path, get_codex_config_path};
+use crate::config::{get_claude_mcp_path, get_claude_settings_path};
+use crate::error::AppError;
+use crate::gemini_config::get_gemini_dir;
+use crate::mcp::{McpDriftKind, McpReconcileChoice};
+use crate::services::{McpService, ProviderService};
+use crate::store::AppState;
+use crate::sync_policy::should_sync_live;
+
+const APPS: [AppType; 3] = [AppType::Claude, AppType::Codex, AppType::Gemini];
+
+/// What to do when the live config no longer matches the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum WatchPolicy {
+    /// Only log the drift
+    #[default]
+    Report,
+    /// Write the live changes back into the store
+    Adopt,
+    /// Rewrite the live config from the store, discarding the manual edit
+    Reapply,
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// Apps to watch
+    pub apps: Vec<AppType>,
+    /// Policy for the current provider's settings
+    pub provider: WatchPolicy,
+    /// Policy for MCP servers
+    pub mcp: WatchPolicy,
+    /// How long an app's files must stay unchanged before they are checked
+    pub debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            apps: APPS.to_vec(),
+            provider: WatchPolicy::Report,
+            mcp: WatchPolicy::Report,
+            debounce: Duration::from_millis(1500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchSubject {
+    Provider {
+        id: String,
+        name: String,
+    },
+    McpServer {
+        id: String,
+        kind: McpDriftKind,
+    },
+    /// The live config could not be read or compared
+    LiveConfig,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchOutcome {
+    Reported,
+    Adopted,
+    Reapplied,
+    Failed(String),
+}
+
+/// One drift found by the watcher and what was done about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchEvent {
+    pub app_type: AppType,
+    pub subject: WatchSubject,
+    /// Changed keys (provider) or fields (MCP server)
+    pub changes: Vec<String>,
+    pub outcome: WatchOutcome,
+}
+
+impl WatchEvent {
+    pub fn describe(&self) -> String {
+        let subject = match &self.subject {
+            WatchSubject::Provider { id, name } if id == name => format!("provider '{id}'"),
+            WatchSubject::Provider { id, name } => format!("provider '{name}' ({id})"),
+            WatchSubject::McpServer { id, kind } => {
+                let kind = match kind {
+                    McpDriftKind::Added => "added",
+                    McpDriftKind::Removed => "removed",
+                    McpDriftKind::Changed => "changed",
+                };
+                format!("MCP server '{id}' ({kind} in live)")
+            }
+            WatchSubject::LiveConfig => "live config".to_string(),
+        };
+        let outcome = match &self.outcome {
+            WatchOutcome::Reported => "drift detected".to_string(),
+            WatchOutcome::Adopted => "adopted live changes".to_string(),
+            WatchOutcome::Reapplied => "re-applied stored config".to_string(),
+            WatchOutcome::Failed(e) => format!("failed: {e}"),
+        };
+        let mut line = format!("{}: {subject}: {outcome}", self.app_type.as_str());
+        if !self.changes.is_empty() {
+            line.push_str(&format!(" [{}]", self.changes.join(", ")));
+        }
+        line
+    }
+}
+
+pub struct WatchService;
+
+impl WatchService {
+    /// Live files watched for an app (Gemini: every file directly in `~/.gemini`)
+    pub fn watched_paths(app: &AppType) -> Vec<PathBuf> {
+        match app {
+            AppType::Claude => vec![get_claude_settings_path(), get_claude_mcp_path()],
+            AppType::Codex => vec![get_codex_config_path(), get_codex_auth_path()],
+            AppType::Gemini => {
+                let mut paths: Vec<PathBuf> = fs::read_dir(get_gemini_dir())
+                    .map(|entries| {
+                        entries
+                            .filter_map(Result::ok)
+                            .map(|e| e.path())
+                            .filter(|p| p.is_file())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                paths.sort();
+                paths
+            }
+        }
+    }
+
+    /// Compare an app's live config with the store once and apply the policies.
+    ///
+    /// Apps that are not set up on this machine are skipped.
+    pub fn check(
+        state: &AppState,
+        app: &AppType,
+        options: &WatchOptions,
+    ) -> Result<Vec<WatchEvent>, AppError> {
+        if !should_sync_live(app) {
+            return Ok(Vec::new());
+        }
+        state.reload()?;
+
+        let mut events = Vec::new();
+        if let Some(drift) = ProviderService::live_drift(state, app.clone())? {
+            if !drift.is_clean() {
+                let result = match options.provider {
+                    WatchPolicy::Report => Ok(WatchOutcome::Reported),
+                    WatchPolicy::Adopt => ProviderService::adopt_live(state, app.clone())
+                        .map(|_| WatchOutcome::Adopted),
+                    WatchPolicy::Reapply => ProviderService::reapply_current(state, app.clone())
+                        .map(|_| WatchOutcome::Reapplied),
+                };
+                events.push(WatchEvent {
+                    app_type: app.clone(),
+                    subject: WatchSubject::Provider {
+                        id: drift.provider_id,
+                        name: drift.provider_name,
+                    },
+                    changes: drift.changes.into_iter().map(|c| c.key).collect(),
+                    outcome: result.unwrap_or_else(|e| WatchOutcome::Failed(e.to_string())),
+                });
+            }
+        }
+
+        // The provider may have been re-applied above, so the MCP diff is taken afterwards
+        for drift in McpService::diff_live(state, app)? {
+            let choice = match options.mcp {
+                WatchPolicy::Report => None,
+                WatchPolicy::Adopt => Some(McpReconcileChoice::Live),
+                WatchPolicy::Reapply => Some(McpReconcileChoice::Stored),
+            };
+            let outcome = match choice {
+                None => WatchOutcome::Reported,
+                Some(choice) => match McpService::reconcile(state, app, &drift.id, choice) {
+                    Ok(_) if choice == McpReconcileChoice::Live => WatchOutcome::Adopted,
+                    Ok(_) => WatchOutcome::Reapplied,
+                    Err(e) => WatchOutcome::Failed(e.to_string()),
+                },
+            };
+            events.push(WatchEvent {
+                app_type: app.clone(),
+                subject: WatchSubject::McpServer {
+                    id: drift.id,
+                    kind: drift.kind,
+                },
+                changes: drift.fields.iter().map(|f| f.path_label()).collect(),
+                outcome,
+            });
+        }
+        Ok(events)
+    }
+}
+
+/// Size and modification time of a live file
+type Fingerprint = (u64, Option<SystemTime>);
+
+/// Polling watcher: call [`LiveWatcher::poll`] periodically.
+pub struct LiveWatcher {
+    options: WatchOptions,
+    snapshot: BTreeMap<PathBuf, (String, Fingerprint)>,
+    /// App key → time of the last observed change that has not been checked yet
+    pending: BTreeMap<String, Instant>,
+}
+
+impl LiveWatcher {
+    /// Take the initial snapshot; files as they are now are not reported as changes.
+    pub fn new(options: WatchOptions) -> Self {
+        let snapshot = take_snapshot(&options.apps);
+        Self {
+            options,
+            snapshot,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    pub fn options(&self) -> &WatchOptions {
+        &self.options
+    }
+
+    /// Record file changes seen since the last poll and check every app whose files
+    /// have been quiet for the debounce period.
+    pub fn poll(&mut self, state: &AppState, now: Instant) -> Vec<WatchEvent> {
+        let snapshot = take_snapshot(&self.options.apps);
+        for app in changed_apps(&self.snapshot, &snapshot) {
+            self.pending.insert(app, now);
+        }
+        self.snapshot = snapshot;
+
+        let ready: Vec<AppType> = self
+            .options
+            .apps
+            .iter()
+            .filter(|app| {
+                self.pending
+                    .get(app.as_str())
+                    .is_some_and(|changed| now.duration_since(*changed) >= self.options.debounce)
+            })
+            .cloned()
+            .collect();
+        if ready.is_empty() {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        for app in ready {
+            self.pending.remove(app.as_str());
+            match WatchService::check(state, &app, &self.options) {
+                Ok(found) => events.extend(found),
+                Err(e) => events.push(WatchEvent {
+                    app_type: app,
+                    subject: WatchSubject::LiveConfig,
+                    changes: Vec::new(),
+                    outcome: WatchOutcome::Failed(e.to_string()),
+                }),
+            }
+        }
+
+        // Our own writes (adopting MCP servers syncs them to other apps, re-applying
+        // rewrites live files) must not trigger another round
+        if events
+            .iter()
+            .any(|e| matches!(e.outcome, WatchOutcome::Adopted | WatchOutcome::Reapplied))
+        {
+            self.snapshot = take_snapshot(&self.options.apps);
+        }
+        events
+    }
+}
+
+fn take_snapshot(apps: &[AppType]) -> BTreeMap<PathBuf, (String, Fingerprint)> {
+    let mut snapshot = BTreeMap::new();
+    for app in apps {
+        for path in WatchService::watched_paths(app) {
+            if let Ok(meta) = fs::metadata(&path) {
+                let fingerprint = (meta.len(), meta.modified().ok());
+                snapshot.insert(path, (app.as_str().to_string(), fingerprint));
+            }
+        }
+    }
+    snapshot
+}
+
+/// Apps with a file that was created, modified or deleted between two snapshots
+fn changed_apps(
+    before: &BTreeMap<PathBuf, (String, Fingerprint)>,
+    after: &BTreeMap<PathBuf, (String, Fingerprint)>,
+) -> Vec<String> {
+    let mut apps: Vec<String> = before
+        .iter()
+        .filter(|(path, entry)| after.get(*path) != Some(entry))
+        .chain(after.iter().filter(|(path, _)| !before.contains_key(*path)))
+        .map(|(_, (app, _))| app.clone())
+        .collect();
+    apps.sort();
+    apps.dedup();
+    apps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(app: &str, len: u64) -> (String, Fingerprint) {
+        (app.to_string(), (len, None))
+    }
+
+    #[test]
+    fn changed_apps_covers_created_modified_and_deleted_files() {
+        let before = BTreeMap::from([
+            (PathBuf::from("/c/settings.json"), entry("claude", 1)),
+            (PathBuf::from("/x/auth.json"), entry("codex", 1)),
+        ]);
+        let unchanged = before.clone();
+        assert!(changed_apps(&before, &unchanged).is_empty());
+
+        let modified = BTreeMap::from([
+            (PathBuf::from("/c/settings.json"), entry("claude", 2)),
+            (PathBuf::from("/x/auth.json"), entry("codex", 1)),
+        ]);
+        assert_eq!(changed_apps(&before, &modified), ["claude"]);
+
+        let deleted_and_created = BTreeMap::from([
+            (PathBuf::from("/c/settings.json"), entry("claude", 1)),
+            (PathBuf::from("/g/.env"), entry("gemini", 1)),
+        ]);
+        assert_eq!(
+            changed_apps(&before, &deleted_and_created),
+            ["codex", "gemini"]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{OnceLock, RwLock};
@@ -30,6 +30,80 @@ pub struct SecuritySettings {
     pub auth: Option<SecurityAuthSettings>,
 }
 
+/// 远程备份目标；每次创建备份后上传，并按保留策略清理远端旧快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteBackupTarget {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: RemoteBackupKind,
+    /// 远端最多保留的快照数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+    /// 删除早于该天数的远端快照
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+}
+
+/// 凭据字段支持 `${secret:NAME}` 引用密钥存储
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RemoteBackupKind {
+    #[serde(rename_all = "camelCase")]
+    Webdav {
+        /// 存放备份的集合 URL
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        username: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        password: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        prefix: String,
+        access_key_id: String,
+        secret_access_key: String,
+        /// 使用 `endpoint/bucket/key` 形式的路径（MinIO 等兼容存储通常需要）
+        #[serde(default = "default_path_style")]
+        path_style: bool,
+    },
+}
+
+fn default_path_style() -> bool {
+    true
+}
+
+/// 目录使用的配置：`.cc-switch.toml` 的内容，或设置中按目录登记的同样结构
+///
+/// `profile` 先应用；各应用中的 `provider` / `mcp` 覆盖档案中的对应部分，未指定的部分保持不变。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DirectorySelection {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude: Option<DirectoryAppSelection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex: Option<DirectoryAppSelection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini: Option<DirectoryAppSelection>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DirectoryAppSelection {
+    /// 供应商 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// 对该应用启用的 MCP 服务器（完整集合，其余服务器会被停用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp: Option<Vec<String>>,
+}
+
 /// 应用设置结构，允许覆盖默认配置目录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -63,6 +137,18 @@ pub struct AppSettings {
     /// Codex 自定义端点列表
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub custom_endpoints_codex: HashMap<String, CustomEndpoint>,
+    /// 远程备份目标
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backup_targets: Vec<RemoteBackupTarget>,
+    /// 目录（绝对路径）-> 进入该目录时使用的配置，与 `.cc-switch.toml` 等效
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub directory_selections: BTreeMap<String, DirectorySelection>,
+    /// `.cc-switch.toml` 路径 -> 允许（`hook allow`）时文件内容的 SHA-256
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub trusted_project_files: BTreeMap<String, String>,
+    /// 项目 `.claude/settings*.json` 路径 -> MCP 服务器 ID -> cc-switch 写入的 deny 规则
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub project_tool_rules: BTreeMap<String, BTreeMap<String, Vec<String>>>,
 }
 
 fn default_show_in_tray() -> bool {
@@ -88,6 +174,10 @@ impl Default for AppSettings {
             security: None,
             custom_endpoints_claude: HashMap::new(),
             custom_endpoints_codex: HashMap::new(),
+            backup_targets: Vec::new(),
+            directory_selections: BTreeMap::new(),
+            trusted_project_files: BTreeMap::new(),
+            project_tool_rules: BTreeMap::new(),
         }
     }
 }
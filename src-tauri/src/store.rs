@@ -88,9 +88,16 @@ impl AppState {
         let config = self.config.read().map_err(AppError::from)?;
         persist_multi_app_config_to_db(&self.db, &config)
     }
+
+    /// 从数据库重新加载内存中的 config（长时间运行时，其它 cc-switch 进程可能已修改数据库）
+    pub fn reload(&self) -> Result<(), AppError> {
+        let config = export_db_to_multi_app_config(&self.db)?;
+        *self.config.write().map_err(AppError::from)? = config;
+        Ok(())
+    }
 }
 
-fn export_db_to_multi_app_config(db: &Database) -> Result<MultiAppConfig, AppError> {
+pub(crate) fn export_db_to_multi_app_config(db: &Database) -> Result<MultiAppConfig, AppError> {
     use crate::app_config::AppType;
     use crate::provider::ProviderManager;
 
@@ -123,7 +130,18 @@ fn export_db_to_multi_app_config(db: &Database) -> Result<MultiAppConfig, AppErr
     Ok(config)
 }
 
+/// 持久化并把前后差异写入变更日志（日志写入失败只记录警告）
 fn persist_multi_app_config_to_db(db: &Database, config: &MultiAppConfig) -> Result<(), AppError> {
+    let before = export_db_to_multi_app_config(db)?;
+    write_multi_app_config_to_db(db, config)?;
+    let after = export_db_to_multi_app_config(db)?;
+    if let Err(err) = crate::services::history::record_changes(db, &before, &after) {
+        log::warn!("写入变更日志失败: {err}");
+    }
+    Ok(())
+}
+
+fn write_multi_app_config_to_db(db: &Database, config: &MultiAppConfig) -> Result<(), AppError> {
     use crate::app_config::AppType;
 
     for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
@@ -238,6 +256,9 @@ fn load_skills_index_for_migration(
             repo_branch: None,
             apps: SkillApps::only(&crate::app_config::AppType::Claude),
             installed_at,
+            source: None,
+            source_commit: None,
+            content_hash: None,
         };
         index.skills.insert(directory, record);
     }
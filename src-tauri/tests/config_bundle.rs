@@ -0,0 +1,163 @@
+use cc_switch_lib::{
+    AppType, BundleAction, BundleSelection, BundleService, ConflictStrategy, McpApps, McpService,
+    PromptService, ProviderService,
+};
+
+#[path = "support.rs"]
+mod support;
+use support::{
+    claude_config, claude_provider, ensure_test_home, lock_test_mutex, prompt, reset_test_fs,
+    state_from_config, stdio_mcp_server,
+};
+
+#[test]
+fn bundle_export_filters_and_merge_import_resolves_conflicts() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let mut config = claude_config(
+        "p1",
+        [
+            claude_provider("p1", "Shared", "sk-p1"),
+            claude_provider("p2", "Team", "sk-p2"),
+        ],
+    );
+    config
+        .prompts
+        .claude
+        .prompts
+        .insert("rules".to_string(), prompt("rules", "Team rules"));
+    let mut servers = std::collections::HashMap::new();
+    for (id, claude, codex) in [("fs", true, false), ("search", false, true)] {
+        let apps = McpApps {
+            claude,
+            codex,
+            gemini: false,
+            opencode: false,
+        };
+        servers.insert(id.to_string(), stdio_mcp_server(id, "echo", apps));
+    }
+    config.mcp.servers = Some(servers);
+    let state = state_from_config(config);
+    state.save().expect("persist db");
+
+    // 按应用与 ID 过滤
+    let selection = BundleSelection {
+        app: Some(AppType::Claude),
+        providers: Some(vec!["p2".to_string()]),
+        mcp: true,
+        prompts: true,
+        skills: false,
+    };
+    let bundle = BundleService::build(&state, &selection).expect("build bundle");
+    let providers = &bundle.providers["claude"];
+    assert_eq!(providers.len(), 1);
+    assert_eq!(providers[0].id, "p2");
+    let server_ids: Vec<&str> = bundle.mcp_servers.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(server_ids, ["fs"]);
+    assert_eq!(bundle.prompts["claude"][0].id, "rules");
+    assert!(bundle.skills.is_empty());
+
+    let missing = BundleSelection {
+        providers: Some(vec!["nope".to_string()]),
+        ..Default::default()
+    };
+    assert!(BundleService::build(&state, &missing).is_err());
+
+    // YAML 往返
+    let path = home.join("bundles").join("team.yaml");
+    std::fs::create_dir_all(path.parent().unwrap()).expect("create dir");
+    BundleService::write(&bundle, &path, None).expect("write bundle");
+    let text = std::fs::read_to_string(&path).expect("read bundle");
+    assert!(text.contains("format: cc-switch-bundle"), "{text}");
+    let bundle = BundleService::read(&path, None).expect("read bundle");
+
+    // 本地修改后，以默认策略合并：已存在的条目全部跳过
+    ProviderService::update(
+        &state,
+        AppType::Claude,
+        claude_provider("p2", "Local", "sk-p2"),
+    )
+    .expect("edit provider");
+    McpService::delete_server(&state, "fs").expect("delete server");
+    PromptService::enable_prompt(&state, AppType::Claude, "rules").expect("enable prompt");
+
+    let report = BundleService::plan(&state, &bundle, ConflictStrategy::Skip).expect("plan");
+    let actions: Vec<(&str, &BundleAction)> = report
+        .changes
+        .iter()
+        .map(|c| (c.id.as_str(), &c.action))
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            ("p2", &BundleAction::Skip),
+            ("fs", &BundleAction::Add),
+            ("rules", &BundleAction::Skip),
+        ]
+    );
+    assert!(!McpService::get_all_servers(&state)
+        .expect("servers")
+        .contains_key("fs"));
+
+    let report = BundleService::import(&state, &bundle, ConflictStrategy::Skip).expect("import");
+    assert!(report.backup_id.is_some());
+    assert!(McpService::get_all_servers(&state)
+        .expect("servers")
+        .contains_key("fs"));
+    let providers = ProviderService::list(&state, AppType::Claude).expect("providers");
+    assert_eq!(providers["p2"].name, "Local");
+
+    // 重命名：以新 ID 导入，且不启用导入的提示词
+    let report =
+        BundleService::import(&state, &bundle, ConflictStrategy::Rename).expect("import rename");
+    assert_eq!(
+        report.changes[0].action,
+        BundleAction::Rename("p2-2".into())
+    );
+    let providers = ProviderService::list(&state, AppType::Claude).expect("providers");
+    assert_eq!(providers["p2-2"].name, "Team");
+    assert_eq!(providers["p2"].name, "Local");
+    let prompts = PromptService::get_prompts(&state, AppType::Claude).expect("prompts");
+    assert!(!prompts["rules-2"].enabled);
+    assert!(prompts["rules"].enabled);
+
+    // 覆盖：替换内容但保留本地启用状态，当前供应商不变
+    let report = BundleService::import(&state, &bundle, ConflictStrategy::Overwrite)
+        .expect("import overwrite");
+    assert_eq!(report.changes[0].action, BundleAction::Overwrite);
+    let providers = ProviderService::list(&state, AppType::Claude).expect("providers");
+    assert_eq!(providers["p2"].name, "Team");
+    assert_eq!(
+        ProviderService::current(&state, AppType::Claude).expect("current"),
+        "p1"
+    );
+    let prompts = PromptService::get_prompts(&state, AppType::Claude).expect("prompts");
+    assert!(prompts["rules"].enabled);
+}
+
+#[test]
+fn encrypted_bundle_requires_passphrase() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    std::env::remove_var("CC_SWITCH_BACKUP_PASSPHRASE");
+
+    let state = state_from_config(claude_config(
+        "p1",
+        [claude_provider("p1", "Secret", "sk-p1")],
+    ));
+    state.save().expect("persist db");
+
+    let bundle = BundleService::build(&state, &BundleSelection::default()).expect("build bundle");
+    let path = home.join("bundle.json");
+    BundleService::write(&bundle, &path, Some("correct horse")).expect("write bundle");
+    let raw = std::fs::read(&path).expect("read bundle");
+    assert!(!String::from_utf8_lossy(&raw).contains("sk-p1"));
+
+    assert!(BundleService::read(&path, None).is_err());
+    assert!(BundleService::read(&path, Some("wrong")).is_err());
+    let read = BundleService::read(&path, Some("correct horse")).expect("decrypt bundle");
+    assert_eq!(read.providers["claude"][0].id, "p1");
+}
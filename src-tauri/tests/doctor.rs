@@ -0,0 +1,123 @@
+use serde_json::json;
+
+use cc_switch_lib::{
+    get_claude_settings_path, read_json_file, AppType, DoctorFix, DoctorService, DoctorSeverity,
+    McpService, MultiAppConfig, ProviderService,
+};
+
+#[path = "support.rs"]
+mod support;
+use support::{
+    claude_config, claude_only, claude_provider, ensure_test_home, lock_test_mutex, reset_test_fs,
+    state_from_config, stdio_mcp_server,
+};
+
+fn config_with_providers() -> MultiAppConfig {
+    let providers = ["a", "b"].map(|id| {
+        let mut provider = claude_provider(id, &id.to_uppercase(), &format!("sk-{id}"));
+        provider.settings_config["env"]["ANTHROPIC_BASE_URL"] =
+            json!(format!("https://{id}.example.com"));
+        provider
+    });
+    claude_config("a", providers)
+}
+
+#[test]
+fn doctor_reports_problems_and_fixes_the_safe_ones() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    std::fs::create_dir_all(home.join(".claude")).expect("create claude dir (initialized)");
+
+    let state = state_from_config(config_with_providers());
+    ProviderService::switch(&state, AppType::Claude, "a").expect("switch");
+
+    let report = DoctorService::diagnose(Ok(&state));
+    assert_eq!(report.error_count(), 0, "{:#?}", report.findings);
+    assert!(report
+        .findings
+        .iter()
+        .any(|f| f.area == "codex" && f.message.contains("skipped")));
+    assert!(report
+        .findings
+        .iter()
+        .any(|f| f.severity == DoctorSeverity::Ok
+            && f.message.contains("matches current provider 'a'")));
+
+    // 手动修改 live 中的令牌：报告漂移的键名（不含值），且不会自动修复
+    let settings_path = get_claude_settings_path();
+    let mut live: serde_json::Value = read_json_file(&settings_path).expect("read live");
+    live["env"]["ANTHROPIC_AUTH_TOKEN"] = json!("sk-edited");
+    std::fs::write(&settings_path, live.to_string()).expect("write live");
+    let report = DoctorService::diagnose(Ok(&state));
+    let drift = report
+        .findings
+        .iter()
+        .find(|f| f.message.contains("differs from current provider 'a'"))
+        .expect("drift reported");
+    assert!(drift.message.contains("env.ANTHROPIC_AUTH_TOKEN"));
+    assert!(!drift.message.contains("sk-edited"));
+    assert!(drift.fix.is_none());
+
+    // PATH 中不存在的 MCP 命令、损坏的 settings.json、悬空的 Skill 链接
+    McpService::upsert_server(
+        &state,
+        stdio_mcp_server("tool", "cc-switch-missing-command", claude_only()),
+    )
+    .expect("add mcp server");
+    std::fs::write(&settings_path, "{ not json").expect("corrupt live");
+    let skills_dir = home.join(".claude").join("skills");
+    std::fs::create_dir_all(&skills_dir).expect("create skills dir");
+    let ghost = skills_dir.join("ghost");
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(home.join("missing-skill"), &ghost).expect("create link");
+
+    let report = DoctorService::diagnose(Ok(&state));
+    assert_eq!(report.error_count(), 1, "{:#?}", report.findings);
+    let broken = report
+        .findings
+        .iter()
+        .find(|f| f.severity == DoctorSeverity::Error)
+        .expect("broken settings reported");
+    assert_eq!(
+        broken.fix,
+        Some(DoctorFix::ReapplyProvider {
+            app: AppType::Claude,
+            provider_id: "a".to_string(),
+            broken: vec![settings_path.clone()],
+        })
+    );
+    assert!(report
+        .findings
+        .iter()
+        .any(|f| f.area == "mcp" && f.message.contains("cc-switch-missing-command")));
+    #[cfg(unix)]
+    {
+        let remove_ghost = DoctorFix::RemoveDanglingLink {
+            path: ghost.clone(),
+        };
+        assert!(report.fixes().any(|fix| *fix == remove_ghost));
+    }
+
+    for outcome in DoctorService::fix(&state, &report) {
+        outcome.result.expect("fix applied");
+    }
+
+    // live 文件按当前供应商重写，原文件保留在旁边
+    let live: serde_json::Value = read_json_file(&settings_path).expect("rewritten live");
+    assert_eq!(live["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-a");
+    let kept = std::fs::read_dir(home.join(".claude"))
+        .expect("read claude dir")
+        .flatten()
+        .any(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .starts_with("settings.json.broken-")
+        });
+    assert!(kept);
+    assert!(ghost.symlink_metadata().is_err());
+
+    let report = DoctorService::diagnose(Ok(&state));
+    assert_eq!(report.error_count(), 0, "{:#?}", report.findings);
+    assert_eq!(report.fixes().count(), 0);
+}
@@ -0,0 +1,125 @@
+use serde_json::json;
+
+use cc_switch_lib::{
+    get_claude_mcp_path, AppType, HistoryService, McpService, MultiAppConfig, ProviderService,
+};
+
+#[path = "support.rs"]
+mod support;
+use support::{
+    claude_config, claude_only, claude_provider, ensure_test_home, live_claude_token,
+    lock_test_mutex, reset_test_fs, state_from_config, stdio_mcp_server,
+};
+
+fn two_providers() -> MultiAppConfig {
+    claude_config(
+        "a",
+        [
+            claude_provider("a", "A", "sk-a"),
+            claude_provider("b", "B", "sk-b"),
+        ],
+    )
+}
+
+#[test]
+fn history_records_changes_and_undo_resyncs_live_files() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    std::fs::create_dir_all(home.join(".claude")).expect("create claude dir (initialized)");
+
+    let state = state_from_config(two_providers());
+    HistoryService::set_source("setup");
+    state.save().expect("persist initial config");
+
+    HistoryService::set_source("provider switch b");
+    ProviderService::switch(&state, AppType::Claude, "b").expect("switch");
+    assert_eq!(live_claude_token(), "sk-b");
+
+    HistoryService::set_source("provider edit b");
+    let mut b = ProviderService::list(&state, AppType::Claude).expect("list")["b"].clone();
+    b.name = "Renamed".to_string();
+    ProviderService::update(&state, AppType::Claude, b).expect("update");
+
+    HistoryService::set_source("mcp add");
+    McpService::upsert_server(&state, stdio_mcp_server("fetch", "uvx", claude_only()))
+        .expect("add mcp server");
+
+    let sets = HistoryService::list(&state, 3).expect("history");
+    let sources: Vec<&str> = sets.iter().map(|s| s.source.as_str()).collect();
+    assert_eq!(sources, ["mcp add", "provider edit b", "provider switch b"]);
+    let mcp = &sets[0].entries[0];
+    assert_eq!(
+        (mcp.entity_type.as_str(), mcp.action.as_str()),
+        ("mcp_server", "create")
+    );
+    assert!(sets[2]
+        .entries
+        .iter()
+        .any(|e| e.entity_type == "current_provider"
+            && e.before == Some(json!("a"))
+            && e.after == Some(json!("b"))));
+
+    // 撤销最近一次：MCP 服务器从数据库与 live 文件中移除
+    HistoryService::set_source("undo");
+    let undone = HistoryService::undo(&state, 1, false).expect("undo mcp");
+    assert_eq!(undone[0].source, "mcp add");
+    assert!(!McpService::get_all_servers(&state)
+        .expect("servers")
+        .contains_key("fetch"));
+    let mcp_live = std::fs::read_to_string(get_claude_mcp_path()).unwrap_or_default();
+    assert!(!mcp_live.contains("fetch"), "{mcp_live}");
+
+    // 撤销标记不会被再次撤销，继续撤销更早的两次变更
+    let undone = HistoryService::undo(&state, 2, false).expect("undo provider changes");
+    let sources: Vec<&str> = undone.iter().map(|s| s.source.as_str()).collect();
+    assert_eq!(sources, ["provider edit b", "provider switch b"]);
+    assert_eq!(
+        ProviderService::current(&state, AppType::Claude).expect("current"),
+        "a"
+    );
+    let providers = ProviderService::list(&state, AppType::Claude).expect("list");
+    assert_eq!(providers["b"].name, "B");
+    assert_eq!(live_claude_token(), "sk-a");
+
+    let sets = HistoryService::list(&state, 10).expect("history");
+    assert!(sets[0].reverts.is_some());
+    assert!(sets
+        .iter()
+        .filter(|s| s.reverts.is_none() && s.source != "setup")
+        .all(|s| s.undone));
+}
+
+#[test]
+fn undo_refuses_out_of_band_changes_unless_forced() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = state_from_config(two_providers());
+    HistoryService::set_source("setup");
+    state.save().expect("persist initial config");
+
+    HistoryService::set_source("provider edit b");
+    let mut b = ProviderService::list(&state, AppType::Claude).expect("list")["b"].clone();
+    b.name = "Edited".to_string();
+    ProviderService::update(&state, AppType::Claude, b.clone()).expect("update");
+
+    // 绕过服务直接修改数据库（如手动恢复 SQL 备份），日志中不会有记录
+    b.name = "Out of band".to_string();
+    state
+        .db
+        .save_provider(AppType::Claude.as_str(), &b)
+        .expect("direct write");
+
+    let err = HistoryService::undo(&state, 1, false).expect_err("conflict detected");
+    assert!(err.to_string().contains("provider claude/b"), "{err}");
+
+    HistoryService::undo(&state, 1, true).expect("forced undo");
+    let providers = ProviderService::list(&state, AppType::Claude).expect("list");
+    assert_eq!(providers["b"].name, "B");
+    assert!(HistoryService::undo_candidates(&state, 5)
+        .expect("candidates")
+        .iter()
+        .all(|s| s.source != "provider edit b"));
+}
@@ -0,0 +1,164 @@
+use serde_json::json;
+
+use cc_switch_lib::{
+    AppState, AppType, DirectoryAppSelection, DirectorySelection, HookService, HookShell, McpApps,
+    McpService, ProviderService,
+};
+
+#[path = "support.rs"]
+mod support;
+use support::{
+    claude_config, claude_provider, ensure_test_home, live_claude_mcp_ids, lock_test_mutex,
+    reset_test_fs, state_from_config, stdio_mcp_server,
+};
+
+/// Claude 有 "work" 与 "relay" 两个供应商，当前为 "work"，MCP 服务器 "jira" 未启用
+fn seeded_state() -> AppState {
+    let home = ensure_test_home();
+    std::fs::create_dir_all(home.join(".claude")).expect("create claude dir (initialized)");
+
+    let providers = ["work", "relay"].map(|id| {
+        let mut provider = claude_provider(id, &id.to_uppercase(), &format!("sk-{id}"));
+        provider.settings_config["env"]["ANTHROPIC_BASE_URL"] =
+            json!(format!("https://{id}.example.com"));
+        provider
+    });
+    let state = state_from_config(claude_config("work", providers));
+    ProviderService::switch(&state, AppType::Claude, "work").expect("switch");
+
+    McpService::upsert_server(
+        &state,
+        stdio_mcp_server("jira", "jira-mcp", McpApps::default()),
+    )
+    .expect("add mcp server");
+    state
+}
+
+fn project_dir(name: &str) -> std::path::PathBuf {
+    let dir = ensure_test_home()
+        .join(".cc-switch")
+        .join("projects")
+        .join(name);
+    std::fs::create_dir_all(dir.join("src").join("nested")).expect("create project dir");
+    dir.canonicalize().expect("canonicalize")
+}
+
+fn current(state: &AppState) -> String {
+    ProviderService::current(state, AppType::Claude).expect("current")
+}
+
+#[test]
+fn hook_applies_project_file_and_restores_on_leave() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let state = seeded_state();
+    let project = project_dir("api");
+    std::fs::write(
+        project.join(".cc-switch.toml"),
+        "[claude]\nprovider = \"relay\"\nmcp = [\"jira\"]\n",
+    )
+    .expect("write project file");
+    let nested = project.join("src").join("nested");
+
+    let found = HookService::find(&nested)
+        .expect("find")
+        .expect("selection from an ancestor");
+    assert_eq!(found.dir, project);
+    assert!(!found.trusted);
+
+    // 未允许的配置文件不会被应用
+    let outcome = HookService::apply(&state, &nested).expect("enter untrusted project");
+    assert_eq!(outcome.blocked, Some(project.join(".cc-switch.toml")));
+    assert!(outcome.applied.is_none());
+    assert_eq!(current(&state), "work");
+    let env = HookService::env(&state, &nested).expect("env");
+    assert!(env.key.is_none() && env.set.is_empty());
+    assert!(env.blocked.is_some());
+
+    HookService::set_trusted(&nested, true).expect("allow project file");
+    let outcome = HookService::apply(&state, &nested).expect("enter project");
+    assert_eq!(outcome.applied.map(|f| f.dir), Some(project.clone()));
+    assert_eq!(current(&state), "relay");
+    assert_eq!(live_claude_mcp_ids(), ["jira"]);
+
+    // 在项目内移动不会重复应用
+    let outcome = HookService::apply(&state, &project).expect("stay in project");
+    assert_eq!(outcome, Default::default());
+
+    // 离开项目恢复进入前的选择
+    let outcome = HookService::apply(&state, ensure_test_home()).expect("leave project");
+    assert!(outcome.restored.is_some());
+    assert!(outcome.applied.is_none());
+    assert_eq!(current(&state), "work");
+    assert!(live_claude_mcp_ids().is_empty());
+
+    // 引用不存在的供应商时报错且不做改动
+    std::fs::write(
+        project.join(".cc-switch.toml"),
+        "[claude]\nprovider = \"missing\"\n",
+    )
+    .expect("rewrite project file");
+    let outcome = HookService::apply(&state, &project).expect("edited file needs a new allow");
+    assert!(outcome.blocked.is_some());
+    HookService::set_trusted(&project, true).expect("allow edited file");
+    let err = HookService::apply(&state, &project).expect_err("missing provider");
+    assert!(err.to_string().contains("missing"), "{err}");
+    assert_eq!(current(&state), "work");
+}
+
+#[test]
+fn hook_env_mode_exports_provider_env_from_settings_mapping() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let state = seeded_state();
+    let project = project_dir("web");
+
+    let mapped = HookService::set_mapping(
+        &project,
+        Some(DirectorySelection {
+            claude: Some(DirectoryAppSelection {
+                provider: Some("relay".to_string()),
+                mcp: None,
+            }),
+            ..Default::default()
+        }),
+    )
+    .expect("map directory");
+    assert_eq!(mapped, project);
+
+    let env = HookService::env(&state, &project.join("src")).expect("env");
+    assert!(env.key.is_some());
+    assert!(env.blocked.is_none(), "settings mappings need no allow");
+    assert!(env
+        .set
+        .contains(&("ANTHROPIC_AUTH_TOKEN".to_string(), "sk-relay".to_string())));
+    let script = HookService::env_script(HookShell::Bash, &env, &["OLD_TOKEN".to_string()]);
+    assert!(script.starts_with("unset OLD_TOKEN;"), "{script}");
+    assert!(
+        script.contains("export ANTHROPIC_BASE_URL='https://relay.example.com';"),
+        "{script}"
+    );
+
+    // 环境变量模式不改动全局配置
+    assert_eq!(current(&state), "work");
+
+    // 供应商内容变化后标识随之变化，shell 会重新导出
+    let mut relay = ProviderService::list(&state, AppType::Claude)
+        .expect("list providers")
+        .shift_remove("relay")
+        .expect("relay provider");
+    relay.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"] = json!("sk-rotated");
+    ProviderService::update(&state, AppType::Claude, relay).expect("update relay");
+    let rotated = HookService::env(&state, &project).expect("env after rotation");
+    assert_ne!(rotated.key, env.key);
+    assert!(rotated
+        .set
+        .contains(&("ANTHROPIC_AUTH_TOKEN".to_string(), "sk-rotated".to_string())));
+
+    HookService::set_mapping(&project, None).expect("unmap");
+    assert_eq!(
+        HookService::env(&state, &project).expect("env"),
+        Default::default()
+    );
+    assert!(HookService::set_mapping(&project, None).is_err());
+}
@@ -562,6 +562,8 @@ command = "echo"
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            codex_toml: None,
+            tools: Default::default(),
         },
     );
 
@@ -685,6 +687,8 @@ fn import_from_claude_merges_into_config() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            codex_toml: None,
+            tools: Default::default(),
         },
     );
 
@@ -1135,3 +1139,120 @@ fn export_config_to_file_returns_error_when_path_invalid() {
         "expected invalid export path message, got {err}"
     );
 }
+
+#[test]
+fn encrypted_export_and_backup_round_trip_with_passphrase() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    std::env::remove_var("CC_SWITCH_BACKUP_PASSPHRASE");
+    let db_path = home.join(".cc-switch").join("cc-switch.db");
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "p-secret".to_string();
+        manager.providers.insert(
+            "p-secret".to_string(),
+            Provider::with_id(
+                "p-secret".to_string(),
+                "Secret Claude".to_string(),
+                json!({
+                    "env": { "ANTHROPIC_AUTH_TOKEN": "sk-very-secret" }
+                }),
+                None,
+            ),
+        );
+    }
+    let app_state = state_from_config(config);
+    app_state.save().expect("persist db");
+
+    // 加密导出不包含明文密钥
+    let export_path = home.join("shared").join("cc-switch.sql.enc");
+    ConfigService::export_config_to_path_encrypted(&export_path, "correct horse")
+        .expect("encrypted export");
+    let raw = fs::read(&export_path).expect("read export");
+    assert!(raw.starts_with(b"CCSWENC"));
+    assert!(!String::from_utf8_lossy(&raw).contains("sk-very-secret"));
+
+    // 加密的自动备份可被列出并识别
+    let backup_id = ConfigService::create_backup_with_passphrase(
+        &db_path,
+        Some("shared".to_string()),
+        Some("correct horse"),
+    )
+    .expect("encrypted backup");
+    let backups = ConfigService::list_backups(&db_path).expect("list backups");
+    let listed = backups
+        .iter()
+        .find(|b| b.id == backup_id)
+        .expect("backup listed");
+    assert!(listed.encrypted);
+
+    app_state
+        .db
+        .delete_provider(AppType::Claude.as_str(), "p-secret")
+        .expect("remove provider");
+
+    // 缺少口令或口令错误时不修改数据库
+    let err = ConfigService::import_config_from_path(&export_path, &app_state)
+        .expect_err("passphrase required");
+    assert!(
+        err.to_string().contains("CC_SWITCH_BACKUP_PASSPHRASE"),
+        "{err}"
+    );
+    assert!(ConfigService::import_config_from_path_with_passphrase(
+        &export_path,
+        &app_state,
+        Some("wrong")
+    )
+    .is_err());
+    assert!(!has_provider(&app_state.db, "p-secret"));
+
+    ConfigService::import_config_from_path_with_passphrase(
+        &export_path,
+        &app_state,
+        Some("correct horse"),
+    )
+    .expect("import encrypted export");
+    assert!(has_provider(&app_state.db, "p-secret"));
+
+    app_state
+        .db
+        .delete_provider(AppType::Claude.as_str(), "p-secret")
+        .expect("remove provider");
+    ConfigService::restore_from_backup_id_with_passphrase(
+        &backup_id,
+        &app_state,
+        Some("correct horse"),
+    )
+    .expect("restore encrypted backup");
+    assert!(has_provider(&app_state.db, "p-secret"));
+
+    // 提供口令时，导入前的快照同样加密，不留下明文副本
+    let backup_dir = home.join(".cc-switch").join("backups");
+    let names: Vec<String> = fs::read_dir(&backup_dir)
+        .expect("read backups dir")
+        .map(|entry| {
+            entry
+                .expect("dir entry")
+                .file_name()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    assert!(names.iter().any(|n| n.ends_with(".sql.enc")), "{names:?}");
+    assert!(!names.iter().any(|n| n.ends_with(".db")), "{names:?}");
+    for name in &names {
+        let raw = fs::read(backup_dir.join(name)).expect("read backup");
+        assert!(raw.starts_with(b"CCSWENC"), "{name} is not encrypted");
+    }
+}
+
+fn has_provider(db: &Database, id: &str) -> bool {
+    db.get_provider_by_id(id, AppType::Claude.as_str())
+        .expect("read provider")
+        .is_some()
+}
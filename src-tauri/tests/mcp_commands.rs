@@ -3,8 +3,9 @@ use std::{collections::HashMap, fs};
 use serde_json::json;
 
 use cc_switch_lib::{
-    get_claude_mcp_path, get_claude_settings_path, AppError, AppState, AppType, McpApps, McpServer,
-    McpService, MultiAppConfig, ProviderService,
+    get_claude_mcp_path, get_claude_settings_path, get_codex_config_path, AppError, AppState,
+    AppType, McpApps, McpDriftKind, McpProjectScope, McpReconcileChoice, McpServer, McpService,
+    McpToolFilter, MultiAppConfig, ProviderService,
 };
 
 #[path = "support.rs"]
@@ -331,6 +332,8 @@ fn set_mcp_enabled_for_codex_writes_live_config() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            codex_toml: None,
+            tools: Default::default(),
         },
     );
 
@@ -399,6 +402,8 @@ fn upsert_server_skips_live_sync_when_gemini_uninitialized() {
         homepage: None,
         docs: None,
         tags: Vec::new(),
+        codex_toml: None,
+        tools: Default::default(),
     };
 
     McpService::upsert_server(&state, server).expect("upsert server should succeed");
@@ -467,6 +472,8 @@ fn upsert_server_disables_app_removes_from_gemini_live() {
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            codex_toml: None,
+            tools: Default::default(),
         },
     );
 
@@ -490,6 +497,8 @@ fn upsert_server_disables_app_removes_from_gemini_live() {
         homepage: None,
         docs: None,
         tags: Vec::new(),
+        codex_toml: None,
+        tools: Default::default(),
     };
 
     McpService::upsert_server(&state, server).expect("upsert server succeeds");
@@ -507,3 +516,691 @@ fn upsert_server_disables_app_removes_from_gemini_live() {
         "upsert with Gemini disabled should remove it from ~/.gemini/settings.json, got: {settings_text}"
     );
 }
+
+fn stdio_server(id: &str, command: &str) -> McpServer {
+    McpServer {
+        id: id.to_string(),
+        name: id.to_string(),
+        server: json!({
+            "type": "stdio",
+            "command": command
+        }),
+        apps: McpApps::default(),
+        description: None,
+        homepage: None,
+        docs: None,
+        tags: Vec::new(),
+        codex_toml: None,
+        tools: Default::default(),
+    }
+}
+
+#[test]
+fn enable_in_project_writes_shared_and_local_claude_config() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let _home = ensure_test_home();
+    let project = tempfile::tempdir().expect("create project dir");
+
+    let mut config = MultiAppConfig::default();
+    let mut echo = stdio_server("echo", "echo");
+    echo.tools.claude.deny = vec!["rm".to_string()];
+    config
+        .mcp
+        .servers
+        .get_or_insert_with(HashMap::new)
+        .insert("echo".into(), echo);
+    let state = state_from_config(config);
+    let project_settings = |file: &str| -> serde_json::Value {
+        let path = project.path().join(".claude").join(file);
+        serde_json::from_str(&fs::read_to_string(path).expect("read project settings"))
+            .expect("parse project settings")
+    };
+    // 用户为同一服务器手写的规则不由 cc-switch 管理
+    fs::create_dir_all(project.path().join(".claude")).expect("create .claude");
+    fs::write(
+        project.path().join(".claude").join("settings.json"),
+        json!({
+            "permissions": {
+                "allow": ["mcp__echo__search"],
+                "deny": ["mcp__echo__exec"]
+            }
+        })
+        .to_string(),
+    )
+    .expect("seed project settings");
+
+    McpService::enable_in_project(
+        &state,
+        "echo",
+        &AppType::Claude,
+        project.path(),
+        McpProjectScope::Shared,
+    )
+    .expect("enable in shared project config");
+
+    let shared_text = fs::read_to_string(project.path().join(".mcp.json")).expect("read .mcp.json");
+    let shared: serde_json::Value = serde_json::from_str(&shared_text).expect("parse .mcp.json");
+    assert_eq!(
+        shared.pointer("/mcpServers/echo/command"),
+        Some(&json!("echo")),
+        "server should be written to .mcp.json, got: {shared_text}"
+    );
+    assert!(
+        !get_claude_mcp_path().exists(),
+        "shared scope must not touch ~/.claude.json"
+    );
+    // 工具过滤同样作用于项目级配置
+    assert_eq!(
+        project_settings("settings.json")["permissions"],
+        json!({
+            "allow": ["mcp__echo__search"],
+            "deny": ["mcp__echo__exec", "mcp__echo__rm"]
+        })
+    );
+
+    McpService::enable_in_project(
+        &state,
+        "echo",
+        &AppType::Claude,
+        project.path(),
+        McpProjectScope::Local,
+    )
+    .expect("enable in local project config");
+
+    let local =
+        McpService::get_project_servers(&AppType::Claude, project.path(), McpProjectScope::Local)
+            .expect("read local project servers");
+    assert!(local.contains_key("echo"));
+    assert_eq!(
+        project_settings("settings.local.json")["permissions"]["deny"],
+        json!(["mcp__echo__rm"])
+    );
+
+    let user_text = fs::read_to_string(get_claude_mcp_path()).expect("read ~/.claude.json");
+    let user: serde_json::Value = serde_json::from_str(&user_text).expect("parse ~/.claude.json");
+    assert!(
+        user.get("mcpServers").is_none(),
+        "local project scope must not write user-level mcpServers"
+    );
+
+    let removed = McpService::disable_in_project(
+        "echo",
+        &AppType::Claude,
+        project.path(),
+        McpProjectScope::Shared,
+    )
+    .expect("disable in shared project config");
+    assert!(removed);
+    let shared_after =
+        McpService::get_project_servers(&AppType::Claude, project.path(), McpProjectScope::Shared)
+            .expect("read shared project servers");
+    assert!(shared_after.is_empty());
+    assert_eq!(
+        project_settings("settings.json")["permissions"],
+        json!({
+            "allow": ["mcp__echo__search"],
+            "deny": ["mcp__echo__exec"]
+        })
+    );
+
+    // 全局启用状态不受项目级操作影响
+    let servers = McpService::get_all_servers(&state).expect("get servers");
+    assert!(servers["echo"].apps.is_empty());
+}
+
+#[test]
+fn enable_in_project_converts_gemini_format_and_rejects_unsupported_scopes() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let project = tempfile::tempdir().expect("create project dir");
+
+    let mut config = MultiAppConfig::default();
+    let mut remote = stdio_server("remote", "unused");
+    remote.server = json!({ "type": "http", "url": "https://mcp.example.com" });
+    remote.tools.gemini.deny = vec!["push".to_string()];
+    config
+        .mcp
+        .servers
+        .get_or_insert_with(HashMap::new)
+        .insert("remote".into(), remote);
+    let state = state_from_config(config);
+
+    McpService::enable_in_project(
+        &state,
+        "remote",
+        &AppType::Gemini,
+        project.path(),
+        McpProjectScope::Shared,
+    )
+    .expect("enable in gemini project config");
+
+    let settings_path = project.path().join(".gemini").join("settings.json");
+    let settings: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&settings_path).expect("read settings"))
+            .expect("parse settings");
+    assert_eq!(
+        settings.pointer("/mcpServers/remote/httpUrl"),
+        Some(&json!("https://mcp.example.com"))
+    );
+    assert_eq!(
+        settings.pointer("/mcpServers/remote/excludeTools"),
+        Some(&json!(["push"]))
+    );
+    assert!(
+        !home.join(".gemini").exists(),
+        "project scope must not create user-level ~/.gemini"
+    );
+
+    let err = McpService::enable_in_project(
+        &state,
+        "remote",
+        &AppType::Codex,
+        project.path(),
+        McpProjectScope::Shared,
+    )
+    .expect_err("codex has no project-level MCP config");
+    assert!(matches!(err, AppError::Localized { key, .. } if key == "mcp.project_unsupported"));
+
+    // Gemini 没有本机项目配置，--local 不能被静默忽略
+    let other = tempfile::tempdir().expect("create project dir");
+    let err = McpService::enable_in_project(
+        &state,
+        "remote",
+        &AppType::Gemini,
+        other.path(),
+        McpProjectScope::Local,
+    )
+    .expect_err("gemini has no local project scope");
+    assert!(
+        matches!(err, AppError::Localized { key, .. } if key == "mcp.project_local_unsupported")
+    );
+    assert!(!other.path().join(".gemini").exists());
+    assert!(McpService::disable_in_project(
+        "remote",
+        &AppType::Gemini,
+        project.path(),
+        McpProjectScope::Local
+    )
+    .is_err());
+}
+
+#[test]
+fn import_from_project_adds_new_servers_without_enabling_apps() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let _home = ensure_test_home();
+    let project = tempfile::tempdir().expect("create project dir");
+
+    fs::write(
+        project.path().join(".mcp.json"),
+        serde_json::to_string_pretty(&json!({
+            "mcpServers": {
+                "shared": { "command": "npx", "args": ["shared-mcp"] },
+                "existing": { "command": "other" }
+            }
+        }))
+        .expect("serialize .mcp.json"),
+    )
+    .expect("seed .mcp.json");
+
+    let gemini_dir = project.path().join(".gemini");
+    fs::create_dir_all(&gemini_dir).expect("create project .gemini");
+    fs::write(
+        gemini_dir.join("settings.json"),
+        serde_json::to_string_pretty(&json!({
+            "mcpServers": {
+                "gemini-only": { "httpUrl": "https://mcp.example.com" }
+            }
+        }))
+        .expect("serialize gemini settings"),
+    )
+    .expect("seed project gemini settings");
+
+    let mut config = MultiAppConfig::default();
+    let mut existing = stdio_server("existing", "keep-me");
+    existing.apps.claude = true;
+    config
+        .mcp
+        .servers
+        .get_or_insert_with(HashMap::new)
+        .insert("existing".into(), existing);
+    let state = state_from_config(config);
+
+    let count =
+        McpService::import_from_project(&state, project.path()).expect("import from project");
+    assert_eq!(count, 2);
+
+    let servers = McpService::get_all_servers(&state).expect("get servers");
+    assert!(servers["shared"].apps.is_empty());
+    assert_eq!(
+        servers["gemini-only"].server.get("type"),
+        Some(&json!("http"))
+    );
+    assert_eq!(
+        servers["existing"].server.get("command"),
+        Some(&json!("keep-me")),
+        "existing servers must not be overwritten by project import"
+    );
+
+    let persisted = state.db.get_all_mcp_servers().expect("load from db");
+    assert!(persisted.contains_key("shared"));
+}
+
+#[test]
+fn secret_placeholders_resolve_only_in_live_config() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let mut server = stdio_server("github", "npx");
+    server.server["env"] = json!({
+        "GITHUB_TOKEN": "${secret:GITHUB_PAT}",
+        "DB_URL": "postgres://app:${secret:DB_PASSWORD}@localhost/app"
+    });
+    server.apps.claude = true;
+
+    let state = state_from_config(MultiAppConfig::default());
+    McpService::upsert_server(&state, server).expect("upsert server");
+
+    McpService::set_secret(&state, "GITHUB_PAT", "ghp_live_value").expect("set secret");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let secrets_path = home.join(".cc-switch").join("secrets.json");
+        let mode = fs::metadata(&secrets_path)
+            .expect("secrets.json metadata")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600, "secrets.json must be owner-only");
+    }
+    McpService::sync_all_enabled(&state).expect("sync enabled servers");
+
+    let live: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(get_claude_mcp_path()).expect("read ~/.claude.json"),
+    )
+    .expect("parse ~/.claude.json");
+    let env = &live["mcpServers"]["github"]["env"];
+    assert_eq!(env["GITHUB_TOKEN"], json!("ghp_live_value"));
+    assert_eq!(
+        env["DB_URL"],
+        json!("postgres://app:${secret:DB_PASSWORD}@localhost/app"),
+        "unresolved placeholders are written literally"
+    );
+
+    let unresolved = McpService::unresolved_secrets(&state, None).expect("unresolved secrets");
+    assert_eq!(
+        unresolved,
+        vec![("github".to_string(), vec!["DB_PASSWORD".to_string()])]
+    );
+
+    let stored = state.db.get_all_mcp_servers().expect("load from db");
+    assert_eq!(
+        stored["github"].server["env"]["GITHUB_TOKEN"],
+        json!("${secret:GITHUB_PAT}"),
+        "database keeps the placeholder, never the secret value"
+    );
+
+    let dump_path = home.join("export.sql");
+    state.db.export_sql(&dump_path).expect("export sql");
+    let dump = fs::read_to_string(&dump_path).expect("read sql dump");
+    assert!(dump.contains("${secret:GITHUB_PAT}"));
+    assert!(
+        !dump.contains("ghp_live_value"),
+        "secret values must not leak into SQL exports"
+    );
+
+    let usages = McpService::list_secrets(&state).expect("list secrets");
+    let names: Vec<_> = usages
+        .iter()
+        .map(|u| (u.name.as_str(), u.defined))
+        .collect();
+    assert_eq!(names, vec![("DB_PASSWORD", false), ("GITHUB_PAT", true)]);
+}
+
+fn read_claude_live() -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(get_claude_mcp_path()).expect("read ~/.claude.json"))
+        .expect("parse ~/.claude.json")
+}
+
+#[test]
+fn diff_live_reports_manual_claude_edits_and_reconcile_resolves_them() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = state_from_config(MultiAppConfig::default());
+    for id in ["edited", "deleted"] {
+        let mut server = stdio_server(id, "npx");
+        server.server["env"] = json!({ "LEVEL": "info" });
+        server.apps.claude = true;
+        McpService::upsert_server(&state, server).expect("upsert server");
+    }
+
+    // 手动编辑 ~/.claude.json：修改一项、删除一项、新增一项
+    let mut live = read_claude_live();
+    let servers = live["mcpServers"].as_object_mut().expect("mcpServers");
+    servers["edited"]["env"]["LEVEL"] = json!("debug");
+    servers.remove("deleted");
+    servers.insert(
+        "added".into(),
+        json!({ "command": "uvx", "args": ["added-mcp"] }),
+    );
+    fs::write(
+        get_claude_mcp_path(),
+        serde_json::to_string_pretty(&live).expect("serialize"),
+    )
+    .expect("write ~/.claude.json");
+
+    let drifts = McpService::diff_live(&state, &AppType::Claude).expect("diff live");
+    let summary: Vec<_> = drifts.iter().map(|d| (d.id.as_str(), d.kind)).collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("added", McpDriftKind::Added),
+            ("deleted", McpDriftKind::Removed),
+            ("edited", McpDriftKind::Changed),
+        ]
+    );
+    let edited = &drifts[2];
+    assert_eq!(edited.fields.len(), 1);
+    assert_eq!(edited.fields[0].path_label(), "env.LEVEL");
+    assert_eq!(edited.fields[0].stored, Some(json!("info")));
+    assert_eq!(edited.fields[0].live, Some(json!("debug")));
+
+    McpService::reconcile(&state, &AppType::Claude, "edited", McpReconcileChoice::Live)
+        .expect("keep live edit");
+    McpService::reconcile(
+        &state,
+        &AppType::Claude,
+        "deleted",
+        McpReconcileChoice::Stored,
+    )
+    .expect("restore stored server");
+    McpService::reconcile(&state, &AppType::Claude, "added", McpReconcileChoice::Live)
+        .expect("import live server");
+
+    let stored = state.db.get_all_mcp_servers().expect("load from db");
+    assert_eq!(stored["edited"].server["env"]["LEVEL"], json!("debug"));
+    assert!(stored["added"].apps.claude);
+    assert!(read_claude_live()["mcpServers"]["deleted"].is_object());
+    assert!(
+        McpService::diff_live(&state, &AppType::Claude)
+            .expect("diff after reconcile")
+            .is_empty(),
+        "reconciled servers should no longer drift"
+    );
+}
+
+#[test]
+fn diff_live_ignores_format_conversion_and_resolved_secrets() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    fs::create_dir_all(home.join(".codex")).expect("create ~/.codex");
+    fs::create_dir_all(home.join(".gemini")).expect("create ~/.gemini");
+
+    let state = state_from_config(MultiAppConfig::default());
+    McpService::set_secret(&state, "API_TOKEN", "tok_live").expect("set secret");
+
+    let mut server = stdio_server("remote", "unused");
+    server.server = json!({
+        "type": "http",
+        "url": "https://mcp.example.com",
+        "headers": { "Authorization": "Bearer ${secret:API_TOKEN}" }
+    });
+    server.apps.codex = true;
+    server.apps.gemini = true;
+    McpService::upsert_server(&state, server).expect("upsert server");
+
+    let codex_text = fs::read_to_string(get_codex_config_path()).expect("read config.toml");
+    assert!(codex_text.contains("Bearer tok_live"));
+
+    for app in [AppType::Codex, AppType::Gemini] {
+        let drifts = McpService::diff_live(&state, &app).expect("diff live");
+        assert!(
+            drifts.is_empty(),
+            "unexpected drift for {app:?}: {drifts:?}"
+        );
+    }
+
+    // 修改 Codex live 中的 URL：差异中仍以占位符展示未改动的密钥字段
+    fs::write(
+        get_codex_config_path(),
+        codex_text.replace("https://mcp.example.com", "https://mcp.example.org"),
+    )
+    .expect("edit config.toml");
+    let drifts = McpService::diff_live(&state, &AppType::Codex).expect("diff live");
+    assert_eq!(drifts.len(), 1);
+    assert_eq!(drifts[0].fields.len(), 1);
+    assert_eq!(drifts[0].fields[0].path_label(), "url");
+
+    // Gemini live 中另有尚未处理的修改
+    let gemini_path = home.join(".gemini").join("settings.json");
+    let gemini_text = fs::read_to_string(&gemini_path).expect("read gemini settings");
+    let gemini_edited = gemini_text.replace("https://mcp.example.com", "https://mcp.example.net");
+    fs::write(&gemini_path, &gemini_edited).expect("edit gemini settings");
+
+    McpService::reconcile(&state, &AppType::Codex, "remote", McpReconcileChoice::Live)
+        .expect("keep live url");
+    assert_eq!(
+        fs::read_to_string(&gemini_path).expect("read gemini settings"),
+        gemini_edited,
+        "keeping the Codex version must not rewrite other apps' live files"
+    );
+    assert_eq!(
+        McpService::diff_live(&state, &AppType::Gemini)
+            .expect("diff live")
+            .len(),
+        1
+    );
+    let stored = state.db.get_all_mcp_servers().expect("load from db");
+    assert_eq!(
+        stored["remote"].server["url"],
+        json!("https://mcp.example.org")
+    );
+    assert_eq!(
+        stored["remote"].server["headers"]["Authorization"],
+        json!("Bearer ${secret:API_TOKEN}"),
+        "adopting live edits must not pull secret values into the store"
+    );
+    let raw = stored["remote"].codex_toml.as_deref().unwrap_or_default();
+    assert!(
+        !raw.contains("tok_live"),
+        "raw fragment leaks the secret: {raw}"
+    );
+    assert!(
+        fs::read_to_string(get_codex_config_path())
+            .expect("read config.toml")
+            .contains("Bearer tok_live"),
+        "headers are still written from the JSON spec"
+    );
+}
+
+#[test]
+fn codex_raw_toml_round_trips_fields_json_cannot_express() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    fs::create_dir_all(home.join(".codex")).expect("create ~/.codex");
+
+    let original = r#"model = "gpt-5"
+
+[mcp_servers.docs]
+# docs server pinned by hand
+command = "npx"
+args = ["-y", "docs-mcp"]
+startup_timeout_sec = 20
+tool_timeout_sec = 45.5
+enabled_tools = ["search", "fetch"]
+pinned_at = 2024-01-01T00:00:00Z
+
+[mcp_servers.docs.env]
+API_URL = "https://docs.example.com"
+# retries before giving up
+RETRIES = 3
+API_TOKEN = "tok-live-secret"
+
+[mcp_servers.docs.limits]
+burst = { requests = 10, window = 60 }
+"#;
+    fs::write(get_codex_config_path(), original).expect("seed config.toml");
+
+    let state = state_from_config(MultiAppConfig::default());
+    McpService::import_from_codex(&state).expect("import from codex");
+
+    let stored = state.db.get_all_mcp_servers().expect("load from db");
+    let raw = stored["docs"]
+        .codex_toml
+        .as_deref()
+        .expect("raw TOML fragment stored");
+    assert!(raw.contains("RETRIES = 3"));
+    assert!(raw.contains("# docs server pinned by hand"));
+    // env 的字符串值只保存在 JSON 规范中，原始片段不包含 live 中的明文密钥
+    assert!(raw.contains("# retries before giving up"), "{raw}");
+    assert!(!raw.contains("tok-live-secret"), "{raw}");
+
+    // 清空 live 后重新同步：原始片段中的字段应完整恢复
+    fs::write(get_codex_config_path(), "model = \"gpt-5\"\n").expect("reset config.toml");
+    McpService::sync_all_enabled(&state).expect("sync to codex");
+
+    let text = fs::read_to_string(get_codex_config_path()).expect("read config.toml");
+    for expected in [
+        "# docs server pinned by hand",
+        "startup_timeout_sec = 20",
+        "tool_timeout_sec = 45.5",
+        "enabled_tools = [\"search\", \"fetch\"]",
+        "pinned_at = 2024-01-01T00:00:00Z",
+        "RETRIES = 3",
+        "# retries before giving up",
+        "API_TOKEN = \"tok-live-secret\"",
+        "burst = { requests = 10, window = 60 }",
+    ] {
+        assert!(text.contains(expected), "missing `{expected}` in:\n{text}");
+    }
+    assert!(
+        McpService::diff_live(&state, &AppType::Codex)
+            .expect("diff live")
+            .is_empty(),
+        "raw passthrough should not show up as drift"
+    );
+
+    // 通过 JSON 规范修改字段：仅覆盖改动的键，其余原样保留
+    let mut server = stored["docs"].clone();
+    server.server["args"] = json!(["-y", "docs-mcp@2"]);
+    McpService::upsert_server(&state, server).expect("update server");
+
+    let text = fs::read_to_string(get_codex_config_path()).expect("read config.toml");
+    let parsed: toml::Table = toml::from_str(&text).expect("valid TOML");
+    let docs = &parsed["mcp_servers"]["docs"];
+    assert_eq!(
+        docs["args"].as_array().map(|a| a.len()),
+        Some(2),
+        "args should be rewritten from the JSON spec"
+    );
+    assert_eq!(docs["args"][1].as_str(), Some("docs-mcp@2"));
+    assert_eq!(docs["env"]["RETRIES"].as_integer(), Some(3));
+    assert_eq!(docs["limits"]["burst"]["window"].as_integer(), Some(60));
+    assert!(text.contains("# docs server pinned by hand"));
+}
+
+#[test]
+fn tool_filters_are_written_per_app_and_revoked_on_change() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    // 用户自己的权限规则必须保留
+    let settings_path = get_claude_settings_path();
+    fs::create_dir_all(settings_path.parent().unwrap()).expect("create claude dir");
+    fs::write(
+        &settings_path,
+        serde_json::to_string_pretty(&json!({
+            "permissions": { "allow": ["Bash(ls:*)"] }
+        }))
+        .unwrap(),
+    )
+    .expect("seed settings.json");
+    fs::create_dir_all(home.join(".gemini")).expect("create gemini dir");
+    fs::create_dir_all(home.join(".codex")).expect("create codex dir");
+
+    let mut server = stdio_server("github", "npx");
+    server.apps.claude = true;
+    server.apps.codex = true;
+    server.apps.gemini = true;
+    server.tools.claude.deny = vec!["delete_repo".to_string()];
+    server.tools.codex.allow = vec!["search".to_string(), "fetch".to_string()];
+    server.tools.gemini.deny = vec!["push".to_string()];
+
+    let state = state_from_config(MultiAppConfig::default());
+    McpService::upsert_server(&state, server).expect("upsert server");
+
+    let read_settings = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&settings_path).expect("read settings.json"))
+            .expect("parse settings.json")
+    };
+    let settings = read_settings();
+    assert_eq!(settings["permissions"]["allow"], json!(["Bash(ls:*)"]));
+    assert_eq!(
+        settings["permissions"]["deny"],
+        json!(["mcp__github__delete_repo"])
+    );
+
+    let codex: toml::Table =
+        toml::from_str(&fs::read_to_string(get_codex_config_path()).expect("read config.toml"))
+            .expect("parse config.toml");
+    assert_eq!(
+        codex["mcp_servers"]["github"]["enabled_tools"],
+        toml::Value::from(vec!["search", "fetch"])
+    );
+
+    let gemini: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(home.join(".gemini").join("settings.json"))
+            .expect("read gemini settings"),
+    )
+    .expect("parse gemini settings");
+    assert_eq!(
+        gemini["mcpServers"]["github"]["excludeTools"],
+        json!(["push"])
+    );
+
+    for app in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        assert!(
+            McpService::diff_live(&state, &app)
+                .expect("diff live")
+                .is_empty(),
+            "tool filters should not show up as drift for {app:?}"
+        );
+    }
+
+    // Claude 的 allow 规则只会免确认放行，无法限制服务器，因此拒绝允许列表
+    let mut server = state.db.get_all_mcp_servers().expect("load from db")["github"].clone();
+    assert_eq!(server.tools.claude.deny, vec!["delete_repo".to_string()]);
+    server.tools.claude = McpToolFilter {
+        allow: vec!["search".to_string()],
+        deny: Vec::new(),
+    };
+    let err = McpService::upsert_server(&state, server.clone()).expect_err("claude allow list");
+    assert!(matches!(err, AppError::InvalidInput(_)), "{err}");
+
+    // 修改过滤器：旧规则撤销、新规则写入
+    server.tools.claude = McpToolFilter {
+        allow: Vec::new(),
+        deny: vec!["push".to_string()],
+    };
+    McpService::upsert_server(&state, server).expect("update filter");
+
+    let settings = read_settings();
+    assert_eq!(settings["permissions"]["allow"], json!(["Bash(ls:*)"]));
+    assert_eq!(
+        settings["permissions"]["deny"],
+        json!(["mcp__github__push"])
+    );
+
+    // 对 Claude 禁用后，规则随服务器一起移除
+    McpService::toggle_app(&state, "github", AppType::Claude, false).expect("disable claude");
+    let settings = read_settings();
+    assert_eq!(settings["permissions"]["allow"], json!(["Bash(ls:*)"]));
+    assert!(settings["permissions"].get("deny").is_none());
+}
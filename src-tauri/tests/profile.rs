@@ -0,0 +1,150 @@
+use cc_switch_lib::{
+    AppState, AppType, HistoryService, McpService, NewSkillOptions, ProfileService, PromptService,
+    ProviderService, SkillService,
+};
+
+#[path = "support.rs"]
+mod support;
+use support::{
+    claude_config, claude_only, claude_provider, ensure_test_home, live_claude_mcp_ids,
+    lock_test_mutex, prompt, reset_test_fs, state_from_config, stdio_mcp_server,
+};
+
+/// Claude 有两个供应商、一个 MCP 服务器、两个提示词与一个 Skill，当前为 "work" 组合
+fn seeded_state() -> AppState {
+    let home = ensure_test_home();
+    std::fs::create_dir_all(home.join(".claude")).expect("create claude dir (initialized)");
+
+    let state = state_from_config(claude_config(
+        "work",
+        ["work", "personal"].map(|id| claude_provider(id, &id.to_uppercase(), &format!("sk-{id}"))),
+    ));
+    ProviderService::switch(&state, AppType::Claude, "work").expect("switch");
+
+    McpService::upsert_server(&state, stdio_mcp_server("jira", "jira-mcp", claude_only()))
+        .expect("add mcp server");
+
+    for id in ["strict", "casual"] {
+        PromptService::upsert_prompt(
+            &state,
+            AppType::Claude,
+            id,
+            prompt(id, &format!("{id} rules\n")),
+        )
+        .expect("save prompt");
+    }
+    PromptService::enable_prompt(&state, AppType::Claude, "strict").expect("enable prompt");
+
+    SkillService::create_local(&NewSkillOptions {
+        directory: "review".to_string(),
+        description: "Review code".to_string(),
+        with_scripts: false,
+        with_references: false,
+        apps: vec![AppType::Claude],
+    })
+    .expect("create skill");
+
+    state
+}
+
+fn stack(state: &AppState) -> Vec<String> {
+    PromptService::active_stack(state, AppType::Claude)
+        .expect("stack")
+        .into_iter()
+        .map(|p| p.id)
+        .collect()
+}
+
+#[test]
+fn profile_use_switches_everything_in_one_change_set() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let state = seeded_state();
+    let home = ensure_test_home();
+    let skill_link = home.join(".claude").join("skills").join("review");
+
+    let work = ProfileService::save_current(&state, "work", None, &[AppType::Claude], false)
+        .expect("save work");
+    let claude = &work.apps["claude"];
+    assert_eq!(claude.provider.as_deref(), Some("work"));
+    assert_eq!(claude.mcp_servers, ["jira"]);
+    assert_eq!(claude.prompts, ["strict"]);
+    assert_eq!(claude.skills, ["review"]);
+    assert!(
+        ProfileService::save_current(&state, "work", None, &[AppType::Claude], false).is_err(),
+        "existing profile needs overwrite"
+    );
+
+    // 手动切到另一组合并保存为 "personal"
+    ProviderService::switch(&state, AppType::Claude, "personal").expect("switch");
+    McpService::toggle_app(&state, "jira", AppType::Claude, false).expect("disable mcp");
+    PromptService::set_stack(&state, AppType::Claude, &["casual".to_string()]).expect("stack");
+    SkillService::toggle_app("review", &AppType::Claude, false).expect("disable skill");
+    ProfileService::save_current(&state, "personal", None, &[AppType::Claude], false)
+        .expect("save personal");
+
+    ProfileService::use_profile(&state, "work").expect("use work");
+    assert_eq!(
+        ProviderService::current(&state, AppType::Claude).expect("current"),
+        "work"
+    );
+    assert_eq!(live_claude_mcp_ids(), ["jira"]);
+    assert_eq!(stack(&state), ["strict"]);
+    assert!(skill_link.exists(), "skill synced to the claude skills dir");
+    assert_eq!(
+        ProfileService::active(&state).expect("active").as_deref(),
+        Some("work")
+    );
+
+    // 供应商、MCP 与提示词的改动归入同一变更集
+    let latest = HistoryService::list(&state, 1).expect("history");
+    let kinds: Vec<&str> = latest[0]
+        .entries
+        .iter()
+        .map(|e| e.entity_type.as_str())
+        .collect();
+    for kind in ["current_provider", "mcp_server", "prompt"] {
+        assert!(kinds.contains(&kind), "{kind} missing from {kinds:?}");
+    }
+
+    assert!(ProfileService::delete(&state, "work").expect("delete"));
+    assert_eq!(ProfileService::active(&state).expect("active"), None);
+}
+
+#[test]
+fn profile_use_rolls_back_when_a_step_fails() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let state = seeded_state();
+    let home = ensure_test_home();
+
+    ProfileService::save_current(&state, "work", None, &[AppType::Claude], false)
+        .expect("save work");
+    ProviderService::switch(&state, AppType::Claude, "personal").expect("switch");
+    McpService::toggle_app(&state, "jira", AppType::Claude, false).expect("disable mcp");
+    PromptService::set_stack(&state, AppType::Claude, &["casual".to_string()]).expect("stack");
+    SkillService::toggle_app("review", &AppType::Claude, false).expect("disable skill");
+
+    // Skill 仍有记录但 SSOT 目录已丢失：校验通过，启用时失败
+    std::fs::remove_dir_all(home.join(".cc-switch").join("skills").join("review"))
+        .expect("remove skill ssot dir");
+
+    let err = ProfileService::use_profile(&state, "work").expect_err("skill step fails");
+    assert!(err.to_string().contains("已回滚"), "{err}");
+    assert_eq!(
+        ProviderService::current(&state, AppType::Claude).expect("current"),
+        "personal"
+    );
+    assert!(
+        live_claude_mcp_ids().is_empty(),
+        "mcp server disabled again"
+    );
+    assert_eq!(stack(&state), ["casual"]);
+    assert_eq!(ProfileService::active(&state).expect("active"), None);
+
+    // 引用不存在的条目时不做任何改动
+    ProviderService::delete(&state, AppType::Claude, "work").expect("delete provider");
+    let err = ProfileService::use_profile(&state, "work").expect_err("missing provider");
+    assert!(err.to_string().contains("work"), "{err}");
+    assert_eq!(stack(&state), ["casual"]);
+}
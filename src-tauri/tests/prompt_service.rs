@@ -0,0 +1,282 @@
+use cc_switch_lib::{AppType, MultiAppConfig, PromptService};
+
+#[path = "support.rs"]
+mod support;
+use support::{ensure_test_home, lock_test_mutex, prompt, reset_test_fs, state_from_config};
+
+fn section(id: &str, content: &str) -> String {
+    format!("<!-- cc-switch:begin {id} -->\n{content}\n<!-- cc-switch:end {id} -->")
+}
+
+#[test]
+fn templated_prompts_render_on_activation_and_follow_included_edits() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let state = state_from_config(MultiAppConfig::default());
+
+    PromptService::upsert_prompt(
+        &state,
+        AppType::Codex,
+        "base",
+        prompt("base", "Follow the team policy.\n"),
+    )
+    .expect("save base");
+    PromptService::upsert_prompt(
+        &state,
+        AppType::Codex,
+        "main",
+        prompt(
+            "main",
+            "{{> base}}{{#app codex}}Running in {{app}}.\n{{else}}Not codex.\n{{/app}}",
+        ),
+    )
+    .expect("save main");
+
+    let preview = PromptService::render_prompt(&state, AppType::Codex, "main").expect("render");
+    assert_eq!(
+        preview.content,
+        "Follow the team policy.\nRunning in codex.\n"
+    );
+    assert!(preview.unresolved.is_empty());
+
+    PromptService::enable_prompt(&state, AppType::Codex, "main").expect("activate");
+    let agents_md = home.join(".codex").join("AGENTS.md");
+    assert_eq!(
+        std::fs::read_to_string(&agents_md).expect("read AGENTS.md"),
+        format!(
+            "{}\n",
+            section("main", "Follow the team policy.\nRunning in codex.")
+        )
+    );
+
+    // 修改被包含的提示词后，live 文件同步更新，模板本身保持不变
+    PromptService::upsert_prompt(
+        &state,
+        AppType::Codex,
+        "base",
+        prompt("base", "Follow the updated policy.\n"),
+    )
+    .expect("update base");
+    assert_eq!(
+        std::fs::read_to_string(&agents_md).expect("read AGENTS.md"),
+        format!(
+            "{}\n",
+            section("main", "Follow the updated policy.\nRunning in codex.")
+        )
+    );
+
+    // 重新激活时，渲染结果不会被回填覆盖模板
+    PromptService::enable_prompt(&state, AppType::Codex, "main").expect("re-activate");
+    let prompts = PromptService::get_prompts(&state, AppType::Codex).expect("prompts");
+    assert!(prompts["main"].content.starts_with("{{> base}}"));
+    assert_eq!(prompts.len(), 2, "no backup for unchanged rendered output");
+
+    // 手动修改的托管段落另存为备份，而不是覆盖模板
+    std::fs::write(&agents_md, format!("{}\n", section("main", "hand edited")))
+        .expect("edit live file");
+    PromptService::enable_prompt(&state, AppType::Codex, "main").expect("activate again");
+    let prompts = PromptService::get_prompts(&state, AppType::Codex).expect("prompts");
+    assert!(prompts["main"].content.starts_with("{{> base}}"));
+    assert!(prompts
+        .values()
+        .any(|p| p.id.starts_with("backup-") && p.content == "hand edited"));
+}
+
+#[test]
+fn activation_fails_without_side_effects_on_template_errors() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let state = state_from_config(MultiAppConfig::default());
+
+    PromptService::upsert_prompt(&state, AppType::Claude, "a", prompt("a", "{{> b}}"))
+        .expect("save a");
+    PromptService::upsert_prompt(&state, AppType::Claude, "b", prompt("b", "{{> a}}"))
+        .expect("save b");
+
+    let err = PromptService::enable_prompt(&state, AppType::Claude, "a").expect_err("cycle");
+    assert!(err.to_string().contains("a -> b -> a"), "{err}");
+    let prompts = PromptService::get_prompts(&state, AppType::Claude).expect("prompts");
+    assert!(prompts.values().all(|p| !p.enabled));
+    assert!(!home.join(".claude").join("CLAUDE.md").exists());
+}
+
+#[test]
+fn prompts_with_jsx_braces_are_written_verbatim() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let state = state_from_config(MultiAppConfig::default());
+
+    let jsx = "Style inline: <p style={{color: 'red'}}>Hi</p>";
+    PromptService::upsert_prompt(&state, AppType::Claude, "jsx", prompt("jsx", jsx))
+        .expect("save jsx");
+    PromptService::enable_prompt(&state, AppType::Claude, "jsx").expect("enable jsx");
+
+    let claude_md =
+        std::fs::read_to_string(home.join(".claude").join("CLAUDE.md")).expect("read CLAUDE.md");
+    assert!(claude_md.contains(jsx), "{claude_md}");
+}
+
+#[test]
+fn prompt_layers_stack_in_order_and_keep_hand_written_notes() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let state = state_from_config(MultiAppConfig::default());
+
+    let claude_md = home.join(".claude").join("CLAUDE.md");
+    std::fs::create_dir_all(claude_md.parent().unwrap()).expect("create .claude");
+    std::fs::write(&claude_md, "# My notes\nRun tests before pushing.\n").expect("seed notes");
+
+    for (id, content) in [
+        ("team", "Team baseline."),
+        ("rust", "Prefer iterators."),
+        ("project", "This repo ships a CLI."),
+    ] {
+        PromptService::upsert_prompt(&state, AppType::Claude, id, prompt(id, content))
+            .expect("save prompt");
+    }
+
+    PromptService::enable_prompt(&state, AppType::Claude, "team").expect("enable team");
+    PromptService::enable_prompt(&state, AppType::Claude, "project").expect("enable project");
+    PromptService::add_layer(&state, AppType::Claude, "rust", Some(2)).expect("insert rust");
+
+    let stack: Vec<String> = PromptService::active_stack(&state, AppType::Claude)
+        .expect("stack")
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    assert_eq!(stack, ["team", "rust", "project"]);
+    assert_eq!(
+        std::fs::read_to_string(&claude_md).expect("read CLAUDE.md"),
+        format!(
+            "# My notes\nRun tests before pushing.\n\n{}\n\n{}\n\n{}\n",
+            section("team", "Team baseline."),
+            section("rust", "Prefer iterators."),
+            section("project", "This repo ships a CLI."),
+        )
+    );
+
+    // 手动修改的段落回填到对应提示词；移除一层只删除其段落
+    let live = std::fs::read_to_string(&claude_md).expect("read CLAUDE.md");
+    std::fs::write(
+        &claude_md,
+        live.replace("Prefer iterators.", "Prefer iterators and ?."),
+    )
+    .expect("edit section");
+    PromptService::disable_prompt(&state, AppType::Claude, "team").expect("disable team");
+    PromptService::move_layer(&state, AppType::Claude, "project", 1).expect("move project");
+
+    let prompts = PromptService::get_prompts(&state, AppType::Claude).expect("prompts");
+    assert_eq!(prompts["rust"].content, "Prefer iterators and ?.");
+    assert!(!prompts["team"].enabled);
+    assert_eq!(
+        std::fs::read_to_string(&claude_md).expect("read CLAUDE.md"),
+        format!(
+            "# My notes\nRun tests before pushing.\n\n{}\n\n{}\n",
+            section("project", "This repo ships a CLI."),
+            section("rust", "Prefer iterators and ?."),
+        )
+    );
+
+    // 清空栈后只剩用户内容
+    PromptService::set_stack(&state, AppType::Claude, &[]).expect("clear stack");
+    assert_eq!(
+        std::fs::read_to_string(&claude_md).expect("read CLAUDE.md"),
+        "# My notes\nRun tests before pushing.\n"
+    );
+    assert!(PromptService::active_stack(&state, AppType::Claude)
+        .expect("stack")
+        .is_empty());
+}
+
+#[test]
+fn project_prompt_files_are_managed_imported_and_tracked() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let state = state_from_config(MultiAppConfig::default());
+
+    let repo = home.join("work").join("demo-repo");
+    std::fs::create_dir_all(&repo).expect("create repo");
+    let repo = std::fs::canonicalize(&repo).expect("canonical repo");
+    std::fs::write(repo.join("AGENTS.md"), "Run cargo test.\n").expect("seed AGENTS.md");
+
+    // 导入已有的项目文件为预设，激活后原内容由托管段落接管
+    let imported =
+        PromptService::import_from_project(&state, AppType::Codex, &repo).expect("import");
+    let prompts = PromptService::get_prompts(&state, AppType::Codex).expect("prompts");
+    assert_eq!(prompts[&imported].content, "Run cargo test.\n");
+
+    PromptService::activate_in_project(&state, AppType::Codex, &imported, &repo)
+        .expect("activate imported");
+    assert_eq!(
+        std::fs::read_to_string(repo.join("AGENTS.md")).expect("read AGENTS.md"),
+        format!("{}\n", section(&imported, "Run cargo test."))
+    );
+
+    // Claude 项目文件：保留手写内容，模板变量使用项目目录
+    std::fs::write(repo.join("CLAUDE.md"), "# Notes\n").expect("seed CLAUDE.md");
+    PromptService::upsert_prompt(
+        &state,
+        AppType::Claude,
+        "team",
+        prompt("team", "Working on {{project}}."),
+    )
+    .expect("save team");
+    let path = PromptService::activate_in_project(&state, AppType::Claude, "team", &repo)
+        .expect("activate team");
+    assert_eq!(path, repo.join("CLAUDE.md"));
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("read CLAUDE.md"),
+        format!("# Notes\n\n{}\n", section("team", "Working on demo-repo."))
+    );
+    assert!(
+        !home.join(".claude").join("CLAUDE.md").exists(),
+        "user-level file is untouched"
+    );
+
+    let usages = PromptService::project_usages(&state, None).expect("usages");
+    let usage: Vec<(String, String)> = usages
+        .iter()
+        .map(|p| (p.app_type.clone(), p.prompt_id.clone()))
+        .collect();
+    assert_eq!(
+        usage,
+        [
+            ("claude".to_string(), "team".to_string()),
+            ("codex".to_string(), imported.clone())
+        ]
+    );
+    assert!(usages
+        .iter()
+        .all(|p| p.project_path == repo.display().to_string()));
+
+    // 编辑预设会同步到使用它的项目；项目在用的预设不能删除
+    PromptService::upsert_prompt(
+        &state,
+        AppType::Claude,
+        "team",
+        prompt("team", "Shipping {{project}}."),
+    )
+    .expect("update team");
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("read CLAUDE.md"),
+        format!("# Notes\n\n{}\n", section("team", "Shipping demo-repo."))
+    );
+    assert!(PromptService::delete_prompt(&state, AppType::Claude, "team").is_err());
+
+    // 停用后只移除托管段落；文件变空时删除
+    assert!(PromptService::deactivate_in_project(&state, AppType::Claude, &repo).expect("off"));
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("read CLAUDE.md"),
+        "# Notes\n"
+    );
+    assert!(PromptService::deactivate_in_project(&state, AppType::Codex, &repo).expect("off"));
+    assert!(!repo.join("AGENTS.md").exists());
+    assert!(PromptService::project_usages(&state, None)
+        .expect("usages")
+        .is_empty());
+}
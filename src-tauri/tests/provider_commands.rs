@@ -80,6 +80,8 @@ command = "say"
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            codex_toml: None,
+            tools: Default::default(),
         },
     );
 
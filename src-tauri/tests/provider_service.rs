@@ -90,6 +90,8 @@ command = "say"
             homepage: None,
             docs: None,
             tags: Vec::new(),
+            codex_toml: None,
+            tools: Default::default(),
         },
     );
 
@@ -794,3 +796,162 @@ fn provider_service_delete_current_provider_returns_error() {
         other => panic!("expected Config error, got {other:?}"),
     }
 }
+
+#[test]
+fn provider_status_detects_live_edits_and_resolves_them() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    std::fs::create_dir_all(home.join(".claude")).expect("create claude dir (initialized)");
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.providers.insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Relay".to_string(),
+                json!({ "env": {
+                    "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                    "ANTHROPIC_AUTH_TOKEN": "sk-relay"
+                } }),
+                None,
+            ),
+        );
+    }
+    let state = state_from_config(config);
+    ProviderService::switch(&state, AppType::Claude, "relay").expect("switch");
+    let drift = ProviderService::live_drift(&state, AppType::Claude)
+        .expect("diff")
+        .expect("claude live exists");
+    assert!(drift.is_clean(), "{:?}", drift.changes);
+
+    // 模拟 `/login`：令牌被替换，并新增了一个键
+    let settings_path = get_claude_settings_path();
+    let edit_live = |token: &str| {
+        let mut live: serde_json::Value = read_json_file(&settings_path).expect("read live");
+        live["env"]["ANTHROPIC_AUTH_TOKEN"] = json!(token);
+        live["model"] = json!("opus");
+        std::fs::write(&settings_path, live.to_string()).expect("write live");
+    };
+    edit_live("sk-login");
+
+    let drift = ProviderService::live_drift(&state, AppType::Claude)
+        .expect("diff")
+        .expect("claude live exists");
+    let keys: Vec<(&str, Option<&serde_json::Value>, Option<&serde_json::Value>)> = drift
+        .changes
+        .iter()
+        .map(|c| (c.key.as_str(), c.stored.as_ref(), c.live.as_ref()))
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            (
+                "env.ANTHROPIC_AUTH_TOKEN",
+                Some(&json!("sk-relay")),
+                Some(&json!("sk-login"))
+            ),
+            ("model", None, Some(&json!("opus"))),
+        ]
+    );
+
+    // 采纳：存储的供应商更新为 live 的值
+    ProviderService::adopt_live(&state, AppType::Claude).expect("adopt");
+    let providers = ProviderService::list(&state, AppType::Claude).expect("list");
+    assert_eq!(
+        providers["relay"].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-login"
+    );
+    assert!(ProviderService::live_drift(&state, AppType::Claude)
+        .expect("diff")
+        .expect("claude live exists")
+        .is_clean());
+
+    // 另存为新供应商：新供应商成为当前，原供应商不变
+    edit_live("sk-personal");
+    ProviderService::save_live_as(&state, AppType::Claude, "personal", "Personal")
+        .expect("save as");
+    assert!(
+        ProviderService::save_live_as(&state, AppType::Claude, "relay", "Dup").is_err(),
+        "existing id is rejected"
+    );
+    let providers = ProviderService::list(&state, AppType::Claude).expect("list");
+    assert_eq!(
+        ProviderService::current(&state, AppType::Claude).expect("current"),
+        "personal"
+    );
+    assert_eq!(
+        providers["personal"].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-personal"
+    );
+    assert_eq!(
+        providers["relay"].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-login"
+    );
+
+    // 重新应用：live 按存储重写
+    edit_live("sk-stray");
+    ProviderService::reapply_current(&state, AppType::Claude).expect("reapply");
+    let live: serde_json::Value = read_json_file(&settings_path).expect("read live");
+    assert_eq!(live["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-personal");
+    let providers = ProviderService::list(&state, AppType::Claude).expect("list");
+    assert_eq!(
+        providers["personal"].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-personal"
+    );
+}
+
+#[test]
+fn provider_status_reports_codex_config_edits_by_field() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Codex)
+            .expect("codex manager");
+        manager.providers.insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Relay".to_string(),
+                json!({
+                    "auth": { "OPENAI_API_KEY": "sk-relay" },
+                    "config": "base_url = \"https://relay.example.com/v1\"\nmodel = \"gpt-5\"\nwire_api = \"responses\""
+                }),
+                None,
+            ),
+        );
+    }
+    write_codex_live_atomic(&json!({ "OPENAI_API_KEY": "placeholder" }), None)
+        .expect("initialize codex dir");
+    let state = state_from_config(config);
+    ProviderService::switch(&state, AppType::Codex, "relay").expect("switch");
+    assert!(ProviderService::live_drift(&state, AppType::Codex)
+        .expect("diff")
+        .expect("codex live exists")
+        .is_clean());
+
+    let config_path = cc_switch_lib::get_codex_config_path();
+    let text = std::fs::read_to_string(&config_path).expect("read config.toml");
+    std::fs::write(
+        &config_path,
+        text.replace(
+            "https://relay.example.com/v1",
+            "https://edited.example.com/v1",
+        ),
+    )
+    .expect("edit config.toml");
+
+    let drift = ProviderService::live_drift(&state, AppType::Codex)
+        .expect("diff")
+        .expect("codex live exists");
+    let keys: Vec<&str> = drift.changes.iter().map(|c| c.key.as_str()).collect();
+    assert_eq!(keys, ["config.base_url"]);
+}
@@ -1,7 +1,15 @@
+// 每个测试文件只用到其中一部分辅助函数
+#![allow(dead_code)]
+
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
 
-use cc_switch_lib::{update_settings, AppSettings, AppState, Database, MultiAppConfig};
+use serde_json::json;
+
+use cc_switch_lib::{
+    get_claude_mcp_path, get_claude_settings_path, read_json_file, update_settings, AppSettings,
+    AppState, AppType, Database, McpApps, McpServer, MultiAppConfig, Prompt, Provider,
+};
 
 /// 为测试设置隔离的 HOME 目录，避免污染真实用户数据。
 pub fn ensure_test_home() -> &'static Path {
@@ -59,3 +67,85 @@ pub fn state_from_config(config: MultiAppConfig) -> AppState {
         config: RwLock::new(config),
     }
 }
+
+/// `env` 中只有 `ANTHROPIC_AUTH_TOKEN` 的 Claude 供应商
+pub fn claude_provider(id: &str, name: &str, token: &str) -> Provider {
+    Provider::with_id(
+        id.to_string(),
+        name.to_string(),
+        json!({ "env": { "ANTHROPIC_AUTH_TOKEN": token } }),
+        None,
+    )
+}
+
+/// 只含给定 Claude 供应商的配置，`current` 为当前供应商
+pub fn claude_config(
+    current: &str,
+    providers: impl IntoIterator<Item = Provider>,
+) -> MultiAppConfig {
+    let mut config = MultiAppConfig::default();
+    let manager = config
+        .get_manager_mut(&AppType::Claude)
+        .expect("claude manager");
+    manager.current = current.to_string();
+    for provider in providers {
+        manager.providers.insert(provider.id.clone(), provider);
+    }
+    config
+}
+
+/// live `~/.claude/settings.json` 中的 `ANTHROPIC_AUTH_TOKEN`
+pub fn live_claude_token() -> String {
+    let live: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read live settings");
+    live["env"]["ANTHROPIC_AUTH_TOKEN"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// live `~/.claude.json` 中的 MCP 服务器 ID（文件不存在时为空）
+pub fn live_claude_mcp_ids() -> Vec<String> {
+    let live: serde_json::Value = read_json_file(&get_claude_mcp_path()).unwrap_or_default();
+    live["mcpServers"]
+        .as_object()
+        .map(|m| m.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// 名称与 ID 相同、尚未启用的提示词
+pub fn prompt(id: &str, content: &str) -> Prompt {
+    Prompt {
+        id: id.to_string(),
+        name: id.to_string(),
+        content: content.to_string(),
+        description: None,
+        enabled: false,
+        created_at: None,
+        updated_at: None,
+        stack_order: None,
+    }
+}
+
+/// 名称与 ID 相同的 stdio MCP 服务器
+pub fn stdio_mcp_server(id: &str, command: &str, apps: McpApps) -> McpServer {
+    McpServer {
+        id: id.to_string(),
+        name: id.to_string(),
+        server: json!({ "type": "stdio", "command": command }),
+        apps,
+        description: None,
+        homepage: None,
+        docs: None,
+        tags: Vec::new(),
+        codex_toml: None,
+        tools: Default::default(),
+    }
+}
+
+/// 只启用 Claude 的 MCP 应用开关
+pub fn claude_only() -> McpApps {
+    let mut apps = McpApps::default();
+    apps.set_enabled_for(&AppType::Claude, true);
+    apps
+}
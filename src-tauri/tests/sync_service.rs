@@ -0,0 +1,251 @@
+use futures::executor::block_on;
+use serde_json::json;
+use std::path::Path;
+use std::process::Command;
+
+use cc_switch_lib::{
+    AppType, ConflictResolution, McpApps, McpServer, McpService, MultiAppConfig, PromptService,
+    Provider, ProviderService, SyncService,
+};
+
+#[path = "support.rs"]
+mod support;
+use support::{
+    claude_config, claude_provider, ensure_test_home, lock_test_mutex, prompt, reset_test_fs,
+    state_from_config,
+};
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@localhost"])
+        .args(args)
+        .output()
+        .expect("run git");
+    assert!(
+        output.status.success(),
+        "git {args:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn provider_name(state: &cc_switch_lib::AppState, id: &str) -> String {
+    ProviderService::list(state, AppType::Claude).expect("providers")[id]
+        .name
+        .clone()
+}
+
+#[test]
+fn sync_push_pull_through_bare_repo_with_conflicts() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let remote = home.join("sync-remote.git");
+    let _ = std::fs::remove_dir_all(&remote);
+    std::fs::create_dir_all(&remote).expect("create remote");
+    git(&remote, &["init", "--quiet", "--bare"]);
+
+    // 机器 A：推送到空的裸仓库，密钥加密存放
+    let mut config = claude_config("shared", [claude_provider("shared", "Shared", "sk-shared")]);
+    config
+        .prompts
+        .claude
+        .prompts
+        .insert("rules".to_string(), prompt("rules", "Be brief."));
+    let state = state_from_config(config);
+    state.save().expect("persist db");
+    PromptService::enable_prompt(&state, AppType::Claude, "rules").expect("enable prompt");
+
+    SyncService::init(remote.to_str().unwrap(), None).expect("init git target");
+    let report = SyncService::push(&state, Some("sync pass")).expect("push");
+    assert_eq!(report.changes.len(), 2);
+
+    let checkout = home.join("sync-checkout");
+    let _ = std::fs::remove_dir_all(&checkout);
+    let status = Command::new("git")
+        .args(["clone", "--quiet"])
+        .arg(&remote)
+        .arg(&checkout)
+        .status()
+        .expect("clone");
+    assert!(status.success());
+    let provider_file =
+        std::fs::read_to_string(checkout.join("providers/claude/shared.json")).expect("file");
+    assert!(provider_file.contains("<secret>"), "{provider_file}");
+    assert!(!provider_file.contains("sk-shared"));
+    assert!(checkout.join("secrets.enc").exists());
+    let prompt_file =
+        std::fs::read_to_string(checkout.join("prompts/claude/rules.json")).expect("file");
+    assert!(prompt_file.contains("\"enabled\": false"), "{prompt_file}");
+
+    // 未修改时再次推送不产生提交
+    let head = git(&checkout, &["rev-parse", "HEAD"]);
+    assert!(SyncService::push(&state, Some("sync pass"))
+        .expect("push again")
+        .changes
+        .is_empty());
+    git(&checkout, &["pull", "--quiet"]);
+    assert_eq!(git(&checkout, &["rev-parse", "HEAD"]), head);
+
+    // 机器 B：全新环境，拉取后密钥从 secrets.enc 还原，本地独有的条目保留
+    reset_test_fs();
+    let state = state_from_config(claude_config(
+        "laptop",
+        [claude_provider("laptop", "Laptop", "sk-laptop")],
+    ));
+    state.save().expect("persist db");
+    SyncService::init(remote.to_str().unwrap(), None).expect("init on machine B");
+    let report = block_on(SyncService::pull(&state, None, Some("sync pass"))).expect("pull");
+    assert_eq!(report.changes.len(), 2);
+    assert_eq!(report.local_changes, ["providers/claude/laptop.json"]);
+    assert!(report.missing_secrets.is_empty());
+    let providers = ProviderService::list(&state, AppType::Claude).expect("providers");
+    assert_eq!(
+        providers["shared"].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-shared"
+    );
+    assert!(
+        !PromptService::get_prompts(&state, AppType::Claude).expect("prompts")["rules"].enabled
+    );
+
+    // 双方都修改同一供应商：默认保留本地并拒绝推送
+    let file = checkout.join("providers/claude/shared.json");
+    let edited = std::fs::read_to_string(&file)
+        .expect("read")
+        .replace("\"Shared\"", "\"Remote name\"");
+    std::fs::write(&file, edited).expect("write");
+    git(&checkout, &["commit", "--quiet", "-am", "rename on remote"]);
+    git(&checkout, &["push", "--quiet"]);
+
+    let mut shared =
+        ProviderService::list(&state, AppType::Claude).expect("providers")["shared"].clone();
+    shared.name = "Local name".to_string();
+    ProviderService::update(&state, AppType::Claude, shared).expect("edit locally");
+
+    let report = block_on(SyncService::pull(&state, None, Some("sync pass"))).expect("pull");
+    assert_eq!(report.conflicts, ["providers/claude/shared.json"]);
+    assert_eq!(provider_name(&state, "shared"), "Local name");
+    assert!(SyncService::push(&state, None).is_err());
+
+    let report = block_on(SyncService::pull(
+        &state,
+        Some(ConflictResolution::Theirs),
+        None,
+    ))
+    .expect("pull");
+    assert!(report.conflicts.is_empty());
+    assert_eq!(provider_name(&state, "shared"), "Remote name");
+    // 未提供口令时沿用本地密钥
+    let providers = ProviderService::list(&state, AppType::Claude).expect("providers");
+    assert_eq!(
+        providers["shared"].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-shared"
+    );
+
+    let report = SyncService::push(&state, None).expect("push after resolving");
+    let pushed: Vec<&str> = report.changes.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(pushed, ["providers/claude/laptop.json"]);
+    git(&checkout, &["pull", "--quiet"]);
+    assert!(checkout.join("providers/claude/laptop.json").exists());
+}
+
+#[test]
+fn sync_to_plain_directory_propagates_deletions() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let dir = home.join("sync-dir");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let mut config = claude_config("main", [claude_provider("main", "Main", "sk-main")]);
+    config
+        .get_manager_mut(&AppType::Claude)
+        .expect("claude manager")
+        .providers
+        .insert(
+            "spare".to_string(),
+            Provider::with_id(
+                "spare".to_string(),
+                "Spare".to_string(),
+                json!({ "env": {} }),
+                None,
+            ),
+        );
+    let state = state_from_config(config);
+    state.save().expect("persist db");
+
+    SyncService::init(dir.to_str().unwrap(), None).expect("init directory target");
+    SyncService::push(&state, None).expect("push");
+    assert!(dir.join("providers/claude/spare.json").exists());
+    assert!(!dir.join("secrets.enc").exists());
+
+    // 另一台机器删除了 spare
+    std::fs::remove_file(dir.join("providers/claude/spare.json")).expect("remove");
+    let (_, local_changes) = SyncService::status(&state).expect("status");
+    assert!(local_changes.is_empty());
+
+    let report = block_on(SyncService::pull(&state, None, None)).expect("pull");
+    assert_eq!(report.changes.len(), 1);
+    assert!(!ProviderService::list(&state, AppType::Claude)
+        .expect("providers")
+        .contains_key("spare"));
+}
+
+#[test]
+fn sync_push_redacts_mcp_headers_url_tokens_and_codex_toml() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let home = ensure_test_home();
+    let dir = home.join("sync-dir-mcp");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let state = state_from_config(claude_config(
+        "main",
+        [claude_provider("main", "Main", "sk-main")],
+    ));
+    state.save().expect("persist db");
+    let server = McpServer {
+        id: "remote".to_string(),
+        name: "Remote".to_string(),
+        server: json!({
+            "type": "http",
+            "url": "https://mcp.example.com/sse?token=url-token",
+            "headers": { "Authorization": "Bearer header-token" }
+        }),
+        apps: McpApps::default(),
+        description: None,
+        homepage: None,
+        docs: None,
+        tags: Vec::new(),
+        codex_toml: Some(
+            "[mcp_servers.remote]\nurl = \"https://mcp.example.com/sse\"\n\
+             http_headers = { Authorization = \"Bearer toml-token\" }\n"
+                .to_string(),
+        ),
+        tools: Default::default(),
+    };
+    McpService::upsert_server(&state, server.clone()).expect("add mcp server");
+
+    SyncService::init(dir.to_str().unwrap(), None).expect("init directory target");
+    SyncService::push(&state, Some("sync pass")).expect("push");
+
+    let pushed = std::fs::read_to_string(dir.join("mcp/remote.json")).expect("mcp file");
+    for token in ["header-token", "url-token", "toml-token"] {
+        assert!(!pushed.contains(token), "{token} leaked: {pushed}");
+    }
+    assert!(pushed.contains("<secret>"), "{pushed}");
+
+    // 另一台机器凭口令还原全部密钥
+    reset_test_fs();
+    let state = state_from_config(MultiAppConfig::default());
+    state.save().expect("persist db");
+    SyncService::init(dir.to_str().unwrap(), None).expect("init on machine B");
+    let report = block_on(SyncService::pull(&state, None, Some("sync pass"))).expect("pull");
+    assert!(report.missing_secrets.is_empty());
+    let pulled = &McpService::get_all_servers(&state).expect("servers")["remote"];
+    assert_eq!(pulled.server, server.server);
+    assert_eq!(pulled.codex_toml, server.codex_toml);
+}
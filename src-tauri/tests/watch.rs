@@ -0,0 +1,134 @@
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+use cc_switch_lib::{
+    get_claude_mcp_path, get_claude_settings_path, read_json_file, AppState, AppType, LiveWatcher,
+    McpDriftKind, McpService, ProviderService, WatchOptions, WatchOutcome, WatchPolicy,
+    WatchService, WatchSubject,
+};
+
+#[path = "support.rs"]
+mod support;
+use support::{
+    claude_config, claude_provider, ensure_test_home, live_claude_token, lock_test_mutex,
+    reset_test_fs, state_from_config,
+};
+
+fn claude_state() -> AppState {
+    let home = ensure_test_home();
+    std::fs::create_dir_all(home.join(".claude")).expect("create claude dir (initialized)");
+
+    let state = state_from_config(claude_config(
+        "relay",
+        [claude_provider("relay", "Relay", "sk-relay")],
+    ));
+    ProviderService::switch(&state, AppType::Claude, "relay").expect("switch");
+    state
+}
+
+fn edit_live_token(token: &str) {
+    let path = get_claude_settings_path();
+    let mut live: serde_json::Value = read_json_file(&path).expect("read live");
+    live["env"]["ANTHROPIC_AUTH_TOKEN"] = json!(token);
+    std::fs::write(&path, live.to_string()).expect("write live");
+}
+
+#[test]
+fn watch_check_reports_without_changing_anything() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let state = claude_state();
+
+    let options = WatchOptions::default();
+    assert!(WatchService::check(&state, &AppType::Claude, &options)
+        .expect("check")
+        .is_empty());
+
+    edit_live_token("sk-login");
+    let events = WatchService::check(&state, &AppType::Claude, &options).expect("check");
+    assert_eq!(events.len(), 1, "{events:?}");
+    assert_eq!(
+        events[0].subject,
+        WatchSubject::Provider {
+            id: "relay".to_string(),
+            name: "Relay".to_string()
+        }
+    );
+    assert_eq!(events[0].changes, ["env.ANTHROPIC_AUTH_TOKEN"]);
+    assert_eq!(events[0].outcome, WatchOutcome::Reported);
+
+    assert_eq!(live_claude_token(), "sk-login");
+    let providers = ProviderService::list(&state, AppType::Claude).expect("list");
+    assert_eq!(
+        providers["relay"].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+        "sk-relay"
+    );
+}
+
+#[test]
+fn watcher_debounces_and_applies_policies() {
+    let _guard = lock_test_mutex();
+    reset_test_fs();
+    let state = claude_state();
+
+    let mut watcher = LiveWatcher::new(WatchOptions {
+        apps: vec![AppType::Claude],
+        provider: WatchPolicy::Reapply,
+        mcp: WatchPolicy::Adopt,
+        debounce: Duration::from_secs(1),
+    });
+    let start = Instant::now();
+    assert!(watcher.poll(&state, start).is_empty(), "no changes yet");
+
+    edit_live_token("sk-stray-token");
+    std::fs::write(
+        get_claude_mcp_path(),
+        json!({ "mcpServers": { "fs": { "type": "stdio", "command": "npx", "args": ["fs"] } } })
+            .to_string(),
+    )
+    .expect("write claude.json");
+
+    assert!(
+        watcher
+            .poll(&state, start + Duration::from_millis(100))
+            .is_empty(),
+        "changes wait for the debounce period"
+    );
+    assert_eq!(live_claude_token(), "sk-stray-token");
+
+    let events = watcher.poll(&state, start + Duration::from_secs(2));
+    let outcomes: Vec<(&WatchSubject, &WatchOutcome)> =
+        events.iter().map(|e| (&e.subject, &e.outcome)).collect();
+    assert_eq!(
+        outcomes,
+        [
+            (
+                &WatchSubject::Provider {
+                    id: "relay".to_string(),
+                    name: "Relay".to_string()
+                },
+                &WatchOutcome::Reapplied
+            ),
+            (
+                &WatchSubject::McpServer {
+                    id: "fs".to_string(),
+                    kind: McpDriftKind::Added
+                },
+                &WatchOutcome::Adopted
+            ),
+        ]
+    );
+    assert_eq!(live_claude_token(), "sk-relay");
+    let servers = McpService::get_all_servers(&state).expect("servers");
+    assert!(
+        servers["fs"].apps.claude,
+        "live server adopted into the store"
+    );
+
+    assert!(
+        watcher
+            .poll(&state, start + Duration::from_secs(4))
+            .is_empty(),
+        "the watcher's own writes are not reported again"
+    );
+}